@@ -0,0 +1,257 @@
+//! Registry of the actions a hotkey sequence can trigger.
+//!
+//! Each action has a stable id, used as its key in the config file, a display name,
+//! a default binding and a priority. The settings window, the sequence matcher and
+//! the hotkey validator iterate `ACTIONS`, so a new action is added here and given a
+//! handler in `platform::win::action_handlers`.
+//!
+//! Besides the registered actions, a config may bind `activate_layout_<tag>` for any
+//! language tag, see `activate_layout`, and `transform_<id>` for a text transform, see
+//! `transform`.
+
+use std::sync::{Mutex, PoisonError};
+
+use crate::{config::HotkeySequence, domain::text::transform};
+
+/// Identifies a registered action. The wrapped string is its config key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ActionId(&'static str);
+
+impl ActionId {
+    pub const fn as_str(self) -> &'static str {
+        self.0
+    }
+
+    /// The action with config key `key`: a registered action, a layout activation or
+    /// a text transform.
+    pub fn from_key(key: &str) -> Option<Self> {
+        if let Some(action) = ACTIONS.iter().find(|a| a.id.0 == key) {
+            return Some(action.id);
+        }
+        if let Some(id) = key.strip_prefix(TRANSFORM_PREFIX) {
+            return transform::find(id).map(|t| self::transform(t.id()).id);
+        }
+        let tag = key.strip_prefix(ACTIVATE_LAYOUT_PREFIX)?;
+        is_language_tag(tag).then(|| activate_layout(tag).id)
+    }
+
+    /// Language tag of an action activating a layout.
+    pub fn layout_tag(self) -> Option<&'static str> {
+        self.0.strip_prefix(ACTIVATE_LAYOUT_PREFIX)
+    }
+
+    /// Id of the text transform an action runs.
+    pub fn transform_id(self) -> Option<&'static str> {
+        self.0.strip_prefix(TRANSFORM_PREFIX)
+    }
+
+    /// Whether the action is bound only through the config file, without a Hotkeys tab row.
+    pub fn is_config_only(self) -> bool {
+        self.layout_tag().is_some() || self.transform_id().is_some()
+    }
+}
+
+pub const CONVERT_LAST_WORD: ActionId = ActionId("convert_last_word");
+pub const PAUSE: ActionId = ActionId("autoconvert_pause");
+pub const CONVERT_SELECTION: ActionId = ActionId("convert_selection");
+pub const SWITCH_LAYOUT: ActionId = ActionId("switch_layout");
+pub const UNDO_LAST_CONVERSION: ActionId = ActionId("undo_last_conversion");
+pub const TOGGLE_RECENT_LAYOUTS: ActionId = ActionId("toggle_recent_layouts");
+pub const PAUSE_FOR: ActionId = ActionId("autoconvert_pause_timed");
+pub const CONVERT_LAST_PHRASE: ActionId = ActionId("convert_last_phrase");
+pub const CONVERT_LAST_WORDS: ActionId = ActionId("convert_last_words");
+
+/// Config key prefix of the actions activating a layout, followed by its language tag.
+const ACTIVATE_LAYOUT_PREFIX: &str = "activate_layout_";
+const ACTIVATE_LAYOUT_PRIORITY: u8 = 9;
+
+/// Config key prefix of the actions running a text transform, followed by its id.
+const TRANSFORM_PREFIX: &str = "transform_";
+const TRANSFORM_PRIORITY: u8 = 10;
+
+#[derive(Debug)]
+pub struct ActionDef {
+    pub id: ActionId,
+    /// Shown in the settings window and in validation messages.
+    pub name: &'static str,
+    /// Binding of a fresh config, in the text syntax of `config::hotkey_text`.
+    /// Empty for an action that is unbound by default.
+    pub default_binding: &'static str,
+    /// When sequences of several actions complete on the same chord, the lowest value wins.
+    pub priority: u8,
+    /// An action that may be bound to the same sequence as this one.
+    pub shares_binding_with: Option<ActionId>,
+}
+
+impl ActionDef {
+    /// The default binding, `None` for an unbound action.
+    pub fn default_sequence(&self) -> Option<HotkeySequence> {
+        self.default_binding
+            .parse::<HotkeySequence>()
+            .ok()
+            .filter(|seq| !seq.is_empty())
+    }
+}
+
+/// All actions, in the order of the settings window and of validation reports.
+pub const ACTIONS: [ActionDef; 9] = [
+    ActionDef {
+        id: CONVERT_LAST_WORD,
+        name: "Convert last word",
+        default_binding: "LShift, LShift",
+        priority: 1,
+        // Converts the selection when there is one.
+        shares_binding_with: Some(CONVERT_SELECTION),
+    },
+    ActionDef {
+        id: PAUSE,
+        name: "Autoconvert pause",
+        default_binding: "LShift+RShift",
+        priority: 3,
+        shares_binding_with: None,
+    },
+    ActionDef {
+        id: CONVERT_SELECTION,
+        name: "Convert selection",
+        default_binding: "LShift, LShift",
+        priority: 2,
+        shares_binding_with: Some(CONVERT_LAST_WORD),
+    },
+    ActionDef {
+        id: SWITCH_LAYOUT,
+        name: "Switch keyboard layout",
+        default_binding: "CapsLock (tap 300ms)",
+        priority: 0,
+        shares_binding_with: None,
+    },
+    ActionDef {
+        id: UNDO_LAST_CONVERSION,
+        name: "Undo last conversion",
+        default_binding: "",
+        priority: 4,
+        shares_binding_with: None,
+    },
+    ActionDef {
+        id: TOGGLE_RECENT_LAYOUTS,
+        name: "Toggle recent layouts",
+        default_binding: "",
+        priority: 5,
+        shares_binding_with: None,
+    },
+    ActionDef {
+        id: PAUSE_FOR,
+        name: "Pause autoconvert for a while",
+        default_binding: "",
+        priority: 6,
+        shares_binding_with: None,
+    },
+    ActionDef {
+        id: CONVERT_LAST_PHRASE,
+        name: "Convert last phrase",
+        default_binding: "",
+        priority: 7,
+        shares_binding_with: None,
+    },
+    ActionDef {
+        id: CONVERT_LAST_WORDS,
+        // Each further press takes one more word.
+        name: "Convert last words",
+        default_binding: "",
+        priority: 8,
+        shares_binding_with: None,
+    },
+];
+
+/// Actions created by `activate_layout` and `transform`, in creation order.
+static CREATED_ACTIONS: Mutex<Vec<&'static ActionDef>> = Mutex::new(Vec::new());
+
+/// `tag` looks like a language tag such as `en` or `sr-Latn-RS`.
+fn is_language_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag
+            .split('-')
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// The action activating the layout with language tag `tag`, e.g. `en-US`.
+///
+/// Created on first use with no default binding; it lives for the rest of the process.
+pub fn activate_layout(tag: &str) -> &'static ActionDef {
+    created_action(
+        format!("{ACTIVATE_LAYOUT_PREFIX}{tag}"),
+        || format!("Activate layout {tag}"),
+        ACTIVATE_LAYOUT_PRIORITY,
+    )
+}
+
+/// The action running the transform `id` of `domain::text::transform`, created on
+/// first use like `activate_layout`.
+pub fn transform(id: &str) -> &'static ActionDef {
+    created_action(
+        format!("{TRANSFORM_PREFIX}{id}"),
+        || transform::find(id).map_or_else(|| id.to_string(), |t| t.name().to_string()),
+        TRANSFORM_PRIORITY,
+    )
+}
+
+fn created_action(key: String, name: impl FnOnce() -> String, priority: u8) -> &'static ActionDef {
+    let mut defs = CREATED_ACTIONS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(def) = defs.iter().find(|d| d.id.0 == key) {
+        return def;
+    }
+
+    let def: &'static ActionDef = Box::leak(Box::new(ActionDef {
+        id: ActionId(key.leak()),
+        name: name().leak(),
+        default_binding: "",
+        priority,
+        shares_binding_with: None,
+    }));
+    defs.push(def);
+    def
+}
+
+fn created_actions() -> Vec<&'static ActionDef> {
+    CREATED_ACTIONS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// The registered or created action `id`.
+pub fn find(id: ActionId) -> Option<&'static ActionDef> {
+    ACTIONS
+        .iter()
+        .find(|a| a.id == id)
+        .or_else(|| created_actions().into_iter().find(|a| a.id == id))
+}
+
+/// Display name of `id`.
+pub fn name(id: ActionId) -> &'static str {
+    find(id).map_or(id.as_str(), |a| a.name)
+}
+
+/// Priority of `id`, see `ActionDef::priority`.
+pub fn priority(id: ActionId) -> u8 {
+    find(id).map_or(u8::MAX, |a| a.priority)
+}
+
+/// Position of `id` in `ACTIONS`, followed by the created actions.
+pub fn index_of(id: ActionId) -> Option<usize> {
+    ACTIONS.iter().position(|a| a.id == id).or_else(|| {
+        created_actions()
+            .iter()
+            .position(|a| a.id == id)
+            .map(|i| ACTIONS.len() + i)
+    })
+}
+
+/// The action at `index`, see `index_of`.
+pub fn at(index: usize) -> Option<ActionId> {
+    match ACTIONS.get(index) {
+        Some(action) => Some(action.id),
+        None => created_actions().get(index - ACTIONS.len()).map(|a| a.id),
+    }
+}
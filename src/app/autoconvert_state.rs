@@ -0,0 +1,121 @@
+//! Whether autoconvert is on, off or paused for a while.
+//!
+//! Reads the time from a `Clock`, so tests drive a pause with a fake clock.
+
+use std::time::{Duration, Instant};
+
+/// Source of the current time.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoconvertStatus {
+    Off,
+    On,
+    /// On again once `remaining` is over.
+    Paused {
+        remaining: Duration,
+    },
+}
+
+impl AutoconvertStatus {
+    /// Short description for the tray tooltip, e.g. `Autoconvert: paused, 15 min left`.
+    pub fn describe(self) -> String {
+        match self {
+            Self::Off => "Autoconvert: off".to_string(),
+            Self::On => "Autoconvert: on".to_string(),
+            Self::Paused { remaining } => {
+                let minutes = remaining.as_secs().div_ceil(60).max(1);
+                format!("Autoconvert: paused, {minutes} min left")
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct AutoconvertState<C = SystemClock> {
+    clock: C,
+    enabled: bool,
+    paused_until: Option<Instant>,
+}
+
+impl<C: Default> Default for AutoconvertState<C> {
+    fn default() -> Self {
+        Self::new(C::default())
+    }
+}
+
+impl<C> AutoconvertState<C> {
+    /// Off, reading the time from `clock`.
+    pub fn new(clock: C) -> Self {
+        Self {
+            clock,
+            enabled: false,
+            paused_until: None,
+        }
+    }
+
+    /// The choice stored in the config: on also while paused.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turns autoconvert on or off, ending a pause.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.paused_until = None;
+    }
+}
+
+impl<C: Clock> AutoconvertState<C> {
+    pub fn status(&self) -> AutoconvertStatus {
+        let now = self.clock.now();
+        match self.paused_until {
+            Some(until) if until > now => AutoconvertStatus::Paused {
+                remaining: until - now,
+            },
+            _ if self.enabled => AutoconvertStatus::On,
+            _ => AutoconvertStatus::Off,
+        }
+    }
+
+    /// Whether words are autoconverted now.
+    pub fn is_active(&self) -> bool {
+        self.status() == AutoconvertStatus::On
+    }
+
+    /// Turns autoconvert off when it is on, else on, ending a pause. Returns whether
+    /// it is on.
+    pub fn toggle(&mut self) -> bool {
+        let enabled = !self.is_active();
+        self.set_enabled(enabled);
+        enabled
+    }
+
+    /// Stops autoconvert for `duration`, after which it is on, also when it was off.
+    pub fn pause(&mut self, duration: Duration) {
+        self.enabled = true;
+        self.paused_until = Some(self.clock.now() + duration);
+    }
+
+    /// Ends a pause whose time is over. Returns whether it ended.
+    pub fn resume_if_due(&mut self) -> bool {
+        let due = self
+            .paused_until
+            .is_some_and(|until| until <= self.clock.now());
+        if due {
+            self.paused_until = None;
+        }
+        due
+    }
+}
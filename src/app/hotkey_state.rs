@@ -0,0 +1,98 @@
+//! Hotkey bindings and matching progress, kept free of window handles so the
+//! input pipeline can use them outside Windows.
+
+use std::collections::BTreeMap;
+
+use crate::{app::actions::ActionId, config, input::sequence_matcher::SequenceMatcher};
+
+/// Hotkey sequence of each bound action.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HotkeyBindings {
+    sequences: BTreeMap<ActionId, config::HotkeySequence>,
+}
+
+impl HotkeyBindings {
+    /// Bindings of the actions of `cfg`, see `Config::actions`. Config entries of
+    /// unknown actions are skipped.
+    pub fn from_config(cfg: &config::Config) -> Self {
+        let mut bindings = Self::default();
+        for id in cfg.actions() {
+            bindings.set(id, cfg.hotkey(id).cloned());
+        }
+        bindings
+    }
+
+    pub fn get(&self, id: ActionId) -> Option<&config::HotkeySequence> {
+        self.sequences.get(&id)
+    }
+
+    /// Binds `id` to `seq`. `None` or an empty sequence unbinds it.
+    pub fn set(&mut self, id: ActionId, seq: Option<config::HotkeySequence>) {
+        match seq.filter(|s| !s.is_empty()) {
+            Some(seq) => {
+                self.sequences.insert(id, seq);
+            }
+            None => {
+                self.sequences.remove(&id);
+            }
+        }
+    }
+
+    /// Unbinds the actions `keep` returns `false` for.
+    pub fn retain(&mut self, mut keep: impl FnMut(ActionId) -> bool) {
+        self.sequences.retain(|&id, _| keep(id));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ActionId, &config::HotkeySequence)> + '_ {
+        self.sequences.iter().map(|(&id, seq)| (id, seq))
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RuntimeChordCapture {
+    pub pending_mods_vks: u32,
+    pub pending_mods: u32,
+    pub pending_mods_valid: bool,
+    pub saw_non_mod: bool,
+    /// When the first of the pending modifiers went down.
+    pub pending_since_ms: u64,
+}
+
+/// A key held back from the foreground window until its press is known to be a
+/// tap, a hold or a plain keystroke.
+#[derive(Debug, Clone, Copy)]
+pub struct HeldKey {
+    pub chord: config::HotkeyChord,
+    pub down_ms: u64,
+    /// Past this long the press can no longer match and the key is given back.
+    pub window_ms: u64,
+    /// The key was given back; its repeats and release pass through.
+    pub released: bool,
+}
+
+/// The last release of a tapped trigger, for double taps.
+#[derive(Debug, Clone, Copy)]
+pub struct LastTap {
+    pub chord: config::HotkeyChord,
+    pub released_ms: u64,
+}
+
+/// Hotkey state of the runtime (not capture) path of the keyboard hook.
+#[derive(Debug, Default)]
+pub struct RuntimeHotkeys {
+    /// Bindings applied from config, matched against input.
+    pub bindings: HotkeyBindings,
+    pub matcher: SequenceMatcher,
+    pub chord_capture: RuntimeChordCapture,
+    pub held_key: Option<HeldKey>,
+    pub last_tap: Option<LastTap>,
+}
+
+impl RuntimeHotkeys {
+    pub fn from_config(cfg: &config::Config) -> Self {
+        Self {
+            bindings: HotkeyBindings::from_config(cfg),
+            ..Self::default()
+        }
+    }
+}
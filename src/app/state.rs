@@ -0,0 +1,234 @@
+//! Defines the application state and associated constants.
+//!
+//! The `AppState` structure holds handles to all of the controls in
+//! the settings window along with the font used for drawing text.
+//! Constants representing control identifiers are defined here so
+//! that they can be shared between modules.
+
+use std::collections::VecDeque;
+
+use windows::Win32::{
+    Foundation::HWND,
+    Graphics::Gdi::HFONT,
+    UI::{Input::KeyboardAndMouse::HKL, WindowsAndMessaging::HMENU},
+};
+
+use super::{
+    HotkeyBindings, RuntimeHotkeys,
+    actions::{self, ActionId},
+    autoconvert_state::AutoconvertState,
+};
+use crate::{
+    config,
+    domain::text::{layout_memory::LayoutMemory, layout_policy::RecentLayouts},
+};
+
+#[derive(Debug, Clone)]
+pub struct UiError {
+    pub title: String,
+    pub user_text: String,
+    pub _debug_text: String,
+}
+
+#[derive(Debug, Default)]
+pub struct HotkeyCaptureUi {
+    pub active: bool,
+    pub action: Option<ActionId>,
+
+    pub pending_mods_vks: u32,
+    pub pending_mods: u32,
+    pub pending_mods_valid: bool,
+    pub saw_non_mod: bool,
+
+    // Last successful chord capture time in milliseconds since boot (GetTickCount64).
+    // Used to reset sequence after a long pause.
+    pub last_input_tick_ms: u64,
+}
+
+/// Per-window state used throughout the application.
+///
+/// Stored in window user data. Contains handles of child controls and UI resources.
+#[derive(Debug, Default)]
+pub struct AppState {
+    /// Font used for all controls. Assigned after window creation.
+    pub font: HFONT,
+
+    pub checkboxes: Checkboxes,
+    pub edits: Edits,
+    pub hotkeys: HotkeyEdits,
+    pub buttons: Buttons,
+    pub dictionary: DictionaryControls,
+    pub snippets: SnippetControls,
+
+    /// Whether autoconvert is on, off or paused; the on/off choice is saved in the config.
+    pub autoconvert: AutoconvertState,
+    pub errors: VecDeque<UiError>,
+
+    /// Temporary hotkeys currently shown in UI. Committed on Apply.
+    pub hotkey_bindings: HotkeyBindings,
+
+    /// Which hotkey edit is currently capturing input.
+    pub hotkey_capture: HotkeyCaptureUi,
+
+    /// Active (already applied) sequences used by the runtime hotkey recognizer,
+    /// with their matching progress. This must NOT be tied to temporary edits in the UI.
+    pub runtime_hotkeys: RuntimeHotkeys,
+
+    /// Layouts last used in the foreground window, for toggling between the two.
+    pub recent_layouts: RecentLayouts<HKL>,
+
+    /// Layout of each application or window, restored when it gets the focus.
+    pub layout_memory: LayoutMemory<HKL>,
+
+    /// Last foreground window of another process and the settings of its application rule.
+    /// Cleared when a config is applied.
+    pub foreground_app: Option<ForegroundApp>,
+
+    pub current_theme_dark: bool,
+
+    /// Last applied config. Fields without a settings control are carried over on Apply.
+    pub config: config::Config,
+}
+
+#[derive(Debug)]
+pub struct ForegroundApp {
+    /// Raw window handle.
+    pub window: isize,
+    /// Executable file name, empty when the process could not be queried.
+    pub exe: String,
+    pub title: String,
+    pub settings: config::AppSettings,
+}
+
+#[derive(Debug, Default)]
+pub struct Checkboxes {
+    pub autostart: HWND,
+}
+
+#[derive(Debug, Default)]
+pub struct Edits {
+    pub delay_ms: HWND,
+}
+
+/// Hotkey edit of each action, in the order of `actions::ACTIONS`.
+#[derive(Debug, Default)]
+pub struct HotkeyEdits {
+    pub edits: Vec<(ActionId, HWND)>,
+}
+
+impl HotkeyEdits {
+    pub fn get(&self, id: ActionId) -> Option<HWND> {
+        self.edits
+            .iter()
+            .find(|(action, _)| *action == id)
+            .map(|&(_, hwnd)| hwnd)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Buttons {
+    pub apply: HWND,
+    pub cancel: HWND,
+    pub exit: HWND,
+}
+
+#[derive(Debug, Default)]
+pub struct DictionaryControls {
+    pub list: HWND,
+    pub word: HWND,
+    pub learned: HWND,
+}
+
+#[derive(Debug, Default)]
+pub struct SnippetControls {
+    pub list: HWND,
+    pub trigger: HWND,
+    pub text: HWND,
+}
+
+/// Control identifiers used in `WM_COMMAND` and as `HMENU` in `CreateWindowExW`.
+#[repr(i32)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum ControlId {
+    Autostart = 1001,
+    Tray = 1002,
+    DelayMs = 1003,
+
+    DictionaryList = 1301,
+    DictionaryWord = 1302,
+    DictionaryNever = 1303,
+    DictionaryAlways = 1304,
+    DictionaryRemove = 1305,
+    DictionaryImport = 1306,
+    DictionaryExport = 1307,
+    LearnedList = 1308,
+    LearnedForget = 1309,
+
+    SnippetList = 1401,
+    SnippetTrigger = 1402,
+    SnippetText = 1403,
+    SnippetSave = 1404,
+    SnippetRemove = 1405,
+
+    Apply = 1101,
+    Cancel = 1102,
+    Exit = 1103,
+}
+
+impl ControlId {
+    #[inline]
+    pub const fn from_i32(v: i32) -> Option<Self> {
+        match v {
+            1001 => Some(Self::Autostart),
+            1002 => Some(Self::Tray),
+            1003 => Some(Self::DelayMs),
+
+            1301 => Some(Self::DictionaryList),
+            1302 => Some(Self::DictionaryWord),
+            1303 => Some(Self::DictionaryNever),
+            1304 => Some(Self::DictionaryAlways),
+            1305 => Some(Self::DictionaryRemove),
+            1306 => Some(Self::DictionaryImport),
+            1307 => Some(Self::DictionaryExport),
+            1308 => Some(Self::LearnedList),
+            1309 => Some(Self::LearnedForget),
+
+            1401 => Some(Self::SnippetList),
+            1402 => Some(Self::SnippetTrigger),
+            1403 => Some(Self::SnippetText),
+            1404 => Some(Self::SnippetSave),
+            1405 => Some(Self::SnippetRemove),
+
+            1101 => Some(Self::Apply),
+            1102 => Some(Self::Cancel),
+            1103 => Some(Self::Exit),
+
+            _ => None,
+        }
+    }
+
+    #[inline]
+    pub fn hmenu(self) -> windows::Win32::UI::WindowsAndMessaging::HMENU {
+        control_hmenu(self as u16)
+    }
+}
+
+/// Hotkey edits take the control ids from 1201 up, one per action in `actions::ACTIONS`.
+const HOTKEY_EDIT_ID_BASE: u16 = 1201;
+
+/// `HMENU` of the hotkey edit of the action at `index` in `actions::ACTIONS`.
+pub fn hotkey_edit_hmenu(index: usize) -> HMENU {
+    control_hmenu(HOTKEY_EDIT_ID_BASE + index as u16)
+}
+
+/// Action of the hotkey edit with control id `id`.
+pub fn hotkey_edit_action(id: i32) -> Option<ActionId> {
+    let index = usize::try_from(id - i32::from(HOTKEY_EDIT_ID_BASE)).ok()?;
+    actions::ACTIONS.get(index).map(|a| a.id)
+}
+
+fn control_hmenu(id: u16) -> HMENU {
+    use std::ffi::c_void;
+
+    HMENU(usize::from(id) as *mut c_void)
+}
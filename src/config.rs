@@ -1,21 +1,40 @@
+mod app_rules;
 mod config_validator;
-pub mod constants;
+mod hotkey_text;
+mod legacy_hotkeys;
 
 use std::{
+    collections::BTreeMap,
     io,
     path::{Path, PathBuf},
 };
 
-use serde::{Deserialize, Serialize};
+pub use app_rules::{AppRule, AppSettings, ReplacementStrategy, WindowInfo};
+pub use config_validator::{IssueKind, Severity, ValidationIssue, describe_issues};
+pub use hotkey_text::HotkeyParseError;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{
+    app::actions::{ACTIONS, ActionId},
+    domain::text::{
+        case_fix::CaseFixSettings,
+        layout::{LayoutPairTable, RU_EN},
+        layout_memory::LayoutMemoryScope,
+        learned_exceptions::LearnedExceptions,
+        selection::DEFAULT_MAX_SELECTION_CHARS,
+        snippets::Snippets,
+        user_dictionary::UserDictionary,
+    },
+    input::word_boundaries::WordBoundaries,
+};
 
 const APP_DIR: &str = "RustSwitcher";
 const CONFIG_FILE: &str = "config.json";
-
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
-pub struct Hotkey {
-    pub vk: u32,
-    pub mods: u32,
-}
+const LAYOUTS_DIR: &str = "layouts";
+const LAYOUT_FILE_EXT: &str = "toml";
+const DICTIONARY_FILE: &str = "dictionary.txt";
+const LEARNED_FILE: &str = "learned.toml";
+const SNIPPETS_FILE: &str = "snippets.toml";
 
 pub const MODVK_LCTRL: u32 = 1 << 0;
 pub const MODVK_RCTRL: u32 = 1 << 1;
@@ -26,6 +45,30 @@ pub const MODVK_RALT: u32 = 1 << 5;
 pub const MODVK_LWIN: u32 = 1 << 6;
 pub const MODVK_RWIN: u32 = 1 << 7;
 
+/// How the trigger of a chord has to be pressed.
+///
+/// The trigger is the key of the chord, or the modifiers themselves for a chord without a key.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "style", rename_all = "snake_case")]
+pub enum PressStyle {
+    /// Recognized when the key goes down. Chords without a key are recognized on release.
+    #[default]
+    Press,
+    /// Released within `max_ms` of the press.
+    Tap { max_ms: u32 },
+    /// Held for at least `min_ms`. Recognized on release.
+    Hold { min_ms: u32 },
+    /// Two taps, each shorter than `max_gap_ms`, with the second press at most
+    /// `max_gap_ms` after the first release.
+    DoubleTap { max_gap_ms: u32 },
+}
+
+impl PressStyle {
+    pub fn is_press(&self) -> bool {
+        matches!(self, Self::Press)
+    }
+}
+
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 pub struct HotkeyChord {
     pub mods: u32,
@@ -34,90 +77,220 @@ pub struct HotkeyChord {
     pub mods_vks: u32,
 
     pub vk: Option<u32>,
+
+    #[serde(default, skip_serializing_if = "PressStyle::is_press")]
+    pub press: PressStyle,
 }
 
+/// Gap of sequence steps that do not set one.
+pub const DEFAULT_GAP_MS: u32 = 1000;
+
+/// Longest sequence recorded by the hotkey capture UI.
+pub const MAX_CAPTURED_STEPS: usize = 4;
+
+/// One chord of a hotkey sequence.
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-pub struct HotkeySequence {
-    pub first: HotkeyChord,
-    pub second: Option<HotkeyChord>,
+pub struct HotkeyStep {
+    pub chord: HotkeyChord,
+    /// Longest pause after the previous step. Ignored for the first step.
     pub max_gap_ms: u32,
 }
 
+/// Chords pressed one after another, such as a double tap of Shift or `Ctrl+K, Ctrl+R, L`.
+///
+/// Stored in the config as text, see `hotkey_text`.
+///
+/// An empty sequence in the config leaves its action unbound.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(try_from = "HotkeySequenceRepr")]
+pub struct HotkeySequence {
+    pub steps: Vec<HotkeyStep>,
+}
+
+impl Serialize for HotkeySequence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Accepted config shapes of a sequence: text, and the `steps` list and `first`/`second`
+/// pair of bitmasks written by older versions.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HotkeySequenceRepr {
+    Text(String),
+    Steps {
+        steps: Vec<HotkeyStep>,
+    },
+    Pair {
+        first: HotkeyChord,
+        second: Option<HotkeyChord>,
+        max_gap_ms: u32,
+    },
+}
+
+impl TryFrom<HotkeySequenceRepr> for HotkeySequence {
+    type Error = HotkeyParseError;
+
+    fn try_from(repr: HotkeySequenceRepr) -> Result<Self, Self::Error> {
+        match repr {
+            HotkeySequenceRepr::Text(text) => text.parse(),
+            HotkeySequenceRepr::Steps { steps } => Ok(Self { steps }),
+            HotkeySequenceRepr::Pair {
+                first,
+                second,
+                max_gap_ms,
+            } => {
+                let chords: Vec<HotkeyChord> = std::iter::once(first).chain(second).collect();
+                Ok(Self::new(&chords, max_gap_ms))
+            }
+        }
+    }
+}
+
+impl HotkeySequence {
+    /// Sequence of `chords`, each pressed within `max_gap_ms` of the previous one.
+    pub fn new(chords: &[HotkeyChord], max_gap_ms: u32) -> Self {
+        Self {
+            steps: chords
+                .iter()
+                .map(|&chord| HotkeyStep { chord, max_gap_ms })
+                .collect(),
+        }
+    }
+
+    pub fn chords(&self) -> impl Iterator<Item = HotkeyChord> + '_ {
+        self.steps.iter().map(|s| s.chord)
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub delay_ms: u32,
 
-    pub hotkey_convert_last_word: Option<Hotkey>,
-    pub hotkey_convert_selection: Option<Hotkey>,
-    pub hotkey_switch_layout: Option<Hotkey>,
-    pub hotkey_pause: Option<Hotkey>,
+    /// Id of the layout pair used for conversion, built-in or from `layouts_dir`.
+    #[serde(default = "default_layout_pair")]
+    pub layout_pair: String,
+
+    /// Hotkey sequence of each action, keyed by action id, see `app::actions`.
+    ///
+    /// Use `hotkey` and `set_hotkey`: an empty sequence unbinds its action, and
+    /// entries of unknown actions are kept so they survive a save.
+    #[serde(default)]
+    pub hotkeys: BTreeMap<String, HotkeySequence>,
 
+    /// Whether the layout is remembered per application or per window and restored on focus.
     #[serde(default)]
-    pub hotkey_convert_last_word_sequence: Option<HotkeySequence>,
+    pub layout_memory: LayoutMemoryScope,
+
+    /// Language tag of the layout pinned to an application, keyed by executable name.
+    #[serde(default)]
+    pub app_layouts: BTreeMap<String, String>,
+
+    /// Rules for listed applications, the first matching one applies, see `app_rules`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub app_rules: Vec<AppRule>,
+
+    /// Characters that end words and trigger autoconvert, by layout.
     #[serde(default)]
-    pub hotkey_pause_sequence: Option<HotkeySequence>,
+    pub word_boundaries: WordBoundaries,
+
+    /// Whether autoconvert is on when the app starts. Saved whenever it is turned on or off.
     #[serde(default)]
-    pub hotkey_convert_selection_sequence: Option<HotkeySequence>,
+    pub autoconvert_enabled: bool,
+
+    /// Length of the pause started by the `autoconvert_pause_timed` hotkey.
+    #[serde(default = "default_pause_minutes")]
+    pub pause_minutes: u32,
+
+    /// Longest selection converted, in characters. Longer ones are left as they are.
+    #[serde(default = "default_max_selection_chars")]
+    pub max_selection_chars: usize,
+
+    /// Whether autoconvert fixes words typed with Caps Lock on or with two capitals.
     #[serde(default)]
-    pub hotkey_switch_layout_sequence: Option<HotkeySequence>,
+    pub case_fix: CaseFixSettings,
+}
+
+fn default_layout_pair() -> String {
+    RU_EN.to_string()
 }
+
+const fn default_pause_minutes() -> u32 {
+    15
+}
+
+const fn default_max_selection_chars() -> usize {
+    DEFAULT_MAX_SELECTION_CHARS
+}
+
 impl Default for Config {
     fn default() -> Self {
-        Self {
+        let mut cfg = Self {
             delay_ms: 100,
+            layout_pair: default_layout_pair(),
+            hotkeys: BTreeMap::new(),
+            layout_memory: LayoutMemoryScope::default(),
+            app_layouts: BTreeMap::new(),
+            app_rules: Vec::new(),
+            word_boundaries: WordBoundaries::default(),
+            autoconvert_enabled: false,
+            pause_minutes: default_pause_minutes(),
+            max_selection_chars: default_max_selection_chars(),
+            case_fix: CaseFixSettings::default(),
+        };
+        cfg.bind_missing_actions();
+        cfg
+    }
+}
+
+impl Config {
+    /// Reads a config file. Hotkeys of older versions are moved into `hotkeys`,
+    /// and actions without an entry get their default binding.
+    pub fn parse(text: &str) -> Result<Self, toml::de::Error> {
+        let mut table: toml::Table = toml::from_str(text)?;
+        legacy_hotkeys::migrate(&mut table);
+
+        let mut cfg: Self = toml::Value::Table(table).try_into()?;
+        cfg.bind_missing_actions();
+        Ok(cfg)
+    }
+
+    /// The registered actions, followed by the layout activations and text transforms
+    /// with an entry in `hotkeys`.
+    pub fn actions(&self) -> Vec<ActionId> {
+        let created = self
+            .hotkeys
+            .keys()
+            .filter_map(|key| ActionId::from_key(key))
+            .filter(|id| id.is_config_only());
+        ACTIONS.iter().map(|a| a.id).chain(created).collect()
+    }
+
+    /// The sequence bound to `id`, `None` when the action is unbound.
+    pub fn hotkey(&self, id: ActionId) -> Option<&HotkeySequence> {
+        self.hotkeys.get(id.as_str()).filter(|seq| !seq.is_empty())
+    }
 
-            hotkey_switch_layout: None,
-            hotkey_pause: None,
-            hotkey_convert_last_word: None,
-            hotkey_convert_selection: None,
-
-            hotkey_convert_last_word_sequence: Some(HotkeySequence {
-                first: HotkeyChord {
-                    mods: 4,
-                    mods_vks: 4,
-                    vk: None,
-                },
-                second: Some(HotkeyChord {
-                    mods: 4,
-                    mods_vks: 4,
-                    vk: None,
-                }),
-                max_gap_ms: 1000,
-            }),
-
-            hotkey_pause_sequence: Some(HotkeySequence {
-                first: HotkeyChord {
-                    mods: 4,
-                    mods_vks: 12,
-                    vk: None,
-                },
-                second: None,
-                max_gap_ms: 1000,
-            }),
-
-            hotkey_convert_selection_sequence: Some(HotkeySequence {
-                first: HotkeyChord {
-                    mods: 4,
-                    mods_vks: 4,
-                    vk: None,
-                },
-                second: Some(HotkeyChord {
-                    mods: 4,
-                    mods_vks: 4,
-                    vk: None,
-                }),
-                max_gap_ms: 1000,
-            }),
-
-            hotkey_switch_layout_sequence: Some(HotkeySequence {
-                first: HotkeyChord {
-                    mods: 0,
-                    mods_vks: 0,
-                    vk: Some(20),
-                },
-                second: None,
-                max_gap_ms: 1000,
-            }),
+    /// Binds `id` to `seq`, `None` unbinds it.
+    pub fn set_hotkey(&mut self, id: ActionId, seq: Option<HotkeySequence>) {
+        self.hotkeys
+            .insert(id.as_str().to_string(), seq.unwrap_or_default());
+    }
+
+    fn bind_missing_actions(&mut self) {
+        for action in &ACTIONS {
+            if !self.hotkeys.contains_key(action.id.as_str()) {
+                self.set_hotkey(action.id, action.default_sequence());
+            }
         }
     }
 }
@@ -129,6 +302,157 @@ pub fn config_path() -> io::Result<PathBuf> {
     Ok(PathBuf::from(appdata).join(APP_DIR).join(CONFIG_FILE))
 }
 
+/// Joins `name` to the directory of `config.json`.
+fn next_to_config(name: &str) -> io::Result<PathBuf> {
+    let path = config_path()?;
+    let dir = path
+        .parent()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "config path has no parent"))?;
+    Ok(dir.join(name))
+}
+
+/// Directory with user layout pair tables, next to `config.json`.
+pub fn layouts_dir() -> io::Result<PathBuf> {
+    next_to_config(LAYOUTS_DIR)
+}
+
+/// Reads all user layout pair tables (`*.toml`) from `layouts_dir`, sorted by file name.
+///
+/// A missing directory yields an empty list. A file that fails to parse is reported
+/// as `InvalidData` with the file path in the message.
+pub fn load_layout_pair_tables() -> io::Result<Vec<LayoutPairTable>> {
+    let dir = layouts_dir()?;
+
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == LAYOUT_FILE_EXT) {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let text = std::fs::read_to_string(path)?;
+            LayoutPairTable::parse(&text).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {e}", path.display()),
+                )
+            })
+        })
+        .collect()
+}
+
+/// User dictionary file, next to `config.json`.
+pub fn dictionary_path() -> io::Result<PathBuf> {
+    next_to_config(DICTIONARY_FILE)
+}
+
+/// Loads the user dictionary from `dictionary_path`. A missing file yields an empty dictionary.
+pub fn load_user_dictionary() -> io::Result<UserDictionary> {
+    match read_user_dictionary(&dictionary_path()?) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(UserDictionary::new()),
+        r => r,
+    }
+}
+
+pub fn save_user_dictionary(dict: &UserDictionary) -> io::Result<()> {
+    let path = dictionary_path()?;
+    ensure_parent_dir(&path)?;
+    write_user_dictionary(&path, dict)
+}
+
+/// Reads a dictionary in the plain text format, used for import.
+///
+/// A file that fails to parse is reported as `InvalidData` with the file path in the message.
+pub fn read_user_dictionary(path: &Path) -> io::Result<UserDictionary> {
+    let text = std::fs::read_to_string(path)?;
+    UserDictionary::parse(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+/// Writes a dictionary in the plain text format, used for export.
+pub fn write_user_dictionary(path: &Path, dict: &UserDictionary) -> io::Result<()> {
+    std::fs::write(path, dict.to_text())
+}
+
+/// Snippets file, next to `config.json`.
+pub fn snippets_path() -> io::Result<PathBuf> {
+    next_to_config(SNIPPETS_FILE)
+}
+
+/// Loads the snippets from `snippets_path`. A missing file yields no snippets.
+///
+/// A file that fails to parse is reported as `InvalidData` with the file path in the message.
+pub fn load_snippets() -> io::Result<Snippets> {
+    let path = snippets_path()?;
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Snippets::new()),
+        Err(e) => return Err(e),
+    };
+
+    Snippets::parse(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+pub fn save_snippets(snippets: &Snippets) -> io::Result<()> {
+    let path = snippets_path()?;
+    ensure_parent_dir(&path)?;
+    let text = snippets
+        .to_toml()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, text)
+}
+
+/// Store of the exceptions learned from reverted autoconversions, next to `config.json`.
+pub fn learned_path() -> io::Result<PathBuf> {
+    next_to_config(LEARNED_FILE)
+}
+
+/// Loads the learned exceptions. A missing file yields an empty store.
+pub fn load_learned_exceptions() -> io::Result<LearnedExceptions> {
+    let path = learned_path()?;
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(LearnedExceptions::new()),
+        Err(e) => return Err(e),
+    };
+
+    LearnedExceptions::parse(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+pub fn save_learned_exceptions(learned: &LearnedExceptions) -> io::Result<()> {
+    let path = learned_path()?;
+    ensure_parent_dir(&path)?;
+    let text = learned
+        .to_toml()
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    std::fs::write(path, text)
+}
+
 fn ensure_parent_dir(path: &Path) -> io::Result<()> {
     let Some(dir) = path.parent() else {
         return Ok(());
@@ -140,10 +464,29 @@ fn confy_err(e: confy::ConfyError) -> io::Error {
     io::Error::other(e)
 }
 
+/// Loads `config.json`, writing the default config when there is none.
+///
+/// A file that fails to parse is reported as `InvalidData` with the file path in the message.
 pub fn load() -> io::Result<Config> {
     let path = config_path()?;
     ensure_parent_dir(&path)?;
-    confy::load_path(path).map_err(confy_err)
+
+    let text = match std::fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let cfg = Config::default();
+            confy::store_path(&path, &cfg).map_err(confy_err)?;
+            return Ok(cfg);
+        }
+        Err(e) => return Err(e),
+    };
+
+    Config::parse(&text).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
 }
 
 #[allow(dead_code)]
@@ -151,6 +494,6 @@ pub fn save(cfg: &Config) -> io::Result<()> {
     let path = config_path()?;
     ensure_parent_dir(&path)?;
     cfg.validate_hotkey_sequences()
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        .map_err(|issues| io::Error::new(io::ErrorKind::InvalidInput, describe_issues(&issues)))?;
     confy::store_path(path, cfg).map_err(confy_err)
 }
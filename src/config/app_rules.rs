@@ -0,0 +1,220 @@
+//! Rules that change what the app does in listed applications.
+//!
+//! A rule matches windows by executable name, window class and a title regex; every
+//! criterion it sets must match. The first matching rule in `Config::app_rules` applies.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use super::Config;
+use crate::app::actions::ActionId;
+
+/// How converted text is typed into the target window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplacementStrategy {
+    /// Unicode key events (`KEYEVENTF_UNICODE`).
+    #[default]
+    Unicode,
+    /// Ctrl+V from the clipboard, which is restored afterwards. For terminals and
+    /// remote desktop clients that drop Unicode key events.
+    Clipboard,
+}
+
+/// A regex matched against window titles.
+#[derive(Debug, Clone)]
+pub struct TitlePattern(regex::Regex);
+
+impl TitlePattern {
+    pub fn new(pattern: &str) -> Result<Self, regex::Error> {
+        regex::Regex::new(pattern).map(Self)
+    }
+
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl PartialEq for TitlePattern {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Serialize for TitlePattern {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for TitlePattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let pattern = String::deserialize(deserializer)?;
+        Self::new(&pattern).map_err(serde::de::Error::custom)
+    }
+}
+
+/// The window a rule is matched against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WindowInfo {
+    /// File name of the executable, e.g. `mstsc.exe`.
+    pub exe: String,
+    pub class: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AppRule {
+    /// Executable file name, matched ignoring case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exe: Option<String>,
+    /// Window class name, matched ignoring case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class: Option<String>,
+    /// Regex searched for in the window title.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<TitlePattern>,
+
+    /// Whether autoconvert and snippets work in matching windows; on when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub autoconvert: Option<bool>,
+    /// Ids of the actions whose hotkeys work in matching windows; all when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<String>>,
+    /// Delay before a conversion, instead of the one of the settings window.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delay_ms: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replacement: Option<ReplacementStrategy>,
+}
+
+impl AppRule {
+    /// Whether every criterion of the rule matches `window`. A rule without criteria
+    /// matches nothing.
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        let any_criterion = self.exe.is_some() || self.class.is_some() || self.title.is_some();
+        any_criterion
+            && self
+                .exe
+                .as_ref()
+                .is_none_or(|exe| exe.eq_ignore_ascii_case(&window.exe))
+            && self
+                .class
+                .as_ref()
+                .is_none_or(|class| class.eq_ignore_ascii_case(&window.class))
+            && self
+                .title
+                .as_ref()
+                .is_none_or(|title| title.0.is_match(&window.title))
+    }
+
+    /// Whether the rule matches by executable name alone, as the tray menu creates them.
+    fn is_exe_rule(&self, exe: &str) -> bool {
+        self.exe
+            .as_ref()
+            .is_some_and(|e| e.eq_ignore_ascii_case(exe))
+            && self.class.is_none()
+            && self.title.is_none()
+    }
+
+    fn has_settings(&self) -> bool {
+        self.autoconvert.is_some()
+            || self.actions.is_some()
+            || self.delay_ms.is_some()
+            || self.replacement.is_some()
+    }
+}
+
+/// What applies in one window, after its rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppSettings {
+    pub autoconvert: bool,
+    /// Actions whose hotkeys work; `None` for all.
+    pub actions: Option<Vec<ActionId>>,
+    pub delay_ms: Option<u32>,
+    pub replacement: ReplacementStrategy,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            autoconvert: true,
+            actions: None,
+            delay_ms: None,
+            replacement: ReplacementStrategy::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    pub fn allows(&self, action: ActionId) -> bool {
+        self.actions
+            .as_ref()
+            .is_none_or(|actions| actions.contains(&action))
+    }
+}
+
+impl Config {
+    /// The first rule matching `window`.
+    pub fn app_rule(&self, window: &WindowInfo) -> Option<&AppRule> {
+        self.app_rules.iter().find(|rule| rule.matches(window))
+    }
+
+    /// Settings of `window` after its rule. Unknown action ids of the rule are ignored.
+    pub fn app_settings(&self, window: &WindowInfo) -> AppSettings {
+        let Some(rule) = self.app_rule(window) else {
+            return AppSettings::default();
+        };
+        AppSettings {
+            autoconvert: rule.autoconvert.unwrap_or(true),
+            actions: rule
+                .actions
+                .as_ref()
+                .map(|ids| ids.iter().filter_map(|id| ActionId::from_key(id)).collect()),
+            delay_ms: rule.delay_ms,
+            replacement: rule.replacement.unwrap_or_default(),
+        }
+    }
+
+    /// Whether the rule of `exe` that `set_app_disabled` edits turns everything off.
+    pub fn is_app_disabled(&self, exe: &str) -> bool {
+        self.app_rules
+            .iter()
+            .find(|r| r.is_exe_rule(exe))
+            .is_some_and(|r| {
+                r.autoconvert == Some(false) && r.actions.as_ref().is_some_and(Vec::is_empty)
+            })
+    }
+
+    /// Turns autoconvert and every hotkey off or back on in the application `exe`.
+    ///
+    /// Edits the first rule matching by `exe` alone. When disabling without one, it is
+    /// added first in the list so it wins; it is removed when left without settings.
+    pub fn set_app_disabled(&mut self, exe: &str, disabled: bool) {
+        let index = match self.app_rules.iter().position(|r| r.is_exe_rule(exe)) {
+            Some(index) => index,
+            None if disabled => {
+                self.app_rules.insert(
+                    0,
+                    AppRule {
+                        exe: Some(exe.to_string()),
+                        ..AppRule::default()
+                    },
+                );
+                0
+            }
+            None => return,
+        };
+
+        let rule = &mut self.app_rules[index];
+        if disabled {
+            rule.autoconvert = Some(false);
+            rule.actions = Some(Vec::new());
+        } else {
+            rule.autoconvert = None;
+            rule.actions = None;
+            if !rule.has_settings() {
+                self.app_rules.remove(index);
+            }
+        }
+    }
+}
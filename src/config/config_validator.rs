@@ -1,63 +1,287 @@
 use std::fmt::Write as _;
 
-use crate::config::{
-    Config,
-    constants::{CONVERT_LAST_WORD, CONVERT_SELECTION, PAUSE, SWITCH_LAYOUT},
+use crate::{
+    app::actions::{self, ActionId},
+    config::{Config, HotkeyChord, HotkeySequence, PressStyle},
+    input::key_event::{MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN},
 };
 
-pub fn find_duplicate_hotkey_sequences(config: &Config) -> Option<String> {
-    let sequences = [
-        (CONVERT_LAST_WORD, &config.hotkey_convert_last_word_sequence),
-        (PAUSE, &config.hotkey_pause_sequence),
-        (CONVERT_SELECTION, &config.hotkey_convert_selection_sequence),
-        (SWITCH_LAYOUT, &config.hotkey_switch_layout_sequence),
-    ];
-
-    // Allowed duplicates (bidirectional check)
-    let is_allowed_duplicate = |a: &str, b: &str| {
-        (a == CONVERT_SELECTION && b == CONVERT_LAST_WORD)
-            || (a == CONVERT_LAST_WORD && b == CONVERT_SELECTION)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The bindings work, but maybe not as intended.
+    Warning,
+    /// A binding can never fire. Such a config is not saved.
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// Two actions have the same sequence.
+    Duplicate,
+    /// A sequence can never complete because a shorter one completes inside it.
+    Shadowed,
+    /// Both sequences can complete on the same chord and action priority decides.
+    Ambiguous,
+    /// A chord is a system shortcut.
+    SystemShortcut,
+    /// A tap, hold or double tap duration is zero.
+    InvalidDuration,
+}
+
+/// A problem found in the hotkey sequences of a config.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    pub kind: IssueKind,
+    /// Actions involved, in registry order. The affected action comes last for `Shadowed`.
+    pub actions: Vec<ActionId>,
+    pub explanation: String,
+}
+
+/// Whether `a` may be bound to the same sequence as `b`, see `ActionDef::shares_binding_with`.
+fn may_share(a: ActionId, b: ActionId) -> bool {
+    actions::find(a).is_some_and(|def| def.shares_binding_with == Some(b))
+}
+
+/// Whether some input matches both chords.
+///
+/// A plain press matches the key going down, which starts every tap, hold and double tap.
+fn chords_overlap(a: HotkeyChord, b: HotkeyChord) -> bool {
+    a.mods == b.mods
+        && a.vk == b.vk
+        && (a.mods_vks == 0 || b.mods_vks == 0 || a.mods_vks == b.mods_vks)
+        && (a.press.is_press()
+            || b.press.is_press()
+            || std::mem::discriminant(&a.press) == std::mem::discriminant(&b.press))
+}
+
+/// Whether `inner` overlaps `outer` step by step, starting at step `at` of `outer`.
+fn overlaps_at(inner: &HotkeySequence, outer: &HotkeySequence, at: usize) -> bool {
+    at + inner.len() <= outer.len()
+        && inner
+            .chords()
+            .zip(outer.chords().skip(at))
+            .all(|(a, b)| chords_overlap(a, b))
+}
+
+/// Compares `a` against `b`, where `a` is not longer than `b`.
+fn pair_issue(
+    (id_a, a): (ActionId, &HotkeySequence),
+    (id_b, b): (ActionId, &HotkeySequence),
+) -> Option<ValidationIssue> {
+    let (name_a, name_b) = (actions::name(id_a), actions::name(id_b));
+    let in_report_order = |first: ActionId, second: ActionId| {
+        if actions::index_of(first) <= actions::index_of(second) {
+            vec![first, second]
+        } else {
+            vec![second, first]
+        }
     };
 
-    let duplicates: Vec<_> = sequences
-        .iter()
-        .enumerate()
-        .filter_map(|(i, (name1, seq1_opt))| {
-            seq1_opt.as_ref().map(|seq1| {
-                sequences.iter().enumerate().skip(i + 1).filter_map(
-                    move |(_j, (name2, seq2_opt))| {
-                        seq2_opt
-                            .as_ref()
-                            .filter(|seq2| seq1 == *seq2 && !is_allowed_duplicate(name1, name2))
-                            .map(|_| (*name1, *name2))
-                    },
-                )
-            })
-        })
-        .flatten()
-        .collect();
+    if a == b {
+        if may_share(id_a, id_b) {
+            return None;
+        }
+        return Some(ValidationIssue {
+            severity: Severity::Error,
+            kind: IssueKind::Duplicate,
+            actions: in_report_order(id_a, id_b),
+            explanation: format!("'{name_a}' and '{name_b}' use the same hotkey sequence."),
+        });
+    }
 
-    if duplicates.is_empty() {
-        None
+    if a.is_empty() {
+        return None;
+    }
+
+    let last = b.len() - a.len();
+    if (0..last).any(|at| overlaps_at(a, b, at)) {
+        return Some(ValidationIssue {
+            severity: Severity::Error,
+            kind: IssueKind::Shadowed,
+            actions: vec![id_a, id_b],
+            explanation: format!(
+                "'{name_b}' can never fire: '{name_a}' completes first on its chords."
+            ),
+        });
+    }
+
+    if !overlaps_at(a, b, last) {
+        return None;
+    }
+
+    let (winner, loser) = if actions::priority(id_a) < actions::priority(id_b) {
+        (id_a, id_b)
     } else {
-        let mut error = String::from("Duplicate hotkey sequences found:\n\n");
+        (id_b, id_a)
+    };
+
+    if a.len() < b.len() && winner == id_a {
+        return Some(ValidationIssue {
+            severity: Severity::Error,
+            kind: IssueKind::Shadowed,
+            actions: vec![id_a, id_b],
+            explanation: format!(
+                "'{name_b}' can never fire: '{name_a}' completes on its last chord and takes priority."
+            ),
+        });
+    }
 
-        for (name1, name2) in &duplicates {
-            debug_assert!(
-                writeln!(error, "• '{name1}' and '{name2}'").is_ok(),
-                "writing to String must not fail"
-            );
+    Some(ValidationIssue {
+        severity: Severity::Warning,
+        kind: IssueKind::Ambiguous,
+        actions: in_report_order(id_a, id_b),
+        explanation: format!(
+            "'{}' and '{}' can complete on the same chord; '{}' wins and '{}' is skipped.",
+            name_a,
+            name_b,
+            actions::name(winner),
+            actions::name(loser)
+        ),
+    })
+}
+
+struct SystemShortcut {
+    mods: u32,
+    vk: u32,
+    name: &'static str,
+    /// Windows handles it before the keyboard hook can.
+    reserved: bool,
+}
+
+const SYSTEM_SHORTCUTS: [SystemShortcut; 4] = [
+    SystemShortcut {
+        mods: MOD_CONTROL | MOD_ALT,
+        vk: 0x2E,
+        name: "Ctrl+Alt+Del",
+        reserved: true,
+    },
+    SystemShortcut {
+        mods: MOD_WIN,
+        vk: 0x4C,
+        name: "Win+L",
+        reserved: true,
+    },
+    SystemShortcut {
+        mods: MOD_ALT,
+        vk: 0x09,
+        name: "Alt+Tab",
+        reserved: false,
+    },
+    SystemShortcut {
+        mods: MOD_CONTROL | MOD_SHIFT,
+        vk: 0x1B,
+        name: "Ctrl+Shift+Esc",
+        reserved: false,
+    },
+];
+
+fn system_shortcut_issue(id: ActionId, seq: &HotkeySequence) -> Option<ValidationIssue> {
+    let shortcut = SYSTEM_SHORTCUTS.iter().find(|sc| {
+        seq.chords()
+            .any(|c| c.mods == sc.mods && c.vk == Some(sc.vk))
+    })?;
+
+    let name = actions::name(id);
+    let (severity, explanation) = if shortcut.reserved {
+        (
+            Severity::Error,
+            format!(
+                "'{name}' uses {}, which Windows handles before this app sees it.",
+                shortcut.name
+            ),
+        )
+    } else {
+        (
+            Severity::Warning,
+            format!(
+                "'{name}' uses {}, replacing the system shortcut.",
+                shortcut.name
+            ),
+        )
+    };
+
+    Some(ValidationIssue {
+        severity,
+        kind: IssueKind::SystemShortcut,
+        actions: vec![id],
+        explanation,
+    })
+}
+
+fn has_zero_duration(seq: &HotkeySequence) -> bool {
+    seq.chords().any(|chord| match chord.press {
+        PressStyle::Press => false,
+        PressStyle::Tap { max_ms } => max_ms == 0,
+        PressStyle::Hold { min_ms } => min_ms == 0,
+        PressStyle::DoubleTap { max_gap_ms } => max_gap_ms == 0,
+    })
+}
+
+/// Finds every problem of the hotkey sequences, errors and warnings alike.
+pub fn analyze_hotkey_sequences(config: &Config) -> Vec<ValidationIssue> {
+    let sequences: Vec<(ActionId, &HotkeySequence)> = config
+        .actions()
+        .into_iter()
+        .filter_map(|id| config.hotkey(id).map(|seq| (id, seq)))
+        .collect();
+
+    let mut issues = Vec::new();
+
+    for (i, &first) in sequences.iter().enumerate() {
+        for &second in &sequences[i + 1..] {
+            let (shorter, longer) = if first.1.len() <= second.1.len() {
+                (first, second)
+            } else {
+                (second, first)
+            };
+            issues.extend(pair_issue(shorter, longer));
+        }
+    }
+
+    for &(id, seq) in &sequences {
+        issues.extend(system_shortcut_issue(id, seq));
+
+        if has_zero_duration(seq) {
+            issues.push(ValidationIssue {
+                severity: Severity::Error,
+                kind: IssueKind::InvalidDuration,
+                actions: vec![id],
+                explanation: format!(
+                    "'{}' has a tap, hold or double tap duration of 0 ms.",
+                    actions::name(id)
+                ),
+            });
         }
+    }
+
+    issues
+}
 
-        error.push_str("\nEach action must have a unique hotkey sequence.");
-        Some(error)
+/// Renders issues as a bulleted list for a message box or notification.
+pub fn describe_issues(issues: &[ValidationIssue]) -> String {
+    let mut text = String::from("Hotkey sequence problems found:\n\n");
+    for issue in issues {
+        let label = match issue.severity {
+            Severity::Error => "Error",
+            Severity::Warning => "Warning",
+        };
+        let _ = writeln!(text, "• {label}: {}", issue.explanation);
     }
+    text
 }
 
 impl Config {
-    pub fn validate_hotkey_sequences(&self) -> Result<(), String> {
-        if let Some(error) = find_duplicate_hotkey_sequences(self) {
-            Err(error)
+    /// Every problem of the hotkey sequences, see `analyze_hotkey_sequences`.
+    pub fn hotkey_sequence_issues(&self) -> Vec<ValidationIssue> {
+        analyze_hotkey_sequences(self)
+    }
+
+    /// Fails with all issues when any of them is an error.
+    pub fn validate_hotkey_sequences(&self) -> Result<(), Vec<ValidationIssue>> {
+        let issues = self.hotkey_sequence_issues();
+        if issues.iter().any(|i| i.severity == Severity::Error) {
+            Err(issues)
         } else {
             Ok(())
         }
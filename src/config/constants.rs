@@ -1,4 +0,0 @@
-pub const CONVERT_LAST_WORD: &str = "Convert last word";
-pub const CONVERT_SELECTION: &str = "Convert selection";
-pub const PAUSE: &str = "Autoconvert pause";
-pub const SWITCH_LAYOUT: &str = "Switch keyboard layout";
@@ -0,0 +1,376 @@
+//! Readable text form of hotkeys, used by the config file and the UI.
+//!
+//! A chord is its modifiers and key joined by `+`, with an optional press style:
+//!
+//! ```text
+//! Ctrl+Alt+K
+//! LShift+RShift
+//! CapsLock (tap 300ms)
+//! ```
+//!
+//! A sequence is its chords separated by `,`. A `within` after the last chord sets
+//! the gap of every step, otherwise each step may carry its own; steps without one
+//! use `DEFAULT_GAP_MS`:
+//!
+//! ```text
+//! LShift, LShift within 400ms
+//! Ctrl+K within 1000ms, Ctrl+R within 500ms
+//! ```
+//!
+//! `Display` writes the canonical form and `FromStr` reads it back unchanged.
+//! Names are matched case insensitively.
+
+use std::{fmt, str::FromStr};
+
+use crate::{
+    config::{
+        DEFAULT_GAP_MS, HotkeyChord, HotkeySequence, HotkeyStep, MODVK_LALT, MODVK_LCTRL,
+        MODVK_LSHIFT, MODVK_LWIN, MODVK_RALT, MODVK_RCTRL, MODVK_RSHIFT, MODVK_RWIN, PressStyle,
+    },
+    input::key_event::{MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN},
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotkeyParseError {
+    EmptyChord,
+    UnknownKey(String),
+    SeveralKeys(String),
+    BadDuration(String),
+    UnknownPressStyle(String),
+}
+
+impl fmt::Display for HotkeyParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyParseError::EmptyChord => write!(f, "empty chord"),
+            HotkeyParseError::UnknownKey(name) => write!(f, "unknown key '{name}'"),
+            HotkeyParseError::SeveralKeys(chord) => {
+                write!(f, "chord '{chord}' has more than one non-modifier key")
+            }
+            HotkeyParseError::BadDuration(text) => {
+                write!(
+                    f,
+                    "bad duration '{text}', expected milliseconds like '400ms'"
+                )
+            }
+            HotkeyParseError::UnknownPressStyle(text) => write!(
+                f,
+                "unknown press style '{text}', expected 'tap', 'hold' or 'double tap'"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HotkeyParseError {}
+
+/// Modifier families in display order: `MOD_*` flag, left and right `MODVK_*` flags, name.
+const MODIFIERS: [(u32, u32, u32, &str); 4] = [
+    (MOD_CONTROL, MODVK_LCTRL, MODVK_RCTRL, "Ctrl"),
+    (MOD_ALT, MODVK_LALT, MODVK_RALT, "Alt"),
+    (MOD_SHIFT, MODVK_LSHIFT, MODVK_RSHIFT, "Shift"),
+    (MOD_WIN, MODVK_LWIN, MODVK_RWIN, "Win"),
+];
+
+const KEY_NAMES: [(u32, &str); 43] = [
+    (0x08, "Backspace"),
+    (0x09, "Tab"),
+    (0x0D, "Enter"),
+    (0x13, "Pause"),
+    (0x14, "CapsLock"),
+    (0x1B, "Esc"),
+    (0x20, "Space"),
+    (0x21, "PageUp"),
+    (0x22, "PageDown"),
+    (0x23, "End"),
+    (0x24, "Home"),
+    (0x25, "Left"),
+    (0x26, "Up"),
+    (0x27, "Right"),
+    (0x28, "Down"),
+    (0x2C, "PrintScreen"),
+    (0x2D, "Insert"),
+    (0x2E, "Delete"),
+    (0x5D, "Menu"),
+    (0x6A, "NumMultiply"),
+    (0x6B, "NumAdd"),
+    (0x6D, "NumSubtract"),
+    (0x6E, "NumDecimal"),
+    (0x6F, "NumDivide"),
+    (0x90, "NumLock"),
+    (0x91, "ScrollLock"),
+    (0xBA, "Semicolon"),
+    (0xBB, "Equals"),
+    (0xBC, "Comma"),
+    (0xBD, "Minus"),
+    (0xBE, "Period"),
+    (0xBF, "Slash"),
+    (0xC0, "Backquote"),
+    (0xDB, "LBracket"),
+    (0xDC, "Backslash"),
+    (0xDD, "RBracket"),
+    (0xDE, "Quote"),
+    (0xE2, "Oem102"),
+    // Aliases, read but never written.
+    (0x1B, "Escape"),
+    (0x0D, "Return"),
+    (0x2E, "Del"),
+    (0x21, "PgUp"),
+    (0x22, "PgDn"),
+];
+
+fn key_name(vk: u32) -> String {
+    match vk {
+        0x30..=0x39 | 0x41..=0x5A => char::from_u32(vk).map(String::from).unwrap_or_default(),
+        0x60..=0x69 => format!("Num{}", vk - 0x60),
+        0x70..=0x87 => format!("F{}", vk - 0x6F),
+        _ => KEY_NAMES
+            .iter()
+            .find(|(code, _)| *code == vk)
+            .map_or_else(|| format!("0x{vk:02X}"), |(_, name)| (*name).to_string()),
+    }
+}
+
+fn parse_key(name: &str) -> Option<u32> {
+    let upper = name.to_ascii_uppercase();
+
+    if let [c] = upper.as_bytes()
+        && c.is_ascii_alphanumeric()
+    {
+        return Some(u32::from(*c));
+    }
+    if let Some(hex) = upper.strip_prefix("0X") {
+        return u32::from_str_radix(hex, 16).ok();
+    }
+    if let Some(n) = upper
+        .strip_prefix("NUM")
+        .and_then(|d| d.parse::<u32>().ok())
+        && n <= 9
+    {
+        return Some(0x60 + n);
+    }
+    if let Some(n) = upper.strip_prefix('F').and_then(|d| d.parse::<u32>().ok())
+        && (1..=24).contains(&n)
+    {
+        return Some(0x6F + n);
+    }
+
+    KEY_NAMES
+        .iter()
+        .find(|(_, n)| n.eq_ignore_ascii_case(name))
+        .map(|(vk, _)| *vk)
+}
+
+/// Applies a modifier name to `chord`, returns `false` for other names.
+fn apply_modifier(chord: &mut HotkeyChord, name: &str) -> bool {
+    for (mod_bit, left, right, family) in MODIFIERS {
+        let side = if name.eq_ignore_ascii_case(family) {
+            0
+        } else if name.len() == family.len() + 1
+            && name
+                .get(1..)
+                .is_some_and(|rest| rest.eq_ignore_ascii_case(family))
+        {
+            match name.as_bytes()[0].to_ascii_uppercase() {
+                b'L' => left,
+                b'R' => right,
+                _ => continue,
+            }
+        } else {
+            continue;
+        };
+
+        chord.mods |= mod_bit;
+        chord.mods_vks |= side;
+        return true;
+    }
+    false
+}
+
+fn parse_ms(text: &str) -> Result<u32, HotkeyParseError> {
+    text.trim()
+        .strip_suffix("ms")
+        .and_then(|n| n.trim().parse().ok())
+        .ok_or_else(|| HotkeyParseError::BadDuration(text.trim().to_string()))
+}
+
+impl fmt::Display for PressStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PressStyle::Press => Ok(()),
+            PressStyle::Tap { max_ms } => write!(f, "tap {max_ms}ms"),
+            PressStyle::Hold { min_ms } => write!(f, "hold {min_ms}ms"),
+            PressStyle::DoubleTap { max_gap_ms } => write!(f, "double tap {max_gap_ms}ms"),
+        }
+    }
+}
+
+impl FromStr for PressStyle {
+    type Err = HotkeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let lower = s.to_ascii_lowercase();
+        let unknown = || HotkeyParseError::UnknownPressStyle(s.to_string());
+
+        if let Some(rest) = lower.strip_prefix("double tap") {
+            return Ok(PressStyle::DoubleTap {
+                max_gap_ms: parse_ms(rest)?,
+            });
+        }
+        if let Some(rest) = lower.strip_prefix("tap") {
+            return Ok(PressStyle::Tap {
+                max_ms: parse_ms(rest)?,
+            });
+        }
+        if let Some(rest) = lower.strip_prefix("hold") {
+            return Ok(PressStyle::Hold {
+                min_ms: parse_ms(rest)?,
+            });
+        }
+        Err(unknown())
+    }
+}
+
+impl fmt::Display for HotkeyChord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = Vec::new();
+
+        for (mod_bit, left, right, family) in MODIFIERS {
+            let sides = self.mods_vks & (left | right);
+            if sides == 0 {
+                if self.mods & mod_bit != 0 {
+                    parts.push(family.to_string());
+                }
+                continue;
+            }
+            if sides & left != 0 {
+                parts.push(format!("L{family}"));
+            }
+            if sides & right != 0 {
+                parts.push(format!("R{family}"));
+            }
+        }
+
+        if let Some(vk) = self.vk {
+            parts.push(key_name(vk));
+        }
+
+        if parts.is_empty() {
+            write!(f, "None")?;
+        } else {
+            write!(f, "{}", parts.join("+"))?;
+        }
+
+        if !self.press.is_press() {
+            write!(f, " ({})", self.press)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for HotkeyChord {
+    type Err = HotkeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (keys, press) = match s.strip_suffix(')').and_then(|r| r.rsplit_once('(')) {
+            Some((keys, style)) => (keys.trim(), style.parse()?),
+            None => (s, PressStyle::Press),
+        };
+
+        let mut chord = HotkeyChord {
+            mods: 0,
+            mods_vks: 0,
+            vk: None,
+            press,
+        };
+
+        if keys.eq_ignore_ascii_case("None") {
+            return Ok(chord);
+        }
+        if keys.is_empty() {
+            return Err(HotkeyParseError::EmptyChord);
+        }
+
+        for part in keys.split('+').map(str::trim) {
+            if part.is_empty() {
+                return Err(HotkeyParseError::EmptyChord);
+            }
+            if apply_modifier(&mut chord, part) {
+                continue;
+            }
+            let vk =
+                parse_key(part).ok_or_else(|| HotkeyParseError::UnknownKey(part.to_string()))?;
+            if chord.vk.replace(vk).is_some() {
+                return Err(HotkeyParseError::SeveralKeys(keys.to_string()));
+            }
+        }
+
+        Ok(chord)
+    }
+}
+
+impl fmt::Display for HotkeySequence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uniform_gap = self
+            .steps
+            .first()
+            .map(|s| s.max_gap_ms)
+            .filter(|&gap| self.steps.iter().all(|s| s.max_gap_ms == gap));
+
+        for (i, step) in self.steps.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", step.chord)?;
+            if uniform_gap.is_none() {
+                write!(f, " within {}ms", step.max_gap_ms)?;
+            }
+        }
+
+        if let Some(gap) = uniform_gap
+            && gap != DEFAULT_GAP_MS
+        {
+            write!(f, " within {gap}ms")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for HotkeySequence {
+    type Err = HotkeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Ok(HotkeySequence { steps: Vec::new() });
+        }
+
+        let mut chords = Vec::new();
+        let mut gaps = Vec::new();
+        for step in s.split(',') {
+            let (chord, gap) = match step.rsplit_once(" within ") {
+                Some((chord, gap)) => (chord, Some(parse_ms(gap)?)),
+                None => (step, None),
+            };
+            chords.push(chord.parse::<HotkeyChord>()?);
+            gaps.push(gap);
+        }
+
+        // A gap only after the last chord applies to every step.
+        let shared = match gaps.split_last() {
+            Some((Some(gap), rest)) if rest.iter().all(Option::is_none) => Some(*gap),
+            _ => None,
+        };
+
+        let steps = chords
+            .into_iter()
+            .zip(gaps)
+            .map(|(chord, gap)| HotkeyStep {
+                chord,
+                max_gap_ms: shared.or(gap).unwrap_or(DEFAULT_GAP_MS),
+            })
+            .collect();
+
+        Ok(HotkeySequence { steps })
+    }
+}
@@ -0,0 +1,92 @@
+//! Hotkey fields of configs written before the action registry.
+//!
+//! Each action had a `hotkey_<name>_sequence` field and an older single chord
+//! `hotkey_<name>` field registered with `RegisterHotKey`. Both are moved into the
+//! `hotkeys` table under the action id; the sequence wins when both are set.
+
+use serde::Deserialize;
+
+use crate::{
+    app::actions::{
+        ActionId, CONVERT_LAST_WORD, CONVERT_SELECTION, PAUSE, SWITCH_LAYOUT, UNDO_LAST_CONVERSION,
+    },
+    config::{DEFAULT_GAP_MS, HotkeyChord, HotkeySequence, PressStyle},
+};
+
+const HOTKEYS_KEY: &str = "hotkeys";
+
+/// Sequence field, single chord field and action of every legacy hotkey.
+const LEGACY_FIELDS: [(&str, &str, ActionId); 5] = [
+    (
+        "hotkey_convert_last_word_sequence",
+        "hotkey_convert_last_word",
+        CONVERT_LAST_WORD,
+    ),
+    ("hotkey_pause_sequence", "hotkey_pause", PAUSE),
+    (
+        "hotkey_convert_selection_sequence",
+        "hotkey_convert_selection",
+        CONVERT_SELECTION,
+    ),
+    (
+        "hotkey_switch_layout_sequence",
+        "hotkey_switch_layout",
+        SWITCH_LAYOUT,
+    ),
+    (
+        "hotkey_undo_last_conversion_sequence",
+        "hotkey_undo_last_conversion",
+        UNDO_LAST_CONVERSION,
+    ),
+];
+
+/// A single chord hotkey; `vk` is 0 for a chord of modifiers only.
+#[derive(Deserialize)]
+struct Hotkey {
+    vk: u32,
+    mods: u32,
+}
+
+fn hotkey_to_sequence(value: toml::Value) -> Option<toml::Value> {
+    let hk: Hotkey = value.try_into().ok()?;
+    let chord = HotkeyChord {
+        mods: hk.mods,
+        mods_vks: 0,
+        vk: (hk.vk != 0).then_some(hk.vk),
+        press: PressStyle::Press,
+    };
+    let seq = HotkeySequence::new(&[chord], DEFAULT_GAP_MS);
+    Some(toml::Value::String(seq.to_string()))
+}
+
+/// Moves the legacy hotkey fields of `table` into its `hotkeys` table.
+///
+/// An action with no legacy field was unbound in such a config and stays unbound.
+/// Entries already in `hotkeys` are kept.
+pub(super) fn migrate(table: &mut toml::Table) {
+    let is_legacy = LEGACY_FIELDS
+        .iter()
+        .any(|(seq, single, _)| table.contains_key(*seq) || table.contains_key(*single));
+    if !is_legacy {
+        return;
+    }
+
+    let mut moved = toml::Table::new();
+    for (seq_key, single_key, id) in LEGACY_FIELDS {
+        let seq = table.remove(seq_key);
+        let single = table.remove(single_key).and_then(hotkey_to_sequence);
+        let value = seq
+            .or(single)
+            .unwrap_or_else(|| toml::Value::String(String::new()));
+        moved.insert(id.as_str().to_string(), value);
+    }
+
+    let hotkeys = table
+        .entry(HOTKEYS_KEY)
+        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+    if let toml::Value::Table(hotkeys) = hotkeys {
+        for (key, value) in moved {
+            hotkeys.entry(key).or_insert(value);
+        }
+    }
+}
@@ -100,6 +100,46 @@ pub fn get_unicode_text() -> Option<String> {
     }
 }
 
+/// Replaces the clipboard content with `text` as `CF_UNICODETEXT`.
+///
+/// Returns `false` if the clipboard cannot be opened or the data cannot be set.
+pub fn set_unicode_text(text: &str) -> bool {
+    let units: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+    let bytes = units.len() * std::mem::size_of::<u16>();
+
+    let Some(_clip) = ClipboardGuard::open() else {
+        return false;
+    };
+    unsafe {
+        let _ = EmptyClipboard();
+    }
+
+    let Ok(hmem) = (unsafe { GlobalAlloc(GMEM_MOVEABLE, bytes) }) else {
+        return false;
+    };
+    let mut mem = GlobalMem::new(hmem);
+
+    let ptr = unsafe { GlobalLock(hmem) };
+    if ptr.is_null() {
+        return false;
+    }
+    unsafe {
+        std::ptr::copy_nonoverlapping(units.as_ptr(), ptr.cast(), units.len());
+    }
+    let _ = unsafe { GlobalUnlock(hmem) };
+
+    match unsafe { SetClipboardData(CF_UNICODETEXT_ID, Some(HANDLE(hmem.0))) } {
+        Ok(_) => {
+            mem.disarm();
+            true
+        }
+        Err(e) => {
+            tracing::warn!(error = ?e, "SetClipboardData failed");
+            false
+        }
+    }
+}
+
 struct GlobalMem {
     handle: HGLOBAL,
     owned: bool,
@@ -1,18 +1,32 @@
+use std::{thread, time::Duration};
+
 use windows::Win32::UI::Input::KeyboardAndMouse::{
-    INPUT, INPUT_0, INPUT_KEYBOARD, KEYBD_EVENT_FLAGS, KEYBDINPUT, KEYEVENTF_KEYUP, SendInput,
-    VIRTUAL_KEY, VK_CONTROL,
+    GetKeyState, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBD_EVENT_FLAGS, KEYBDINPUT,
+    KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP, SendInput, VIRTUAL_KEY, VK_CAPITAL, VK_CONTROL,
+};
+
+use super::clipboard as clip;
+use crate::{
+    config::ReplacementStrategy,
+    domain::text::selection::{
+        CHUNK_UTF16_UNITS, caret_steps, clipboard_line_endings, has_line_break, runs,
+    },
 };
 
+/// Virtual key code for the V key, pasted with Ctrl.
+const VK_V_KEY: VIRTUAL_KEY = VIRTUAL_KEY(0x56);
+
+/// Time the target window gets to read a paste before the clipboard is restored.
+const PASTE_SETTLE_MS: u64 = 50;
+
+/// Time the target window gets to process one chunk before the next one is sent.
+const CHUNK_PAUSE_MS: u64 = 5;
+
 /// Virtual key code for the Left Arrow key.
 ///
-/// Used to move the caret left when selecting previously inserted text.
+/// Used with Shift to select previously inserted text.
 const VK_LEFT_KEY: VIRTUAL_KEY = VIRTUAL_KEY(0x25);
 
-/// Virtual key code for the Right Arrow key.
-///
-/// Used to move the caret right when selecting previously inserted text.
-const VK_RIGHT_KEY: VIRTUAL_KEY = VIRTUAL_KEY(0x27);
-
 /// Virtual key code for the Shift key.
 ///
 /// Used as a selection modifier.
@@ -111,30 +125,118 @@ pub fn send_text_unicode(text: &str) -> bool {
     sent == inputs.len()
 }
 
-/// Reselects the last inserted text by moving the caret left and selecting right.
+/// Types `text` into the foreground window with `strategy`.
 ///
-/// `units` is the number of UTF-16 code units to reselect. This matches the unit
-/// count produced by `str::encode_utf16()`.
+/// Text with line breaks is pasted whatever the strategy, see `has_line_break`.
 ///
-/// Behavior:
-/// - Move caret left `units` times
-/// - Hold Shift
-/// - Move caret right `units` times (selecting the range)
+/// Returns `true` if all input was sent.
+pub fn insert_text(text: &str, strategy: ReplacementStrategy) -> bool {
+    if has_line_break(text) {
+        return paste_text(text);
+    }
+    match strategy {
+        ReplacementStrategy::Unicode => type_text_chunked(text),
+        ReplacementStrategy::Clipboard => paste_text(text),
+    }
+}
+
+/// Types the single line `text` in chunks, pausing between them so a long text does
+/// not stall the target window.
+fn type_text_chunked(text: &str) -> bool {
+    runs(text, CHUNK_UTF16_UNITS)
+        .into_iter()
+        .enumerate()
+        .all(|(i, run)| {
+            if i > 0 {
+                thread::sleep(Duration::from_millis(CHUNK_PAUSE_MS));
+            }
+            send_text_unicode(run)
+        })
+}
+
+/// Whether Caps Lock is toggled on for the calling thread.
+pub fn caps_lock_on() -> bool {
+    let state = unsafe { GetKeyState(i32::from(VK_CAPITAL.0)) };
+    state & 1 != 0
+}
+
+/// Turns Caps Lock off when it is on.
 ///
-/// The selection length is capped to avoid excessive key events.
-pub fn reselect_last_inserted_text_utf16_units(units: usize) -> bool {
-    const MAX_UNITS: usize = 4096;
+/// Returns `true` if it is off afterwards.
+pub fn caps_lock_off() -> bool {
+    !caps_lock_on() || KeySequence::tap(VK_CAPITAL)
+}
+
+/// Pastes `text` with Ctrl+V, then puts the previous clipboard content back.
+fn paste_text(text: &str) -> bool {
+    if text.is_empty() {
+        return true;
+    }
+
+    let saved = clip::snapshot();
+    let pasted = clip::set_unicode_text(&clipboard_line_endings(text)) && send_ctrl_combo(VK_V_KEY);
+    thread::sleep(Duration::from_millis(PASTE_SETTLE_MS));
+    if let Some(saved) = saved
+        && !clip::restore_snapshot(&saved)
+    {
+        tracing::warn!("clipboard restore after paste failed");
+    }
+    pasted
+}
 
-    if units == 0 {
+/// Selects `text`, just inserted before the caret, with Shift+Left.
+///
+/// Presses Left once per caret step of `text`, counting each line break as one step,
+/// so the selection spans lines. The presses are sent in chunks like typed text.
+pub fn reselect_inserted_text(text: &str) -> bool {
+    let steps = caret_steps(text);
+    if steps == 0 {
         return true;
     }
 
-    let units = units.min(MAX_UNITS);
     let mut seq = KeySequence::new();
+    seq.down(VK_SHIFT_KEY) && send_taps_chunked(VK_LEFT_KEY, steps)
+}
+
+/// Taps the extended key `vk` `count` times, `CHUNK_UTF16_UNITS` taps per `SendInput` call.
+///
+/// The extended flag keeps arrow keys from being read as numpad keys while Shift is held.
+fn send_taps_chunked(vk: VIRTUAL_KEY, count: usize) -> bool {
+    let Some(input_size) = input_struct_size_i32() else {
+        return false;
+    };
 
-    (0..units).all(|_| KeySequence::tap(VK_LEFT_KEY))
-        && seq.down(VK_SHIFT_KEY)
-        && (0..units).all(|_| KeySequence::tap(VK_RIGHT_KEY))
+    let key = |flags| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: 0,
+                dwFlags: KEYEVENTF_EXTENDEDKEY | flags,
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    };
+    let tap = [key(KEYBD_EVENT_FLAGS::default()), key(KEYEVENTF_KEYUP)];
+
+    let mut left = count;
+    let mut first = true;
+    while left > 0 {
+        if !first {
+            thread::sleep(Duration::from_millis(CHUNK_PAUSE_MS));
+        }
+        first = false;
+
+        let n = left.min(CHUNK_UTF16_UNITS);
+        let inputs: Vec<INPUT> = (0..n).flat_map(|_| tap).collect();
+        let sent = unsafe { SendInput(&inputs, input_size) } as usize;
+        if sent != inputs.len() {
+            return false;
+        }
+        left -= n;
+    }
+    true
 }
 
 fn input_struct_size_i32() -> Option<i32> {
@@ -2,6 +2,9 @@ pub mod clipboard;
 pub mod input;
 
 pub use crate::domain::text::{
-    convert::{convert_selection, convert_selection_if_any},
-    last_word::convert_last_word,
+    convert::{convert_selection, convert_selection_if_any, transform_selection_if_any},
+    history::ConversionKind,
+    last_word::{convert_last_phrase, convert_last_word, convert_last_words, transform_last_word},
+    mapping::ConvertDirection,
+    undo::undo_last_conversion,
 };
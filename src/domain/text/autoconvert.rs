@@ -0,0 +1,334 @@
+//! Per word autoconvert decision.
+//!
+//! The last word pipeline extracts the word before the caret and converts it to the other
+//! layout, then an `AutoconvertDecider` tells whether the conversion should replace the word.
+//! Nothing here depends on the platform, so deciders can be tested and swapped anywhere.
+
+use std::{
+    str::FromStr,
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+use lingua::{IsoCode639_1, Language, LanguageDetector, LanguageDetectorBuilder};
+
+use super::{
+    layout::LayoutPair,
+    script::{
+        is_plausible_cyrillic_like_token, is_plausible_latin_like_token, looks_like_cyrillic_word,
+        looks_like_latin_word,
+    },
+};
+
+/// Shortest word `HeuristicDecider` scores, lingua is unreliable below it.
+pub const MIN_WORD_LEN: usize = 4;
+const MIN_CONVERTED_CONFIDENCE: f64 = 0.70;
+const MIN_CONFIDENCE_GAIN: f64 = 0.25;
+const MIN_CONVERTED_LATIN_CONF_FOR_OVERRIDE: f64 = 0.80;
+
+/// Why a word was left as typed.
+///
+/// The first variants are reported by the last word pipeline, the user dictionary and
+/// the learned exceptions before a decider runs, the rest by `HeuristicDecider`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    Reentry,
+    SuffixHasNewline,
+    NotAWord,
+    NoChangeAfterConvert,
+    NoLanguageModel,
+    UserNeverConvert,
+    LearnedException,
+    TooShort,
+    ScriptCheckFailed,
+    AlreadyCorrect,
+    ConvertedConfidenceLow,
+    NotBetterEnough,
+    NoCaseError,
+    CaseAbbreviation,
+}
+
+impl SkipReason {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SkipReason::Reentry => "reentry",
+            SkipReason::SuffixHasNewline => "suffix_has_newline",
+            SkipReason::NotAWord => "not_a_word",
+            SkipReason::NoChangeAfterConvert => "no_change_after_convert",
+            SkipReason::NoLanguageModel => "no_language_model",
+            SkipReason::UserNeverConvert => "user_never_convert",
+            SkipReason::LearnedException => "learned_exception",
+            SkipReason::TooShort => "too_short",
+            SkipReason::ScriptCheckFailed => "script_check_failed",
+            SkipReason::AlreadyCorrect => "already_correct",
+            SkipReason::ConvertedConfidenceLow => "converted_confidence_low",
+            SkipReason::NotBetterEnough => "not_better_enough",
+            SkipReason::NoCaseError => "no_case_error",
+            SkipReason::CaseAbbreviation => "case_abbreviation",
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Replace the typed word with its conversion.
+    Convert,
+    /// Keep the typed word.
+    Skip,
+}
+
+/// Language confidences of the typed and the converted word, each in `0.0..=1.0`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Scores {
+    pub word_latin: f64,
+    pub word_cyrillic: f64,
+    pub converted_latin: f64,
+    pub converted_cyrillic: f64,
+}
+
+/// Outcome of an `AutoconvertDecider`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Decision {
+    pub action: Action,
+    /// Set when `action` is `Skip`.
+    pub reason: Option<SkipReason>,
+    /// `None` when the decision was made before scoring, for example for short words.
+    pub scores: Option<Scores>,
+}
+
+impl Decision {
+    pub fn convert(scores: Option<Scores>) -> Self {
+        Self {
+            action: Action::Convert,
+            reason: None,
+            scores,
+        }
+    }
+
+    pub fn skip(reason: SkipReason, scores: Option<Scores>) -> Self {
+        Self {
+            action: Action::Skip,
+            reason: Some(reason),
+            scores,
+        }
+    }
+
+    pub fn is_convert(&self) -> bool {
+        self.action == Action::Convert
+    }
+}
+
+/// Decides whether a typed word should be replaced with its layout conversion.
+///
+/// `word` and `converted` may end with punctuation typed on the same keys
+/// (see `split_trailing_convertible_punct`).
+pub trait AutoconvertDecider {
+    fn decide(&self, word: &str, converted: &str) -> Decision;
+}
+
+/// Scores how likely a text is written in a language.
+pub trait LanguageScorer {
+    /// Returns a confidence in `0.0..=1.0`.
+    fn confidence(&self, text: &str, lang: Language) -> f64;
+}
+
+impl LanguageScorer for LanguageDetector {
+    fn confidence(&self, text: &str, lang: Language) -> f64 {
+        self.compute_language_confidence_values(text)
+            .iter()
+            .find(|(l, _)| *l == lang)
+            .map_or(0.0, |(_, v)| *v)
+    }
+}
+
+/// Languages of the Latin and the Cyrillic layout of a pair.
+///
+/// The autoconvert heuristics compare a word in one script with its conversion in the other,
+/// so only pairs with one Latin and one Cyrillic layout are supported.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PairLanguages {
+    pub latin: Language,
+    pub cyrillic: Language,
+}
+
+/// Resolves the languages of a pair from the layout tags.
+///
+/// Returns `None` when a language has no compiled in model (see the `lang-*` features) or both
+/// layouts use the same script.
+pub fn pair_languages(pair: &LayoutPair) -> Option<PairLanguages> {
+    let primary = tag_language(pair.primary().tag())?;
+    let secondary = tag_language(pair.secondary().tag())?;
+
+    let cyrillic = Language::all_with_cyrillic_script();
+    match (cyrillic.contains(&primary), cyrillic.contains(&secondary)) {
+        (false, true) => Some(PairLanguages {
+            latin: primary,
+            cyrillic: secondary,
+        }),
+        (true, false) => Some(PairLanguages {
+            latin: secondary,
+            cyrillic: primary,
+        }),
+        _ => None,
+    }
+}
+
+fn tag_language(tag: &str) -> Option<Language> {
+    let code = tag.split(['-', '_']).next()?;
+    IsoCode639_1::from_str(code)
+        .ok()
+        .map(|iso| Language::from_iso_code_639_1(&iso))
+}
+
+/// Returns a detector for the languages of a pair.
+///
+/// Detectors are built once per language set and kept for the process lifetime,
+/// there are only as many of them as layout pairs.
+pub fn language_detector(langs: PairLanguages) -> &'static LanguageDetector {
+    type Cache = Vec<(PairLanguages, &'static LanguageDetector)>;
+    static DETECTORS: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+    let mut cache = DETECTORS
+        .get_or_init(|| Mutex::new(Vec::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    if let Some((_, detector)) = cache.iter().find(|(l, _)| *l == langs) {
+        return detector;
+    }
+
+    let detector: &'static LanguageDetector = Box::leak(Box::new(
+        LanguageDetectorBuilder::from_languages(&[langs.latin, langs.cyrillic])
+            .with_minimum_relative_distance(0.20)
+            .build(),
+    ));
+    cache.push((langs, detector));
+    detector
+}
+
+/// Returns the decider of a pair.
+///
+/// With the `frequency-scorer` feature, words shorter than `MIN_WORD_LEN` go to
+/// `ShortWordDecider` when the pair has frequency lists. Returns `None` when the pair
+/// has no usable languages (see `pair_languages`).
+pub fn decider_for_pair(pair: &LayoutPair) -> Option<Box<dyn AutoconvertDecider>> {
+    let heuristic = HeuristicDecider::for_pair(pair)?;
+
+    #[cfg(feature = "frequency-scorer")]
+    if let Some(short) = super::frequency::ShortWordDecider::for_pair(pair) {
+        return Some(Box::new(super::frequency::ShortWordRouter::new(
+            short, heuristic,
+        )));
+    }
+
+    Some(Box::new(heuristic))
+}
+
+/// Default decider: script checks, plausibility guards and language confidence.
+///
+/// A word is converted when it is not a plausible word of its own script and its
+/// conversion scores clearly better in the other language.
+pub struct HeuristicDecider<'a, S: ?Sized = LanguageDetector> {
+    scorer: &'a S,
+    langs: PairLanguages,
+}
+
+impl HeuristicDecider<'static> {
+    /// Builds a decider with the shared lingua detector of a pair.
+    ///
+    /// Returns `None` when the pair has no usable languages (see `pair_languages`).
+    pub fn for_pair(pair: &LayoutPair) -> Option<Self> {
+        let langs = pair_languages(pair)?;
+        Some(Self::new(language_detector(langs), langs))
+    }
+}
+
+impl<'a, S: LanguageScorer + ?Sized> HeuristicDecider<'a, S> {
+    pub fn new(scorer: &'a S, langs: PairLanguages) -> Self {
+        Self { scorer, langs }
+    }
+}
+
+impl<S: LanguageScorer + ?Sized> AutoconvertDecider for HeuristicDecider<'_, S> {
+    fn decide(&self, word: &str, converted: &str) -> Decision {
+        let (word_core, _word_punct) = split_trailing_convertible_punct(word);
+        let (conv_core, _conv_punct) = split_trailing_convertible_punct(converted);
+
+        if word_core.chars().count() < MIN_WORD_LEN {
+            return Decision::skip(SkipReason::TooShort, None);
+        }
+
+        let w_is_lat = looks_like_latin_word(word_core);
+        let w_is_cyr = looks_like_cyrillic_word(word_core);
+        let c_is_lat = looks_like_latin_word(conv_core);
+        let c_is_cyr = looks_like_cyrillic_word(conv_core);
+
+        if !(w_is_lat || w_is_cyr) || !(c_is_lat || c_is_cyr) {
+            return Decision::skip(SkipReason::ScriptCheckFailed, None);
+        }
+
+        let scores = Scores {
+            word_latin: self.scorer.confidence(word_core, self.langs.latin),
+            word_cyrillic: self.scorer.confidence(word_core, self.langs.cyrillic),
+            converted_latin: self.scorer.confidence(conv_core, self.langs.latin),
+            converted_cyrillic: self.scorer.confidence(conv_core, self.langs.cyrillic),
+        };
+        let skip = |reason| Decision::skip(reason, Some(scores));
+
+        // Keep the Latin guard: do not convert real English or German words to Cyrillic.
+        if w_is_lat && is_plausible_latin_like_token(word_core) {
+            return skip(SkipReason::AlreadyCorrect);
+        }
+
+        // Cyrillic guard is conditional: if conversion yields a strong Latin candidate, do not short circuit.
+        if w_is_cyr && is_plausible_cyrillic_like_token(word_core) {
+            let converted_looks_latin = is_plausible_latin_like_token(conv_core)
+                && scores.converted_latin >= MIN_CONVERTED_LATIN_CONF_FOR_OVERRIDE;
+            if !converted_looks_latin {
+                return skip(SkipReason::AlreadyCorrect);
+            }
+        }
+
+        let w_best = scores.word_cyrillic.max(scores.word_latin);
+        let c_best = scores.converted_cyrillic.max(scores.converted_latin);
+
+        let (w_in_target, c_in_target) = if w_is_lat {
+            (scores.word_cyrillic, scores.converted_cyrillic)
+        } else {
+            (scores.word_latin, scores.converted_latin)
+        };
+
+        if c_best < MIN_CONVERTED_CONFIDENCE {
+            return skip(SkipReason::ConvertedConfidenceLow);
+        }
+
+        let min_abs = if w_best < 0.30 {
+            0.55
+        } else {
+            MIN_CONVERTED_CONFIDENCE
+        };
+        if c_in_target < min_abs {
+            return skip(SkipReason::ConvertedConfidenceLow);
+        }
+
+        if c_in_target - w_in_target < MIN_CONFIDENCE_GAIN {
+            return skip(SkipReason::NotBetterEnough);
+        }
+
+        Decision::convert(Some(scores))
+    }
+}
+
+/// Splits punctuation typed on the same keys in both layouts off the end of a word.
+pub fn split_trailing_convertible_punct(s: &str) -> (&str, &str) {
+    let bytes = s.as_bytes();
+    let mut i = bytes.len();
+
+    while i > 0 {
+        match bytes[i - 1] {
+            b'?' | b'/' | b',' | b'.' => i -= 1,
+            _ => break,
+        }
+    }
+
+    s.split_at(i)
+}
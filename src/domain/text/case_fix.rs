@@ -0,0 +1,95 @@
+//! Case errors of a typed word, fixed by autoconvert: Caps Lock left on (`hELLO`), or
+//! Shift held over the second letter (`HEllo`).
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    autoconvert::SkipReason,
+    transform::{CaseTransform, TextTransform},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseError {
+    /// Typed with Caps Lock on, Shift pressed for the first letter.
+    CapsLock,
+    /// Shift released one letter late.
+    TwoCapitals,
+}
+
+/// A word with its case error fixed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CaseFix {
+    pub error: CaseError,
+    pub word: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CaseFixSettings {
+    /// Whether autoconvert fixes case errors.
+    pub enabled: bool,
+    /// Whether Caps Lock is turned off after fixing a word typed with it on.
+    pub turn_off_caps_lock: bool,
+}
+
+impl Default for CaseFixSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            turn_off_caps_lock: true,
+        }
+    }
+}
+
+/// Lower case letters after two capitals below which a word is taken for an
+/// abbreviation, such as `PCs`.
+const MIN_TWO_CAPITALS_TAIL: usize = 2;
+
+/// Finds a case error in `word` and fixes it. Only letters are looked at.
+///
+/// Names such as `iOS` look like Caps Lock words, so a Caps Lock error is reported only
+/// while `caps_lock_on`.
+pub fn detect(word: &str, caps_lock_on: bool) -> Result<CaseFix, SkipReason> {
+    let letters: Vec<char> = word.chars().filter(|c| c.is_alphabetic()).collect();
+    let [first, second, rest @ ..] = letters.as_slice() else {
+        return Err(SkipReason::TooShort);
+    };
+
+    if caps_lock_on && first.is_lowercase() && letters[1..].iter().all(|c| c.is_uppercase()) {
+        return Ok(CaseFix {
+            error: CaseError::CapsLock,
+            word: CaseTransform::Invert.apply(word),
+        });
+    }
+
+    if first.is_uppercase()
+        && second.is_uppercase()
+        && !rest.is_empty()
+        && rest.iter().all(|c| c.is_lowercase())
+    {
+        if rest.len() < MIN_TWO_CAPITALS_TAIL {
+            return Err(SkipReason::CaseAbbreviation);
+        }
+        return Ok(CaseFix {
+            error: CaseError::TwoCapitals,
+            word: lower_second_letter(word),
+        });
+    }
+
+    Err(SkipReason::NoCaseError)
+}
+
+fn lower_second_letter(word: &str) -> String {
+    let mut letters = 0;
+    word.chars()
+        .map(|ch| {
+            if ch.is_alphabetic() {
+                letters += 1;
+                if letters == 2 {
+                    return ch.to_lowercase().to_string();
+                }
+            }
+            ch.to_string()
+        })
+        .collect()
+}
@@ -1,9 +1,9 @@
-use std::{ptr::null_mut, thread, time::Duration};
+use std::{ptr::null_mut, sync::Arc, thread, time::Duration};
 
-use mapping::convert_ru_en_bidirectional;
 use windows::Win32::{
     Foundation::{HWND, LPARAM, WPARAM},
-    System::DataExchange::GetClipboardSequenceNumber,
+    Globalization::LCIDToLocaleName,
+    System::{DataExchange::GetClipboardSequenceNumber, SystemServices::LOCALE_NAME_MAX_LENGTH},
     UI::{
         Input::KeyboardAndMouse::{
             GetAsyncKeyState, GetKeyboardLayout, GetKeyboardLayoutList, HKL, VIRTUAL_KEY,
@@ -15,20 +15,24 @@ use windows::Win32::{
     },
 };
 
-use super::mapping;
+use super::{
+    history::{ConversionKind, ConversionRecord, UndoRefusal, with_history},
+    layout::Layout,
+    layout_policy::{InstalledLayout, LayoutTarget, RecentLayouts, choose_layout, next_layout},
+    mapping::{self, ConvertDirection},
+    selection::is_convertible_selection,
+    transform::{LayoutSwap, TextTransform},
+};
 use crate::{
     app::AppState,
+    config::ReplacementStrategy,
     conversion::{
         clipboard as clip,
-        input::{
-            KeySequence, reselect_last_inserted_text_utf16_units, send_ctrl_combo,
-            send_text_unicode,
-        },
+        input::{KeySequence, insert_text, reselect_inserted_text, send_ctrl_combo},
     },
+    platform::win::{app_rules, keyboard_layouts::layout_for_hkl},
 };
 
-const MAX_SELECTION_CHARS: usize = 512;
-
 /// Virtual key code for the `C` key.
 ///
 /// Used together with Ctrl to trigger the standard Copy shortcut.
@@ -48,7 +52,7 @@ const VK_DELETE_KEY: VIRTUAL_KEY = VIRTUAL_KEY(0x2E);
 /// - restores previous clipboard contents via `ClipboardRestore`
 ///
 /// Return value:
-/// - `None` if there is no eligible selection (empty, multiline, too long, or clipboard did not change)
+/// - `None` if there is no eligible selection (empty, too long, or clipboard did not change)
 /// - `Some(Ok(()))` if selection was converted successfully
 /// - `Some(Err(ConvertSelectionError))` if selection was present but the conversion pipeline failed
 ///
@@ -58,10 +62,11 @@ const VK_DELETE_KEY: VIRTUAL_KEY = VIRTUAL_KEY(0x2E);
 fn try_convert_selection_from_clipboard(
     state: &mut AppState,
     max_chars: usize,
+    transform: &dyn TextTransform,
 ) -> Option<std::result::Result<(), ConvertSelectionError>> {
     copy_selection_text_with_clipboard_restore(max_chars).map(|s| {
         tracing::trace!(len = s.chars().count(), "selection detected");
-        convert_selection_from_text(state, &s)
+        convert_selection_from_text(state, &s, transform)
     })
 }
 
@@ -70,8 +75,16 @@ fn try_convert_selection_from_clipboard(
 /// Returns `true` if a non empty eligible selection was found (conversion attempted),
 /// otherwise `false`.
 #[tracing::instrument(level = "trace", skip(state))]
-pub fn convert_selection_if_any(state: &mut AppState) -> bool {
-    match convert_selection_outcome(state, MAX_SELECTION_CHARS) {
+pub fn convert_selection_if_any(state: &mut AppState, direction: ConvertDirection) -> bool {
+    transform_selection_if_any(state, &LayoutSwap(direction))
+}
+
+/// Replaces the selection with `transform` applied to it, if there is any selection.
+///
+/// Returns `true` if a non empty eligible selection was found, see `convert_selection_if_any`.
+pub fn transform_selection_if_any(state: &mut AppState, transform: &dyn TextTransform) -> bool {
+    let max_chars = state.config.max_selection_chars;
+    match convert_selection_outcome(state, max_chars, transform) {
         ConvertOutcome::Noop => false,
         ConvertOutcome::Ok => true,
         ConvertOutcome::Err(e) => {
@@ -81,7 +94,7 @@ pub fn convert_selection_if_any(state: &mut AppState) -> bool {
     }
 }
 
-pub fn convert_selection(state: &mut AppState) {
+pub fn convert_selection(state: &mut AppState, direction: ConvertDirection) {
     tracing::trace!("convert_selection called");
     let fg = unsafe { GetForegroundWindow() };
     if fg.0.is_null() {
@@ -94,7 +107,8 @@ pub fn convert_selection(state: &mut AppState) {
         return;
     }
 
-    match convert_selection_outcome(state, MAX_SELECTION_CHARS) {
+    let max_chars = state.config.max_selection_chars;
+    match convert_selection_outcome(state, max_chars, &LayoutSwap(direction)) {
         ConvertOutcome::Noop => tracing::trace!("no selection"),
         ConvertOutcome::Ok => {}
         ConvertOutcome::Err(e) => {
@@ -116,8 +130,12 @@ enum ConvertOutcome {
 /// Attempts to convert selection and returns a high level outcome.
 ///
 /// This function does not perform UI safety checks.
-fn convert_selection_outcome(state: &mut AppState, max_chars: usize) -> ConvertOutcome {
-    match try_convert_selection_from_clipboard(state, max_chars) {
+fn convert_selection_outcome(
+    state: &mut AppState,
+    max_chars: usize,
+    transform: &dyn TextTransform,
+) -> ConvertOutcome {
+    match try_convert_selection_from_clipboard(state, max_chars, transform) {
         None => ConvertOutcome::Noop,
         Some(Ok(())) => ConvertOutcome::Ok,
         Some(Err(e)) => ConvertOutcome::Err(e),
@@ -126,7 +144,7 @@ fn convert_selection_outcome(state: &mut AppState, max_chars: usize) -> ConvertO
 
 /// Errors that can occur while replacing the current selection with converted text.
 #[derive(Debug)]
-enum ConvertSelectionError {
+pub(crate) enum ConvertSelectionError {
     /// Failed to send Delete to remove the current selection.
     Delete,
     /// Failed to inject Unicode text via `SendInput`.
@@ -145,48 +163,96 @@ impl ConvertSelectionError {
     }
 }
 
-/// Replaces currently selected text with layout converted text.
+/// Replaces currently selected text with `transform` applied to it.
 ///
 /// Returns `Ok(())` when:
 /// - Delete tap succeeded
 /// - Unicode injection succeeded
 /// - reselect succeeded within retry budget
 ///
-/// Keyboard layout switching is best effort and does not affect the result.
+/// Keyboard layout switching, for transforms that switch it, is best effort and does
+/// not affect the result.
 fn convert_selection_from_text(
     state: &mut AppState,
     text: &str,
+    transform: &dyn TextTransform,
 ) -> Result<(), ConvertSelectionError> {
-    let delay_ms = crate::helpers::get_edit_u32(state.edits.delay_ms).unwrap_or(100);
+    let settings = app_rules::foreground_settings(state);
+    let delay_ms = app_rules::conversion_delay_ms(state, &settings);
 
-    let converted = convert_ru_en_bidirectional(text);
-    let converted_units = converted.encode_utf16().count();
+    let converted = transform.apply(text);
 
     thread::sleep(Duration::from_millis(u64::from(delay_ms)));
 
+    let before = foreground_layout();
+    replace_selection(&converted, settings.replacement)?;
+
+    let switched = transform.switches_layout()
+        && match switch_keyboard_layout() {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::trace!(error = ?e, "layout switch failed");
+                false
+            }
+        };
+
+    if let Some((fg, layout)) = before {
+        with_history(|h| {
+            h.push(ConversionRecord {
+                kind: ConversionKind::Selection,
+                original: text.to_string(),
+                converted,
+                caret_delta: 0,
+                window: fg.0 as isize,
+                previous_layout: switched.then_some(layout.0 as isize),
+                journal_after: String::new(),
+                journal_before: String::new(),
+            });
+        });
+    }
+
+    Ok(())
+}
+
+/// Replaces the selection with `text` and selects the inserted text.
+///
+/// `text` may span lines; its line endings are kept.
+pub(crate) fn replace_selection(
+    text: &str,
+    strategy: ReplacementStrategy,
+) -> Result<(), ConvertSelectionError> {
     let _seq = KeySequence::new();
 
     KeySequence::tap(VK_DELETE_KEY)
         .then_some(())
         .ok_or(ConvertSelectionError::Delete)?;
 
-    send_text_unicode(&converted)
+    insert_text(text, strategy)
         .then_some(())
         .ok_or(ConvertSelectionError::InsertConverted)?;
 
-    reselect_with_retry(
-        converted_units,
-        Duration::from_millis(120),
-        Duration::from_millis(5),
-    )
-    .then_some(())
-    .ok_or(ConvertSelectionError::Reselect)?;
+    reselect_with_retry(text, Duration::from_millis(120), Duration::from_millis(5))
+        .then_some(())
+        .ok_or(ConvertSelectionError::Reselect)
+}
 
-    if let Err(e) = switch_keyboard_layout() {
-        tracing::trace!(error = ?e, "layout switch failed");
+/// Puts the original text of a selection conversion back.
+///
+/// Refuses unless the selection still is the converted text.
+pub(crate) fn undo_selection(
+    record: &ConversionRecord,
+    strategy: ReplacementStrategy,
+) -> Result<(), UndoRefusal> {
+    let max_chars = record.converted.chars().count().max(1);
+    let selected = copy_selection_text_with_clipboard_restore(max_chars);
+    if selected.as_deref() != Some(record.converted.as_str()) {
+        return Err(UndoRefusal::SelectionChanged);
     }
 
-    Ok(())
+    replace_selection(&record.original, strategy).map_err(|e| {
+        tracing::warn!(user_text = e.user_text(), error = ?e, "undo selection failed");
+        UndoRefusal::InjectionFailed
+    })
 }
 
 /// Attempts to reselect the last inserted text using bounded retries.
@@ -195,10 +261,10 @@ fn convert_selection_from_text(
 /// This helper retries for a short time budget to reduce flakiness without adding a long fixed delay.
 ///
 /// Returns `true` if reselect succeeds within `budget`, otherwise `false`.
-fn reselect_with_retry(units: usize, budget: Duration, step_sleep: Duration) -> bool {
+fn reselect_with_retry(text: &str, budget: Duration, step_sleep: Duration) -> bool {
     let deadline = std::time::Instant::now() + budget;
 
-    std::iter::repeat_with(|| reselect_last_inserted_text_utf16_units(units))
+    std::iter::repeat_with(|| reselect_inserted_text(text))
         .take_while(|_| std::time::Instant::now() < deadline)
         .inspect(|ok| {
             if !ok {
@@ -209,19 +275,35 @@ fn reselect_with_retry(units: usize, budget: Duration, step_sleep: Duration) ->
 }
 
 /// Returns the current foreground window, or `None` if it is null.
-fn foreground_window() -> Option<HWND> {
+pub(crate) fn foreground_window() -> Option<HWND> {
     let fg = unsafe { GetForegroundWindow() };
     (!fg.0.is_null()).then_some(fg)
 }
 
 /// Returns the current keyboard layout for the thread owning `fg`.
-fn current_layout_for_window(fg: HWND) -> HKL {
+pub(crate) fn current_layout_for_window(fg: HWND) -> HKL {
     unsafe {
         let tid = GetWindowThreadProcessId(fg, None);
         GetKeyboardLayout(tid)
     }
 }
 
+/// Returns the foreground window and its keyboard layout.
+pub(crate) fn foreground_layout() -> Option<(HWND, HKL)> {
+    foreground_window().map(|fg| (fg, current_layout_for_window(fg)))
+}
+
+/// Installed keyboard layouts with their language tags.
+pub(crate) fn installed_layouts_with_tags() -> Vec<InstalledLayout<HKL>> {
+    installed_layouts()
+        .into_iter()
+        .map(|handle| InstalledLayout {
+            handle,
+            tag: layout_tag(handle),
+        })
+        .collect()
+}
+
 /// Enumerates installed keyboard layouts for the current desktop.
 ///
 /// Returns an empty vector when enumeration fails or yields no results.
@@ -248,6 +330,47 @@ fn installed_layouts() -> Vec<HKL> {
     layouts
 }
 
+/// Language tag of `hkl`, e.g. `en-US`, from the language id in its low word.
+///
+/// Empty when the language id has no locale name.
+fn layout_tag(hkl: HKL) -> String {
+    let lcid = (hkl.0 as usize & 0xFFFF) as u32;
+    let mut name = [0u16; LOCALE_NAME_MAX_LENGTH as usize];
+    let n = unsafe { LCIDToLocaleName(lcid, Some(&mut name), 0) };
+    // The count includes the terminating null.
+    match usize::try_from(n) {
+        Ok(len) if len > 0 => String::from_utf16_lossy(&name[..len - 1]),
+        _ => String::new(),
+    }
+}
+
+/// Converts text between the foreground window layout and the layout `switch_keyboard_layout`
+/// activates next.
+///
+/// Both layouts are read from the system, so the conversion follows the installed layouts.
+/// Falls back to the configured layout pair table when there is no foreground window,
+/// only one layout is installed, or a layout produces no characters.
+///
+/// Mixed text is converted run by run, see `mapping::convert_with_layouts`.
+pub(crate) fn convert_for_layout_switch(text: &str, direction: ConvertDirection) -> String {
+    match system_layouts() {
+        Some((current, next)) => mapping::convert_with_layouts(text, &current, &next, direction),
+        None => mapping::convert_with_active_pair(text, direction),
+    }
+}
+
+/// Returns the layouts of the foreground window and of the next installed layout.
+fn system_layouts() -> Option<(Arc<Layout>, Arc<Layout>)> {
+    let fg = foreground_window()?;
+    let cur = current_layout_for_window(fg);
+    let next = next_layout(&installed_layouts(), cur);
+    if next == cur {
+        return None;
+    }
+
+    Some((layout_for_hkl(cur)?, layout_for_hkl(next)?))
+}
+
 /// Switches the keyboard layout for the current foreground window to the next installed layout.
 ///
 /// Algorithm:
@@ -277,21 +400,41 @@ pub fn switch_keyboard_layout() -> windows::core::Result<()> {
     post_layout_change(fg, next)
 }
 
-/// Returns the next layout in `layouts` after `cur`, cycling back to the first.
+/// Switches the foreground window to the layout `layout_policy::choose_layout` picks for `target`.
 ///
-/// If `cur` is not found, returns `cur`.
-fn next_layout(layouts: &[HKL], cur: HKL) -> HKL {
-    layouts
-        .iter()
-        .position(|&h| h == cur)
-        .and_then(|i| layouts.get((i + 1) % layouts.len()).copied())
-        .unwrap_or(cur)
+/// The layouts in use before and after the switch are recorded in `recent`.
+///
+/// Returns `Ok(())` when the operation is completed or skipped:
+/// - no foreground window
+/// - no installed layout matches `target`, or it is already active
+///
+/// Returns `Err` only if posting the request fails.
+pub fn activate_layout(
+    target: LayoutTarget<'_>,
+    recent: &mut RecentLayouts<HKL>,
+) -> windows::core::Result<()> {
+    let Some(fg) = foreground_window() else {
+        return Ok(());
+    };
+
+    let cur = current_layout_for_window(fg);
+    recent.observe(cur);
+
+    let layouts = installed_layouts_with_tags();
+    let Some(next) = choose_layout(&layouts, cur, target, recent) else {
+        tracing::trace!(?target, "no layout to activate");
+        return Ok(());
+    };
+
+    post_layout_change(fg, next)?;
+    recent.observe(next);
+    Ok(())
 }
 
 /// Posts a layout change request message to the foreground window.
 ///
 /// Uses `WM_INPUTLANGCHANGEREQUEST`. The `hkl` is passed through `LPARAM`.
-fn post_layout_change(fg: HWND, hkl: HKL) -> windows::core::Result<()> {
+pub(crate) fn post_layout_change(fg: HWND, hkl: HKL) -> windows::core::Result<()> {
     unsafe {
         PostMessageW(
             Some(fg),
@@ -364,7 +507,7 @@ impl Drop for ClipboardRestore {
 
 /// Copies current selection via Ctrl+C, reads Unicode text from clipboard, then restores clipboard.
 ///
-/// Returns `None` when selection is empty, multiline, too long, or clipboard did not change.
+/// Returns `None` when selection is empty, too long, or clipboard did not change.
 /// `max_chars` is counted in Unicode scalar values.
 fn copy_selection_text_with_clipboard_restore(max_chars: usize) -> Option<String> {
     let restore = ClipboardRestore::capture();
@@ -376,11 +519,3 @@ fn copy_selection_text_with_clipboard_restore(max_chars: usize) -> Option<String
         .and_then(|_| clip::get_unicode_text())
         .filter(|s| is_convertible_selection(s, max_chars))
 }
-
-/// Checks whether clipboard text is eligible for selection conversion.
-///
-/// Optimization:
-/// `s.chars().nth(max_chars).is_none()` stops early for long strings, unlike `chars().count()`.
-fn is_convertible_selection(s: &str, max_chars: usize) -> bool {
-    !s.is_empty() && !s.contains('\n') && !s.contains('\r') && s.chars().nth(max_chars).is_none()
-}
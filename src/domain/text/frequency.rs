@@ -0,0 +1,345 @@
+//! Word frequency scorer for short words.
+//!
+//! lingua needs a few letters of context, so `HeuristicDecider` skips words shorter than
+//! `MIN_WORD_LEN`. This scorer is backed by embedded word frequency lists (`res/frequency`)
+//! and char bigram and trigram tables built from them, which gives usable likelihoods
+//! for two and three letter words such as `yt` (не) or `ghb` (при).
+//!
+//! A language is added by dropping a `<iso 639-1>.txt` list into `res/frequency` and
+//! listing it in `EMBEDDED_LISTS`.
+
+use std::{collections::HashMap, sync::OnceLock};
+
+use lingua::Language;
+
+use super::{
+    autoconvert::{
+        AutoconvertDecider, Decision, LanguageScorer, MIN_WORD_LEN, PairLanguages, Scores,
+        SkipReason, pair_languages, split_trailing_convertible_punct,
+    },
+    layout::LayoutPair,
+    script::{looks_like_cyrillic_word, looks_like_latin_word},
+};
+
+const EMBEDDED_LISTS: &[(Language, &str)] = &[
+    (
+        Language::English,
+        include_str!("../../../res/frequency/en.txt"),
+    ),
+    (
+        Language::Russian,
+        include_str!("../../../res/frequency/ru.txt"),
+    ),
+];
+
+/// Shortest word `ShortWordDecider` converts.
+pub const MIN_SHORT_WORD_LEN: usize = 2;
+
+/// Share of the probability mass given to listed words, the rest goes to the char model.
+const LISTED_WORD_WEIGHT: f64 = 0.9;
+
+/// Interpolation weights of the trigram, bigram and unigram char models.
+const NGRAM_WEIGHTS: [f64; 3] = [0.6, 0.3, 0.1];
+
+const MIN_SHORT_CONVERTED_CONFIDENCE: f64 = 0.90;
+const MIN_SHORT_CONFIDENCE_GAIN: f64 = 0.40;
+
+/// Pads words in the char tables: word start and word end.
+const WORD_START: char = '^';
+const WORD_END: char = '$';
+
+/// Parses a frequency list: one `word count` pair per line, `#` starts a comment line.
+pub fn parse_frequency_list(text: &str) -> Result<Vec<(String, u64)>, String> {
+    let mut words: Vec<(String, u64)> = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line_no = i + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut parts = line.split_whitespace();
+        let (Some(word), Some(count), None) = (parts.next(), parts.next(), parts.next()) else {
+            return Err(format!("line {line_no}: expected 'word count'"));
+        };
+        let count: u64 = count
+            .parse()
+            .map_err(|_| format!("line {line_no}: invalid count '{count}'"))?;
+        if count == 0 {
+            return Err(format!("line {line_no}: count must be positive"));
+        }
+
+        let word = word.to_lowercase();
+        if words.iter().any(|(w, _)| *w == word) {
+            return Err(format!("line {line_no}: duplicate word '{word}'"));
+        }
+        words.push((word, count));
+    }
+
+    if words.is_empty() {
+        return Err("no words".to_string());
+    }
+    Ok(words)
+}
+
+/// Word list and char n-gram counts of one language.
+#[derive(Debug)]
+struct FrequencyModel {
+    lang: Language,
+    words: HashMap<String, u64>,
+    total: u64,
+    /// Letters seen in the list plus `WORD_END`.
+    alphabet_len: usize,
+    unigrams: HashMap<char, u64>,
+    unigram_total: u64,
+    bigrams: HashMap<[char; 2], u64>,
+    bigram_contexts: HashMap<char, u64>,
+    trigrams: HashMap<[char; 3], u64>,
+    trigram_contexts: HashMap<[char; 2], u64>,
+}
+
+impl FrequencyModel {
+    /// Builds the model. The char tables count every listed word once, so they
+    /// describe the shape of words rather than the most frequent ones.
+    fn new(lang: Language, list: &[(String, u64)]) -> Self {
+        let mut model = Self {
+            lang,
+            words: list.iter().cloned().collect(),
+            total: list.iter().map(|(_, c)| c).sum(),
+            alphabet_len: 0,
+            unigrams: HashMap::new(),
+            unigram_total: 0,
+            bigrams: HashMap::new(),
+            bigram_contexts: HashMap::new(),
+            trigrams: HashMap::new(),
+            trigram_contexts: HashMap::new(),
+        };
+
+        for (word, _) in list {
+            for [a, b, c] in padded_trigrams(word) {
+                *model.unigrams.entry(c).or_default() += 1;
+                model.unigram_total += 1;
+                *model.bigrams.entry([b, c]).or_default() += 1;
+                *model.bigram_contexts.entry(b).or_default() += 1;
+                *model.trigrams.entry([a, b, c]).or_default() += 1;
+                *model.trigram_contexts.entry([a, b]).or_default() += 1;
+            }
+        }
+        model.alphabet_len = model.unigrams.len();
+        model
+    }
+
+    fn is_known(&self, word: &str) -> bool {
+        self.words.contains_key(word)
+    }
+
+    fn covers(&self, word: &str) -> bool {
+        word.chars().all(|c| self.unigrams.contains_key(&c))
+    }
+
+    /// Natural log of the char model probability of `word`, end marker included.
+    fn ngram_log_prob(&self, word: &str) -> f64 {
+        let ratio = |num: Option<&u64>, den: Option<&u64>| match (num, den) {
+            (Some(&n), Some(&d)) => n as f64 / d as f64,
+            _ => 0.0,
+        };
+        let [w3, w2, w1] = NGRAM_WEIGHTS;
+
+        padded_trigrams(word)
+            .map(|[a, b, c]| {
+                let tri = ratio(
+                    self.trigrams.get(&[a, b, c]),
+                    self.trigram_contexts.get(&[a, b]),
+                );
+                let bi = ratio(self.bigrams.get(&[b, c]), self.bigram_contexts.get(&b));
+                // Add one smoothing keeps every covered letter possible.
+                let uni = (self.unigrams.get(&c).copied().unwrap_or(0) + 1) as f64
+                    / (self.unigram_total + self.alphabet_len as u64) as f64;
+                (w3 * tri + w2 * bi + w1 * uni).ln()
+            })
+            .sum()
+    }
+
+    /// Probability that `word` is a word of this language rather than a random string
+    /// of its letters, assuming both are equally likely up front.
+    fn confidence(&self, word: &str) -> f64 {
+        if word.is_empty() || !self.covers(word) {
+            return 0.0;
+        }
+
+        let listed = self
+            .words
+            .get(word)
+            .map_or(0.0, |&c| c as f64 / self.total as f64);
+        let log_p = (LISTED_WORD_WEIGHT * listed
+            + (1.0 - LISTED_WORD_WEIGHT) * self.ngram_log_prob(word).exp())
+        .ln();
+        let log_noise = -((word.chars().count() + 1) as f64) * (self.alphabet_len as f64).ln();
+
+        1.0 / (1.0 + (log_noise - log_p).exp())
+    }
+}
+
+/// Trigrams of `^^word$`, one per letter plus one for the end of the word.
+fn padded_trigrams(word: &str) -> impl Iterator<Item = [char; 3]> + '_ {
+    let mut prev = [WORD_START, WORD_START];
+    word.chars().chain(std::iter::once(WORD_END)).map(move |c| {
+        let trigram = [prev[0], prev[1], c];
+        prev = [prev[1], c];
+        trigram
+    })
+}
+
+/// `LanguageScorer` backed by the embedded frequency lists.
+///
+/// Languages without a list score `0.0`.
+#[derive(Debug)]
+pub struct FrequencyScorer {
+    models: Vec<FrequencyModel>,
+}
+
+impl FrequencyScorer {
+    /// Builds a scorer from `(language, frequency list)` pairs.
+    pub fn from_lists(lists: &[(Language, &str)]) -> Result<Self, String> {
+        let models = lists
+            .iter()
+            .map(|&(lang, text)| {
+                parse_frequency_list(text)
+                    .map(|list| FrequencyModel::new(lang, &list))
+                    .map_err(|e| format!("{lang} frequency list: {e}"))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { models })
+    }
+
+    pub fn supports(&self, lang: Language) -> bool {
+        self.model(lang).is_some()
+    }
+
+    /// Whether `word` is in the frequency list of `lang`, case insensitively.
+    pub fn is_known(&self, word: &str, lang: Language) -> bool {
+        self.model(lang)
+            .is_some_and(|m| m.is_known(&word.to_lowercase()))
+    }
+
+    fn model(&self, lang: Language) -> Option<&FrequencyModel> {
+        self.models.iter().find(|m| m.lang == lang)
+    }
+}
+
+impl LanguageScorer for FrequencyScorer {
+    fn confidence(&self, text: &str, lang: Language) -> f64 {
+        self.model(lang)
+            .map_or(0.0, |m| m.confidence(&text.to_lowercase()))
+    }
+}
+
+/// Returns the scorer of the embedded lists, built on first use.
+pub fn frequency_scorer() -> &'static FrequencyScorer {
+    static SCORER: OnceLock<FrequencyScorer> = OnceLock::new();
+
+    SCORER.get_or_init(|| {
+        FrequencyScorer::from_lists(EMBEDDED_LISTS).unwrap_or_else(|e| {
+            tracing::error!(error = %e, "embedded frequency list is invalid");
+            FrequencyScorer { models: Vec::new() }
+        })
+    })
+}
+
+/// Decider for words shorter than `MIN_WORD_LEN`.
+///
+/// Short words carry little signal, so it is stricter than `HeuristicDecider`:
+/// a word is converted only when it is not a listed word of its own language
+/// and its conversion is a listed word of the other one.
+pub struct ShortWordDecider<'a> {
+    scorer: &'a FrequencyScorer,
+    langs: PairLanguages,
+}
+
+impl ShortWordDecider<'static> {
+    /// Builds a decider with the embedded lists.
+    ///
+    /// Returns `None` unless both languages of the pair have a list.
+    pub fn for_pair(pair: &LayoutPair) -> Option<Self> {
+        let langs = pair_languages(pair)?;
+        Self::new(frequency_scorer(), langs)
+    }
+}
+
+impl<'a> ShortWordDecider<'a> {
+    pub fn new(scorer: &'a FrequencyScorer, langs: PairLanguages) -> Option<Self> {
+        (scorer.supports(langs.latin) && scorer.supports(langs.cyrillic))
+            .then_some(Self { scorer, langs })
+    }
+}
+
+impl AutoconvertDecider for ShortWordDecider<'_> {
+    fn decide(&self, word: &str, converted: &str) -> Decision {
+        let (word_core, _) = split_trailing_convertible_punct(word);
+        let (conv_core, _) = split_trailing_convertible_punct(converted);
+
+        if word_core.chars().count() < MIN_SHORT_WORD_LEN {
+            return Decision::skip(SkipReason::TooShort, None);
+        }
+
+        let (own, target) =
+            if looks_like_latin_word(word_core) && looks_like_cyrillic_word(conv_core) {
+                (self.langs.latin, self.langs.cyrillic)
+            } else if looks_like_cyrillic_word(word_core) && looks_like_latin_word(conv_core) {
+                (self.langs.cyrillic, self.langs.latin)
+            } else {
+                return Decision::skip(SkipReason::ScriptCheckFailed, None);
+            };
+
+        let scores = Scores {
+            word_latin: self.scorer.confidence(word_core, self.langs.latin),
+            word_cyrillic: self.scorer.confidence(word_core, self.langs.cyrillic),
+            converted_latin: self.scorer.confidence(conv_core, self.langs.latin),
+            converted_cyrillic: self.scorer.confidence(conv_core, self.langs.cyrillic),
+        };
+        let skip = |reason| Decision::skip(reason, Some(scores));
+
+        if self.scorer.is_known(word_core, own) {
+            return skip(SkipReason::AlreadyCorrect);
+        }
+
+        let (w_own, c_target) = if own == self.langs.latin {
+            (scores.word_latin, scores.converted_cyrillic)
+        } else {
+            (scores.word_cyrillic, scores.converted_latin)
+        };
+
+        if !self.scorer.is_known(conv_core, target) || c_target < MIN_SHORT_CONVERTED_CONFIDENCE {
+            return skip(SkipReason::ConvertedConfidenceLow);
+        }
+        if c_target - w_own < MIN_SHORT_CONFIDENCE_GAIN {
+            return skip(SkipReason::NotBetterEnough);
+        }
+
+        Decision::convert(Some(scores))
+    }
+}
+
+/// Sends words shorter than `MIN_WORD_LEN` to `short` and the rest to `long`.
+pub struct ShortWordRouter<S, L> {
+    short: S,
+    long: L,
+}
+
+impl<S: AutoconvertDecider, L: AutoconvertDecider> ShortWordRouter<S, L> {
+    pub fn new(short: S, long: L) -> Self {
+        Self { short, long }
+    }
+}
+
+impl<S: AutoconvertDecider, L: AutoconvertDecider> AutoconvertDecider for ShortWordRouter<S, L> {
+    fn decide(&self, word: &str, converted: &str) -> Decision {
+        let (word_core, _) = split_trailing_convertible_punct(word);
+        if word_core.chars().count() < MIN_WORD_LEN {
+            self.short.decide(word, converted)
+        } else {
+            self.long.decide(word, converted)
+        }
+    }
+}
@@ -0,0 +1,145 @@
+//! History of applied conversions, consumed by `HotkeyAction::UndoLastConversion`.
+//!
+//! Each record holds what is needed to put the original text back and the state the
+//! target window was left in, so an undo can refuse when that state is gone.
+
+use std::{
+    collections::VecDeque,
+    sync::{Mutex, OnceLock, PoisonError},
+};
+
+/// Records kept for undo.
+pub const HISTORY_LEN: usize = 16;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConversionKind {
+    /// The selection was replaced and the converted text is selected again.
+    Selection,
+    /// The word before the caret was replaced by a hotkey.
+    LastWord,
+    /// Several words or the phrase before the caret were replaced by a hotkey.
+    LastWords,
+    /// The word before the caret was replaced by autoconvert.
+    Autoconvert,
+    /// A snippet trigger before the caret was replaced by its text.
+    Snippet,
+}
+
+/// One applied conversion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConversionRecord {
+    pub kind: ConversionKind,
+    /// Text before the conversion.
+    pub original: String,
+    /// Text inserted in place of `original`.
+    pub converted: String,
+    /// Characters between the end of `converted` and the caret, such as the space after a word.
+    pub caret_delta: usize,
+    /// Raw handle of the foreground window the conversion was applied to.
+    pub window: isize,
+    /// Raw handle of the keyboard layout active before the conversion switched it,
+    /// `None` when the layout was not switched.
+    pub previous_layout: Option<isize>,
+    /// Input journal text right after the conversion.
+    pub journal_after: String,
+    /// Input journal text to restore after an undo.
+    pub journal_before: String,
+}
+
+/// Why an undo was refused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UndoRefusal {
+    NothingToUndo,
+    WindowChanged,
+    JournalChanged,
+    SelectionChanged,
+    InjectionFailed,
+}
+
+impl UndoRefusal {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            UndoRefusal::NothingToUndo => "nothing_to_undo",
+            UndoRefusal::WindowChanged => "window_changed",
+            UndoRefusal::JournalChanged => "journal_changed",
+            UndoRefusal::SelectionChanged => "selection_changed",
+            UndoRefusal::InjectionFailed => "injection_failed",
+        }
+    }
+
+    /// Text for the tray notification.
+    pub fn user_text(self) -> &'static str {
+        match self {
+            UndoRefusal::NothingToUndo => "There is no conversion to undo.",
+            UndoRefusal::WindowChanged => "The conversion was made in another window.",
+            UndoRefusal::JournalChanged => {
+                "Text was typed or the caret moved after the conversion."
+            }
+            UndoRefusal::SelectionChanged => "The converted text is no longer selected.",
+            UndoRefusal::InjectionFailed => "Failed to restore the original text.",
+        }
+    }
+}
+
+impl ConversionRecord {
+    /// Checks that the target window and the typed text are as the conversion left them.
+    ///
+    /// The journal is not checked for selections: it does not see the mouse, so the
+    /// selection itself is compared with `converted` before undoing.
+    pub fn check_undo(&self, window: isize, journal: &str) -> Result<(), UndoRefusal> {
+        if window != self.window {
+            return Err(UndoRefusal::WindowChanged);
+        }
+        if self.kind != ConversionKind::Selection && journal != self.journal_after {
+            return Err(UndoRefusal::JournalChanged);
+        }
+        Ok(())
+    }
+}
+
+/// Most recent conversions, newest last.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ConversionHistory {
+    records: VecDeque<ConversionRecord>,
+}
+
+impl ConversionHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a record, dropping the oldest one beyond `HISTORY_LEN`.
+    pub fn push(&mut self, record: ConversionRecord) {
+        self.records.push_back(record);
+        while self.records.len() > HISTORY_LEN {
+            let _ = self.records.pop_front();
+        }
+    }
+
+    pub fn last(&self) -> Option<&ConversionRecord> {
+        self.records.back()
+    }
+
+    pub fn pop(&mut self) -> Option<ConversionRecord> {
+        self.records.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+}
+
+static HISTORY: OnceLock<Mutex<ConversionHistory>> = OnceLock::new();
+
+/// Runs `f` with the history shared by the conversion pipelines.
+pub fn with_history<R>(f: impl FnOnce(&mut ConversionHistory) -> R) -> R {
+    let mut history = HISTORY
+        .get_or_init(|| Mutex::new(ConversionHistory::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    f(&mut history)
+}
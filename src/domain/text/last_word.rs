@@ -2,35 +2,89 @@
 
 use std::{
     sync::{
-        OnceLock,
+        Mutex, PoisonError,
         atomic::{AtomicBool, Ordering},
     },
     thread,
     time::Duration,
 };
 
-use windows::Win32::UI::{
-    Input::KeyboardAndMouse::VIRTUAL_KEY, WindowsAndMessaging::GetForegroundWindow,
+use windows::Win32::{
+    System::SystemInformation::GetLocalTime,
+    UI::{Input::KeyboardAndMouse::VIRTUAL_KEY, WindowsAndMessaging::GetForegroundWindow},
 };
 
-use super::{mapping::convert_ru_en_bidirectional, switch_keyboard_layout, wait_shift_released};
+use super::{
+    autoconvert::{self, Decision, SkipReason, split_trailing_convertible_punct},
+    case_fix::{self, CaseError, CaseFixSettings},
+    convert::{convert_for_layout_switch, foreground_layout},
+    history::{ConversionKind, ConversionRecord, with_history},
+    layout,
+    learned_exceptions::{unix_now, with_active_learned},
+    mapping::ConvertDirection,
+    snippets::{self, Placeholder},
+    switch_keyboard_layout,
+    transform::{LayoutSwap, TextTransform},
+    user_dictionary, wait_shift_released,
+};
 use crate::{
     app::AppState,
-    conversion::input::{KeySequence, send_text_unicode},
+    config::{AppSettings, ReplacementStrategy},
+    conversion::{
+        clipboard,
+        input::{KeySequence, caps_lock_off, caps_lock_on, insert_text},
+    },
+    input::{ring_buffer::Span, word_boundaries::BoundaryChars},
+    platform::win::app_rules,
 };
 
 const VK_BACKSPACE_KEY: VIRTUAL_KEY = VIRTUAL_KEY(0x08);
 const VK_LEFT_KEY: VIRTUAL_KEY = VIRTUAL_KEY(0x25);
 const VK_RIGHT_KEY: VIRTUAL_KEY = VIRTUAL_KEY(0x27);
 
-const MIN_WORD_LEN: usize = 4;
-const MIN_CONVERTED_CONFIDENCE: f64 = 0.70;
-const MIN_CONFIDENCE_GAIN: f64 = 0.25;
-
 static AUTOCONVERT_IN_PROGRESS: AtomicBool = AtomicBool::new(false);
 
-pub fn convert_last_word(state: &mut AppState) {
-    convert_last_word_impl(state, true);
+/// Word as typed before the last successful autoconversion.
+static LAST_AUTOCONVERSION: Mutex<Option<String>> = Mutex::new(None);
+
+/// The last `convert_last_words`, extended by the next press while nothing was typed.
+static LAST_WORDS_RUN: Mutex<Option<WordsRun>> = Mutex::new(None);
+
+/// Words converted by `convert_last_words`.
+struct WordsRun {
+    words: usize,
+    /// Text of the words before any press of the run converted them.
+    original: String,
+    converted: String,
+    journal_after: String,
+}
+
+pub fn convert_last_word(state: &mut AppState, direction: ConvertDirection) {
+    convert_last_word_impl(state, &LayoutSwap(direction));
+}
+
+/// Replaces the last word with `transform` applied to it.
+pub fn transform_last_word(state: &mut AppState, transform: &dyn TextTransform) {
+    convert_last_word_impl(state, transform);
+}
+
+/// Converts the words typed since the last sentence end or line break.
+pub fn convert_last_phrase(state: &mut AppState, direction: ConvertDirection) {
+    let _ = convert_span(state, Span::Phrase, None, direction);
+}
+
+/// Converts the last word. Pressed again with nothing typed in between, it converts
+/// one more word each time.
+pub fn convert_last_words(state: &mut AppState, direction: ConvertDirection) {
+    let mut run = LAST_WORDS_RUN
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    let previous = run
+        .take()
+        .filter(|r| r.journal_after == crate::input_journal::snapshot());
+    let words = previous.as_ref().map_or(1, |r| r.words + 1);
+
+    *run = convert_span(state, Span::Words(words), previous.as_ref(), direction).or(previous);
 }
 
 pub fn autoconvert_last_word(state: &mut AppState) {
@@ -39,6 +93,12 @@ pub fn autoconvert_last_word(state: &mut AppState) {
         return;
     }
 
+    let settings = app_rules::foreground_settings(state);
+    if !settings.autoconvert {
+        tracing::trace!("autoconvert skip: off by application rule");
+        return;
+    }
+
     let _guard = match AutoconvertGuard::try_acquire() {
         Ok(g) => g,
         Err(reason) => {
@@ -47,8 +107,9 @@ pub fn autoconvert_last_word(state: &mut AppState) {
         }
     };
 
-    sleep_before_convert(state);
+    sleep_before_convert(state, &settings);
 
+    let journal_before = crate::input_journal::snapshot();
     let Some(payload) = take_last_word_payload() else {
         tracing::trace!("journal: no last word");
         return;
@@ -56,24 +117,23 @@ pub fn autoconvert_last_word(state: &mut AppState) {
 
     let mut restore = JournalRestore::new(&payload);
 
-    let converted = match autoconvert_candidate(&payload) {
-        Ok(v) => v,
+    let replacement = match autoconvert_replacement(&payload, &state.config.case_fix) {
+        Ok(r) => r,
         Err(reason) => {
-            tracing::trace!(reason = %reason.as_str(), "autoconvert skip: candidate");
+            tracing::trace!(reason = %reason.as_str(), "autoconvert skip");
             return;
         }
     };
+    let converted = replacement.text;
 
-    let detector = language_detector();
-
-    if let Err(reason) = should_autoconvert_word(detector, &payload.word, &converted) {
-        tracing::trace!(reason = %reason.as_str(), "autoconvert skip: decision");
-        return;
-    }
-
-    tracing::trace!(word = %payload.word, converted = %converted, "autoconvert decision");
+    tracing::trace!(
+        word = %payload.word,
+        converted = %converted,
+        case_error = ?replacement.case_error,
+        "autoconvert decision"
+    );
 
-    if let Err(err) = apply_last_word_replacement(&payload, &converted) {
+    if let Err(err) = apply_last_word_replacement(&payload, &converted, settings.replacement) {
         tracing::warn!(error = %err.as_str(), "autoconvert apply failed");
         return;
     }
@@ -81,10 +141,188 @@ pub fn autoconvert_last_word(state: &mut AppState) {
     update_journal(&payload, &converted);
     crate::input_journal::mark_last_token_autoconverted();
     restore.commit();
+    *LAST_AUTOCONVERSION
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner) = Some(payload.word.clone());
 
-    match switch_keyboard_layout() {
-        Ok(()) => tracing::trace!("layout switched (autoconvert)"),
-        Err(e) => tracing::warn!(error = ?e, "layout switch failed (autoconvert)"),
+    if replacement.case_error == Some(CaseError::CapsLock)
+        && state.config.case_fix.turn_off_caps_lock
+        && !caps_lock_off()
+    {
+        tracing::warn!("caps lock off failed (autoconvert)");
+    }
+
+    let before = foreground_layout();
+    let switched = replacement.layout_converted
+        && match switch_keyboard_layout() {
+            Ok(()) => {
+                tracing::trace!("layout switched (autoconvert)");
+                true
+            }
+            Err(e) => {
+                tracing::warn!(error = ?e, "layout switch failed (autoconvert)");
+                false
+            }
+        };
+
+    record_conversion(
+        ConversionKind::Autoconvert,
+        &payload,
+        converted,
+        journal_before,
+        before
+            .filter(|_| switched)
+            .map(|(_, layout)| layout.0 as isize),
+    );
+}
+
+/// Replaces the last word with its snippet, see `snippets`. Returns whether it did.
+///
+/// An application rule that turns autoconvert off turns snippets off too.
+/// A snippet with line breaks is pasted, whatever the replacement strategy, see `insert_text`.
+/// With `{cursor}` in the snippet the caret is moved into the text, so the journal no
+/// longer follows it: it is cleared and the expansion cannot be undone.
+pub fn expand_snippet(state: &mut AppState) -> bool {
+    let snippets = snippets::active_snippets();
+    if snippets.is_empty() || !foreground_window_alive() {
+        return false;
+    }
+
+    let settings = app_rules::foreground_settings(state);
+    if !settings.autoconvert {
+        tracing::trace!("snippet skip: autoconvert off by application rule");
+        return false;
+    }
+
+    let Ok(_guard) = AutoconvertGuard::try_acquire() else {
+        tracing::trace!("snippet skip: reentry");
+        return false;
+    };
+
+    let journal_before = crate::input_journal::snapshot();
+    let Some(payload) = take_last_word_payload() else {
+        return false;
+    };
+
+    let mut restore = JournalRestore::new(&payload);
+
+    if payload.suffix_has_newline {
+        return false;
+    }
+
+    let converted = convert_for_layout_switch(&payload.word, ConvertDirection::Auto);
+    let Some(found) = snippets.find(&payload.word, &converted) else {
+        return false;
+    };
+
+    sleep_before_convert(state, &settings);
+
+    let expansion = snippets::expand(found.text, placeholder_value);
+    let text = format!("{}{}", expansion.text, found.punct);
+    tracing::trace!(trigger = %found.trigger, caret_back = expansion.caret_back, "snippet expand");
+
+    if let Err(err) = apply_last_word_replacement(&payload, &text, settings.replacement) {
+        tracing::warn!(error = %err.as_str(), "snippet apply failed");
+        return false;
+    }
+
+    restore.commit();
+
+    if expansion.caret_back > 0 {
+        let back = expansion.caret_back + found.punct.chars().count() + payload.suffix_len;
+        if !move_caret_left(back) {
+            tracing::warn!("caret move failed (snippet)");
+        }
+        crate::input::ring_buffer::invalidate();
+        return true;
+    }
+
+    update_journal(&payload, &text);
+    record_conversion(
+        ConversionKind::Snippet,
+        &payload,
+        text,
+        journal_before,
+        None,
+    );
+    true
+}
+
+/// Text of a snippet placeholder other than `{cursor}`.
+fn placeholder_value(placeholder: Placeholder) -> String {
+    let now = unsafe { GetLocalTime() };
+    match placeholder {
+        Placeholder::Date => format!("{:04}-{:02}-{:02}", now.wYear, now.wMonth, now.wDay),
+        Placeholder::Time => format!("{:02}:{:02}", now.wHour, now.wMinute),
+        Placeholder::Clipboard => clipboard::get_unicode_text().unwrap_or_default(),
+        Placeholder::Cursor => String::new(),
+    }
+}
+
+/// Adds an applied last word conversion to the undo history.
+fn record_conversion(
+    kind: ConversionKind,
+    p: &LastWordPayload,
+    converted: String,
+    journal_before: String,
+    previous_layout: Option<isize>,
+) {
+    let Some((fg, _)) = foreground_layout() else {
+        return;
+    };
+
+    with_history(|h| {
+        h.push(ConversionRecord {
+            kind,
+            original: p.word.clone(),
+            converted,
+            caret_delta: p.suffix_len,
+            window: fg.0 as isize,
+            previous_layout,
+            journal_after: crate::input_journal::snapshot(),
+            journal_before,
+        });
+    });
+}
+
+/// Puts the original word of a last word conversion back, leaving the caret where it was.
+pub(crate) fn undo_last_word(record: &ConversionRecord, strategy: ReplacementStrategy) -> bool {
+    move_caret_left(record.caret_delta)
+        && delete_with_backspace(record.converted.chars().count())
+        && insert_text(&record.original, strategy)
+        && move_caret_right(record.caret_delta)
+}
+
+/// Takes the word as typed before the last autoconversion.
+///
+/// Callers use it when the user reverts that conversion, which is only the case while
+/// the journal still ends with the converted token (see `last_token_autoconverted`).
+pub fn take_last_autoconversion() -> Option<String> {
+    LAST_AUTOCONVERSION
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .take()
+}
+
+/// Consults the user dictionary and the learned exceptions first, then the decider
+/// of the active layout pair.
+fn decide_autoconvert(word: &str, converted: &str) -> Decision {
+    if let Some(decision) = user_dictionary::active_dictionary().decide(word, converted) {
+        tracing::trace!(?decision, "autoconvert: user dictionary entry");
+        return decision;
+    }
+
+    let now = unix_now();
+    if with_active_learned(|learned| learned.is_exception(word, now)) {
+        return Decision::skip(SkipReason::LearnedException, None);
+    }
+
+    match layout::active_pair()
+        .as_deref()
+        .and_then(autoconvert::decider_for_pair)
+    {
+        Some(decider) => decider.decide(word, converted),
+        None => Decision::skip(SkipReason::NoLanguageModel, None),
     }
 }
 
@@ -133,117 +371,6 @@ impl Drop for JournalRestore<'_> {
     }
 }
 
-#[derive(Copy, Clone, Debug)]
-pub(crate) enum SkipReason {
-    Reentry,
-    SuffixHasNewline,
-    NotAWord,
-    NoChangeAfterConvert,
-    TooShort,
-    ScriptCheckFailed,
-    AlreadyCorrect,
-    ConvertedConfidenceLow,
-    NotBetterEnough,
-}
-
-impl SkipReason {
-    fn as_str(self) -> &'static str {
-        match self {
-            SkipReason::Reentry => "reentry",
-            SkipReason::SuffixHasNewline => "suffix_has_newline",
-            SkipReason::NotAWord => "not_a_word",
-            SkipReason::NoChangeAfterConvert => "no_change_after_convert",
-            SkipReason::TooShort => "too_short",
-            SkipReason::ScriptCheckFailed => "script_check_failed",
-            SkipReason::AlreadyCorrect => "already_correct",
-            SkipReason::ConvertedConfidenceLow => "converted_confidence_low",
-            SkipReason::NotBetterEnough => "not_better_enough",
-        }
-    }
-}
-
-fn has_ascii_vowel(s: &str) -> bool {
-    s.chars().any(|ch| {
-        let c = ch.to_ascii_lowercase();
-        matches!(c, 'a' | 'e' | 'i' | 'o' | 'u')
-    })
-}
-
-fn has_cyrillic_vowel(s: &str) -> bool {
-    s.chars().any(|ch| {
-        let c = ch.to_lowercase().next().unwrap_or(ch);
-        matches!(c, 'а' | 'е' | 'ё' | 'и' | 'о' | 'у' | 'ы' | 'э' | 'ю' | 'я')
-    })
-}
-
-fn is_plausible_english_like_token(s: &str) -> bool {
-    if !looks_like_ascii_word(s) {
-        return false;
-    }
-
-    let has_vowel = has_ascii_vowel(s);
-
-    // 'y' intentionally treated as consonant here to reduce false positives.
-    let mut consonant_run = 0usize;
-    let mut max_consonant_run = 0usize;
-    let mut rare = 0usize;
-
-    for ch in s.chars() {
-        if ch == '\'' {
-            continue;
-        }
-
-        let c = ch.to_ascii_lowercase();
-        let is_vowel = matches!(c, 'a' | 'e' | 'i' | 'o' | 'u');
-
-        if is_vowel {
-            consonant_run = 0;
-        } else {
-            consonant_run += 1;
-            max_consonant_run = max_consonant_run.max(consonant_run);
-
-            if matches!(c, 'j' | 'q' | 'x' | 'z') {
-                rare += 1;
-            }
-        }
-    }
-
-    has_vowel && max_consonant_run <= 4 && rare <= 1
-}
-
-fn is_plausible_russian_like_token(s: &str) -> bool {
-    if !looks_like_cyrillic_word(s) {
-        return false;
-    }
-    if !has_cyrillic_vowel(s) {
-        return false;
-    }
-
-    let mut consonant_run = 0usize;
-    let mut max_consonant_run = 0usize;
-
-    for ch in s.chars() {
-        if ch == '\'' || ch == '-' {
-            continue;
-        }
-        if !ch.is_alphabetic() {
-            continue;
-        }
-
-        let c = ch.to_lowercase().next().unwrap_or(ch);
-        let is_vowel = matches!(c, 'а' | 'е' | 'ё' | 'и' | 'о' | 'у' | 'ы' | 'э' | 'ю' | 'я');
-
-        if is_vowel {
-            consonant_run = 0;
-        } else {
-            consonant_run += 1;
-            max_consonant_run = max_consonant_run.max(consonant_run);
-        }
-    }
-
-    max_consonant_run <= 4
-}
-
 #[derive(Copy, Clone, Debug)]
 enum ApplyError {
     KeyInjectionFailed,
@@ -257,171 +384,94 @@ impl ApplyError {
     }
 }
 
-fn split_trailing_convertible_punct(s: &str) -> (&str, &str) {
-    let bytes = s.as_bytes();
-    let mut i = bytes.len();
-
-    while i > 0 {
-        match bytes[i - 1] {
-            b'?' | b'/' | b',' | b'.' => i -= 1,
-            _ => break,
-        }
-    }
-
-    s.split_at(i)
+/// What autoconvert puts in place of the last word.
+struct Replacement {
+    text: String,
+    /// Whether `text` is in the other layout, which is switched to afterwards.
+    layout_converted: bool,
+    case_error: Option<CaseError>,
 }
 
-fn autoconvert_candidate(p: &LastWordPayload) -> Result<String, SkipReason> {
+/// Fixes the case of the last word first, see `case_fix`, then converts it to the
+/// other layout when the decider agrees. A case fix alone is a replacement too.
+///
+/// A `never` entry of the user dictionary keeps the word as typed, case included.
+fn autoconvert_replacement(
+    p: &LastWordPayload,
+    case_fix: &CaseFixSettings,
+) -> Result<Replacement, SkipReason> {
     ensure_no_newline(p)?;
-    ensure_has_letters(&p.word)?;
-
-    let (word_core, word_punct) = split_trailing_convertible_punct(&p.word);
-
-    let converted_core = convert_ru_en_bidirectional(word_core);
-
-    let mut converted = String::with_capacity(converted_core.len() + word_punct.len());
-    converted.push_str(&converted_core);
-    converted.push_str(word_punct);
-
-    ensure_changed(&p.word, &converted)?;
-
-    Ok(converted)
-}
-
-fn language_detector() -> &'static lingua::LanguageDetector {
-    use lingua::{Language, LanguageDetector, LanguageDetectorBuilder};
-
-    static DETECTOR: OnceLock<LanguageDetector> = OnceLock::new();
-    DETECTOR.get_or_init(|| {
-        LanguageDetectorBuilder::from_languages(&[Language::English, Language::Russian])
-            .with_minimum_relative_distance(0.20)
-            .build()
-    })
-}
-
-fn looks_like_ascii_word(s: &str) -> bool {
-    let bytes = s.as_bytes();
-    if bytes.is_empty() {
-        return false;
-    }
-
-    let is_ascii_letter = |b: u8| b.is_ascii_uppercase() || b.is_ascii_lowercase();
-
-    let mut has_letter = false;
-
-    for i in 0..bytes.len() {
-        let b = bytes[i];
-
-        if is_ascii_letter(b) {
-            has_letter = true;
-            continue;
-        }
-
-        if b == b'\'' {
-            continue;
-        }
 
-        // Allow dot or comma only when it is between ASCII letters.
-        if (b == b'.' || b == b',')
-            && i > 0
-            && i + 1 < bytes.len()
-            && is_ascii_letter(bytes[i - 1])
-            && is_ascii_letter(bytes[i + 1])
-        {
-            continue;
-        }
-
-        return false;
+    if user_dictionary::active_dictionary().never_converts(&p.word) {
+        return Err(SkipReason::UserNeverConvert);
+    }
+
+    let fix = case_fix
+        .enabled
+        .then(|| case_fix::detect(&p.word, caps_lock_on()))
+        .and_then(|fix| {
+            fix.inspect_err(|reason| {
+                tracing::trace!(reason = %reason.as_str(), "autoconvert: no case fix");
+            })
+            .ok()
+        });
+    if fix.is_some() && with_active_learned(|learned| learned.is_exception(&p.word, unix_now())) {
+        return Err(SkipReason::LearnedException);
+    }
+
+    let case_error = fix.as_ref().map(|f| f.error);
+    let word = fix.as_ref().map_or(p.word.as_str(), |f| f.word.as_str());
+
+    match layout_candidate(word) {
+        Ok(text) => Ok(Replacement {
+            text,
+            layout_converted: true,
+            case_error,
+        }),
+        Err(reason) => match fix {
+            Some(fix) => {
+                tracing::trace!(reason = %reason.as_str(), "autoconvert: case fix only");
+                Ok(Replacement {
+                    text: fix.word,
+                    layout_converted: false,
+                    case_error,
+                })
+            }
+            None => Err(reason),
+        },
     }
-
-    has_letter
 }
 
-fn should_autoconvert_word(
-    detector: &lingua::LanguageDetector,
-    word: &str,
-    converted: &str,
-) -> Result<(), SkipReason> {
-    use lingua::Language;
-
-    const MIN_CONVERTED_EN_CONF_FOR_OVERRIDE: f64 = 0.80;
-
-    let (word_core, _word_punct) = split_trailing_convertible_punct(word);
-    let (conv_core, _conv_punct) = split_trailing_convertible_punct(converted);
-
-    if word_core.chars().count() < MIN_WORD_LEN {
-        return Err(SkipReason::TooShort);
-    }
+/// Converts `word` to the other layout when the decider agrees.
+fn layout_candidate(word: &str) -> Result<String, SkipReason> {
+    let converted = autoconvert_candidate(word)?;
 
-    let w_is_ascii = looks_like_ascii_word(word_core);
-    let w_is_cyr = looks_like_cyrillic_word(word_core);
-    let c_is_ascii = looks_like_ascii_word(conv_core);
-    let c_is_cyr = looks_like_cyrillic_word(conv_core);
-
-    if !(w_is_ascii || w_is_cyr) || !(c_is_ascii || c_is_cyr) {
-        return Err(SkipReason::ScriptCheckFailed);
-    }
-
-    let w_ru = confidence(detector, word_core, Language::Russian);
-    let w_en = confidence(detector, word_core, Language::English);
-    let c_ru = confidence(detector, conv_core, Language::Russian);
-    let c_en = confidence(detector, conv_core, Language::English);
-
-    // Keep the English guard: do not convert real English words to Russian.
-    if w_is_ascii && is_plausible_english_like_token(word_core) {
-        return Err(SkipReason::AlreadyCorrect);
-    }
-
-    // Russian guard is conditional: if conversion yields a strong English candidate, do not short circuit.
-    if w_is_cyr && is_plausible_russian_like_token(word_core) {
-        let converted_looks_english = is_plausible_english_like_token(conv_core)
-            && c_en >= MIN_CONVERTED_EN_CONF_FOR_OVERRIDE;
-        if !converted_looks_english {
-            return Err(SkipReason::AlreadyCorrect);
-        }
+    let decision = decide_autoconvert(word, &converted);
+    if !decision.is_convert() {
+        tracing::trace!(
+            reason = decision.reason.map_or("", SkipReason::as_str),
+            scores = ?decision.scores,
+            "autoconvert skip: decision"
+        );
+        return Err(decision.reason.unwrap_or(SkipReason::AlreadyCorrect));
     }
+    Ok(converted)
+}
 
-    let w_best = w_ru.max(w_en);
-    let c_best = c_ru.max(c_en);
-
-    let target = if w_is_ascii {
-        Language::Russian
-    } else {
-        Language::English
-    };
+fn autoconvert_candidate(word: &str) -> Result<String, SkipReason> {
+    ensure_has_letters(word)?;
 
-    let (w_in_target, c_in_target) = if matches!(target, Language::Russian) {
-        (w_ru, c_ru)
-    } else {
-        (w_en, c_en)
-    };
-
-    if c_best < MIN_CONVERTED_CONFIDENCE {
-        return Err(SkipReason::ConvertedConfidenceLow);
-    }
+    let (word_core, word_punct) = split_trailing_convertible_punct(word);
 
-    let min_abs = if w_best < 0.30 {
-        0.55
-    } else {
-        MIN_CONVERTED_CONFIDENCE
-    };
-    if c_in_target < min_abs {
-        return Err(SkipReason::ConvertedConfidenceLow);
-    }
+    let converted_core = convert_for_layout_switch(word_core, ConvertDirection::Auto);
 
-    if c_in_target - w_in_target < MIN_CONFIDENCE_GAIN {
-        return Err(SkipReason::NotBetterEnough);
-    }
+    let mut converted = String::with_capacity(converted_core.len() + word_punct.len());
+    converted.push_str(&converted_core);
+    converted.push_str(word_punct);
 
-    Ok(())
-}
+    ensure_changed(word, &converted)?;
 
-fn confidence(detector: &lingua::LanguageDetector, text: &str, lang: lingua::Language) -> f64 {
-    detector
-        .compute_language_confidence_values(text)
-        .iter()
-        .find(|(l, _)| *l == lang)
-        .map_or(0.0, |(_, v)| *v)
+    Ok(converted)
 }
 
 fn ensure_no_newline(p: &LastWordPayload) -> Result<(), SkipReason> {
@@ -445,54 +495,34 @@ fn ensure_changed(word: &str, converted: &str) -> Result<(), SkipReason> {
     Err(SkipReason::NoChangeAfterConvert)
 }
 
-fn looks_like_cyrillic_word(s: &str) -> bool {
-    let mut has_alpha = false;
-
-    for ch in s.chars() {
-        if ch.is_alphabetic() {
-            if !is_cyrillic(ch) {
-                return false;
-            }
-            has_alpha = true;
-            continue;
-        }
-
-        if ch == '\'' || ch == '-' {
-            continue;
-        }
-
-        return false;
-    }
-
-    has_alpha
-}
-
-fn is_cyrillic(ch: char) -> bool {
-    ('\u{0400}'..='\u{04FF}').contains(&ch) || ('\u{0500}'..='\u{052F}').contains(&ch)
-}
-
-fn apply_last_word_replacement(p: &LastWordPayload, converted: &str) -> Result<(), ApplyError> {
-    if apply_last_word_conversion(p, converted) {
+fn apply_last_word_replacement(
+    p: &LastWordPayload,
+    converted: &str,
+    strategy: ReplacementStrategy,
+) -> Result<(), ApplyError> {
+    if apply_last_word_conversion(p, converted, strategy) {
         Ok(())
     } else {
         Err(ApplyError::KeyInjectionFailed)
     }
 }
 
-#[tracing::instrument(level = "trace", skip(state))]
-fn convert_last_word_impl(state: &mut AppState, switch_layout: bool) {
+#[tracing::instrument(level = "trace", skip_all, fields(transform = transform.id()))]
+fn convert_last_word_impl(state: &mut AppState, transform: &dyn TextTransform) {
     if !foreground_window_alive() {
         tracing::warn!("foreground window is null");
         return;
     }
 
-    if switch_layout && !wait_shift_released(150) {
+    if !wait_shift_released(150) {
         tracing::info!("wait_shift_released returned false");
         return;
     }
 
-    sleep_before_convert(state);
+    let settings = app_rules::foreground_settings(state);
+    sleep_before_convert(state, &settings);
 
+    let journal_before = crate::input_journal::snapshot();
     let Some(payload) = take_last_word_payload() else {
         tracing::info!("journal: no last word");
         return;
@@ -505,10 +535,10 @@ fn convert_last_word_impl(state: &mut AppState, switch_layout: bool) {
         return;
     }
 
-    let converted = convert_ru_en_bidirectional(&payload.word);
+    let converted = transform.apply(&payload.word);
     tracing::trace!(%converted, "converted");
 
-    if let Err(err) = apply_last_word_replacement(&payload, &converted) {
+    if let Err(err) = apply_last_word_replacement(&payload, &converted, settings.replacement) {
         tracing::warn!(error = %err.as_str(), "convert apply failed");
         return;
     }
@@ -516,12 +546,118 @@ fn convert_last_word_impl(state: &mut AppState, switch_layout: bool) {
     update_journal(&payload, &converted);
     restore.commit();
 
-    if switch_layout {
-        match switch_keyboard_layout() {
-            Ok(()) => tracing::trace!("layout switched"),
-            Err(e) => tracing::warn!(error = ?e, "layout switch failed"),
-        }
+    let before = foreground_layout();
+    let switched = transform.switches_layout()
+        && match switch_keyboard_layout() {
+            Ok(()) => {
+                tracing::trace!("layout switched");
+                true
+            }
+            Err(e) => {
+                tracing::warn!(error = ?e, "layout switch failed");
+                false
+            }
+        };
+
+    record_conversion(
+        ConversionKind::LastWord,
+        &payload,
+        converted,
+        journal_before,
+        before
+            .filter(|_| switched)
+            .map(|(_, layout)| layout.0 as isize),
+    );
+}
+
+/// Converts the words of `span` before the caret. `previous` is the run a repeated
+/// press of `convert_last_words` extends.
+///
+/// Returns the applied conversion, `None` when nothing was converted.
+fn convert_span(
+    state: &mut AppState,
+    span: Span,
+    previous: Option<&WordsRun>,
+    direction: ConvertDirection,
+) -> Option<WordsRun> {
+    if !foreground_window_alive() {
+        tracing::warn!("foreground window is null");
+        return None;
+    }
+
+    if !wait_shift_released(150) {
+        tracing::info!("wait_shift_released returned false");
+        return None;
     }
+
+    let settings = app_rules::foreground_settings(state);
+    sleep_before_convert(state, &settings);
+
+    let journal_before = crate::input_journal::snapshot();
+    let Some(payload) = take_span_payload(span) else {
+        tracing::info!(?span, "journal: no words");
+        return None;
+    };
+
+    let mut restore = JournalRestore::new(&payload);
+
+    if payload.suffix_has_newline {
+        tracing::trace!("suffix contains newline, skipping span conversion");
+        return None;
+    }
+    if let Span::Words(n) = span
+        && payload.words < n
+    {
+        tracing::trace!(n, found = payload.words, "no more words before the caret");
+        return None;
+    }
+
+    // The words converted by earlier presses are converted again from their original
+    // text, so the whole span is converted as one.
+    let original = previous
+        .and_then(|r| {
+            let head = payload.word.strip_suffix(r.converted.as_str())?;
+            Some(format!("{head}{}", r.original))
+        })
+        .unwrap_or_else(|| payload.word.clone());
+    let converted = convert_for_layout_switch(&original, direction);
+    tracing::trace!(%original, %converted, words = payload.words, "span converted");
+
+    if let Err(err) = apply_last_word_replacement(&payload, &converted, settings.replacement) {
+        tracing::warn!(error = %err.as_str(), "span convert apply failed");
+        return None;
+    }
+
+    update_journal(&payload, &converted);
+    restore.commit();
+
+    // The first press switched the layout already.
+    let before = foreground_layout();
+    let switched = previous.is_none()
+        && match switch_keyboard_layout() {
+            Ok(()) => true,
+            Err(e) => {
+                tracing::warn!(error = ?e, "layout switch failed");
+                false
+            }
+        };
+
+    record_conversion(
+        ConversionKind::LastWords,
+        &payload,
+        converted.clone(),
+        journal_before,
+        before
+            .filter(|_| switched)
+            .map(|(_, layout)| layout.0 as isize),
+    );
+
+    Some(WordsRun {
+        words: payload.words,
+        original,
+        converted,
+        journal_after: crate::input_journal::snapshot(),
+    })
 }
 
 fn foreground_window_alive() -> bool {
@@ -529,8 +665,8 @@ fn foreground_window_alive() -> bool {
     !fg.0.is_null()
 }
 
-fn sleep_before_convert(state: &AppState) {
-    let delay_ms = crate::helpers::get_edit_u32(state.edits.delay_ms).unwrap_or(100);
+fn sleep_before_convert(state: &AppState, settings: &AppSettings) {
+    let delay_ms = app_rules::conversion_delay_ms(state, settings);
     tracing::trace!(delay_ms, "sleep before convert");
     thread::sleep(Duration::from_millis(u64::from(delay_ms)));
 }
@@ -538,17 +674,21 @@ fn sleep_before_convert(state: &AppState) {
 struct LastWordPayload {
     word: String,
     suffix: String,
+    /// Words in `word`, more than one for a phrase or several words.
+    words: usize,
     word_len: usize,
     suffix_len: usize,
     suffix_spaces_only: bool,
     suffix_has_newline: bool,
 }
 
-fn normalize_last_word_payload(mut word: String, mut suffix: String) -> Option<LastWordPayload> {
-    fn is_convertible_trailing_punct(ch: char) -> bool {
-        matches!(ch, '?' | '/' | ',' | '.')
-    }
-
+/// Moves word punctuation at the start of `suffix` into `word`, see
+/// `BoundaryChars::word_punctuation`.
+fn normalize_last_word_payload(
+    mut word: String,
+    mut suffix: String,
+    boundaries: &BoundaryChars,
+) -> Option<LastWordPayload> {
     if word.is_empty() {
         return None;
     }
@@ -556,7 +696,7 @@ fn normalize_last_word_payload(mut word: String, mut suffix: String) -> Option<L
     let suffix_has_newline = suffix.contains('\n') || suffix.contains('\r');
 
     let (first, rest) = match suffix.chars().next() {
-        Some(ch) if is_convertible_trailing_punct(ch) => {
+        Some(ch) if boundaries.is_word_punctuation(ch) => {
             let ch_len = ch.len_utf8();
             (Some(ch), &suffix[ch_len..])
         }
@@ -588,6 +728,7 @@ fn normalize_last_word_payload(mut word: String, mut suffix: String) -> Option<L
     Some(LastWordPayload {
         word,
         suffix,
+        words: 1,
         word_len,
         suffix_len,
         suffix_spaces_only,
@@ -597,10 +738,21 @@ fn normalize_last_word_payload(mut word: String, mut suffix: String) -> Option<L
 
 fn take_last_word_payload() -> Option<LastWordPayload> {
     crate::input_journal::take_last_word_with_suffix()
-        .and_then(|(word, suffix)| normalize_last_word_payload(word, suffix))
+        .and_then(|last| normalize_last_word_payload(last.word, last.suffix, &last.boundaries))
 }
 
-fn apply_last_word_conversion(p: &LastWordPayload, converted: &str) -> bool {
+fn take_span_payload(span: Span) -> Option<LastWordPayload> {
+    let last = crate::input_journal::take_last_with_suffix(span)?;
+    let words = last.words;
+    normalize_last_word_payload(last.word, last.suffix, &last.boundaries)
+        .map(|p| LastWordPayload { words, ..p })
+}
+
+fn apply_last_word_conversion(
+    p: &LastWordPayload,
+    converted: &str,
+    strategy: ReplacementStrategy,
+) -> bool {
     const MAX_TAPS: usize = 4096;
 
     let word_len = p.word_len.min(MAX_TAPS);
@@ -609,14 +761,14 @@ fn apply_last_word_conversion(p: &LastWordPayload, converted: &str) -> bool {
     if p.suffix_spaces_only {
         move_caret_left(suffix_len)
             && delete_with_backspace(word_len)
-            && send_text_unicode(converted)
+            && insert_text(converted, strategy)
             && move_caret_right(suffix_len)
     } else {
         let delete_count = p.word_len.saturating_add(p.suffix_len).min(MAX_TAPS);
 
         delete_with_backspace(delete_count)
-            && send_text_unicode(converted)
-            && (p.suffix.is_empty() || send_text_unicode(&p.suffix))
+            && insert_text(converted, strategy)
+            && (p.suffix.is_empty() || insert_text(&p.suffix, strategy))
     }
 }
 
@@ -662,92 +814,21 @@ fn repeat_tap(vk: VIRTUAL_KEY, count: usize, err_msg: &'static str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use lingua::{Language, LanguageDetectorBuilder};
-
     use super::*;
-
-    fn detector_ru_en() -> lingua::LanguageDetector {
-        LanguageDetectorBuilder::from_languages(&[Language::Russian, Language::English])
-            .with_minimum_relative_distance(0.20)
-            .build()
-    }
-
-    #[test]
-    fn autoconvert_does_not_touch_correct_russian_word() {
-        let detector = detector_ru_en();
-
-        let word = "привет";
-        let converted = convert_ru_en_bidirectional(word);
-        assert_eq!(converted, "ghbdtn");
-
-        let decision = should_autoconvert_word(&detector, word, &converted);
-        assert!(
-            decision.is_err(),
-            "should not autoconvert correct Russian word"
-        );
-    }
-
-    #[test]
-    fn autoconvert_converts_mistyped_russian_layout_word() {
-        let detector = detector_ru_en();
-
-        let word = "ghbdtn";
-        let converted = convert_ru_en_bidirectional(word);
-        assert_eq!(converted, "привет");
-
-        match should_autoconvert_word(&detector, word, &converted) {
-            Ok(()) => {}
-            Err(reason) => {
-                panic!("should autoconvert mistyped Russian layout word, got Err({reason:?})");
-            }
-        }
-    }
-
-    #[test]
-    fn autoconvert_does_not_touch_correct_english_ascii_word() {
-        let detector = detector_ru_en();
-
-        let word = "world";
-        let converted = convert_ru_en_bidirectional(word);
-        assert_ne!(converted, word);
-
-        let decision = should_autoconvert_word(&detector, word, &converted);
-        assert!(
-            decision.is_err(),
-            "should not autoconvert correct English ASCII word"
-        );
-    }
-
-    #[test]
-    fn autoconvert_skips_too_short_words() {
-        let detector = detector_ru_en();
-
-        let word = "rfr"; // "как"
-        let converted = convert_ru_en_bidirectional(word);
-        assert_eq!(converted, "как");
-
-        let decision = should_autoconvert_word(&detector, word, &converted);
-        assert!(
-            decision.is_err(),
-            "must skip short words to avoid false positives"
-        );
-    }
-
-    #[test]
-    fn autoconvert_skips_mixed_or_nonword_tokens() {
-        let detector = detector_ru_en();
-
-        let word = ";tklf"; // starts with punctuation, should fail script heuristics
-        let converted = convert_ru_en_bidirectional(word);
-        assert_ne!(converted, word);
-
-        let decision = should_autoconvert_word(&detector, word, &converted);
-        assert!(decision.is_err(), "must skip nonword tokens");
-    }
+    use crate::domain::text::{
+        autoconvert::{AutoconvertDecider, HeuristicDecider, PairLanguages, language_detector},
+        layout::RU_EN,
+        mapping::convert_with_pair,
+    };
 
     #[test]
     fn normalize_moves_convertible_punct_into_word_when_suffix_is_whitespace() {
-        let p = normalize_last_word_payload("ghbdtn".to_string(), "? ".to_string()).unwrap();
+        let p = normalize_last_word_payload(
+            "ghbdtn".to_string(),
+            "? ".to_string(),
+            &BoundaryChars::default(),
+        )
+        .unwrap();
         assert_eq!(p.word, "ghbdtn?");
         assert_eq!(p.suffix, " ");
         assert!(p.suffix_spaces_only);
@@ -756,7 +837,12 @@ mod tests {
 
     #[test]
     fn normalize_does_not_move_punct_when_suffix_has_newline() {
-        let p = normalize_last_word_payload("ghbdtn".to_string(), "?\n".to_string()).unwrap();
+        let p = normalize_last_word_payload(
+            "ghbdtn".to_string(),
+            "?\n".to_string(),
+            &BoundaryChars::default(),
+        )
+        .unwrap();
         assert_eq!(p.word, "ghbdtn");
         assert_eq!(p.suffix, "?\n");
         assert!(p.suffix_has_newline);
@@ -764,94 +850,67 @@ mod tests {
 
     #[test]
     fn normalize_does_not_move_punct_when_suffix_has_nonspace_tail() {
-        let p = normalize_last_word_payload("ghbdtn".to_string(), "?x".to_string()).unwrap();
+        let p = normalize_last_word_payload(
+            "ghbdtn".to_string(),
+            "?x".to_string(),
+            &BoundaryChars::default(),
+        )
+        .unwrap();
         assert_eq!(p.word, "ghbdtn");
         assert_eq!(p.suffix, "?x");
     }
 
-    #[test]
-    fn autoconvert_skips_english_typo_like_hellp() {
-        let detector = detector_ru_en();
-
-        let word = "hellp";
-        let converted = convert_ru_en_bidirectional(word);
-        assert_ne!(converted, word);
-
-        let decision = should_autoconvert_word(&detector, word, &converted);
-        assert!(decision.is_err(), "must skip english-looking token: {word}");
-    }
-
-    #[test]
-    fn autoconvert_converts_token_from_reported_sequence_hjyxnmyuj() {
-        let detector = detector_ru_en();
-
-        let word = "hjyxnmyuj";
-        let converted = convert_ru_en_bidirectional(word);
-        assert_eq!(converted, "рончтьнго");
-
-        match should_autoconvert_word(&detector, word, &converted) {
-            Ok(()) => {}
-            Err(reason) => {
-                panic!(
-                    "must autoconvert token from reported sequence: {word} -> {converted}, got Err({reason:?})"
-                );
-            }
-        }
-    }
     #[test]
     fn normalize_does_not_move_nonconvertible_punct() {
-        let p = normalize_last_word_payload("ghbdtn".to_string(), "! ".to_string()).unwrap();
+        let p = normalize_last_word_payload(
+            "ghbdtn".to_string(),
+            "! ".to_string(),
+            &BoundaryChars::default(),
+        )
+        .unwrap();
         assert_eq!(p.word, "ghbdtn");
         assert_eq!(p.suffix, "! ");
     }
 
     #[test]
-    fn autoconvert_regression_reported_sequence_batch() {
-        let detector = detector_ru_en();
-
-        // (token, expected_autoconvert)
-        let cases = [
-            ("ghbdtn", true),    // привет
-            ("hjyxnmyuj", true), // рончтьнго (из репорта)
-            ("gjyznyj", true),   // понятно
-            ("fdujlyj", true), // "авгодно" (ошибка/мусор, но автоконверт должен срабатывать по текущей цели)
-            ("hellp", false),  // английскоподобная опечатка, не трогать
-            ("world", false),  // корректное английское слово, не трогать
-            ("привет", false), // корректное русское слово, не трогать
-        ];
-
-        for (word, should_convert) in cases {
-            let converted = convert_ru_en_bidirectional(word);
-
-            let decision = should_autoconvert_word(&detector, word, &converted);
-            match (should_convert, decision) {
-                (true, Ok(())) => {}
-                (false, Err(_)) => {}
-                (true, Err(reason)) => {
-                    panic!(
-                        "expected autoconvert for token: {word} -> {converted}, got Err({reason:?})"
-                    );
-                }
-                (false, Ok(())) => {
-                    panic!("expected skip for token: {word} -> {converted}, got Ok(())");
-                }
-            }
-        }
+    fn normalize_plus_case_fix_keeps_moved_punct() {
+        let p = normalize_last_word_payload(
+            "hELLO".to_string(),
+            ", ".to_string(),
+            &BoundaryChars::default(),
+        )
+        .unwrap();
+        assert_eq!(p.word, "hELLO,");
+
+        let fix = case_fix::detect(&p.word, true).unwrap();
+        assert_eq!(fix.error, CaseError::CapsLock);
+        assert_eq!(fix.word, "Hello,");
     }
+
     #[test]
     fn normalize_plus_decision_converts_with_trailing_punct_and_space_suffix() {
-        let detector = detector_ru_en();
-
-        let p = normalize_last_word_payload("ghbdtn".to_string(), ",   \t".to_string()).unwrap();
+        let langs = PairLanguages {
+            latin: lingua::Language::English,
+            cyrillic: lingua::Language::Russian,
+        };
+        let decider = HeuristicDecider::new(language_detector(langs), langs);
+
+        let p = normalize_last_word_payload(
+            "ghbdtn".to_string(),
+            ",   \t".to_string(),
+            &BoundaryChars::default(),
+        )
+        .unwrap();
 
         assert_eq!(p.word.as_str(), "ghbdtn,");
         assert_eq!(p.suffix.as_str(), "   \t");
 
-        let converted = convert_ru_en_bidirectional(&p.word);
-        let decision = should_autoconvert_word(&detector, &p.word, &converted);
+        let pair = layout::builtin_pair(RU_EN).unwrap();
+        let converted = convert_with_pair(&p.word, &pair, ConvertDirection::Auto);
+        let decision = decider.decide(&p.word, &converted);
 
         assert!(
-            decision.is_ok(),
+            decision.is_convert(),
             "expected autoconvert decision for normalized token: {} -> {}, got {:?}",
             p.word,
             converted,
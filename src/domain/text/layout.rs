@@ -0,0 +1,352 @@
+//! Declarative keyboard layout pair tables.
+//!
+//! A keyboard layout is modelled as a map from a physical key (its scan code) and a shift
+//! level to the produced character. Conversion between two layouts maps a character to the
+//! key that produced it in the source layout and emits the character of the same key in the
+//! target layout.
+//!
+//! A layout pair describes two keyboard layouts over the letter block. Each layout lists the
+//! characters produced by every key of the letter block, for the base and the Shift level.
+//! Layouts of installed keyboard layouts are built by a platform backend with `Layout::from_keys`;
+//! the only backend reads Windows layouts and is compiled on Windows only. The model and the
+//! conversion have no platform code and are tested on every platform.
+//!
+//! Built-in pairs are embedded from `res/layouts`. Users can add or override pairs with
+//! files of the same format, see `config::load_layout_pair_tables`.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Number of key positions in each row of the letter block, top to bottom.
+///
+/// - row 0: grave, digits, minus, equals
+/// - row 1: `Q` .. `]`, backslash
+/// - row 2: `A` .. `'`
+/// - row 3: `Z` .. `/`
+pub const ROW_LENGTHS: [usize; 4] = [13, 13, 11, 10];
+
+/// Set 1 scan codes of the letter block keys, row by row (see `ROW_LENGTHS`).
+const LETTER_BLOCK: [&[u16]; 4] = [
+    &[
+        0x29, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C, 0x0D,
+    ],
+    &[
+        0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x1B, 0x2B,
+    ],
+    &[
+        0x1E, 0x1F, 0x20, 0x21, 0x22, 0x23, 0x24, 0x25, 0x26, 0x27, 0x28,
+    ],
+    &[0x2C, 0x2D, 0x2E, 0x2F, 0x30, 0x31, 0x32, 0x33, 0x34, 0x35],
+];
+
+/// Scan code of the additional key between left Shift and `Z` on ISO keyboards.
+const ISO_EXTRA_KEY: u16 = 0x56;
+
+/// Id of the built-in Russian / English pair.
+pub const RU_EN: &str = "ru-en";
+
+/// Placeholder for a key position that produces no character in a table row.
+const UNMAPPED: char = ' ';
+
+const BUILTIN_TABLES: &[&str] = &[
+    include_str!("../../../res/layouts/ru-en.toml"),
+    include_str!("../../../res/layouts/uk-en.toml"),
+    include_str!("../../../res/layouts/be-en.toml"),
+    include_str!("../../../res/layouts/kk-en.toml"),
+    include_str!("../../../res/layouts/de-ru.toml"),
+];
+
+/// Characters produced by one keyboard layout, as stored in a layout pair file.
+///
+/// `base` and `shift` hold one string per row of the letter block (see `ROW_LENGTHS`).
+/// The n-th character of a row string is the character of the n-th key of that row.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LayoutTable {
+    /// Language tag of the layout, for example `en-US`.
+    pub tag: String,
+    pub base: Vec<String>,
+    pub shift: Vec<String>,
+}
+
+/// Declarative description of a layout pair, as stored in a layout pair file.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct LayoutPairTable {
+    pub id: String,
+    pub name: String,
+    pub primary: LayoutTable,
+    pub secondary: LayoutTable,
+}
+
+impl LayoutPairTable {
+    /// Parses a layout pair file.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.to_string())
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ShiftLevel {
+    Base,
+    Shift,
+}
+
+/// Set 1 scan code of a physical key.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ScanCode(pub u16);
+
+impl ScanCode {
+    /// Scan code of the letter block key at `row` and `col`, if the position exists.
+    pub fn at(row: usize, col: usize) -> Option<Self> {
+        LETTER_BLOCK
+            .get(row)
+            .and_then(|keys| keys.get(col))
+            .map(|&code| Self(code))
+    }
+}
+
+/// Scan codes of all keys that produce characters relevant for conversion:
+/// the letter block plus the ISO extra key.
+pub fn character_keys() -> impl Iterator<Item = ScanCode> {
+    LETTER_BLOCK
+        .iter()
+        .flat_map(|keys| keys.iter())
+        .chain(std::iter::once(&ISO_EXTRA_KEY))
+        .map(|&code| ScanCode(code))
+}
+
+type KeySlot = (ScanCode, ShiftLevel);
+
+/// A keyboard layout: characters produced by physical keys at each shift level.
+#[derive(Debug, Clone)]
+pub struct Layout {
+    tag: String,
+    keys: HashMap<KeySlot, char>,
+    chars: HashMap<char, KeySlot>,
+}
+
+impl Layout {
+    fn from_table(table: &LayoutTable) -> Result<Self, String> {
+        let mut layout = Self {
+            tag: table.tag.clone(),
+            keys: HashMap::new(),
+            chars: HashMap::new(),
+        };
+
+        layout.add_level(&table.tag, ShiftLevel::Base, &table.base)?;
+        layout.add_level(&table.tag, ShiftLevel::Shift, &table.shift)?;
+
+        Ok(layout)
+    }
+
+    fn add_level(&mut self, tag: &str, level: ShiftLevel, rows: &[String]) -> Result<(), String> {
+        if rows.len() > ROW_LENGTHS.len() {
+            return Err(format!(
+                "layout '{tag}' {level:?}: expected at most {} rows, got {}",
+                ROW_LENGTHS.len(),
+                rows.len()
+            ));
+        }
+
+        for (row, (text, &max_len)) in rows.iter().zip(ROW_LENGTHS.iter()).enumerate() {
+            let len = text.chars().count();
+            if len > max_len {
+                return Err(format!(
+                    "layout '{tag}' {level:?} row {row}: expected at most {max_len} keys, got {len}"
+                ));
+            }
+
+            for (col, ch) in text.chars().enumerate() {
+                if ch == UNMAPPED {
+                    continue;
+                }
+
+                let key = ScanCode::at(row, col)
+                    .ok_or_else(|| format!("layout '{tag}': no key at row {row} col {col}"))?;
+
+                // A key may produce the same character with and without Shift; it is
+                // reverse mapped to the base level.
+                match self.chars.get(&ch) {
+                    Some(&(prev, _)) if prev == key => {}
+                    Some(_) => {
+                        return Err(format!("layout '{tag}': character {ch:?} is mapped twice"));
+                    }
+                    None => {
+                        self.chars.insert(ch, (key, level));
+                    }
+                }
+                self.keys.insert((key, level), ch);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Builds a layout from keys reported by a platform backend.
+    ///
+    /// A character produced by several keys is reverse mapped to the first one,
+    /// so backends should report base levels before shifted ones.
+    pub fn from_keys(
+        tag: impl Into<String>,
+        keys: impl IntoIterator<Item = (ScanCode, ShiftLevel, char)>,
+    ) -> Self {
+        let mut layout = Self {
+            tag: tag.into(),
+            keys: HashMap::new(),
+            chars: HashMap::new(),
+        };
+
+        for (key, level, ch) in keys {
+            layout.keys.insert((key, level), ch);
+            layout.chars.entry(ch).or_insert((key, level));
+        }
+
+        layout
+    }
+
+    /// Language tag of the layout, for example `en-US`.
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// Returns the character produced by `key` at `level`, if any.
+    pub fn char_at(&self, key: ScanCode, level: ShiftLevel) -> Option<char> {
+        self.keys.get(&(key, level)).copied()
+    }
+
+    /// Returns the key and shift level that produce `ch`, if any.
+    pub fn key_for(&self, ch: char) -> Option<(ScanCode, ShiftLevel)> {
+        self.chars.get(&ch).copied()
+    }
+
+    pub fn contains(&self, ch: char) -> bool {
+        self.chars.contains_key(&ch)
+    }
+
+    /// Iterates over all characters produced by this layout, in no particular order.
+    pub fn chars(&self) -> impl Iterator<Item = char> + '_ {
+        self.chars.keys().copied()
+    }
+
+    /// Maps `ch` to the character produced by the same key and shift level in `target`.
+    pub fn map_to(&self, target: &Layout, ch: char) -> Option<char> {
+        self.key_for(ch)
+            .and_then(|(key, level)| target.char_at(key, level))
+    }
+}
+
+/// Converts text typed with `source` into what the same keystrokes produce with `target`.
+///
+/// Every character is reverse mapped to its key in `source` only, so characters present
+/// in both layouts on different keys (`,` `.` `?` `/` for RU/EN) convert unambiguously.
+/// Characters without a key in `source` or without a character in `target` are copied unchanged.
+pub fn convert_between(text: &str, source: &Layout, target: &Layout) -> String {
+    text.chars()
+        .map(|ch| source.map_to(target, ch).unwrap_or(ch))
+        .collect()
+}
+
+/// Two keyboard layouts over the same key positions.
+#[derive(Debug, Clone)]
+pub struct LayoutPair {
+    id: String,
+    name: String,
+    primary: Layout,
+    secondary: Layout,
+}
+
+impl LayoutPair {
+    /// Compiles and validates a declarative table.
+    ///
+    /// Fails when a row is longer than the letter block allows or when a layout maps
+    /// the same character to more than one key.
+    pub fn from_table(table: &LayoutPairTable) -> Result<Self, String> {
+        Ok(Self {
+            id: table.id.clone(),
+            name: table.name.clone(),
+            primary: Layout::from_table(&table.primary)?,
+            secondary: Layout::from_table(&table.secondary)?,
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn primary(&self) -> &Layout {
+        &self.primary
+    }
+
+    pub fn secondary(&self) -> &Layout {
+        &self.secondary
+    }
+}
+
+/// Returns all pairs shipped with the application.
+///
+/// Built-in tables are covered by tests, a table that fails to compile is logged and skipped.
+pub fn builtin_pairs() -> &'static [Arc<LayoutPair>] {
+    static PAIRS: OnceLock<Vec<Arc<LayoutPair>>> = OnceLock::new();
+    PAIRS.get_or_init(|| {
+        BUILTIN_TABLES
+            .iter()
+            .filter_map(|text| {
+                LayoutPairTable::parse(text)
+                    .and_then(|t| LayoutPair::from_table(&t))
+                    .map_err(|e| tracing::error!(error = %e, "built-in layout table is invalid"))
+                    .ok()
+            })
+            .map(Arc::new)
+            .collect()
+    })
+}
+
+pub fn builtin_pair(id: &str) -> Option<Arc<LayoutPair>> {
+    builtin_pairs().iter().find(|p| p.id() == id).cloned()
+}
+
+/// Compiles user tables and merges them over the built-in pairs.
+///
+/// A user table with the id of a built-in pair replaces it.
+pub fn merge_with_builtin(tables: &[LayoutPairTable]) -> Result<Vec<Arc<LayoutPair>>, String> {
+    let mut pairs: Vec<Arc<LayoutPair>> = builtin_pairs().to_vec();
+
+    for table in tables {
+        let pair = Arc::new(LayoutPair::from_table(table)?);
+        match pairs.iter_mut().find(|p| p.id() == pair.id()) {
+            Some(slot) => *slot = pair,
+            None => pairs.push(pair),
+        }
+    }
+
+    Ok(pairs)
+}
+
+static ACTIVE_PAIR: OnceLock<Mutex<Option<Arc<LayoutPair>>>> = OnceLock::new();
+
+fn active_pair_cell() -> &'static Mutex<Option<Arc<LayoutPair>>> {
+    ACTIVE_PAIR.get_or_init(|| Mutex::new(None))
+}
+
+/// Sets the pair used by the conversion pipelines. `None` restores the built-in RU/EN pair.
+pub fn set_active_pair(pair: Option<Arc<LayoutPair>>) {
+    if let Ok(mut active) = active_pair_cell().lock() {
+        *active = pair;
+    }
+}
+
+/// Returns the pair used by the conversion pipelines.
+pub fn active_pair() -> Option<Arc<LayoutPair>> {
+    active_pair_cell()
+        .lock()
+        .ok()
+        .and_then(|active| active.clone())
+        .or_else(|| builtin_pair(RU_EN))
+}
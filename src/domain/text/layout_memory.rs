@@ -0,0 +1,124 @@
+//! Keyboard layout remembered per application or per window and restored when it
+//! gets the focus again.
+//!
+//! Fed with focus changes by the platform, so it can be driven by simulated focus
+//! events in tests.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+
+/// Longest list of remembered layouts; the least recently focused entry is dropped.
+const MAX_REMEMBERED: usize = 64;
+
+/// What a layout is remembered for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LayoutMemoryScope {
+    /// Layouts are not remembered. Pinned layouts still apply.
+    #[default]
+    Off,
+    /// One layout per executable.
+    Application,
+    /// One layout per top level window.
+    Window,
+}
+
+/// A window that had or got the focus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FocusTarget {
+    /// Raw window handle.
+    pub window: isize,
+    /// File name of the executable owning the window, e.g. `code.exe`.
+    pub app: String,
+}
+
+/// Layout to activate for a window that got the focus.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Restore<H> {
+    /// The layout the window or its application last had.
+    Layout(H),
+    /// The layout with this language tag, pinned to the application.
+    Tag(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum MemoryKey {
+    App(String),
+    Window(isize),
+}
+
+#[derive(Debug, Clone)]
+pub struct LayoutMemory<H> {
+    scope: LayoutMemoryScope,
+    /// Language tag pinned to each application, keyed by lowercase executable name.
+    pinned: BTreeMap<String, String>,
+    /// Most recently focused first.
+    remembered: VecDeque<(MemoryKey, H)>,
+}
+
+impl<H> Default for LayoutMemory<H> {
+    fn default() -> Self {
+        Self {
+            scope: LayoutMemoryScope::Off,
+            pinned: BTreeMap::new(),
+            remembered: VecDeque::new(),
+        }
+    }
+}
+
+impl<H: Copy> LayoutMemory<H> {
+    /// Applies the settings of a config; layouts remembered so far are kept while the
+    /// scope stays the same.
+    pub fn configure(&mut self, scope: LayoutMemoryScope, pinned: &BTreeMap<String, String>) {
+        if scope != self.scope {
+            self.remembered.clear();
+        }
+        self.scope = scope;
+        self.pinned = pinned
+            .iter()
+            .map(|(app, tag)| (app.to_lowercase(), tag.clone()))
+            .collect();
+    }
+
+    /// Records the focus moving from `left`, which had `left_layout`, to `entered`.
+    ///
+    /// Returns the layout `entered` should get: its pinned layout if its application has
+    /// one, else the layout it last had. `left` is `None` when the window that lost the
+    /// focus is already gone.
+    pub fn focus_changed(
+        &mut self,
+        left: Option<(&FocusTarget, H)>,
+        entered: &FocusTarget,
+    ) -> Option<Restore<H>> {
+        if let Some((target, layout)) = left
+            && let Some(key) = self.key(target)
+        {
+            self.remember(key, layout);
+        }
+
+        if let Some(tag) = self.pinned.get(&entered.app.to_lowercase()) {
+            return Some(Restore::Tag(tag.clone()));
+        }
+
+        let key = self.key(entered)?;
+        self.remembered
+            .iter()
+            .find(|(k, _)| *k == key)
+            .map(|&(_, layout)| Restore::Layout(layout))
+    }
+
+    fn key(&self, target: &FocusTarget) -> Option<MemoryKey> {
+        match self.scope {
+            LayoutMemoryScope::Off => None,
+            LayoutMemoryScope::Application => Some(MemoryKey::App(target.app.to_lowercase())),
+            LayoutMemoryScope::Window => Some(MemoryKey::Window(target.window)),
+        }
+    }
+
+    fn remember(&mut self, key: MemoryKey, layout: H) {
+        self.remembered.retain(|(k, _)| *k != key);
+        self.remembered.push_front((key, layout));
+        self.remembered.truncate(MAX_REMEMBERED);
+    }
+}
@@ -0,0 +1,107 @@
+//! Choice of the keyboard layout a layout action activates.
+//!
+//! Works over an abstract list of installed layouts, so the platform backend only
+//! enumerates layouts and posts the change.
+
+/// An installed keyboard layout.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledLayout<H> {
+    /// Platform handle of the layout.
+    pub handle: H,
+    /// Language tag of the layout, e.g. `en-US`.
+    pub tag: String,
+}
+
+/// Layout an action switches to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutTarget<'a> {
+    /// The layout after the current one, cycling back to the first.
+    Next,
+    /// The layout with a language tag, see `layout_with_tag`.
+    Tag(&'a str),
+    /// The most recently used layout other than the current one.
+    Recent,
+}
+
+/// The two most recently used layouts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RecentLayouts<H> {
+    latest: Option<H>,
+    previous: Option<H>,
+}
+
+impl<H> Default for RecentLayouts<H> {
+    fn default() -> Self {
+        Self {
+            latest: None,
+            previous: None,
+        }
+    }
+}
+
+impl<H: Copy + PartialEq> RecentLayouts<H> {
+    /// Records `layout` as in use.
+    pub fn observe(&mut self, layout: H) {
+        if self.latest != Some(layout) {
+            self.previous = self.latest;
+            self.latest = Some(layout);
+        }
+    }
+
+    /// The most recently used layout other than `cur`.
+    pub fn other_than(&self, cur: H) -> Option<H> {
+        [self.latest, self.previous]
+            .into_iter()
+            .flatten()
+            .find(|&h| h != cur)
+    }
+}
+
+/// Returns the next layout in `layouts` after `cur`, cycling back to the first.
+///
+/// If `cur` is not found, returns `cur`.
+pub fn next_layout<H: Copy + PartialEq>(layouts: &[H], cur: H) -> H {
+    layouts
+        .iter()
+        .position(|&h| h == cur)
+        .and_then(|i| layouts.get((i + 1) % layouts.len()).copied())
+        .unwrap_or(cur)
+}
+
+/// The first layout whose language tag is `tag`, ignoring case.
+///
+/// A tag without a region, such as `en`, matches the first layout of that language.
+pub fn layout_with_tag<H: Copy>(layouts: &[InstalledLayout<H>], tag: &str) -> Option<H> {
+    let exact = layouts.iter().find(|l| l.tag.eq_ignore_ascii_case(tag));
+    let language = || {
+        layouts.iter().find(|l| {
+            l.tag
+                .split_once('-')
+                .is_some_and(|(lang, _)| lang.eq_ignore_ascii_case(tag))
+        })
+    };
+    exact.or_else(language).map(|l| l.handle)
+}
+
+/// The layout to activate for `target` while `cur` is active.
+///
+/// `Recent` falls back to the next layout while no other layout was used, or when the
+/// recent one is no longer installed. `None` when `target` names no installed layout
+/// or is already active.
+pub fn choose_layout<H: Copy + PartialEq>(
+    layouts: &[InstalledLayout<H>],
+    cur: H,
+    target: LayoutTarget<'_>,
+    recent: &RecentLayouts<H>,
+) -> Option<H> {
+    let handles: Vec<H> = layouts.iter().map(|l| l.handle).collect();
+    let chosen = match target {
+        LayoutTarget::Next => next_layout(&handles, cur),
+        LayoutTarget::Tag(tag) => layout_with_tag(layouts, tag)?,
+        LayoutTarget::Recent => recent
+            .other_than(cur)
+            .filter(|h| handles.contains(h))
+            .unwrap_or_else(|| next_layout(&handles, cur)),
+    };
+    (chosen != cur).then_some(chosen)
+}
@@ -0,0 +1,169 @@
+//! Exceptions learned from reverted autoconversions.
+//!
+//! Every time the user undoes an autoconversion, the original word gets one point.
+//! Points decay with a half life. One revert makes the word an exception, so retyping it
+//! right after the undo keeps it; a slip is forgotten once its point has halved.
+//!
+//! Times are Unix seconds passed by the caller.
+
+use std::{
+    collections::BTreeMap,
+    sync::{Mutex, OnceLock, PoisonError},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::autoconvert::split_trailing_convertible_punct;
+
+/// Time for a score to halve.
+pub const HALF_LIFE_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// Decayed score at which a word stops being autoconverted: one undo for a half life.
+pub const EXCEPTION_THRESHOLD: f64 = 0.5;
+
+/// Entries below this score are dropped on save.
+const FORGET_BELOW: f64 = 0.05;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Counter {
+    score: f64,
+    updated_at: u64,
+}
+
+impl Counter {
+    fn score_at(self, now: u64) -> f64 {
+        let elapsed = now.saturating_sub(self.updated_at) as f64;
+        self.score * 0.5f64.powf(elapsed / HALF_LIFE_SECS as f64)
+    }
+}
+
+/// Decaying undo counters per word, matched case insensitively.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LearnedExceptions {
+    counters: BTreeMap<String, Counter>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LearnedFile {
+    #[serde(default)]
+    words: Vec<LearnedRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LearnedRecord {
+    word: String,
+    score: f64,
+    updated_at: u64,
+}
+
+impl LearnedExceptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds one point to `word` after decaying its current score to `now`.
+    pub fn record_undo(&mut self, word: &str, now: u64) {
+        let Some(key) = normalize_word(word) else {
+            return;
+        };
+
+        let score = self.counters.get(&key).map_or(0.0, |c| c.score_at(now)) + 1.0;
+        self.counters.insert(
+            key,
+            Counter {
+                score,
+                updated_at: now,
+            },
+        );
+    }
+
+    /// Decayed score of `word`, `0.0` when it was never reverted.
+    pub fn score(&self, word: &str, now: u64) -> f64 {
+        normalize_word(word)
+            .and_then(|key| self.counters.get(&key))
+            .map_or(0.0, |c| c.score_at(now))
+    }
+
+    /// Whether autoconvert should leave `word` alone.
+    pub fn is_exception(&self, word: &str, now: u64) -> bool {
+        self.score(word, now) >= EXCEPTION_THRESHOLD
+    }
+
+    /// Forgets a word, returns whether it was known.
+    pub fn remove(&mut self, word: &str) -> bool {
+        normalize_word(word).is_some_and(|key| self.counters.remove(&key).is_some())
+    }
+
+    /// Words that are exceptions at `now` with their decayed scores, sorted by word.
+    pub fn exceptions(&self, now: u64) -> impl Iterator<Item = (&str, f64)> {
+        self.counters
+            .iter()
+            .map(move |(word, c)| (word.as_str(), c.score_at(now)))
+            .filter(|(_, score)| *score >= EXCEPTION_THRESHOLD)
+    }
+
+    /// Drops words whose score decayed to almost nothing.
+    pub fn prune(&mut self, now: u64) {
+        self.counters.retain(|_, c| c.score_at(now) >= FORGET_BELOW);
+    }
+
+    /// Parses the TOML store written by `to_toml`.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let file: LearnedFile = toml::from_str(text).map_err(|e| e.to_string())?;
+
+        let mut learned = Self::new();
+        for record in file.words {
+            let Some(key) = normalize_word(&record.word) else {
+                return Err(format!("invalid learned word '{}'", record.word));
+            };
+            learned.counters.insert(
+                key,
+                Counter {
+                    score: record.score,
+                    updated_at: record.updated_at,
+                },
+            );
+        }
+        Ok(learned)
+    }
+
+    pub fn to_toml(&self) -> Result<String, String> {
+        let file = LearnedFile {
+            words: self
+                .counters
+                .iter()
+                .map(|(word, c)| LearnedRecord {
+                    word: word.clone(),
+                    score: c.score,
+                    updated_at: c.updated_at,
+                })
+                .collect(),
+        };
+        toml::to_string(&file).map_err(|e| e.to_string())
+    }
+}
+
+/// Lowercased word without trailing punctuation typed on the same keys in both layouts.
+fn normalize_word(word: &str) -> Option<String> {
+    let (core, _) = split_trailing_convertible_punct(word.trim());
+    (!core.is_empty() && !core.chars().any(char::is_whitespace)).then(|| core.to_lowercase())
+}
+
+/// Current time in Unix seconds, `0` if the clock is before 1970.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+static ACTIVE_LEARNED: OnceLock<Mutex<LearnedExceptions>> = OnceLock::new();
+
+/// Runs `f` with the exceptions consulted by the last word pipeline.
+pub fn with_active_learned<R>(f: impl FnOnce(&mut LearnedExceptions) -> R) -> R {
+    let mut active = ACTIVE_LEARNED
+        .get_or_init(|| Mutex::new(LearnedExceptions::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    f(&mut active)
+}
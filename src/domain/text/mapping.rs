@@ -1,112 +1,332 @@
+use super::{
+    layout::{self, Layout, LayoutPair},
+    script::{is_cyrillic, is_plausible_cyrillic_like_token, is_plausible_latin_like_token},
+};
+
 /// Converts text between English QWERTY and Russian ЙЦУКЕН keyboard layouts in both directions.
 ///
 /// Behavior:
-/// - For each input Unicode scalar value (`char`), tries to map it as EN -> RU.
-/// - If EN -> RU mapping is not found, tries RU -> EN.
-/// - If neither mapping exists, the character is copied unchanged.
-///
-/// Mapping coverage:
-/// - Letters a-z and A-Z.
-/// - Punctuation on the same physical keys: brackets, semicolon, quote, comma, dot, backtick.
-/// - Russian letters include ё and Ё.
-/// - Curly braces `{}` and quotes `"` are produced from shifted bracket/quote keys, matching typical layouts.
-///
-/// Complexity:
-/// - O(n) over Unicode scalar values of the input string.
+/// - Uses the built-in `ru-en` layout pair table (`res/layouts/ru-en.toml`).
+/// - Same as `convert_with_pair` with `ConvertDirection::Auto`: text of one layout converts
+///   as a whole, mixed text converts only the runs that look mistyped.
+/// - Characters without a counterpart on the same key are copied unchanged.
 ///
 /// Notes:
 /// - This is a layout conversion, not a transliteration.
-/// - Non ASCII and non Russian letters are preserved as is.
 pub fn convert_ru_en_bidirectional(text: &str) -> String {
-    fn is_cyrillic(ch: char) -> bool {
-        matches!(ch, 'а'..='я' | 'А'..='Я' | 'ё' | 'Ё')
+    layout::builtin_pair(layout::RU_EN).map_or_else(
+        || text.to_string(),
+        |pair| convert_with_pair(text, &pair, ConvertDirection::Auto),
+    )
+}
+
+/// Direction of a layout conversion.
+///
+/// `ToRu` and `ToEn` name the layouts of the default RU/EN pair. For other layouts they
+/// select the Cyrillic and the Latin layout. When the layouts do not have one of each,
+/// both behave like `Auto`.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub enum ConvertDirection {
+    /// Convert only the runs that look mistyped.
+    #[default]
+    Auto,
+    /// Convert every run into the Cyrillic layout.
+    ToRu,
+    /// Convert every run into the Latin layout.
+    ToEn,
+}
+
+/// Converts text with the active layout pair (see `layout::active_pair`).
+///
+/// Used by the selection and last word pipelines when the installed layouts are unknown.
+/// Falls back to copying the text unchanged if no pair is available.
+pub fn convert_with_active_pair(text: &str, direction: ConvertDirection) -> String {
+    layout::active_pair().map_or_else(
+        || text.to_string(),
+        |pair| convert_with_pair(text, &pair, direction),
+    )
+}
+
+/// Converts text with a layout pair, run by run (see `convert_with_layouts`).
+///
+/// Text without layout specific characters converts to the primary layout.
+pub fn convert_with_pair(text: &str, pair: &LayoutPair, direction: ConvertDirection) -> String {
+    convert_with_layouts(text, pair.secondary(), pair.primary(), direction)
+}
+
+/// Converts text between the current keyboard layout and the layout to switch to.
+///
+/// The text is split into runs of characters typed with one layout. Characters produced
+/// by both layouts join the surrounding run, other characters (spaces, digits) end it.
+/// Numbers standing apart from letters are kept as typed, see `standalone_numbers`.
+///
+/// - Text with runs of one layout only is converted as a whole into the other layout,
+///   shared punctuation included. Text without layout specific characters is treated
+///   as typed with `current`.
+/// - Mixed text keeps the runs of the target layout and converts the others. Shared
+///   characters at the end of a converted run are kept, they are usually real punctuation.
+///
+/// With `ConvertDirection::Auto` the target of mixed text is the layout whose runs look like
+/// real words, for example `ghbdtn, мир` becomes `привет, мир`. When that does not decide,
+/// the layout with fewer specific characters is the target.
+///
+/// Every converted character is mapped through its physical key (see `layout::convert_between`).
+pub fn convert_with_layouts(
+    text: &str,
+    current: &Layout,
+    other: &Layout,
+    direction: ConvertDirection,
+) -> String {
+    let layouts = [current, other];
+    let chars: Vec<char> = text.chars().collect();
+    let neutral = standalone_numbers(&chars);
+    let runs = split_runs(&chars, &neutral, current, other);
+
+    let requested = match direction {
+        ConvertDirection::Auto => None,
+        ConvertDirection::ToRu => cyrillic_side(current, other),
+        ConvertDirection::ToEn => cyrillic_side(current, other).map(Side::flip),
+    };
+
+    let first = runs.first().map_or(Side::Current, |r| r.side);
+    if runs.iter().all(|r| r.side == first) {
+        if requested == Some(first) {
+            return text.to_string();
+        }
+        let (from, to) = (layouts[first.index()], layouts[first.flip().index()]);
+        return chars
+            .iter()
+            .zip(&neutral)
+            .map(|(&ch, &keep)| {
+                if keep {
+                    ch
+                } else {
+                    from.map_to(to, ch).unwrap_or(ch)
+                }
+            })
+            .collect();
     }
 
-    #[rustfmt::skip]
-    fn is_latin(ch: char) -> bool {
-        ch.is_ascii_alphabetic()
-            || matches!(
-                ch,
-                
-                '[' | ']' | ';' | '\'' | ',' | '.' | '`' | '{' | '}' | ':' | '"' | '<' | '>' | '~'
-                    | '?' | '/'
-            )
+    let target = requested.unwrap_or_else(|| auto_target(&chars, &runs));
+    let to = layouts[target.index()];
+
+    let mut out = String::with_capacity(text.len());
+    let mut pos = 0usize;
+    for run in runs.iter().filter(|r| r.side != target) {
+        let from = layouts[run.side.index()];
+        let body_end = run.end - run.trailing;
+
+        out.extend(&chars[pos..run.start]);
+        out.extend(
+            chars[run.start..body_end]
+                .iter()
+                .map(|&ch| from.map_to(to, ch).unwrap_or(ch)),
+        );
+        pos = body_end;
     }
+    out.extend(&chars[pos..]);
+
+    out
+}
 
-    fn map_ru_to_en(ch: char) -> char {
-        #[rustfmt::skip]
-        match ch {
-            'й' => 'q', 'ц' => 'w', 'у' => 'e', 'к' => 'r', 'е' => 't', 'н' => 'y', 'г' => 'u', 'ш' => 'i', 'щ' => 'o', 'з' => 'p',
-            'х' => '[', 'ъ' => ']',
-            'ф' => 'a', 'ы' => 's', 'в' => 'd', 'а' => 'f', 'п' => 'g', 'р' => 'h', 'о' => 'j', 'л' => 'k', 'д' => 'l',
-            'ж' => ';', 'э' => '\'',
-            'я' => 'z', 'ч' => 'x', 'с' => 'c', 'м' => 'v', 'и' => 'b', 'т' => 'n', 'ь' => 'm',
-            'б' => ',', 'ю' => '.',
-            'ё' => '`',
-
-            // punctuation rules you requested
-            ',' => '?',
-            '.' => '/',
-
-            'Й' => 'Q', 'Ц' => 'W', 'У' => 'E', 'К' => 'R', 'Е' => 'T', 'Н' => 'Y', 'Г' => 'U', 'Ш' => 'I', 'Щ' => 'O', 'З' => 'P',
-            'Х' => '{', 'Ъ' => '}',
-            'Ф' => 'A', 'Ы' => 'S', 'В' => 'D', 'А' => 'F', 'П' => 'G', 'Р' => 'H', 'О' => 'J', 'Л' => 'K', 'Д' => 'L',
-            'Ж' => ':', 'Э' => '"',
-            'Я' => 'Z', 'Ч' => 'X', 'С' => 'C', 'М' => 'V', 'И' => 'B', 'Т' => 'N', 'Ь' => 'M',
-            'Б' => '<', 'Ю' => '>',
-            'Ё' => '~',
-            _ => ch,
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Side {
+    Current,
+    Other,
+}
+
+impl Side {
+    fn flip(self) -> Self {
+        match self {
+            Side::Current => Side::Other,
+            Side::Other => Side::Current,
         }
     }
 
-    fn map_en_to_ru(ch: char) -> char {
-        #[rustfmt::skip]
-        match ch {
-            'q' => 'й', 'w' => 'ц', 'e' => 'у', 'r' => 'к', 't' => 'е', 'y' => 'н', 'u' => 'г', 'i' => 'ш', 'o' => 'щ', 'p' => 'з',
-            '[' => 'х', ']' => 'ъ',
-            'a' => 'ф', 's' => 'ы', 'd' => 'в', 'f' => 'а', 'g' => 'п', 'h' => 'р', 'j' => 'о', 'k' => 'л', 'l' => 'д',
-            ';' => 'ж', '\'' => 'э',
-            'z' => 'я', 'x' => 'ч', 'c' => 'с', 'v' => 'м', 'b' => 'и', 'n' => 'т', 'm' => 'ь',
-            ',' => 'б', '.' => 'ю',
-            '`' => 'ё',
-
-            // punctuation rules you requested
-            '?' => ',',
-            '/' => '.',
-
-            'Q' => 'Й', 'W' => 'Ц', 'E' => 'У', 'R' => 'К', 'T' => 'Е', 'Y' => 'Н', 'U' => 'Г', 'I' => 'Ш', 'O' => 'Щ', 'P' => 'З',
-            '{' => 'Х', '}' => 'Ъ',
-            'A' => 'Ф', 'S' => 'Ы', 'D' => 'В', 'F' => 'А', 'G' => 'П', 'H' => 'Р', 'J' => 'О', 'K' => 'Л', 'L' => 'Д',
-            ':' => 'Ж', '"' => 'Э',
-            'Z' => 'Я', 'X' => 'Ч', 'C' => 'С', 'V' => 'М', 'B' => 'И', 'N' => 'Т', 'M' => 'Ь',
-            '<' => 'Б', '>' => 'Ю',
-            '~' => 'Ё',
-            _ => ch,
+    fn index(self) -> usize {
+        match self {
+            Side::Current => 0,
+            Side::Other => 1,
         }
     }
+}
 
-    let mut cyr = 0usize;
-    let mut lat = 0usize;
-    for ch in text.chars() {
-        if is_cyrillic(ch) {
-            cyr += 1;
-        } else if is_latin(ch) {
-            lat += 1;
+/// A maximal run of characters typed with one layout.
+///
+/// `start..end` are char indices, `trailing` counts the characters produced by both
+/// layouts at the end of the run.
+#[derive(Debug)]
+struct Run {
+    side: Side,
+    start: usize,
+    end: usize,
+    trailing: usize,
+}
+
+/// Run being built by `split_runs`. `side` stays `None` while it holds only shared characters.
+struct OpenRun {
+    side: Option<Side>,
+    start: usize,
+    end: usize,
+    trailing: usize,
+}
+
+impl OpenRun {
+    fn new(start: usize) -> Self {
+        Self {
+            side: None,
+            start,
+            end: start,
+            trailing: 0,
         }
     }
 
-    let ru_to_en = cyr >= lat;
+    fn close(self, runs: &mut Vec<Run>) {
+        if let Some(side) = self.side {
+            runs.push(Run {
+                side,
+                start: self.start,
+                end: self.end,
+                trailing: self.trailing,
+            });
+        }
+    }
+}
 
-    let mut out = String::with_capacity(text.len());
-    if ru_to_en {
-        for ch in text.chars() {
-            out.push(map_ru_to_en(ch));
+/// Characters kept inside a number when a digit follows them.
+const NUMBER_SEPARATORS: &[char] = &['.', ',', ':', '/', '-'];
+
+/// Marks the numbers that touch no letter, such as `2024`, `3.14` or `2024-10-18`.
+///
+/// Some layouts put letters on the digit keys (Kazakh `ә` on `2`), so a digit inside a
+/// word is a mistyped letter, while a number on its own is meant as typed.
+fn standalone_numbers(chars: &[char]) -> Vec<bool> {
+    let mut neutral = vec![false; chars.len()];
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            i += 1;
+            continue;
         }
-    } else {
-        for ch in text.chars() {
-            out.push(map_en_to_ru(ch));
+
+        let start = i;
+        while chars.get(i).is_some_and(char::is_ascii_digit)
+            || (i > start
+                && chars.get(i).is_some_and(|c| NUMBER_SEPARATORS.contains(c))
+                && chars.get(i + 1).is_some_and(char::is_ascii_digit))
+        {
+            i += 1;
+        }
+        let touches_letter = start
+            .checked_sub(1)
+            .is_some_and(|before| chars[before].is_alphabetic())
+            || chars.get(i).is_some_and(|c| c.is_alphabetic());
+        if !touches_letter {
+            neutral[start..i].fill(true);
+        }
+    }
+    neutral
+}
+
+/// Splits text into runs, see `convert_with_layouts`. Characters marked `neutral` end runs.
+///
+/// Shared characters before the first layout specific character of a run belong to that run,
+/// shared characters between runs of different layouts belong to the earlier run.
+fn split_runs(chars: &[char], neutral: &[bool], current: &Layout, other: &Layout) -> Vec<Run> {
+    let mut runs = Vec::new();
+    let mut open: Option<OpenRun> = None;
+
+    for (i, &ch) in chars.iter().enumerate() {
+        let produced = if neutral[i] {
+            (false, false)
+        } else {
+            (current.contains(ch), other.contains(ch))
+        };
+        let side = match produced {
+            (true, true) => {
+                let run = open.get_or_insert_with(|| OpenRun::new(i));
+                run.end = i + 1;
+                run.trailing += 1;
+                continue;
+            }
+            (false, false) => {
+                if let Some(run) = open.take() {
+                    run.close(&mut runs);
+                }
+                continue;
+            }
+            (true, false) => Side::Current,
+            (false, true) => Side::Other,
+        };
+
+        if open
+            .as_ref()
+            .is_some_and(|run| run.side.is_some_and(|s| s != side))
+            && let Some(run) = open.take()
+        {
+            run.close(&mut runs);
         }
+
+        let run = open.get_or_insert_with(|| OpenRun::new(i));
+        run.side = Some(side);
+        run.end = i + 1;
+        run.trailing = 0;
     }
 
-    out
+    if let Some(run) = open.take() {
+        run.close(&mut runs);
+    }
+
+    runs
+}
+
+/// Picks the target side of mixed text for `ConvertDirection::Auto`.
+fn auto_target(chars: &[char], runs: &[Run]) -> Side {
+    let mut plausible = [0usize; 2];
+    let mut total = [0usize; 2];
+
+    for run in runs {
+        let word: String = chars[run.start..run.end - run.trailing].iter().collect();
+        let len = run.end - run.trailing - run.start;
+
+        total[run.side.index()] += len;
+        if is_plausible_latin_like_token(&word) || is_plausible_cyrillic_like_token(&word) {
+            plausible[run.side.index()] += len;
+        }
+    }
+
+    let [current, other] = plausible;
+    if current != other {
+        return if current > other {
+            Side::Current
+        } else {
+            Side::Other
+        };
+    }
+
+    // Same as the whole text vote: the side with more specific characters is the mistyped one.
+    if total[Side::Current.index()] > total[Side::Other.index()] {
+        Side::Other
+    } else {
+        Side::Current
+    }
+}
+
+/// Returns the side whose layout produces Cyrillic letters, if exactly one does.
+fn cyrillic_side(current: &Layout, other: &Layout) -> Option<Side> {
+    match (is_cyrillic_layout(current), is_cyrillic_layout(other)) {
+        (true, false) => Some(Side::Current),
+        (false, true) => Some(Side::Other),
+        _ => None,
+    }
+}
+
+fn is_cyrillic_layout(layout: &Layout) -> bool {
+    let (cyrillic, letters) = layout
+        .chars()
+        .filter(|ch| ch.is_alphabetic())
+        .fold((0usize, 0usize), |(c, n), ch| {
+            (c + usize::from(is_cyrillic(ch)), n + 1)
+        });
+    cyrillic * 2 > letters
 }
@@ -1,5 +1,24 @@
+pub mod autoconvert;
+pub mod case_fix;
+#[cfg(windows)]
 pub mod convert;
+#[cfg(feature = "frequency-scorer")]
+pub mod frequency;
+pub mod history;
+#[cfg(windows)]
 pub mod last_word;
+pub mod layout;
+pub mod layout_memory;
+pub mod layout_policy;
+pub mod learned_exceptions;
 pub mod mapping;
+pub mod script;
+pub mod selection;
+pub mod snippets;
+pub mod transform;
+#[cfg(windows)]
+pub mod undo;
+pub mod user_dictionary;
 
-pub use convert::{switch_keyboard_layout, wait_shift_released};
+#[cfg(windows)]
+pub use convert::{activate_layout, switch_keyboard_layout, wait_shift_released};
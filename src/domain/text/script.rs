@@ -0,0 +1,163 @@
+//! Script checks and word plausibility heuristics for Latin and Cyrillic text.
+//!
+//! Pure helpers shared by the conversion and autoconvert pipelines.
+
+fn is_latin_vowel(ch: char) -> bool {
+    let c = ch.to_lowercase().next().unwrap_or(ch);
+    matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'ä' | 'ö' | 'ü')
+}
+
+/// Vowels of the Russian, Ukrainian, Belarusian and Kazakh alphabets.
+fn is_cyrillic_vowel(ch: char) -> bool {
+    let c = ch.to_lowercase().next().unwrap_or(ch);
+    matches!(
+        c,
+        'а' | 'е'
+            | 'ё'
+            | 'и'
+            | 'о'
+            | 'у'
+            | 'ы'
+            | 'э'
+            | 'ю'
+            | 'я'
+            | 'і'
+            | 'ї'
+            | 'є'
+            | 'ә'
+            | 'ө'
+            | 'ү'
+            | 'ұ'
+    )
+}
+
+fn has_cyrillic_vowel(s: &str) -> bool {
+    s.chars().any(is_cyrillic_vowel)
+}
+
+pub fn is_plausible_latin_like_token(s: &str) -> bool {
+    if !looks_like_latin_word(s) {
+        return false;
+    }
+
+    let has_vowel = s.chars().any(is_latin_vowel);
+
+    // 'y' intentionally treated as consonant here to reduce false positives.
+    let mut consonant_run = 0usize;
+    let mut max_consonant_run = 0usize;
+    let mut rare = 0usize;
+
+    for ch in s.chars() {
+        if ch == '\'' {
+            continue;
+        }
+
+        if is_latin_vowel(ch) {
+            consonant_run = 0;
+        } else {
+            consonant_run += 1;
+            max_consonant_run = max_consonant_run.max(consonant_run);
+
+            if matches!(ch.to_ascii_lowercase(), 'j' | 'q' | 'x' | 'z') {
+                rare += 1;
+            }
+        }
+    }
+
+    has_vowel && max_consonant_run <= 4 && rare <= 1
+}
+
+pub fn is_plausible_cyrillic_like_token(s: &str) -> bool {
+    if !looks_like_cyrillic_word(s) {
+        return false;
+    }
+    if !has_cyrillic_vowel(s) {
+        return false;
+    }
+
+    let mut consonant_run = 0usize;
+    let mut max_consonant_run = 0usize;
+
+    for ch in s.chars() {
+        if ch == '\'' || ch == '-' {
+            continue;
+        }
+        if !ch.is_alphabetic() {
+            continue;
+        }
+
+        if is_cyrillic_vowel(ch) {
+            consonant_run = 0;
+        } else {
+            consonant_run += 1;
+            max_consonant_run = max_consonant_run.max(consonant_run);
+        }
+    }
+
+    max_consonant_run <= 4
+}
+
+/// ASCII letters plus the Latin-1 and Latin Extended-A/B letters (ä, ö, ü, ß, ...).
+fn is_latin_letter(ch: char) -> bool {
+    ch.is_ascii_alphabetic() || (ch.is_alphabetic() && ('\u{00C0}'..='\u{024F}').contains(&ch))
+}
+
+pub fn looks_like_latin_word(s: &str) -> bool {
+    let chars: Vec<char> = s.chars().collect();
+    if chars.is_empty() {
+        return false;
+    }
+
+    let mut has_letter = false;
+
+    for (i, &ch) in chars.iter().enumerate() {
+        if is_latin_letter(ch) {
+            has_letter = true;
+            continue;
+        }
+
+        if ch == '\'' {
+            continue;
+        }
+
+        // Allow dot or comma only when it is between Latin letters.
+        if (ch == '.' || ch == ',')
+            && i > 0
+            && i + 1 < chars.len()
+            && is_latin_letter(chars[i - 1])
+            && is_latin_letter(chars[i + 1])
+        {
+            continue;
+        }
+
+        return false;
+    }
+
+    has_letter
+}
+
+pub fn looks_like_cyrillic_word(s: &str) -> bool {
+    let mut has_alpha = false;
+
+    for ch in s.chars() {
+        if ch.is_alphabetic() {
+            if !is_cyrillic(ch) {
+                return false;
+            }
+            has_alpha = true;
+            continue;
+        }
+
+        if ch == '\'' || ch == '-' {
+            continue;
+        }
+
+        return false;
+    }
+
+    has_alpha
+}
+
+pub fn is_cyrillic(ch: char) -> bool {
+    ('\u{0400}'..='\u{04FF}').contains(&ch) || ('\u{0500}'..='\u{052F}').contains(&ch)
+}
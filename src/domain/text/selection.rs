@@ -0,0 +1,68 @@
+//! Splitting converted selections for injection, and measuring them for reselection.
+//!
+//! A selection may span many lines and thousands of characters. A single line is
+//! typed in runs of bounded length; text with line breaks is pasted instead, so the
+//! target window receives its line endings as they are.
+
+use std::borrow::Cow;
+
+/// Default upper bound of a converted selection, in characters.
+pub const DEFAULT_MAX_SELECTION_CHARS: usize = 10_000;
+
+/// UTF-16 units typed with one `SendInput` call.
+pub const CHUNK_UTF16_UNITS: usize = 256;
+
+/// Checks whether copied text is eligible for selection conversion.
+///
+/// `s.chars().nth(max_chars).is_none()` stops early for long strings, unlike `chars().count()`.
+pub fn is_convertible_selection(s: &str, max_chars: usize) -> bool {
+    !s.is_empty() && s.chars().nth(max_chars).is_none()
+}
+
+/// Whether `text` has a line break, `\r` or `\n`.
+///
+/// Such text is pasted rather than typed: a typed `\r` or `\n` is not a line break in
+/// every window, and a typed Enter submits single-line inputs and chat boxes.
+pub fn has_line_break(text: &str) -> bool {
+    text.contains(['\r', '\n'])
+}
+
+/// Writes every `\n` not preceded by `\r` as `\r\n`, the line break of clipboard text.
+///
+/// Snippets keep their line breaks as `\n`, which plain edit controls would paste as
+/// nothing. A line break stays one caret step either way, see `caret_steps`.
+pub fn clipboard_line_endings(text: &str) -> Cow<'_, str> {
+    if !text.contains('\n') {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.replace("\r\n", "\n").replace('\n', "\r\n"))
+}
+
+/// Splits `line` into runs of at most `max_units` UTF-16 units.
+///
+/// Runs never split a character, so surrogate pairs stay together, and a character
+/// longer than `max_units` is a run of its own.
+pub fn runs(mut line: &str, max_units: usize) -> Vec<&str> {
+    let mut out = Vec::new();
+    while !line.is_empty() {
+        let mut units = 0;
+        let end = line
+            .char_indices()
+            .find(|&(i, c)| {
+                units += c.len_utf16();
+                i > 0 && units > max_units
+            })
+            .map_or(line.len(), |(i, _)| i);
+        let (run, tail) = line.split_at(end);
+        out.push(run);
+        line = tail;
+    }
+    out
+}
+
+/// Number of Left presses that move the caret from the end of `text` to its start.
+///
+/// Each character is one step, and so is each line break, `\r\n` included.
+pub fn caret_steps(text: &str) -> usize {
+    text.chars().count() - text.matches("\r\n").count()
+}
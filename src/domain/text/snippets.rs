@@ -0,0 +1,273 @@
+//! Snippets: a trigger word typed before a word boundary is replaced with its text.
+//!
+//! Snippets are stored as TOML next to the config:
+//!
+//! ```toml
+//! [[snippet]]
+//! trigger = "sig"
+//! text = """
+//! Best regards,
+//! {cursor}"""
+//! ```
+//!
+//! The text may hold placeholders, see `Placeholder`. `{{` and `}}` stand for literal
+//! braces, and an unknown name in braces is kept as typed.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use serde::{Deserialize, Serialize};
+
+use super::autoconvert::split_trailing_convertible_punct;
+
+/// A value put into the snippet text when it is expanded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placeholder {
+    /// `{date}`, the local date as `YYYY-MM-DD`.
+    Date,
+    /// `{time}`, the local time as `HH:MM`.
+    Time,
+    /// `{clipboard}`, the text on the clipboard.
+    Clipboard,
+    /// `{cursor}`, where the caret is left. Only the first one counts.
+    Cursor,
+}
+
+impl Placeholder {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "date" => Some(Self::Date),
+            "time" => Some(Self::Time),
+            "clipboard" => Some(Self::Clipboard),
+            "cursor" => Some(Self::Cursor),
+            _ => None,
+        }
+    }
+}
+
+/// Snippet text with its placeholders filled in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Expansion {
+    /// Line breaks are `\n`, so each character is one caret step.
+    pub text: String,
+    /// Left presses from the end of `text` to `{cursor}`, zero without one.
+    pub caret_back: usize,
+}
+
+/// Fills in the placeholders of `template`. `value` is asked for each placeholder
+/// other than `Cursor`, in order of appearance.
+pub fn expand(template: &str, mut value: impl FnMut(Placeholder) -> String) -> Expansion {
+    let template = template.replace("\r\n", "\n");
+    let mut text = String::with_capacity(template.len());
+    let mut cursor = None;
+    let mut rest = template.as_str();
+
+    while let Some(i) = rest.find(['{', '}']) {
+        text.push_str(&rest[..i]);
+        let tail = &rest[i..];
+
+        if tail.starts_with("{{") || tail.starts_with("}}") {
+            text.push_str(&tail[..1]);
+            rest = &tail[2..];
+            continue;
+        }
+
+        let placeholder = tail
+            .strip_prefix('{')
+            .and_then(|t| t.split_once('}'))
+            .and_then(|(name, after)| Some((Placeholder::parse(name)?, after)));
+        match placeholder {
+            Some((Placeholder::Cursor, after)) => {
+                cursor.get_or_insert(text.chars().count());
+                rest = after;
+            }
+            Some((p, after)) => {
+                text.push_str(&value(p).replace("\r\n", "\n"));
+                rest = after;
+            }
+            None => {
+                text.push_str(&tail[..1]);
+                rest = &tail[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+
+    let caret_back = cursor.map_or(0, |at| text.chars().count() - at);
+    Expansion { text, caret_back }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnippetError {
+    EmptyTrigger,
+    TriggerHasWhitespace(String),
+    EmptyText(String),
+    DuplicateTrigger(String),
+    Syntax(String),
+}
+
+impl fmt::Display for SnippetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnippetError::EmptyTrigger => write!(f, "trigger is empty"),
+            SnippetError::TriggerHasWhitespace(trigger) => {
+                write!(f, "trigger '{trigger}' contains whitespace")
+            }
+            SnippetError::EmptyText(trigger) => write!(f, "snippet '{trigger}' has no text"),
+            SnippetError::DuplicateTrigger(trigger) => {
+                write!(f, "trigger '{trigger}' is defined more than once")
+            }
+            SnippetError::Syntax(msg) => write!(f, "{msg}"),
+        }
+    }
+}
+
+impl std::error::Error for SnippetError {}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SnippetFile {
+    #[serde(default)]
+    snippet: Vec<SnippetRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct SnippetRecord {
+    trigger: String,
+    text: String,
+}
+
+/// A found snippet for the typed word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnippetMatch<'a> {
+    pub trigger: &'a str,
+    pub text: &'a str,
+    /// Punctuation typed after the trigger, kept after the expansion.
+    pub punct: &'a str,
+}
+
+/// Snippets by trigger, matched case insensitively.
+///
+/// A trigger matches the word as typed or its conversion to the other layout, so a
+/// trigger typed in the wrong layout is expanded too. Punctuation typed on the same
+/// keys in both layouts is ignored at the end of a word, as in the user dictionary.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Snippets {
+    entries: BTreeMap<String, String>,
+}
+
+impl Snippets {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds a snippet or changes the text of an existing one.
+    pub fn insert(&mut self, trigger: &str, text: &str) -> Result<(), SnippetError> {
+        let key = normalize_trigger(trigger)?;
+        if text.is_empty() {
+            return Err(SnippetError::EmptyText(key));
+        }
+        self.entries.insert(key, text.to_string());
+        Ok(())
+    }
+
+    /// Removes a snippet, returns whether it existed.
+    pub fn remove(&mut self, trigger: &str) -> bool {
+        normalize_trigger(trigger).is_ok_and(|key| self.entries.remove(&key).is_some())
+    }
+
+    pub fn get(&self, trigger: &str) -> Option<&str> {
+        let key = normalize_trigger(trigger).ok()?;
+        self.entries.get(&key).map(String::as_str)
+    }
+
+    /// Snippets sorted by trigger.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries.iter().map(|(t, s)| (t.as_str(), s.as_str()))
+    }
+
+    /// Finds the snippet of `word`, or of `converted`, its conversion to the other layout.
+    pub fn find<'a>(&'a self, word: &'a str, converted: &'a str) -> Option<SnippetMatch<'a>> {
+        [word, converted].into_iter().find_map(|typed| {
+            let (core, punct) = split_trailing_convertible_punct(typed);
+            let key = normalize_trigger(core).ok()?;
+            let (trigger, text) = self.entries.get_key_value(&key)?;
+            Some(SnippetMatch {
+                trigger,
+                text,
+                punct,
+            })
+        })
+    }
+
+    /// Parses the TOML format, see the module docs.
+    pub fn parse(text: &str) -> Result<Self, SnippetError> {
+        let file: SnippetFile =
+            toml::from_str(text).map_err(|e| SnippetError::Syntax(e.to_string()))?;
+
+        let mut snippets = Self::new();
+        for record in file.snippet {
+            if snippets.get(&record.trigger).is_some() {
+                return Err(SnippetError::DuplicateTrigger(record.trigger));
+            }
+            snippets.insert(&record.trigger, &record.text)?;
+        }
+        Ok(snippets)
+    }
+
+    /// Formats the snippets as TOML, `parse` reads them back.
+    pub fn to_toml(&self) -> Result<String, SnippetError> {
+        let file = SnippetFile {
+            snippet: self
+                .entries()
+                .map(|(trigger, text)| SnippetRecord {
+                    trigger: trigger.to_string(),
+                    text: text.to_string(),
+                })
+                .collect(),
+        };
+        toml::to_string(&file).map_err(|e| SnippetError::Syntax(e.to_string()))
+    }
+}
+
+fn normalize_trigger(trigger: &str) -> Result<String, SnippetError> {
+    let trigger = trigger.trim();
+    if trigger.is_empty() {
+        return Err(SnippetError::EmptyTrigger);
+    }
+    if trigger.chars().any(char::is_whitespace) {
+        return Err(SnippetError::TriggerHasWhitespace(trigger.to_string()));
+    }
+    Ok(trigger.to_lowercase())
+}
+
+static ACTIVE_SNIPPETS: OnceLock<Mutex<Arc<Snippets>>> = OnceLock::new();
+
+fn active_snippets_cell() -> &'static Mutex<Arc<Snippets>> {
+    ACTIVE_SNIPPETS.get_or_init(|| Mutex::new(Arc::new(Snippets::new())))
+}
+
+/// Sets the snippets expanded after a typed trigger.
+pub fn set_active_snippets(snippets: Snippets) {
+    if let Ok(mut active) = active_snippets_cell().lock() {
+        *active = Arc::new(snippets);
+    }
+}
+
+/// Returns the snippets expanded after a typed trigger, empty until some are set.
+pub fn active_snippets() -> Arc<Snippets> {
+    active_snippets_cell()
+        .lock()
+        .map(|active| Arc::clone(&active))
+        .unwrap_or_default()
+}
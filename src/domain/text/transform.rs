@@ -0,0 +1,330 @@
+//! Text transforms applied to the selection or to the last word.
+//!
+//! Each transform of `TRANSFORMS` can be bound to a hotkey as `transform_<id>`, see
+//! `app::actions::transform`. The layout swap is the conversion of the other actions;
+//! the rest work on the text alone.
+
+#[cfg(windows)]
+use super::convert::convert_for_layout_switch;
+use super::mapping::ConvertDirection;
+// Without installed layouts to read, the layout pair table converts.
+#[cfg(not(windows))]
+use super::mapping::convert_with_active_pair as convert_for_layout_switch;
+
+/// A transform from text to text.
+pub trait TextTransform: Sync {
+    /// Config key of the transform, bound as `transform_<id>`.
+    fn id(&self) -> &'static str;
+
+    /// Shown in validation messages.
+    fn name(&self) -> &'static str;
+
+    fn apply(&self, text: &str) -> String;
+
+    /// Whether the keyboard layout is switched after the text was replaced.
+    fn switches_layout(&self) -> bool {
+        false
+    }
+}
+
+/// Retypes text in the other layout, see `convert::convert_for_layout_switch`.
+///
+/// With an explicit direction the text may already be in the requested layout, so the
+/// keyboard layout is switched only by `ConvertDirection::Auto`.
+#[derive(Debug, Clone, Copy)]
+pub struct LayoutSwap(pub ConvertDirection);
+
+impl TextTransform for LayoutSwap {
+    fn id(&self) -> &'static str {
+        match self.0 {
+            ConvertDirection::Auto => "layout_swap",
+            ConvertDirection::ToRu => "layout_to_cyrillic",
+            ConvertDirection::ToEn => "layout_to_latin",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self.0 {
+            ConvertDirection::Auto => "Swap layout",
+            ConvertDirection::ToRu => "Retype in the Cyrillic layout",
+            ConvertDirection::ToEn => "Retype in the Latin layout",
+        }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        convert_for_layout_switch(text, self.0)
+    }
+
+    fn switches_layout(&self) -> bool {
+        self.0 == ConvertDirection::Auto
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaseTransform {
+    /// Swaps upper and lower case, for text typed with Caps Lock on by accident.
+    Invert,
+    Upper,
+    Lower,
+    /// Upper case at the start of each word, lower case elsewhere.
+    Title,
+}
+
+impl TextTransform for CaseTransform {
+    fn id(&self) -> &'static str {
+        match self {
+            Self::Invert => "invert_case",
+            Self::Upper => "upper_case",
+            Self::Lower => "lower_case",
+            Self::Title => "title_case",
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Invert => "Invert case",
+            Self::Upper => "Upper case",
+            Self::Lower => "Lower case",
+            Self::Title => "Title case",
+        }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        match self {
+            Self::Invert => text
+                .chars()
+                .map(|ch| {
+                    if ch.is_lowercase() {
+                        ch.to_uppercase().to_string()
+                    } else {
+                        ch.to_lowercase().to_string()
+                    }
+                })
+                .collect(),
+            Self::Upper => text.to_uppercase(),
+            Self::Lower => text.to_lowercase(),
+            Self::Title => title_case(text),
+        }
+    }
+}
+
+fn is_apostrophe(ch: char) -> bool {
+    ch == '\'' || ch == '\u{2019}'
+}
+
+fn title_case(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_word = false;
+    for ch in text.chars() {
+        if ch.is_alphanumeric() {
+            if in_word {
+                out.extend(ch.to_lowercase());
+            } else {
+                out.extend(ch.to_uppercase());
+            }
+            in_word = true;
+        } else {
+            out.push(ch);
+            in_word = in_word && is_apostrophe(ch);
+        }
+    }
+    out
+}
+
+/// Cyrillic to Latin transliteration of GOST 7.79-2000 System A, the same as ISO 9.
+///
+/// Each letter has its own Latin spelling, so text in Latin is transliterated back.
+#[derive(Debug, Clone, Copy)]
+pub struct Iso9Transliteration;
+
+/// Lower case letters of ISO 9, Cyrillic then Latin. `ґ` is `g` with a combining grave.
+const ISO9: [(char, &str); 38] = [
+    ('а', "a"),
+    ('б', "b"),
+    ('в', "v"),
+    ('г', "g"),
+    ('д', "d"),
+    ('е', "e"),
+    ('ё', "ë"),
+    ('ж', "ž"),
+    ('з', "z"),
+    ('и', "i"),
+    ('й', "j"),
+    ('к', "k"),
+    ('л', "l"),
+    ('м', "m"),
+    ('н', "n"),
+    ('о', "o"),
+    ('п', "p"),
+    ('р', "r"),
+    ('с', "s"),
+    ('т', "t"),
+    ('у', "u"),
+    ('ф', "f"),
+    ('х', "h"),
+    ('ц', "c"),
+    ('ч', "č"),
+    ('ш', "š"),
+    ('щ', "ŝ"),
+    ('ъ', "ʺ"),
+    ('ы', "y"),
+    ('ь', "ʹ"),
+    ('э', "è"),
+    ('ю', "û"),
+    ('я', "â"),
+    ('і', "ì"),
+    ('ї', "ï"),
+    ('є', "ê"),
+    ('ґ', "g\u{300}"),
+    ('ў', "ǔ"),
+];
+
+fn to_lower(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+/// Whether `ch` is a letter or mark of the ISO 9 Latin spellings that plain Latin text lacks.
+fn is_iso9_mark(ch: char) -> bool {
+    !ch.is_ascii() && ISO9.iter().any(|&(_, lat)| lat.contains(to_lower(ch)))
+}
+
+/// Length of `lat` at the start of `text`, matched case insensitively.
+fn latin_prefix_len(text: &str, lat: &str) -> Option<usize> {
+    let mut chars = text.char_indices();
+    let mut len = 0;
+    for expected in lat.chars() {
+        let (i, ch) = chars.next()?;
+        if to_lower(ch) != expected {
+            return None;
+        }
+        len = i + ch.len_utf8();
+    }
+    Some(len)
+}
+
+impl TextTransform for Iso9Transliteration {
+    fn id(&self) -> &'static str {
+        "translit_iso9"
+    }
+
+    fn name(&self) -> &'static str {
+        "Transliterate (GOST 7.79 / ISO 9)"
+    }
+
+    /// Transliterates Cyrillic text to Latin. Text without Cyrillic letters is transliterated
+    /// back only when it has ISO 9 diacritics, so plain Latin text is left as it is.
+    fn apply(&self, text: &str) -> String {
+        if text
+            .chars()
+            .any(|ch| ISO9.iter().any(|&(cyr, _)| cyr == to_lower(ch)))
+        {
+            return text
+                .chars()
+                .map(
+                    |ch| match ISO9.iter().find(|&&(cyr, _)| cyr == to_lower(ch)) {
+                        Some(&(_, lat)) if ch.is_uppercase() => {
+                            let mut chars = lat.chars();
+                            chars.next().map_or_else(String::new, |first| {
+                                first.to_uppercase().chain(chars).collect()
+                            })
+                        }
+                        Some(&(_, lat)) => lat.to_string(),
+                        None => ch.to_string(),
+                    },
+                )
+                .collect();
+        }
+
+        if !text.chars().any(is_iso9_mark) {
+            return text.to_string();
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some(ch) = rest.chars().next() {
+            // The longest spelling wins, so `g̀` is not read as `g` and a stray mark.
+            let found = ISO9
+                .iter()
+                .filter_map(|&(cyr, lat)| Some((latin_prefix_len(rest, lat)?, cyr)))
+                .max_by_key(|&(len, _)| len);
+            let len = match found {
+                Some((len, cyr)) if ch.is_uppercase() => {
+                    out.extend(cyr.to_uppercase());
+                    len
+                }
+                Some((len, cyr)) => {
+                    out.push(cyr);
+                    len
+                }
+                None => {
+                    out.push(ch);
+                    ch.len_utf8()
+                }
+            };
+            rest = &rest[len..];
+        }
+        out
+    }
+}
+
+/// Replaces straight double quotes with `«»` and hyphens used as dashes with `—`.
+#[derive(Debug, Clone, Copy)]
+pub struct Typography;
+
+impl TextTransform for Typography {
+    fn id(&self) -> &'static str {
+        "typography"
+    }
+
+    fn name(&self) -> &'static str {
+        "Typographic quotes and dashes"
+    }
+
+    /// A quote at the start, or after whitespace or an opening bracket, opens. A hyphen
+    /// between spaces, and a double hyphen, become a dash.
+    fn apply(&self, text: &str) -> String {
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = String::with_capacity(text.len());
+        let mut i = 0;
+        while i < chars.len() {
+            let prev = out.chars().next_back();
+            let next = chars.get(i + 1).copied();
+            match chars[i] {
+                '"' => {
+                    let opens = prev.is_none_or(|p| p.is_whitespace() || "([{«—".contains(p));
+                    out.push(if opens { '«' } else { '»' });
+                }
+                '-' if next == Some('-') => {
+                    out.push('—');
+                    i += 1;
+                }
+                '-' if prev.is_some_and(char::is_whitespace)
+                    && next.is_some_and(char::is_whitespace) =>
+                {
+                    out.push('—');
+                }
+                ch => out.push(ch),
+            }
+            i += 1;
+        }
+        out
+    }
+}
+
+/// The built-in transforms, in the order of validation reports.
+pub static TRANSFORMS: [&dyn TextTransform; 9] = [
+    &LayoutSwap(ConvertDirection::Auto),
+    &LayoutSwap(ConvertDirection::ToRu),
+    &LayoutSwap(ConvertDirection::ToEn),
+    &CaseTransform::Invert,
+    &CaseTransform::Upper,
+    &CaseTransform::Lower,
+    &CaseTransform::Title,
+    &Iso9Transliteration,
+    &Typography,
+];
+
+/// The transform with id `id`.
+pub fn find(id: &str) -> Option<&'static dyn TextTransform> {
+    TRANSFORMS.iter().copied().find(|t| t.id() == id)
+}
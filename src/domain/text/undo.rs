@@ -0,0 +1,51 @@
+use std::{thread, time::Duration};
+
+use windows::Win32::UI::Input::KeyboardAndMouse::HKL;
+
+use super::{
+    convert::{foreground_window, post_layout_change, undo_selection},
+    history::{ConversionKind, UndoRefusal, with_history},
+    last_word::undo_last_word,
+    wait_shift_released,
+};
+use crate::{app::AppState, platform::win::app_rules};
+
+/// Reverts the most recent conversion if the target window still shows its result.
+///
+/// The record is dropped from the history only when the undo succeeded.
+pub fn undo_last_conversion(state: &mut AppState) -> Result<ConversionKind, UndoRefusal> {
+    let record = with_history(|h| h.last().cloned()).ok_or(UndoRefusal::NothingToUndo)?;
+    let fg = foreground_window().ok_or(UndoRefusal::WindowChanged)?;
+
+    record.check_undo(fg.0 as isize, &crate::input_journal::snapshot())?;
+
+    if !wait_shift_released(150) {
+        return Err(UndoRefusal::InjectionFailed);
+    }
+
+    let settings = app_rules::foreground_settings(state);
+    let delay_ms = app_rules::conversion_delay_ms(state, &settings);
+    thread::sleep(Duration::from_millis(u64::from(delay_ms)));
+
+    match record.kind {
+        ConversionKind::Selection => undo_selection(&record, settings.replacement)?,
+        ConversionKind::LastWord
+        | ConversionKind::LastWords
+        | ConversionKind::Autoconvert
+        | ConversionKind::Snippet => {
+            if !undo_last_word(&record, settings.replacement) {
+                return Err(UndoRefusal::InjectionFailed);
+            }
+            crate::input_journal::restore(&record.journal_before);
+        }
+    }
+
+    if let Some(raw) = record.previous_layout
+        && let Err(e) = post_layout_change(fg, HKL(raw as *mut _))
+    {
+        tracing::warn!(error = ?e, "layout restore failed (undo)");
+    }
+
+    let _ = with_history(|h| h.pop());
+    Ok(record.kind)
+}
@@ -0,0 +1,226 @@
+//! User dictionary for autoconvert.
+//!
+//! Entries override the autoconvert decider for single words: product names and CLI flags
+//! that must stay as typed, or jargon the language models do not know.
+//!
+//! The dictionary is stored as plain text, one entry per line:
+//!
+//! ```text
+//! # comment
+//! never rust-switcher
+//! never --verbose
+//! always hjyxnmyuj
+//! ```
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use super::autoconvert::{Decision, SkipReason, split_trailing_convertible_punct};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DictionaryRule {
+    /// Keep the word as typed.
+    NeverConvert,
+    /// Convert the word without asking the decider.
+    AlwaysConvert,
+}
+
+impl DictionaryRule {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DictionaryRule::NeverConvert => "never",
+            DictionaryRule::AlwaysConvert => "always",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "never" => Some(DictionaryRule::NeverConvert),
+            "always" => Some(DictionaryRule::AlwaysConvert),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DictionaryError {
+    EmptyWord,
+    WordHasWhitespace(String),
+    UnknownRule { line: usize, rule: String },
+    MissingWord { line: usize },
+    TrailingText { line: usize },
+}
+
+impl fmt::Display for DictionaryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DictionaryError::EmptyWord => write!(f, "word is empty"),
+            DictionaryError::WordHasWhitespace(word) => {
+                write!(f, "word '{word}' contains whitespace")
+            }
+            DictionaryError::UnknownRule { line, rule } => {
+                write!(
+                    f,
+                    "line {line}: unknown rule '{rule}', expected 'never' or 'always'"
+                )
+            }
+            DictionaryError::MissingWord { line } => write!(f, "line {line}: missing word"),
+            DictionaryError::TrailingText { line } => {
+                write!(f, "line {line}: expected one word after the rule")
+            }
+        }
+    }
+}
+
+impl std::error::Error for DictionaryError {}
+
+/// Words with a fixed autoconvert outcome, matched case insensitively.
+///
+/// A `NeverConvert` entry matches the word as typed. An `AlwaysConvert` entry matches
+/// the word as typed or its conversion, so both `hjyxnmyuj` and `рончтьнго` work.
+/// Punctuation typed on the same keys in both layouts is ignored at the end of a word.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserDictionary {
+    entries: BTreeMap<String, DictionaryRule>,
+}
+
+impl UserDictionary {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Adds an entry or changes the rule of an existing one.
+    pub fn insert(&mut self, word: &str, rule: DictionaryRule) -> Result<(), DictionaryError> {
+        let key = normalize_word(word)?;
+        self.entries.insert(key, rule);
+        Ok(())
+    }
+
+    /// Removes an entry, returns whether it existed.
+    pub fn remove(&mut self, word: &str) -> bool {
+        normalize_word(word).is_ok_and(|key| self.entries.remove(&key).is_some())
+    }
+
+    pub fn get(&self, word: &str) -> Option<DictionaryRule> {
+        let key = normalize_word(word).ok()?;
+        self.entries.get(&key).copied()
+    }
+
+    /// Entries sorted by word.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, DictionaryRule)> {
+        self.entries.iter().map(|(w, r)| (w.as_str(), *r))
+    }
+
+    /// Adds all entries of `other`, its rules win on conflicts.
+    pub fn merge(&mut self, other: &UserDictionary) {
+        self.entries.extend(
+            other
+                .entries
+                .iter()
+                .map(|(word, rule)| (word.clone(), *rule)),
+        );
+    }
+
+    /// Whether `word` is a `never` entry, ignoring punctuation typed after it.
+    pub fn never_converts(&self, word: &str) -> bool {
+        let (word_core, _) = split_trailing_convertible_punct(word);
+        self.get(word_core) == Some(DictionaryRule::NeverConvert)
+    }
+
+    /// Returns the decision forced by an entry for `word`, if any.
+    pub fn decide(&self, word: &str, converted: &str) -> Option<Decision> {
+        if self.never_converts(word) {
+            return Some(Decision::skip(SkipReason::UserNeverConvert, None));
+        }
+
+        let (word_core, _) = split_trailing_convertible_punct(word);
+        let (conv_core, _) = split_trailing_convertible_punct(converted);
+        if self.get(word_core) == Some(DictionaryRule::AlwaysConvert) {
+            return Some(Decision::convert(None));
+        }
+
+        (self.get(conv_core) == Some(DictionaryRule::AlwaysConvert))
+            .then(|| Decision::convert(None))
+    }
+
+    /// Parses the plain text format, see the module docs.
+    pub fn parse(text: &str) -> Result<Self, DictionaryError> {
+        let mut dict = Self::new();
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.split_whitespace();
+            let rule_text = parts.next().unwrap_or_default();
+            let rule =
+                DictionaryRule::parse(rule_text).ok_or_else(|| DictionaryError::UnknownRule {
+                    line: line_no,
+                    rule: rule_text.to_string(),
+                })?;
+            let word = parts
+                .next()
+                .ok_or(DictionaryError::MissingWord { line: line_no })?;
+            if parts.next().is_some() {
+                return Err(DictionaryError::TrailingText { line: line_no });
+            }
+
+            dict.insert(word, rule)?;
+        }
+
+        Ok(dict)
+    }
+
+    /// Formats the dictionary in the plain text format, `parse` reads it back.
+    pub fn to_text(&self) -> String {
+        self.entries()
+            .map(|(word, rule)| format!("{} {word}\n", rule.as_str()))
+            .collect()
+    }
+}
+
+fn normalize_word(word: &str) -> Result<String, DictionaryError> {
+    let word = word.trim();
+    if word.is_empty() {
+        return Err(DictionaryError::EmptyWord);
+    }
+    if word.chars().any(char::is_whitespace) {
+        return Err(DictionaryError::WordHasWhitespace(word.to_string()));
+    }
+    Ok(word.to_lowercase())
+}
+
+static ACTIVE_DICTIONARY: OnceLock<Mutex<Arc<UserDictionary>>> = OnceLock::new();
+
+fn active_dictionary_cell() -> &'static Mutex<Arc<UserDictionary>> {
+    ACTIVE_DICTIONARY.get_or_init(|| Mutex::new(Arc::new(UserDictionary::new())))
+}
+
+/// Sets the dictionary consulted by the last word pipeline.
+pub fn set_active_dictionary(dict: UserDictionary) {
+    if let Ok(mut active) = active_dictionary_cell().lock() {
+        *active = Arc::new(dict);
+    }
+}
+
+/// Returns the dictionary consulted by the last word pipeline, empty until one is set.
+pub fn active_dictionary() -> Arc<UserDictionary> {
+    active_dictionary_cell()
+        .lock()
+        .map(|active| Arc::clone(&active))
+        .unwrap_or_default()
+}
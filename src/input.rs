@@ -1,2 +1,6 @@
 pub mod hotkeys;
-pub(crate) mod ring_buffer;
+pub mod key_event;
+pub mod pipeline;
+pub mod ring_buffer;
+pub mod sequence_matcher;
+pub mod word_boundaries;
@@ -0,0 +1,199 @@
+//! Platform neutral keyboard input.
+//!
+//! The low level hook translates `KBDLLHOOKSTRUCT` into `KeyEvent`, and the
+//! input pipeline reports what it recognized through an `ActionSink`. A recorded
+//! event stream can be replayed through the same pipeline without Windows.
+
+use crate::{
+    app::actions::ActionId,
+    config::{self, MODVK_LCTRL, MODVK_RALT},
+};
+
+// Values of the Win32 `MOD_*` flags stored in `config::HotkeyChord::mods`.
+pub const MOD_ALT: u32 = 0x0001;
+pub const MOD_CONTROL: u32 = 0x0002;
+pub const MOD_SHIFT: u32 = 0x0004;
+pub const MOD_WIN: u32 = 0x0008;
+
+// Win32 virtual key codes the pipeline handles specially.
+pub const VK_BACK: u32 = 0x08;
+pub const VK_TAB: u32 = 0x09;
+pub const VK_RETURN: u32 = 0x0D;
+pub const VK_ESCAPE: u32 = 0x1B;
+pub const VK_PRIOR: u32 = 0x21;
+pub const VK_NEXT: u32 = 0x22;
+pub const VK_END: u32 = 0x23;
+pub const VK_HOME: u32 = 0x24;
+pub const VK_LEFT: u32 = 0x25;
+pub const VK_UP: u32 = 0x26;
+pub const VK_RIGHT: u32 = 0x27;
+pub const VK_DOWN: u32 = 0x28;
+pub const VK_INSERT: u32 = 0x2D;
+pub const VK_DELETE: u32 = 0x2E;
+pub const VK_LWIN: u32 = 0x5B;
+pub const VK_RWIN: u32 = 0x5C;
+pub const VK_LSHIFT: u32 = 0xA0;
+pub const VK_RSHIFT: u32 = 0xA1;
+pub const VK_LCONTROL: u32 = 0xA2;
+pub const VK_RCONTROL: u32 = 0xA3;
+pub const VK_LMENU: u32 = 0xA4;
+pub const VK_RMENU: u32 = 0xA5;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum KeyEventKind {
+    Down,
+    Up,
+}
+
+/// One key press or release.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct KeyEvent {
+    /// Virtual key code, with left and right modifiers told apart.
+    pub vk: u32,
+    pub scan_code: u32,
+    pub kind: KeyEventKind,
+    /// Set for synthesized input, including the text this app injects.
+    pub injected: bool,
+    /// Milliseconds since boot.
+    pub time_ms: u64,
+}
+
+impl KeyEvent {
+    pub fn is_mod(&self) -> bool {
+        mod_bit_for_vk(self.vk).is_some()
+    }
+}
+
+/// Events built by the replay tests.
+#[cfg(test)]
+impl KeyEvent {
+    pub fn down(vk: u32, time_ms: u64) -> Self {
+        Self {
+            vk,
+            scan_code: 0,
+            kind: KeyEventKind::Down,
+            injected: false,
+            time_ms,
+        }
+    }
+
+    pub fn up(vk: u32, time_ms: u64) -> Self {
+        Self {
+            kind: KeyEventKind::Up,
+            ..Self::down(vk, time_ms)
+        }
+    }
+}
+
+pub fn mod_bit_for_vk(vk: u32) -> Option<u32> {
+    match vk {
+        VK_LCONTROL | VK_RCONTROL => Some(MOD_CONTROL),
+        VK_LSHIFT | VK_RSHIFT => Some(MOD_SHIFT),
+        VK_LMENU | VK_RMENU => Some(MOD_ALT),
+        VK_LWIN | VK_RWIN => Some(MOD_WIN),
+        _ => None,
+    }
+}
+
+pub fn mod_vk_bit_for_vk(vk: u32) -> Option<u32> {
+    match vk {
+        VK_LCONTROL => Some(config::MODVK_LCTRL),
+        VK_RCONTROL => Some(config::MODVK_RCTRL),
+        VK_LSHIFT => Some(config::MODVK_LSHIFT),
+        VK_RSHIFT => Some(config::MODVK_RSHIFT),
+        VK_LMENU => Some(config::MODVK_LALT),
+        VK_RMENU => Some(config::MODVK_RALT),
+        VK_LWIN => Some(config::MODVK_LWIN),
+        VK_RWIN => Some(config::MODVK_RWIN),
+        _ => None,
+    }
+}
+
+/// Modifiers held down, tracked from key events.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModsDown {
+    /// `MOD_*` flags.
+    pub mods: u32,
+    /// `config::MODVK_*` flags.
+    pub mods_vks: u32,
+}
+
+impl ModsDown {
+    pub fn press(&mut self, vk: u32) {
+        if let Some(bit) = mod_bit_for_vk(vk) {
+            self.mods |= bit;
+        }
+        if let Some(bit) = mod_vk_bit_for_vk(vk) {
+            self.mods_vks |= bit;
+        }
+    }
+
+    pub fn release(&mut self, vk: u32) {
+        if let Some(bit) = mod_bit_for_vk(vk) {
+            self.mods &= !bit;
+        }
+        if let Some(bit) = mod_vk_bit_for_vk(vk) {
+            self.mods_vks &= !bit;
+        }
+    }
+
+    pub fn apply(&mut self, event: &KeyEvent) {
+        match event.kind {
+            KeyEventKind::Down => self.press(event.vk),
+            KeyEventKind::Up => self.release(event.vk),
+        }
+    }
+
+    /// Chord of `vk` with the held modifiers.
+    pub fn chord(self, vk: u32) -> config::HotkeyChord {
+        let mut mods_vks = self.mods_vks;
+
+        // Windows AltGr often arrives as RAlt + LCtrl
+        if (mods_vks & MODVK_RALT) != 0 {
+            mods_vks &= !MODVK_LCTRL;
+        }
+
+        config::HotkeyChord {
+            mods: self.mods,
+            mods_vks,
+            vk: Some(vk),
+            press: config::PressStyle::Press,
+        }
+    }
+
+    pub fn ctrl_or_alt(self) -> bool {
+        (self.mods & (MOD_CONTROL | MOD_ALT)) != 0
+    }
+}
+
+/// What the system knows about the keyboard beyond the events themselves.
+pub trait KeyboardState {
+    /// Raw handle of the foreground window, 0 when there is none.
+    fn foreground_window(&self) -> isize;
+
+    /// Raw handle of the foreground window's keyboard layout, 0 when there is none.
+    fn layout(&self) -> isize;
+
+    /// Text typed by the key in the foreground window's layout.
+    ///
+    /// `None` for keys that type nothing, dead keys and control characters.
+    fn translate(&self, event: &KeyEvent) -> Option<String>;
+}
+
+/// Something the pipeline recognized in the input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// The hotkey sequence of an action was completed.
+    Hotkey(ActionId),
+    /// A word was finished by a trigger character and may be autoconverted.
+    Autoconvert,
+    /// The user backspaced into the last autoconverted word.
+    AutoconvertReverted,
+}
+
+/// Receives the actions recognized by the pipeline.
+pub trait ActionSink {
+    type Error;
+
+    fn emit(&mut self, action: Action) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,339 @@
+//! Keyboard pipeline: hotkey sequence matching and the input journal.
+//!
+//! Works on `KeyEvent`s only. The hook supplies the system state through
+//! `KeyboardState` and delivers the recognized actions through `ActionSink`;
+//! tests replay recorded events with fakes of both.
+
+use crate::{
+    app::{HeldKey, LastTap, RuntimeHotkeys},
+    config,
+    input::{
+        key_event::{
+            Action, ActionSink, KeyEvent, KeyEventKind, KeyboardState, ModsDown, VK_BACK,
+            VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_HOME, VK_INSERT, VK_LEFT, VK_NEXT, VK_PRIOR,
+            VK_RETURN, VK_RIGHT, VK_TAB, VK_UP,
+        },
+        ring_buffer::InputJournal,
+        sequence_matcher::{ChordInput, MatchOutcome, release_window_ms},
+    },
+};
+
+/// Whether the hook lets a key event through to the foreground window.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum HookDecision {
+    Pass,
+    Swallow,
+    /// Swallow the event and inject a press of `vk` followed by the event itself.
+    ///
+    /// Gives back a key that was held back as a possible tap or hold.
+    Replay {
+        vk: u32,
+    },
+}
+
+impl HookDecision {
+    pub fn should_swallow(self) -> bool {
+        !matches!(self, Self::Pass)
+    }
+
+    /// Whether the event reaches the foreground window, directly or replayed.
+    pub fn delivers_event(self) -> bool {
+        !matches!(self, Self::Swallow)
+    }
+
+    fn from_matched(matched: bool) -> Self {
+        if matched { Self::Swallow } else { Self::Pass }
+    }
+}
+
+/// Feeds a chord to the sequence matcher and reports a completed sequence.
+pub fn try_match_any_sequence<S: ActionSink>(
+    hotkeys: &mut RuntimeHotkeys,
+    input: ChordInput,
+    sink: &mut S,
+) -> Result<MatchOutcome, S::Error> {
+    let outcome = hotkeys.matcher.feed(&hotkeys.bindings, input);
+    if let MatchOutcome::Completed(action) = outcome {
+        sink.emit(Action::Hotkey(action))?;
+    }
+    Ok(outcome)
+}
+
+/// Feeds the release of a trigger pressed for `held_ms`.
+fn match_release<S: ActionSink>(
+    hotkeys: &mut RuntimeHotkeys,
+    chord: config::HotkeyChord,
+    held_ms: u64,
+    now_ms: u64,
+    sink: &mut S,
+) -> Result<MatchOutcome, S::Error> {
+    let pressed_ms = now_ms.saturating_sub(held_ms);
+    let since_last_tap_ms = hotkeys
+        .last_tap
+        .filter(|tap| tap.chord == chord)
+        .map(|tap| pressed_ms.saturating_sub(tap.released_ms));
+
+    let input = ChordInput::up(chord, held_ms, since_last_tap_ms, now_ms);
+    let outcome = try_match_any_sequence(hotkeys, input, sink)?;
+
+    // A completed double tap must not be the first tap of the next one.
+    hotkeys.last_tap = match outcome {
+        MatchOutcome::Completed(_) => None,
+        _ => Some(LastTap {
+            chord,
+            released_ms: now_ms,
+        }),
+    };
+    Ok(outcome)
+}
+
+/// Handles a key press. `mods` must already include the event.
+///
+/// Injected input never matches hotkeys, so replayed keys reach the foreground window.
+pub fn handle_keydown_runtime<S: ActionSink>(
+    hotkeys: &mut RuntimeHotkeys,
+    event: &KeyEvent,
+    mods: ModsDown,
+    sink: &mut S,
+) -> Result<HookDecision, S::Error> {
+    if event.injected {
+        return Ok(HookDecision::Pass);
+    }
+
+    if event.is_mod() {
+        let capture = &mut hotkeys.chord_capture;
+        if !capture.pending_mods_valid {
+            capture.pending_since_ms = event.time_ms;
+        }
+        let chord = mods.chord(event.vk);
+        capture.pending_mods = chord.mods;
+        capture.pending_mods_vks = chord.mods_vks;
+        capture.pending_mods_valid = true;
+        capture.saw_non_mod = false;
+        return Ok(HookDecision::Pass);
+    }
+
+    hotkeys.chord_capture.saw_non_mod = true;
+    hotkeys.chord_capture.pending_mods_valid = false;
+
+    if let Some(held) = hotkeys.held_key.as_mut() {
+        if held.chord.vk == Some(event.vk) {
+            // Autorepeat of the held key.
+            if !held.released && event.time_ms.saturating_sub(held.down_ms) > held.window_ms {
+                held.released = true;
+            }
+            return Ok(if held.released {
+                HookDecision::Pass
+            } else {
+                HookDecision::Swallow
+            });
+        }
+
+        if !held.released {
+            // Another key interrupts the press: it was a plain keystroke after all.
+            held.released = true;
+            return Ok(HookDecision::Replay {
+                vk: held.chord.vk.unwrap_or(event.vk),
+            });
+        }
+    }
+
+    let chord = mods.chord(event.vk);
+    let outcome = try_match_any_sequence(hotkeys, ChordInput::down(chord, event.time_ms), sink)?;
+
+    if !matches!(outcome, MatchOutcome::Completed(_))
+        && let Some(window_ms) = release_window_ms(&hotkeys.bindings, chord)
+    {
+        hotkeys.held_key = Some(HeldKey {
+            chord,
+            down_ms: event.time_ms,
+            window_ms,
+            released: false,
+        });
+    }
+
+    Ok(HookDecision::from_matched(outcome.consumed()))
+}
+
+/// Handles a key release. `mods` are the modifiers still held after it.
+pub fn handle_keyup_runtime<S: ActionSink>(
+    hotkeys: &mut RuntimeHotkeys,
+    event: &KeyEvent,
+    mods: ModsDown,
+    sink: &mut S,
+) -> Result<HookDecision, S::Error> {
+    if event.injected {
+        return Ok(HookDecision::Pass);
+    }
+
+    if !event.is_mod() {
+        return release_held_key(hotkeys, event, sink);
+    }
+
+    if !hotkeys.chord_capture.pending_mods_valid {
+        return Ok(HookDecision::Pass);
+    }
+    if hotkeys.chord_capture.saw_non_mod {
+        return Ok(HookDecision::Pass);
+    }
+
+    if mods.mods != 0 {
+        return Ok(HookDecision::Pass);
+    }
+
+    let chord = config::HotkeyChord {
+        mods: hotkeys.chord_capture.pending_mods,
+        mods_vks: hotkeys.chord_capture.pending_mods_vks,
+        vk: None,
+        press: config::PressStyle::Press,
+    };
+    let held_ms = event
+        .time_ms
+        .saturating_sub(hotkeys.chord_capture.pending_since_ms);
+
+    hotkeys.chord_capture = crate::app::RuntimeChordCapture::default();
+
+    let _ = match_release(hotkeys, chord, held_ms, event.time_ms, sink)?;
+    // The release of a matched modifier chord still reaches the system, so that no
+    // modifier stays down for the foreground window.
+    Ok(HookDecision::Pass)
+}
+
+/// Decides a held key on its release: a matching tap or hold is swallowed,
+/// anything else is replayed as a plain keystroke.
+fn release_held_key<S: ActionSink>(
+    hotkeys: &mut RuntimeHotkeys,
+    event: &KeyEvent,
+    sink: &mut S,
+) -> Result<HookDecision, S::Error> {
+    let Some(held) = hotkeys
+        .held_key
+        .filter(|held| held.chord.vk == Some(event.vk))
+    else {
+        return Ok(HookDecision::Pass);
+    };
+    hotkeys.held_key = None;
+
+    if held.released {
+        return Ok(HookDecision::Pass);
+    }
+
+    let held_ms = event.time_ms.saturating_sub(held.down_ms);
+    let outcome = match_release(hotkeys, held.chord, held_ms, event.time_ms, sink)?;
+
+    Ok(if outcome.consumed() {
+        HookDecision::Swallow
+    } else {
+        HookDecision::Replay { vk: event.vk }
+    })
+}
+
+/// Runs the hotkey matchers for an event. `mods` must already include the event.
+pub fn match_hotkeys<S: ActionSink>(
+    hotkeys: &mut RuntimeHotkeys,
+    mods: ModsDown,
+    event: &KeyEvent,
+    sink: &mut S,
+) -> Result<HookDecision, S::Error> {
+    match event.kind {
+        KeyEventKind::Down => handle_keydown_runtime(hotkeys, event, mods, sink),
+        KeyEventKind::Up => handle_keyup_runtime(hotkeys, event, mods, sink),
+    }
+}
+
+/// Updates the journal for a key press and returns the text it typed.
+pub fn record_keydown<K: KeyboardState>(
+    journal: &mut InputJournal,
+    event: &KeyEvent,
+    mods: ModsDown,
+    keyboard: &K,
+) -> Option<String> {
+    if event.injected {
+        return None;
+    }
+
+    enum JournalAction {
+        Clear,
+        Backspace,
+        Push(String),
+    }
+
+    let mut action: Option<JournalAction> = None;
+    let mut output: Option<String> = None;
+
+    match event.vk {
+        VK_ESCAPE | VK_DELETE | VK_INSERT | VK_LEFT | VK_RIGHT | VK_UP | VK_DOWN | VK_HOME
+        | VK_END | VK_PRIOR | VK_NEXT => action = Some(JournalAction::Clear),
+        VK_BACK => action = Some(JournalAction::Backspace),
+        VK_RETURN => {
+            output = Some("\n".to_string());
+            action = Some(JournalAction::Push("\n".to_string()));
+        }
+        VK_TAB => {
+            output = Some("\t".to_string());
+            action = Some(JournalAction::Push("\t".to_string()));
+        }
+        _ => {}
+    }
+
+    if mods.ctrl_or_alt() {
+        action = Some(JournalAction::Clear);
+    }
+
+    if action.is_none() {
+        let s = keyboard.translate(event)?;
+        output = Some(s.clone());
+        action = Some(JournalAction::Push(s));
+    }
+
+    journal.invalidate_if_foreground_changed(keyboard.foreground_window());
+    match action {
+        Some(JournalAction::Clear) => journal.clear(),
+        Some(JournalAction::Backspace) => journal.backspace(),
+        Some(JournalAction::Push(s)) => journal.push_typed(&s, keyboard.layout()),
+        None => {}
+    }
+
+    output
+}
+
+/// Journals a key press that reached the foreground window and reports autoconvert triggers.
+///
+/// Delivery failures are ignored: a missed autoconvert must not block typing.
+pub fn journal_keydown<K: KeyboardState, S: ActionSink>(
+    journal: &mut InputJournal,
+    event: &KeyEvent,
+    mods: ModsDown,
+    keyboard: &K,
+    sink: &mut S,
+) {
+    let typed = record_keydown(journal, event, mods, keyboard);
+
+    if journal.take_autoconvert_reverted() {
+        let _ = sink.emit(Action::AutoconvertReverted);
+    }
+
+    if typed.is_some() && journal.last_char_triggers_autoconvert() {
+        let _ = sink.emit(Action::Autoconvert);
+    }
+}
+
+/// Runs one event through the whole pipeline, as the hook does outside hotkey capture.
+pub fn process_event<K: KeyboardState, S: ActionSink>(
+    hotkeys: &mut RuntimeHotkeys,
+    mods: &mut ModsDown,
+    journal: &mut InputJournal,
+    event: &KeyEvent,
+    keyboard: &K,
+    sink: &mut S,
+) -> Result<HookDecision, S::Error> {
+    mods.apply(event);
+
+    let decision = match_hotkeys(hotkeys, *mods, event, sink)?;
+
+    if event.kind == KeyEventKind::Down && decision.delivers_event() {
+        journal_keydown(journal, event, *mods, keyboard, sink);
+    }
+
+    Ok(decision)
+}
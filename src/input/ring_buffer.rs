@@ -1,18 +1,9 @@
 use std::{
     collections::VecDeque,
-    sync::{Mutex, OnceLock},
+    sync::{Mutex, OnceLock, PoisonError},
 };
 
-use windows::Win32::UI::{
-    Input::KeyboardAndMouse::{
-        GetAsyncKeyState, GetKeyboardLayout, GetKeyboardState, MOD_ALT, MOD_CONTROL, ToUnicodeEx,
-        VIRTUAL_KEY, VK_BACK, VK_DELETE, VK_DOWN, VK_END, VK_ESCAPE, VK_HOME, VK_INSERT, VK_LEFT,
-        VK_LSHIFT, VK_NEXT, VK_PRIOR, VK_RETURN, VK_RIGHT, VK_RSHIFT, VK_SHIFT, VK_TAB, VK_UP,
-    },
-    WindowsAndMessaging::{
-        GetForegroundWindow, GetWindowThreadProcessId, KBDLLHOOKSTRUCT, LLKHF_INJECTED,
-    },
-};
+use super::word_boundaries::{BoundaryChars, BoundaryRules};
 
 static JOURNAL: OnceLock<Mutex<InputJournal>> = OnceLock::new();
 
@@ -20,237 +11,269 @@ fn journal() -> &'static Mutex<InputJournal> {
     JOURNAL.get_or_init(|| Mutex::new(InputJournal::new(100)))
 }
 
+/// Runs `f` with the journal fed by the keyboard hook.
+pub fn with_journal<R>(f: impl FnOnce(&mut InputJournal) -> R) -> R {
+    let mut j = journal().lock().unwrap_or_else(PoisonError::into_inner);
+    f(&mut j)
+}
+
+/// How much of the text before the caret a conversion takes, see `InputJournal::take_last`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Span {
+    /// The last `n` words and what separates them, within the current line.
+    Words(usize),
+    /// The words since the last line break, or the last sentence end followed by a separator.
+    Phrase,
+}
+
+/// The last word of the journal and the separators typed after it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastWord {
+    /// The word, or the words of a longer span with what separates them.
+    pub word: String,
+    pub suffix: String,
+    /// Words in `word`.
+    pub words: usize,
+    /// Word boundaries of the layout the first suffix character was typed in.
+    pub boundaries: BoundaryChars,
+}
+
+/// Recently typed text of the foreground window, used to find the last word.
 #[derive(Debug, Default)]
-struct InputJournal {
+pub struct InputJournal {
     cap: usize,
-    buf: VecDeque<char>,
+    /// Characters with the raw handle of the layout they were typed in.
+    buf: VecDeque<(char, isize)>,
+    /// Layout of the last typed character, also used for text pushed by the app.
+    layout: isize,
+    boundaries: BoundaryRules,
     last_token_autoconverted: bool,
+    /// Index of the first character of the autoconverted token.
+    autoconverted_start: usize,
+    /// Set when backspaces deleted the whole autoconverted token.
+    autoconvert_reverted: bool,
     last_fg_hwnd: isize,
 }
 
 impl InputJournal {
-    fn new(cap: usize) -> Self {
+    pub fn new(cap: usize) -> Self {
         Self {
             cap,
             buf: VecDeque::with_capacity(cap),
+            layout: 0,
+            boundaries: BoundaryRules::default(),
             last_token_autoconverted: false,
+            autoconverted_start: 0,
+            autoconvert_reverted: false,
             last_fg_hwnd: 0,
         }
     }
 
-    fn clear(&mut self) {
+    pub fn clear(&mut self) {
         self.buf.clear();
         self.last_token_autoconverted = false;
     }
 
-    fn push_str(&mut self, s: &str) {
+    /// Sets the word boundaries used from now on.
+    pub fn set_boundaries(&mut self, boundaries: BoundaryRules) {
+        self.boundaries = boundaries;
+    }
+
+    /// Appends text as typed in the layout of the last typed character.
+    pub fn push_str(&mut self, s: &str) {
         for ch in s.chars() {
-            self.buf.push_back(ch);
+            self.buf.push_back((ch, self.layout));
         }
         while self.buf.len() > self.cap {
             let _ = self.buf.pop_front();
+            self.autoconverted_start = self.autoconverted_start.saturating_sub(1);
         }
     }
 
-    fn backspace(&mut self) {
-        let _ = self.buf.pop_back();
-    }
-
-    fn invalidate_if_foreground_changed(&mut self) {
-        let fg = unsafe { GetForegroundWindow() };
-        let raw = fg.0 as isize;
-        if raw == 0 {
-            self.clear();
-            self.last_fg_hwnd = 0;
+    /// Deletes the last character; deleting the last one left of the autoconverted
+    /// token reverts it. A typo fixed inside the token is not a revert.
+    pub fn backspace(&mut self) {
+        if self.buf.pop_back().is_none() {
             return;
         }
 
-        if self.last_fg_hwnd == 0 {
-            self.last_fg_hwnd = raw;
-            return;
+        if self.last_token_autoconverted && self.buf.len() <= self.autoconverted_start {
+            self.last_token_autoconverted = false;
+            self.autoconvert_reverted = true;
         }
-
-        if self.last_fg_hwnd != raw {
-            self.clear();
-            self.last_fg_hwnd = raw;
-        }
-    }
-}
-
-pub fn mark_last_token_autoconverted() {
-    if let Ok(mut j) = journal().lock() {
-        j.last_token_autoconverted = true;
     }
-}
-
-pub fn last_token_autoconverted() -> bool {
-    journal()
-        .lock()
-        .ok()
-        .is_some_and(|j| j.last_token_autoconverted)
-}
-
-fn mods_ctrl_or_alt_down() -> bool {
-    let mods = crate::platform::win::keyboard::mods::mods_now();
-    (mods & (MOD_CONTROL.0 | MOD_ALT.0)) != 0
-}
 
-fn decode_typed_text(kb: &KBDLLHOOKSTRUCT, vk: VIRTUAL_KEY) -> Option<String> {
-    let fg = unsafe { GetForegroundWindow() };
-    if fg.0.is_null() {
-        return None;
+    /// Marks the last word and the separators after it as the autoconverted token.
+    pub fn mark_last_token_autoconverted(&mut self) {
+        let end = self.buf.len() - self.run_before(self.buf.len(), true);
+        self.autoconverted_start = end - self.run_before(end, false);
+        self.last_token_autoconverted = true;
     }
 
-    let tid = unsafe { GetWindowThreadProcessId(fg, None) };
-    let hkl = unsafe { GetKeyboardLayout(tid) };
-
-    let mut state = [0u8; 256];
-    if unsafe { GetKeyboardState(&mut state) }.is_err() {
-        return None;
+    /// Appends text typed in `layout`; a letter or digit ends the autoconverted token.
+    pub fn push_typed(&mut self, s: &str, layout: isize) {
+        if s.chars().any(char::is_alphanumeric) {
+            self.last_token_autoconverted = false;
+        }
+        self.layout = layout;
+        self.push_str(s);
     }
 
-    let async_down = |vk: VIRTUAL_KEY| -> bool {
-        let v = unsafe { GetAsyncKeyState(i32::from(vk.0)) }.cast_unsigned();
-        (v & 0x8000) != 0
-    };
-
-    let apply_async_key = |state: &mut [u8; 256], vk: VIRTUAL_KEY| {
-        let idx = usize::from(vk.0);
-        if idx >= state.len() {
+    /// Clears the journal when the text goes to another window than before.
+    pub fn invalidate_if_foreground_changed(&mut self, fg: isize) {
+        if fg == 0 {
+            self.clear();
+            self.last_fg_hwnd = 0;
             return;
         }
 
-        if async_down(vk) {
-            state[idx] |= 0x80;
-        } else {
-            state[idx] &= !0x80;
+        if self.last_fg_hwnd == 0 {
+            self.last_fg_hwnd = fg;
+            return;
         }
-    };
-
-    apply_async_key(&mut state, VK_SHIFT);
-    apply_async_key(&mut state, VK_LSHIFT);
-    apply_async_key(&mut state, VK_RSHIFT);
-
-    let mut buf = [0u16; 8];
-    let rc = unsafe { ToUnicodeEx(u32::from(vk.0), kb.scanCode, &state, &mut buf, 0, Some(hkl)) };
 
-    if rc == -1 {
-        let _ =
-            unsafe { ToUnicodeEx(u32::from(vk.0), kb.scanCode, &state, &mut buf, 0, Some(hkl)) };
-        return None;
+        if self.last_fg_hwnd != fg {
+            self.clear();
+            self.last_fg_hwnd = fg;
+        }
     }
 
-    if rc <= 0 {
-        return None;
+    /// Journaled text, oldest character first.
+    pub fn text(&self) -> String {
+        self.buf.iter().map(|&(ch, _)| ch).collect()
     }
 
-    let rc = usize::try_from(rc).ok()?;
-    let s = String::from_utf16_lossy(&buf[..rc]);
-
-    if s.chars().any(char::is_control) {
-        return None;
+    /// Returns whether the user backspaced over the whole autoconverted token since the last call.
+    pub fn take_autoconvert_reverted(&mut self) -> bool {
+        std::mem::take(&mut self.autoconvert_reverted)
     }
 
-    Some(s)
-}
-
-pub fn record_keydown(kb: &KBDLLHOOKSTRUCT, vk: u32) -> Option<String> {
-    if kb.flags.contains(LLKHF_INJECTED) {
-        return None;
+    fn is_separator(&self, (ch, layout): (char, isize)) -> bool {
+        self.boundaries.for_layout(layout).is_separator(ch)
     }
 
-    let vk_u16 = u16::try_from(vk).ok()?;
-    let vk = VIRTUAL_KEY(vk_u16);
+    /// Whether the last character is a trigger of its layout typed right after a word.
+    pub fn last_char_triggers_autoconvert(&self) -> bool {
+        let mut chars = self.buf.iter().rev();
+        let (Some(&(last, layout)), Some(&prev)) = (chars.next(), chars.next()) else {
+            return false;
+        };
 
-    enum JournalAction {
-        Clear,
-        Backspace,
-        Push(String),
+        self.boundaries.for_layout(layout).is_trigger(last) && !self.is_separator(prev)
     }
 
-    let mut action: Option<JournalAction> = None;
-    let mut output: Option<String> = None;
+    /// Removes the last word and the separators after it.
+    ///
+    /// Returns `None` and leaves the journal as it is when there is no word.
+    pub fn take_last_word(&mut self) -> Option<LastWord> {
+        self.take_last(Span::Words(1))
+    }
 
-    match vk {
-        VK_ESCAPE | VK_DELETE | VK_INSERT | VK_LEFT | VK_RIGHT | VK_UP | VK_DOWN | VK_HOME
-        | VK_END | VK_PRIOR | VK_NEXT => action = Some(JournalAction::Clear),
-        VK_BACK => action = Some(JournalAction::Backspace),
-        VK_RETURN => {
-            output = Some("\n".to_string());
-            action = Some(JournalAction::Push("\n".to_string()));
-        }
-        VK_TAB => {
-            output = Some("\t".to_string());
-            action = Some(JournalAction::Push("\t".to_string()));
+    /// Removes the words of `span` and the separators after them.
+    ///
+    /// Takes fewer words than asked for when the line or the journal starts earlier.
+    /// Returns `None` and leaves the journal as it is when there is no word.
+    pub fn take_last(&mut self, span: Span) -> Option<LastWord> {
+        let suffix_len = self.run_before(self.buf.len(), true);
+        let end = self.buf.len() - suffix_len;
+        let (start, words) = match span {
+            Span::Words(n) => self.words_start(end, n),
+            Span::Phrase => self.phrase_start(end),
+        };
+        if words == 0 {
+            return None;
         }
-        _ => {}
-    }
 
-    if mods_ctrl_or_alt_down() {
-        action = Some(JournalAction::Clear);
+        let taken: Vec<(char, isize)> = self.buf.drain(start..).collect();
+        let (word, suffix) = taken.split_at(end - start);
+        let layout = suffix.first().map_or(self.layout, |&(_, layout)| layout);
+
+        Some(LastWord {
+            word: word.iter().map(|&(ch, _)| ch).collect(),
+            suffix: suffix.iter().map(|&(ch, _)| ch).collect(),
+            words,
+            boundaries: self.boundaries.for_layout(layout).clone(),
+        })
     }
 
-    if action.is_none() {
-        let s = decode_typed_text(kb, vk)?;
-        output = Some(s.clone());
-        action = Some(JournalAction::Push(s));
+    /// Length of the run of separators, or of word characters, that ends at `pos`.
+    fn run_before(&self, pos: usize, separators: bool) -> usize {
+        self.buf
+            .range(..pos)
+            .rev()
+            .take_while(|&&c| self.is_separator(c) == separators)
+            .count()
     }
 
-    if let Ok(mut j) = journal().lock() {
-        j.invalidate_if_foreground_changed();
-        if let Some(action) = action {
-            match action {
-                JournalAction::Clear => j.clear(),
-                JournalAction::Backspace => j.backspace(),
-                JournalAction::Push(s) => {
-                    if s.chars().any(char::is_alphanumeric) {
-                        j.last_token_autoconverted = false;
-                    }
-                    j.push_str(&s);
-                }
+    /// Start of the last `n` words ending at `end`, and how many were found.
+    fn words_start(&self, end: usize, n: usize) -> (usize, usize) {
+        let mut start = end;
+        let mut words = 0;
+        while words < n {
+            let gap = self.run_before(start, true);
+            let word = self.run_before(start - gap, false);
+            let crosses_line = self
+                .buf
+                .range(start - gap..start)
+                .any(|&(ch, _)| is_line_break(ch));
+            if word == 0 || crosses_line {
+                break;
             }
+            start -= gap + word;
+            words += 1;
         }
+        (start, words)
     }
 
-    output
+    /// Start of the phrase ending at `end`, and how many words it has.
+    fn phrase_start(&self, end: usize) -> (usize, usize) {
+        let boundary = (0..end).rev().find(|&i| {
+            let (ch, layout) = self.buf[i];
+            is_line_break(ch)
+                || (i + 1 < end
+                    && self.boundaries.for_layout(layout).is_sentence_end(ch)
+                    && self.is_separator(self.buf[i + 1]))
+        });
+
+        let mut start = boundary.map_or(0, |i| i + 1);
+        start += self
+            .buf
+            .range(start..end)
+            .take_while(|&&c| self.is_separator(c))
+            .count();
+
+        let words = (start..end)
+            .filter(|&i| !self.is_separator(self.buf[i]))
+            .filter(|&i| i == start || self.is_separator(self.buf[i - 1]))
+            .count();
+        (start, words)
+    }
 }
 
-pub fn take_last_word_with_suffix() -> Option<(String, String)> {
-    let Ok(mut j) = journal().lock() else {
-        return None;
-    };
-
-    // Trailing whitespace is suffix (spaces, tabs, newlines, etc).
-    let mut suffix: Vec<char> = Vec::new();
-    while let Some(&ch) = j.buf.back() {
-        if ch.is_whitespace() {
-            suffix.push(j.buf.pop_back()?);
-        } else {
-            break;
-        }
-    }
+fn is_line_break(ch: char) -> bool {
+    ch == '\n' || ch == '\r'
+}
 
-    // Token is the last contiguous run of non-whitespace characters.
-    let mut token: Vec<char> = Vec::new();
-    while let Some(&ch) = j.buf.back() {
-        if ch.is_whitespace() {
-            break;
-        }
-        token.push(j.buf.pop_back()?);
+pub fn mark_last_token_autoconverted() {
+    if let Ok(mut j) = journal().lock() {
+        j.mark_last_token_autoconverted();
     }
+}
 
-    if token.is_empty() {
-        // Restore suffix if we didn't get a token.
-        while let Some(ch) = suffix.pop() {
-            j.buf.push_back(ch);
-        }
-        return None;
-    }
+pub fn last_token_autoconverted() -> bool {
+    journal()
+        .lock()
+        .ok()
+        .is_some_and(|j| j.last_token_autoconverted)
+}
 
-    token.reverse();
-    suffix.reverse();
+pub fn take_last_word_with_suffix() -> Option<LastWord> {
+    journal().lock().ok()?.take_last_word()
+}
 
-    Some((token.into_iter().collect(), suffix.into_iter().collect()))
+/// Takes the words of `span` and the separators after them, see `InputJournal::take_last`.
+pub fn take_last_with_suffix(span: Span) -> Option<LastWord> {
+    journal().lock().ok()?.take_last(span)
 }
 
 pub fn push_text(s: &str) {
@@ -259,43 +282,21 @@ pub fn push_text(s: &str) {
     }
 }
 
-pub fn invalidate() {
+/// Returns the journaled text, oldest character first.
+pub fn snapshot() -> String {
+    journal().lock().map(|j| j.text()).unwrap_or_default()
+}
+
+/// Replaces the journaled text, for example after an undo put back older text.
+pub fn restore(text: &str) {
     if let Ok(mut j) = journal().lock() {
         j.clear();
+        j.push_str(text);
     }
 }
 
-pub fn last_char_triggers_autoconvert() -> bool {
-    let Ok(j) = journal().lock() else {
-        return false;
-    };
-
-    let len = j.buf.len();
-    if len == 0 {
-        return false;
-    }
-
-    let Some(&last) = j.buf.back() else {
-        return false;
-    };
-
-    // Trigger on punctuation immediately following a non-whitespace char.
-    if matches!(last, '.' | ',' | '!' | '?' | ';' | ':') {
-        if len < 2 {
-            return false;
-        }
-        let prev = j.buf[len - 2];
-        return !prev.is_whitespace();
-    }
-
-    // Trigger on the first whitespace after a non-whitespace run.
-    if last.is_whitespace() {
-        if len < 2 {
-            return false;
-        }
-        let prev = j.buf[len - 2];
-        return !prev.is_whitespace();
+pub fn invalidate() {
+    if let Ok(mut j) = journal().lock() {
+        j.clear();
     }
-
-    false
 }
@@ -0,0 +1,265 @@
+//! Matching of hotkey sequences against input chords.
+//!
+//! The matcher is an NFA over all bound actions at once: every chord can start a
+//! new partial match for each action whose first step it matches, and advances every
+//! live partial match whose next step it matches. Partial matches that miss a
+//! chord or wait longer than the step's gap are dropped.
+//!
+//! Steps with a tap, hold or double tap style are decided when the trigger is
+//! released. Until then the press keeps them waiting instead of missing.
+
+use crate::{
+    app::{
+        HotkeyBindings,
+        actions::{self, ActionId},
+    },
+    config::{self, PressStyle},
+};
+
+pub fn chord_matches(template: config::HotkeyChord, input: config::HotkeyChord) -> bool {
+    if template.mods != input.mods {
+        return false;
+    }
+    if template.vk != input.vk {
+        return false;
+    }
+    if template.mods_vks == 0 {
+        return true;
+    }
+    template.mods_vks == input.mods_vks
+}
+
+/// What happened to the trigger of a chord.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ChordPhase {
+    /// The key of the chord went down.
+    Down,
+    /// The trigger was released after `held_ms`. `since_last_tap_ms` is the time from
+    /// the previous release of the same chord to this press.
+    Up {
+        held_ms: u64,
+        since_last_tap_ms: Option<u64>,
+    },
+}
+
+/// A chord seen in the input. The press style of `chord` is ignored.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ChordInput {
+    pub chord: config::HotkeyChord,
+    pub phase: ChordPhase,
+    pub time_ms: u64,
+}
+
+impl ChordInput {
+    pub fn down(chord: config::HotkeyChord, time_ms: u64) -> Self {
+        Self {
+            chord,
+            phase: ChordPhase::Down,
+            time_ms,
+        }
+    }
+
+    pub fn up(
+        chord: config::HotkeyChord,
+        held_ms: u64,
+        since_last_tap_ms: Option<u64>,
+        time_ms: u64,
+    ) -> Self {
+        Self {
+            chord,
+            phase: ChordPhase::Up {
+                held_ms,
+                since_last_tap_ms,
+            },
+            time_ms,
+        }
+    }
+
+    /// When the trigger went down.
+    fn pressed_ms(&self) -> u64 {
+        match self.phase {
+            ChordPhase::Down => self.time_ms,
+            ChordPhase::Up { held_ms, .. } => self.time_ms.saturating_sub(held_ms),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum StepMatch {
+    Matched,
+    /// The input may still turn into this step once the trigger is released.
+    Waiting,
+    Missed,
+}
+
+fn step_match(template: config::HotkeyChord, input: &ChordInput) -> StepMatch {
+    if !chord_matches(template, input.chord) {
+        return StepMatch::Missed;
+    }
+
+    let matched = |ok: bool| {
+        if ok {
+            StepMatch::Matched
+        } else {
+            StepMatch::Missed
+        }
+    };
+
+    let (held_ms, since_last_tap_ms) = match input.phase {
+        ChordPhase::Down if template.press.is_press() => return StepMatch::Matched,
+        ChordPhase::Down => return StepMatch::Waiting,
+        ChordPhase::Up {
+            held_ms,
+            since_last_tap_ms,
+        } => (held_ms, since_last_tap_ms),
+    };
+
+    match template.press {
+        // Keys of plain chords match on press, their release is neutral.
+        PressStyle::Press if template.vk.is_some() => StepMatch::Waiting,
+        PressStyle::Press => StepMatch::Matched,
+        PressStyle::Tap { max_ms } => matched(held_ms <= u64::from(max_ms)),
+        PressStyle::Hold { min_ms } => matched(held_ms >= u64::from(min_ms)),
+        PressStyle::DoubleTap { max_gap_ms } => {
+            let max_gap_ms = u64::from(max_gap_ms);
+            if held_ms > max_gap_ms {
+                StepMatch::Missed
+            } else if since_last_tap_ms.is_some_and(|gap| gap <= max_gap_ms) {
+                StepMatch::Matched
+            } else {
+                StepMatch::Waiting
+            }
+        }
+    }
+}
+
+/// How long a press of `chord` may be held back from the foreground window while a
+/// tap, hold or double tap step can still match it.
+///
+/// `None` when no such step uses the chord. `u64::MAX` when a hold step does.
+pub fn release_window_ms(bindings: &HotkeyBindings, chord: config::HotkeyChord) -> Option<u64> {
+    bindings
+        .iter()
+        .flat_map(|(_, seq)| seq.chords())
+        .filter(|&template| chord_matches(template, chord))
+        .filter_map(|template| match template.press {
+            PressStyle::Press => None,
+            PressStyle::Tap { max_ms } => Some(u64::from(max_ms)),
+            PressStyle::DoubleTap { max_gap_ms } => Some(u64::from(max_gap_ms)),
+            PressStyle::Hold { .. } => Some(u64::MAX),
+        })
+        .max()
+}
+
+/// A sequence matched up to, but not including, step `next`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Partial {
+    action: ActionId,
+    next: usize,
+    last_tick_ms: u64,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MatchOutcome {
+    /// The chord is not part of any sequence.
+    NoMatch,
+    /// The chord started, continued or keeps waiting at least one sequence.
+    Progress,
+    /// The chord completed the sequence of the action.
+    Completed(ActionId),
+}
+
+impl MatchOutcome {
+    pub fn consumed(self) -> bool {
+        !matches!(self, Self::NoMatch)
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct SequenceMatcher {
+    partials: Vec<Partial>,
+}
+
+impl SequenceMatcher {
+    /// Feeds one chord seen in the input.
+    ///
+    /// The gap before a step runs from the previous step to the press of its trigger.
+    /// On completion all partial matches are dropped, so the chords of one
+    /// sequence never count toward the next one.
+    /// When sequences of several actions complete on the same chord, the one with
+    /// the lowest `ActionDef::priority` wins.
+    pub fn feed(&mut self, bindings: &HotkeyBindings, input: ChordInput) -> MatchOutcome {
+        let mut next_partials: Vec<Partial> = Vec::new();
+        let mut completed: Option<ActionId> = None;
+        let mut waiting = false;
+
+        let mut advance = |p: Partial, completed: &mut Option<ActionId>| {
+            let Some(seq) = bindings.get(p.action) else {
+                return;
+            };
+            let Some(step) = seq.steps.get(p.next) else {
+                return;
+            };
+
+            let gap_ms = input.pressed_ms().saturating_sub(p.last_tick_ms);
+            if p.next > 0 && gap_ms > u64::from(step.max_gap_ms) {
+                return;
+            }
+
+            match step_match(step.chord, &input) {
+                StepMatch::Missed => return,
+                StepMatch::Waiting => {
+                    waiting = true;
+                    if p.next > 0 {
+                        next_partials.push(p);
+                    }
+                    return;
+                }
+                StepMatch::Matched => {}
+            }
+
+            if p.next + 1 == seq.len() {
+                *completed = Some(match *completed {
+                    Some(other) if actions::priority(other) < actions::priority(p.action) => other,
+                    _ => p.action,
+                });
+            } else {
+                next_partials.push(Partial {
+                    action: p.action,
+                    next: p.next + 1,
+                    last_tick_ms: input.time_ms,
+                });
+            }
+        };
+
+        for p in std::mem::take(&mut self.partials) {
+            advance(p, &mut completed);
+        }
+        for (action, _) in bindings.iter() {
+            advance(
+                Partial {
+                    action,
+                    next: 0,
+                    last_tick_ms: input.time_ms,
+                },
+                &mut completed,
+            );
+        }
+
+        if let Some(action) = completed {
+            return MatchOutcome::Completed(action);
+        }
+
+        for p in next_partials {
+            if !self.partials.contains(&p) {
+                self.partials.push(p);
+            }
+        }
+
+        if waiting || !self.partials.is_empty() {
+            MatchOutcome::Progress
+        } else {
+            MatchOutcome::NoMatch
+        }
+    }
+}
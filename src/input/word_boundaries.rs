@@ -0,0 +1,112 @@
+//! Characters that end words and trigger autoconvert.
+//!
+//! The journal looks them up by the layout each character was typed in: a key that
+//! types `,` in one layout types a letter in the other, so a set that suits one layout
+//! can split words typed in another.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// Word boundary characters for the text typed in one layout.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoundaryChars {
+    /// Characters that trigger autoconvert when typed right after a word. Enter is
+    /// journaled as `\n` and Tab as `\t`.
+    pub triggers: String,
+    /// Characters that end a word, besides whitespace.
+    pub separators: String,
+    /// Separators moved into the word they follow when only spaces come after them,
+    /// as they type letters in the other layout of the pair.
+    pub word_punctuation: String,
+    /// Characters that end a sentence when a separator follows them, as used by the
+    /// last phrase conversion. Line breaks always end one.
+    pub sentence_ends: String,
+}
+
+impl Default for BoundaryChars {
+    fn default() -> Self {
+        Self {
+            triggers: ".,!?;: \t\n".to_string(),
+            separators: String::new(),
+            word_punctuation: "?/,.".to_string(),
+            sentence_ends: ".!?".to_string(),
+        }
+    }
+}
+
+impl BoundaryChars {
+    pub fn is_trigger(&self, ch: char) -> bool {
+        self.triggers.contains(ch)
+    }
+
+    pub fn is_separator(&self, ch: char) -> bool {
+        ch.is_whitespace() || self.separators.contains(ch)
+    }
+
+    pub fn is_word_punctuation(&self, ch: char) -> bool {
+        self.word_punctuation.contains(ch)
+    }
+
+    pub fn is_sentence_end(&self, ch: char) -> bool {
+        self.sentence_ends.contains(ch)
+    }
+}
+
+/// Word boundaries as configured: a default set and sets for some layouts.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WordBoundaries {
+    #[serde(flatten)]
+    pub default: BoundaryChars,
+    /// Sets replacing the default one in layouts with a language tag, e.g. `en-US`.
+    /// A tag without a region, such as `en`, covers every layout of that language.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub layouts: BTreeMap<String, BoundaryChars>,
+}
+
+impl WordBoundaries {
+    /// The set of the layout with language tag `tag`: an exact match ignoring case,
+    /// else a match of its language, else the default set.
+    pub fn for_tag(&self, tag: &str) -> &BoundaryChars {
+        let language = tag.split_once('-').map_or(tag, |(lang, _)| lang);
+        let find = |key: &str| {
+            self.layouts
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, chars)| chars)
+        };
+        find(tag)
+            .or_else(|| find(language))
+            .unwrap_or(&self.default)
+    }
+
+    /// Resolves the sets of the installed layouts, given as raw handle and language tag.
+    pub fn resolve(&self, layouts: &[(isize, String)]) -> BoundaryRules {
+        BoundaryRules {
+            default: self.default.clone(),
+            layouts: layouts
+                .iter()
+                .filter(|(_, tag)| !std::ptr::eq(self.for_tag(tag), &self.default))
+                .map(|(layout, tag)| (*layout, self.for_tag(tag).clone()))
+                .collect(),
+        }
+    }
+}
+
+/// Word boundaries by raw layout handle, as the journal looks them up.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BoundaryRules {
+    default: BoundaryChars,
+    layouts: Vec<(isize, BoundaryChars)>,
+}
+
+impl BoundaryRules {
+    /// The set of `layout`, or the default set for layouts without one.
+    pub fn for_layout(&self, layout: isize) -> &BoundaryChars {
+        self.layouts
+            .iter()
+            .find(|(l, _)| *l == layout)
+            .map_or(&self.default, |(_, chars)| chars)
+    }
+}
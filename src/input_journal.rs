@@ -1,3 +1,4 @@
 pub use crate::input::ring_buffer::{
-    mark_last_token_autoconverted, push_text, take_last_word_with_suffix,
+    last_token_autoconverted, mark_last_token_autoconverted, push_text, restore, snapshot,
+    take_last_with_suffix, take_last_word_with_suffix,
 };
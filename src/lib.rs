@@ -0,0 +1,28 @@
+//! Keyboard layout switcher and text conversion.
+//!
+//! The text model, the input pipeline and the config build on every platform, so
+//! their tests run without Windows. The Win32 application (`platform`, `conversion`
+//! and the parts of `domain::text` that type into other windows) is Windows only.
+
+#![feature(stmt_expr_attributes)]
+
+pub mod app;
+pub mod config;
+#[cfg(windows)]
+mod conversion;
+pub mod domain;
+#[cfg(windows)]
+mod helpers;
+pub mod input;
+pub mod input_journal;
+#[cfg(windows)]
+mod platform;
+pub mod utils;
+
+#[cfg(windows)]
+pub use platform::win::{AUTOSTART_ARG, run};
+#[cfg(windows)]
+pub use utils::helpers::{init_app_user_model_id, single_instance_guard};
+
+#[cfg(test)]
+mod tests;
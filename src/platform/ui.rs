@@ -9,16 +9,17 @@ use windows::{
         Foundation::{HWND, RECT},
         System::SystemServices::SS_RIGHT,
         UI::WindowsAndMessaging::{
-            BS_AUTOCHECKBOX, BS_GROUPBOX, CreateWindowExW, ES_NUMBER, ES_READONLY, GetClientRect,
-            SetWindowTextW, WINDOW_EX_STYLE, WINDOW_STYLE, WS_CHILD, WS_EX_CLIENTEDGE, WS_TABSTOP,
-            WS_VISIBLE,
+            BS_AUTOCHECKBOX, BS_GROUPBOX, CreateWindowExW, ES_AUTOHSCROLL, ES_AUTOVSCROLL,
+            ES_MULTILINE, ES_NUMBER, ES_READONLY, ES_WANTRETURN, GetClientRect,
+            LBS_NOINTEGRALHEIGHT, LBS_NOTIFY, SetWindowTextW, WINDOW_EX_STYLE, WINDOW_STYLE,
+            WS_CHILD, WS_EX_CLIENTEDGE, WS_TABSTOP, WS_VISIBLE, WS_VSCROLL,
         },
     },
     core::{PCWSTR, w},
 };
 
 use crate::{
-    app::{AppState, ControlId},
+    app::{AppState, ControlId, actions::ACTIONS, hotkey_edit_hmenu},
     platform::ui::geom::{Layout, RectI},
     utils::helpers::ws_i32,
 };
@@ -97,6 +98,12 @@ struct UiLayout {
     group_h: i32,
     group_w_left: i32,
     group_w_right: i32,
+    dictionary_y: i32,
+    dictionary_h: i32,
+    snippets_y: i32,
+    snippets_h: i32,
+    full_w: i32,
+    buttons_y: i32,
 }
 
 impl UiLayout {
@@ -109,6 +116,12 @@ impl UiLayout {
             group_h: l.group_h(),
             group_w_left: l.group_w_left(),
             group_w_right: l.group_w_right(),
+            dictionary_y: l.dictionary_y(),
+            dictionary_h: l.dictionary_h(),
+            snippets_y: l.snippets_y(),
+            snippets_h: l.snippets_h(),
+            full_w: l.full_w(),
+            buttons_y: l.buttons_y(),
         }
     }
 }
@@ -119,6 +132,8 @@ pub fn create_controls(hwnd: HWND, state: &mut AppState) -> windows::core::Resul
 
     create_settings_group(hwnd, state, &l)?;
     create_hotkeys_group(hwnd, state, &l)?;
+    create_dictionary_group(hwnd, state, &l)?;
+    create_snippets_group(hwnd, state, &l)?;
     create_buttons(hwnd, state, &l)?;
     create_version_label(hwnd, &l, client_w)?;
 
@@ -126,7 +141,7 @@ pub fn create_controls(hwnd: HWND, state: &mut AppState) -> windows::core::Resul
 }
 
 fn create_version_label(hwnd: HWND, l: &UiLayout, client_w: i32) -> windows::core::Result<()> {
-    let btn_y = l.top_y + l.group_h + 10;
+    let btn_y = l.buttons_y;
 
     let version_w = 90;
     let version_h = 18;
@@ -304,48 +319,24 @@ fn create_hotkey_rows(
 ) -> windows::core::Result<()> {
     let mut hy = g.hy0;
 
-    state.hotkeys.last_word = create_hotkey_row(
-        hwnd,
-        g.hx,
-        hy,
-        g.w_label,
-        g.w_edit,
-        w!("Convert last word:"),
-        Some(ControlId::HotkeyLastWord.hmenu()),
-    )?;
-    hy += 28;
-
-    state.hotkeys.pause = create_hotkey_row(
-        hwnd,
-        g.hx,
-        hy,
-        g.w_label,
-        g.w_edit,
-        w!("Autoconvert pause:"),
-        Some(ControlId::HotkeyPause.hmenu()),
-    )?;
-    hy += 28;
-
-    state.hotkeys.selection = create_hotkey_row(
-        hwnd,
-        g.hx,
-        hy,
-        g.w_label,
-        g.w_edit,
-        w!("Convert selection:"),
-        Some(ControlId::HotkeySelection.hmenu()),
-    )?;
-    hy += 28;
-
-    state.hotkeys.switch_layout = create_hotkey_row(
-        hwnd,
-        g.hx,
-        hy,
-        g.w_label,
-        g.w_edit,
-        w!("Switch keyboard layout:"),
-        Some(ControlId::HotkeySwitchLayout.hmenu()),
-    )?;
+    for (index, action) in ACTIONS.iter().enumerate() {
+        let label: Vec<u16> = format!("{}:", action.name)
+            .encode_utf16()
+            .chain(std::iter::once(0))
+            .collect();
+
+        let edit = create_hotkey_row(
+            hwnd,
+            g.hx,
+            hy,
+            g.w_label,
+            g.w_edit,
+            PCWSTR(label.as_ptr()),
+            Some(hotkey_edit_hmenu(index)),
+        )?;
+        state.hotkeys.edits.push((action.id, edit));
+        hy += 28;
+    }
 
     Ok(())
 }
@@ -364,8 +355,245 @@ fn create_hotkey_row(
     create(hwnd, row.edit)
 }
 
+fn create_dictionary_group(
+    hwnd: HWND,
+    state: &mut AppState,
+    l: &UiLayout,
+) -> windows::core::Result<()> {
+    let x0 = l.left_x + 12;
+    let y0 = l.dictionary_y + 24;
+    let list_w = 220;
+    let list_h = l.dictionary_h - 36;
+    let x1 = x0 + list_w + 12;
+    let x2 = x1 + 260;
+    let learned_w = l.left_x + l.full_w - 12 - x2;
+    let btn_style = WS_CHILD | WS_VISIBLE | WS_TABSTOP;
+    let list_style = ws_i32(
+        WS_CHILD | WS_VISIBLE | WS_TABSTOP | WS_VSCROLL,
+        LBS_NOTIFY | LBS_NOINTEGRALHEIGHT,
+    );
+
+    let _grp_dictionary = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WINDOW_EX_STYLE(0),
+            class: w!("BUTTON"),
+            text: w!("User dictionary"),
+            style: ws_i32(WS_CHILD | WS_VISIBLE, BS_GROUPBOX),
+            rect: RectI::new(l.left_x, l.dictionary_y, l.full_w, l.dictionary_h),
+            menu: None,
+        },
+    )?;
+
+    state.dictionary.list = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WS_EX_CLIENTEDGE,
+            class: w!("LISTBOX"),
+            text: w!(""),
+            style: list_style,
+            rect: RectI::new(x0, y0, list_w, list_h),
+            menu: Some(ControlId::DictionaryList.hmenu()),
+        },
+    )?;
+
+    let _lbl_word = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WINDOW_EX_STYLE(0),
+            class: w!("STATIC"),
+            text: w!("Word:"),
+            style: WS_CHILD | WS_VISIBLE,
+            rect: RectI::new(x1, y0 + 3, 40, 18),
+            menu: None,
+        },
+    )?;
+
+    state.dictionary.word = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WS_EX_CLIENTEDGE,
+            class: w!("EDIT"),
+            text: w!(""),
+            style: ws_i32(WS_CHILD | WS_VISIBLE | WS_TABSTOP, ES_AUTOHSCROLL),
+            rect: RectI::new(x1 + 48, y0, 200, 22),
+            menu: Some(ControlId::DictionaryWord.hmenu()),
+        },
+    )?;
+
+    let _lbl_learned = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WINDOW_EX_STYLE(0),
+            class: w!("STATIC"),
+            text: w!("Learned from undo:"),
+            style: WS_CHILD | WS_VISIBLE,
+            rect: RectI::new(x2, y0, learned_w, 18),
+            menu: None,
+        },
+    )?;
+
+    state.dictionary.learned = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WS_EX_CLIENTEDGE,
+            class: w!("LISTBOX"),
+            text: w!(""),
+            style: list_style,
+            rect: RectI::new(x2, y0 + 20, learned_w, list_h - 54),
+            menu: Some(ControlId::LearnedList.hmenu()),
+        },
+    )?;
+
+    let buttons = [
+        (w!("Never convert"), ControlId::DictionaryNever, x1, y0 + 32),
+        (
+            w!("Always convert"),
+            ControlId::DictionaryAlways,
+            x1 + 128,
+            y0 + 32,
+        ),
+        (w!("Remove"), ControlId::DictionaryRemove, x1, y0 + 64),
+        (w!("Import..."), ControlId::DictionaryImport, x1, y0 + 96),
+        (
+            w!("Export..."),
+            ControlId::DictionaryExport,
+            x1 + 128,
+            y0 + 96,
+        ),
+        (w!("Forget"), ControlId::LearnedForget, x2, y0 + list_h - 26),
+    ];
+    for (text, id, x, y) in buttons {
+        let _ = create(
+            hwnd,
+            ControlSpec {
+                ex_style: WINDOW_EX_STYLE(0),
+                class: w!("BUTTON"),
+                text,
+                style: btn_style,
+                rect: RectI::new(x, y, 120, 26),
+                menu: Some(id.hmenu()),
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
+fn create_snippets_group(
+    hwnd: HWND,
+    state: &mut AppState,
+    l: &UiLayout,
+) -> windows::core::Result<()> {
+    let x0 = l.left_x + 12;
+    let y0 = l.snippets_y + 24;
+    let list_w = 220;
+    let list_h = l.snippets_h - 36;
+    let x1 = x0 + list_w + 12;
+    let x2 = x1 + 260;
+    let text_w = l.left_x + l.full_w - 12 - x2;
+
+    let _grp_snippets = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WINDOW_EX_STYLE(0),
+            class: w!("BUTTON"),
+            text: w!("Snippets"),
+            style: ws_i32(WS_CHILD | WS_VISIBLE, BS_GROUPBOX),
+            rect: RectI::new(l.left_x, l.snippets_y, l.full_w, l.snippets_h),
+            menu: None,
+        },
+    )?;
+
+    state.snippets.list = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WS_EX_CLIENTEDGE,
+            class: w!("LISTBOX"),
+            text: w!(""),
+            style: ws_i32(
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WS_VSCROLL,
+                LBS_NOTIFY | LBS_NOINTEGRALHEIGHT,
+            ),
+            rect: RectI::new(x0, y0, list_w, list_h),
+            menu: Some(ControlId::SnippetList.hmenu()),
+        },
+    )?;
+
+    let _lbl_trigger = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WINDOW_EX_STYLE(0),
+            class: w!("STATIC"),
+            text: w!("Trigger:"),
+            style: WS_CHILD | WS_VISIBLE,
+            rect: RectI::new(x1, y0 + 3, 48, 18),
+            menu: None,
+        },
+    )?;
+
+    state.snippets.trigger = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WS_EX_CLIENTEDGE,
+            class: w!("EDIT"),
+            text: w!(""),
+            style: ws_i32(WS_CHILD | WS_VISIBLE | WS_TABSTOP, ES_AUTOHSCROLL),
+            rect: RectI::new(x1 + 56, y0, 192, 22),
+            menu: Some(ControlId::SnippetTrigger.hmenu()),
+        },
+    )?;
+
+    let _lbl_placeholders = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WINDOW_EX_STYLE(0),
+            class: w!("STATIC"),
+            text: w!("Placeholders: {date} {time}\n{clipboard} {cursor}"),
+            style: WS_CHILD | WS_VISIBLE,
+            rect: RectI::new(x1, y0 + 64, 248, 32),
+            menu: None,
+        },
+    )?;
+
+    state.snippets.text = create(
+        hwnd,
+        ControlSpec {
+            ex_style: WS_EX_CLIENTEDGE,
+            class: w!("EDIT"),
+            text: w!(""),
+            style: ws_i32(
+                WS_CHILD | WS_VISIBLE | WS_TABSTOP | WS_VSCROLL,
+                ES_MULTILINE | ES_AUTOVSCROLL | ES_WANTRETURN,
+            ),
+            rect: RectI::new(x2, y0, text_w, list_h),
+            menu: Some(ControlId::SnippetText.hmenu()),
+        },
+    )?;
+
+    let buttons = [
+        (w!("Save"), ControlId::SnippetSave, x1),
+        (w!("Remove"), ControlId::SnippetRemove, x1 + 128),
+    ];
+    for (text, id, x) in buttons {
+        let _ = create(
+            hwnd,
+            ControlSpec {
+                ex_style: WINDOW_EX_STYLE(0),
+                class: w!("BUTTON"),
+                text,
+                style: WS_CHILD | WS_VISIBLE | WS_TABSTOP,
+                rect: RectI::new(x, y0 + 32, 120, 26),
+                menu: Some(id.hmenu()),
+            },
+        )?;
+    }
+
+    Ok(())
+}
+
 fn create_buttons(hwnd: HWND, state: &mut AppState, l: &UiLayout) -> windows::core::Result<()> {
-    let btn_y = l.top_y + l.group_h + 10;
+    let btn_y = l.buttons_y;
     let btn_h = 28;
     let btn_style = WS_CHILD | WS_VISIBLE | WS_TABSTOP;
 
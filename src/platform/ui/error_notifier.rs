@@ -5,6 +5,8 @@ use windows::Win32::{
 
 pub const WM_APP_ERROR: u32 = WM_APP + 101;
 pub const WM_APP_AUTOCONVERT: u32 = WM_APP + 102;
+pub const WM_APP_AUTOCONVERT_REVERTED: u32 = WM_APP + 104;
+pub const WM_APP_FOREGROUND_CHANGED: u32 = WM_APP + 105;
 
 use crate::app::{AppState, UiError};
 
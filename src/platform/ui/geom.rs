@@ -61,17 +61,21 @@ pub struct Layout {
     group_w_left: i32,
     gap: i32,
     group_w_right: i32,
+    dictionary_h: i32,
+    snippets_h: i32,
+    full_w: i32,
 }
 
 impl Layout {
     pub fn new(client_w: i32) -> Self {
         let margin = 12;
-        let group_h = 170;
+        let group_h = 312;
         let group_w_left = 240;
         let gap = 12;
 
         let fixed = margin * 2 + group_w_left + gap;
         let group_w_right = (client_w - fixed).max(420);
+        let full_w = group_w_left + gap + group_w_right;
 
         Self {
             margin,
@@ -79,6 +83,9 @@ impl Layout {
             group_w_left,
             gap,
             group_w_right,
+            dictionary_h: 160,
+            snippets_h: 132,
+            full_w,
         }
     }
 
@@ -105,4 +112,32 @@ impl Layout {
     pub const fn group_w_right(self) -> i32 {
         self.group_w_right
     }
+
+    /// Top of the dictionary group, below the settings and hotkeys groups.
+    pub const fn dictionary_y(self) -> i32 {
+        self.margin + self.group_h + self.gap
+    }
+
+    pub const fn dictionary_h(self) -> i32 {
+        self.dictionary_h
+    }
+
+    /// Top of the snippets group, below the dictionary group.
+    pub const fn snippets_y(self) -> i32 {
+        self.dictionary_y() + self.dictionary_h + self.gap
+    }
+
+    pub const fn snippets_h(self) -> i32 {
+        self.snippets_h
+    }
+
+    /// Width of a group spanning both columns.
+    pub const fn full_w(self) -> i32 {
+        self.full_w
+    }
+
+    /// Top of the bottom button row.
+    pub const fn buttons_y(self) -> i32 {
+        self.snippets_y() + self.snippets_h + 10
+    }
 }
@@ -5,18 +5,25 @@
 //! routines, the application state, and the UI construction code to
 //! present a settings window and respond to user actions.
 
+pub(crate) mod action_handlers;
+pub(crate) mod app_rules;
+mod autoconvert;
 mod autostart;
 mod commands;
+mod dictionary;
+mod focus;
 pub(crate) mod hotkey_format;
 pub(crate) mod keyboard;
+pub(crate) mod keyboard_layouts;
 pub(crate) mod mouse;
+mod snippets;
 mod state;
 mod themes;
 pub(crate) mod tray;
 mod tray_dispatch;
 mod visuals;
 mod window;
-pub(crate) use hotkey_format::{format_hotkey, format_hotkey_sequence};
+pub(crate) use hotkey_format::format_hotkey_sequence;
 use windows::{
     Win32::{
         Foundation::{HWND, LPARAM, LRESULT, WPARAM},
@@ -39,12 +46,19 @@ use self::{
         set_window_icons,
     },
 };
-pub(crate) const AUTOSTART_ARG: &str = "--autostart";
+pub const AUTOSTART_ARG: &str = "--autostart";
 use crate::{
-    app::AppState,
+    app::{
+        AppState,
+        actions::{ACTIONS, ActionId},
+    },
     config,
-    domain::text::{last_word::autoconvert_last_word, switch_keyboard_layout},
-    input::hotkeys::{HotkeyAction, action_from_id},
+    domain::text::{
+        convert::installed_layouts_with_tags,
+        last_word::{autoconvert_last_word, expand_snippet},
+        layout,
+    },
+    input::{hotkeys::action_from_id, ring_buffer::with_journal},
     platform::{
         ui::{
             self,
@@ -78,36 +92,11 @@ pub fn refresh_autostart_checkbox(state: &mut AppState) -> windows::core::Result
 fn apply_config_to_ui(state: &mut AppState, cfg: &config::Config) -> windows::core::Result<()> {
     helpers::set_edit_u32(state.edits.delay_ms, cfg.delay_ms)?;
 
-    state.hotkey_values = crate::app::HotkeyValues::from_config(cfg);
-    state.hotkey_sequence_values = crate::app::HotkeySequenceValues::from_config(cfg);
-
-    let last_word_text = if cfg.hotkey_convert_last_word_sequence.is_some() {
-        format_hotkey_sequence(cfg.hotkey_convert_last_word_sequence)
-    } else {
-        format_hotkey(cfg.hotkey_convert_last_word)
-    };
-    set_hwnd_text(state.hotkeys.last_word, &last_word_text)?;
-
-    let pause_text = if cfg.hotkey_pause_sequence.is_some() {
-        format_hotkey_sequence(cfg.hotkey_pause_sequence)
-    } else {
-        format_hotkey(cfg.hotkey_pause)
-    };
-    set_hwnd_text(state.hotkeys.pause, &pause_text)?;
-
-    let selection_text = if cfg.hotkey_convert_selection_sequence.is_some() {
-        format_hotkey_sequence(cfg.hotkey_convert_selection_sequence)
-    } else {
-        format_hotkey(cfg.hotkey_convert_selection)
-    };
-    set_hwnd_text(state.hotkeys.selection, &selection_text)?;
+    state.hotkey_bindings = crate::app::HotkeyBindings::from_config(cfg);
 
-    let switch_layout_text = if cfg.hotkey_switch_layout_sequence.is_some() {
-        format_hotkey_sequence(cfg.hotkey_switch_layout_sequence)
-    } else {
-        format_hotkey(cfg.hotkey_switch_layout)
-    };
-    set_hwnd_text(state.hotkeys.switch_layout, &switch_layout_text)?;
+    for &(action, edit) in &state.hotkeys.edits {
+        set_hwnd_text(edit, &format_hotkey_sequence(cfg.hotkey(action)))?;
+    }
 
     Ok(())
 }
@@ -115,35 +104,10 @@ fn apply_config_to_ui(state: &mut AppState, cfg: &config::Config) -> windows::co
 fn read_ui_to_config(state: &AppState, mut cfg: config::Config) -> config::Config {
     cfg.delay_ms = helpers::get_edit_u32(state.edits.delay_ms).unwrap_or(cfg.delay_ms);
 
-    cfg.hotkey_convert_last_word_sequence = state.hotkey_sequence_values.last_word;
-    cfg.hotkey_pause_sequence = state.hotkey_sequence_values.pause;
-    cfg.hotkey_convert_selection_sequence = state.hotkey_sequence_values.selection;
-    cfg.hotkey_switch_layout_sequence = state.hotkey_sequence_values.switch_layout;
-
-    fn hk_or_none_if_double(
-        seq: Option<config::HotkeySequence>,
-        hk: Option<config::Hotkey>,
-    ) -> Option<config::Hotkey> {
-        match seq {
-            Some(s) if s.second.is_some() => None,
-            _ => hk,
-        }
+    for action in &ACTIONS {
+        cfg.set_hotkey(action.id, state.hotkey_bindings.get(action.id).cloned());
     }
 
-    cfg.hotkey_convert_last_word = hk_or_none_if_double(
-        cfg.hotkey_convert_last_word_sequence,
-        state.hotkey_values.last_word,
-    );
-    cfg.hotkey_pause = hk_or_none_if_double(cfg.hotkey_pause_sequence, state.hotkey_values.pause);
-    cfg.hotkey_convert_selection = hk_or_none_if_double(
-        cfg.hotkey_convert_selection_sequence,
-        state.hotkey_values.selection,
-    );
-    cfg.hotkey_switch_layout = match cfg.hotkey_switch_layout_sequence {
-        Some(_) => None,
-        None => state.hotkey_values.switch_layout,
-    };
-
     cfg
 }
 
@@ -152,28 +116,61 @@ fn apply_config_runtime(
     state: &mut AppState,
     cfg: &config::Config,
 ) -> windows::core::Result<()> {
-    state.autoconvert_enabled = false;
+    if state.autoconvert.is_enabled() != cfg.autoconvert_enabled {
+        state.autoconvert.set_enabled(cfg.autoconvert_enabled);
+    }
 
-    state.active_hotkey_sequences = crate::app::HotkeySequenceValues::from_config(cfg);
+    state.runtime_hotkeys = crate::app::RuntimeHotkeys::from_config(cfg);
+    state.foreground_app = None;
+    state
+        .layout_memory
+        .configure(cfg.layout_memory, &cfg.app_layouts);
 
-    state.runtime_chord_capture = crate::app::RuntimeChordCapture::default();
-    state.hotkey_sequence_progress = crate::app::HotkeySequenceProgress::default();
+    let layouts: Vec<(isize, String)> = installed_layouts_with_tags()
+        .into_iter()
+        .map(|l| (l.handle.0 as isize, l.tag))
+        .collect();
+    let boundaries = cfg.word_boundaries.resolve(&layouts);
+    with_journal(|journal| journal.set_boundaries(boundaries));
 
-    state.active_switch_layout_sequence = cfg.hotkey_switch_layout_sequence;
-    state.switch_layout_waiting_second = false;
-    state.switch_layout_first_tick_ms = 0;
+    state.config = cfg.clone();
 
     ui_try!(
         hwnd,
         state,
         T_CONFIG,
-        "Failed to register hotkeys",
-        crate::input::hotkeys::register_from_config(hwnd, cfg)
+        "Failed to load keyboard layout tables, using RU/EN",
+        apply_layout_pair(cfg)
     );
 
     Ok(())
 }
 
+/// Loads user layout tables and activates the pair selected by `cfg.layout_pair`.
+///
+/// On any failure the built-in RU/EN pair stays active.
+fn apply_layout_pair(cfg: &config::Config) -> windows::core::Result<()> {
+    layout::set_active_pair(None);
+
+    let tables = config::load_layout_pair_tables().map_err(io_to_win)?;
+    let pairs = layout::merge_with_builtin(&tables)
+        .map_err(|e| io_to_win(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+
+    let pair = pairs
+        .into_iter()
+        .find(|p| p.id() == cfg.layout_pair)
+        .ok_or_else(|| {
+            io_to_win(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("unknown layout pair '{}'", cfg.layout_pair),
+            ))
+        })?;
+
+    tracing::info!(id = pair.id(), name = pair.name(), "layout pair active");
+    layout::set_active_pair(Some(pair));
+    Ok(())
+}
+
 fn init_font_and_visuals(hwnd: HWND, state: &mut AppState) {
     unsafe {
         match visuals::create_message_font() {
@@ -233,7 +230,8 @@ fn load_config_or_default(hwnd: HWND, state: &mut AppState) -> config::Config {
         .ok()
         .and_then(|cfg| match cfg.validate_hotkey_sequences() {
             Ok(()) => Some(cfg),
-            Err(msg) => {
+            Err(issues) => {
+                let msg = config::describe_issues(&issues);
                 let user_text = msg.clone();
                 let source = io_to_win(std::io::Error::new(std::io::ErrorKind::InvalidInput, msg));
                 crate::platform::ui::error_notifier::push(
@@ -252,8 +250,7 @@ fn on_create(hwnd: HWND) -> LRESULT {
     startup_or_return0!(hwnd, &mut state, "Failed to create UI controls", ui::create_controls(hwnd, &mut state));
     let cfg = load_config_or_default(hwnd, state.as_mut());
 
-    state.hotkey_values = crate::app::HotkeyValues::from_config(&cfg);
-    state.active_hotkey_sequences = crate::app::HotkeySequenceValues::from_config(&cfg);
+    state.runtime_hotkeys = crate::app::RuntimeHotkeys::from_config(&cfg);
 
     #[rustfmt::skip] {
         startup_or_return0!(hwnd, &mut state, "Failed to apply config to UI", apply_config_to_ui(state.as_mut(), &cfg));
@@ -261,8 +258,12 @@ fn on_create(hwnd: HWND) -> LRESULT {
         startup_or_return0!(hwnd, &mut state, "Failed to apply config at runtime", apply_config_runtime(hwnd, state.as_mut(), &cfg));
     }
 
+    dictionary::load(hwnd, state.as_mut());
+    snippets::load(hwnd, state.as_mut());
+
     keyboard::install(hwnd, state.as_mut());
     mouse::install();
+    focus::install(hwnd);
 
     init_font_and_visuals(hwnd, &mut state);
 
@@ -273,6 +274,7 @@ fn on_create(hwnd: HWND) -> LRESULT {
     if let Err(e) = crate::platform::win::tray::ensure_icon(hwnd) {
         tracing::warn!(error = ?e, "tray ensure_icon failed");
     }
+    with_state_mut_do(hwnd, |state| autoconvert::refresh_tray(hwnd, state));
 
     #[cfg(debug_assertions)]
     with_state_mut_do(hwnd, |state| {
@@ -359,7 +361,7 @@ pub extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPA
             }
 
             with_state_mut_do(hwnd, |state| {
-                if state.autoconvert_enabled {
+                if !expand_snippet(state) && state.autoconvert.is_active() {
                     autoconvert_last_word(state);
                 }
             });
@@ -367,6 +369,20 @@ pub extern "system" fn wndproc(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPA
             LRESULT(0)
         }
 
+        crate::platform::ui::error_notifier::WM_APP_AUTOCONVERT_REVERTED => {
+            with_state_mut_do(hwnd, |state| {
+                dictionary::learn_from_revert(hwnd, state);
+            });
+            LRESULT(0)
+        }
+
+        crate::platform::ui::error_notifier::WM_APP_FOREGROUND_CHANGED => {
+            let from = HWND(wparam.0 as *mut _);
+            let to = HWND(lparam.0 as *mut _);
+            with_state_mut_do(hwnd, |state| focus::on_foreground_changed(state, from, to));
+            LRESULT(0)
+        }
+
         WM_APP_TRAY => tray_dispatch::handle_tray_message(hwnd, wparam, lparam),
 
         _ => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
@@ -408,8 +424,9 @@ fn build_and_save_config_from_ui(
     state: &mut AppState,
 ) -> std::result::Result<config::Config, ApplyConfigError> {
     // Apply must not depend on reading existing config file.
-    // Build a fresh config from UI and overwrite on disk.
-    let cfg = read_ui_to_config(state, config::Config::default());
+    // Start from the last applied config so fields without UI controls survive,
+    // then overwrite on disk.
+    let cfg = read_ui_to_config(state, state.config.clone());
 
     config::save(&cfg).map_err(|e| {
         let user_text = match e.kind() {
@@ -459,6 +476,20 @@ fn handle_apply(hwnd: HWND, state: &mut AppState) {
         "Failed to update UI from config",
         apply_config_to_ui(state, &cfg)
     );
+
+    let warnings: Vec<_> = cfg
+        .hotkey_sequence_issues()
+        .into_iter()
+        .filter(|issue| issue.severity == config::Severity::Warning)
+        .collect();
+    if !warnings.is_empty() {
+        crate::platform::ui::info_notifier::push(
+            hwnd,
+            state,
+            "Hotkeys",
+            &config::describe_issues(&warnings),
+        );
+    }
 }
 
 fn handle_cancel(hwnd: HWND, state: &mut AppState) {
@@ -504,26 +535,14 @@ pub fn hotkey_id_from_wparam(wparam: WPARAM) -> i32 {
     wparam.0 as i32
 }
 
-fn handle_pause_toggle(hwnd: HWND, state: &mut AppState) {
-    let enabled = !state.autoconvert_enabled;
-    set_autoconvert_enabled_from_tray(hwnd, state, enabled, true);
-}
-
-fn handle_convert_smart(state: &mut AppState) {
-    if crate::conversion::convert_selection_if_any(state) {
-        return;
-    }
-    crate::conversion::convert_last_word(state);
-}
-
 #[cfg(test)]
-pub(crate) fn hotkey_action_from_wparam(wparam: WPARAM) -> Option<HotkeyAction> {
+pub(crate) fn hotkey_action_from_wparam(wparam: WPARAM) -> Option<ActionId> {
     let id = hotkey_id_from_wparam(wparam);
     action_from_id(id)
 }
 
 #[cfg(not(test))]
-fn hotkey_action_from_wparam(wparam: WPARAM) -> Option<HotkeyAction> {
+fn hotkey_action_from_wparam(wparam: WPARAM) -> Option<ActionId> {
     let id = hotkey_id_from_wparam(wparam);
     action_from_id(id)
 }
@@ -538,17 +557,7 @@ fn on_hotkey(hwnd: HWND, wparam: WPARAM) -> LRESULT {
         return LRESULT(0);
     };
 
-    with_state_mut(hwnd, |state| match action {
-        HotkeyAction::PauseToggle => {
-            tracing::warn!(msg = "autoconvert_toggle", source = "hotkey_pause_toggle");
-            handle_pause_toggle(hwnd, state)
-        }
-        HotkeyAction::ConvertLastWord => handle_convert_smart(state),
-        HotkeyAction::ConvertSelection => crate::conversion::convert_selection(state),
-        HotkeyAction::SwitchLayout => {
-            let _ = switch_keyboard_layout();
-        }
-    });
+    with_state_mut(hwnd, |state| action_handlers::run(hwnd, state, action));
 
     LRESULT(0)
 }
@@ -565,7 +574,7 @@ fn on_app_error(hwnd: HWND) -> LRESULT {
 }
 
 fn on_timer(hwnd: HWND, wparam: WPARAM, _lparam: LPARAM) -> LRESULT {
-    let _ = handle_tray_timer(hwnd, wparam);
+    let _ = handle_tray_timer(hwnd, wparam) || autoconvert::handle_timer(hwnd, wparam);
     #[cfg(debug_assertions)]
     let _ = handle_timer(hwnd, wparam.0);
     LRESULT(0)
@@ -581,40 +590,3 @@ fn toggle_window_visibility_from_tray(hwnd: HWND) {
         }
     }
 }
-
-fn set_autoconvert_enabled_from_tray(
-    hwnd: HWND,
-    state: &mut crate::app::AppState,
-    enabled: bool,
-    show_balloon: bool,
-) {
-    if state.autoconvert_enabled == enabled {
-        return;
-    }
-
-    state.autoconvert_enabled = enabled;
-
-    if let Err(e) = crate::platform::win::tray::switch_tray_icon(hwnd, enabled) {
-        tracing::warn!(error = ?e, "switch_tray_icon failed");
-    }
-
-    if !show_balloon {
-        return;
-    }
-
-    let hotkey_text = if state.hotkey_sequence_values.pause.is_some() {
-        crate::platform::win::format_hotkey_sequence(state.hotkey_sequence_values.pause)
-    } else {
-        crate::platform::win::format_hotkey(state.hotkey_values.pause)
-    };
-
-    let body = if enabled {
-        format!("Status: active.\nAuto convert: ON.\nToggle: {hotkey_text}")
-    } else {
-        format!("Status: paused.\nAuto convert: OFF.\nToggle: {hotkey_text}")
-    };
-
-    if let Err(e) = crate::platform::win::tray::balloon_info(hwnd, "RustSwitcher", &body) {
-        tracing::warn!(error = ?e, "tray balloon failed");
-    }
-}
@@ -0,0 +1,133 @@
+//! Handlers of the actions in `app::actions::ACTIONS`, of the layout activations and
+//! of the text transforms, run on `WM_HOTKEY`.
+
+use windows::Win32::Foundation::HWND;
+
+use super::{autoconvert, dictionary};
+use crate::{
+    app::{
+        AppState,
+        actions::{
+            ActionId, CONVERT_LAST_PHRASE, CONVERT_LAST_WORD, CONVERT_LAST_WORDS,
+            CONVERT_SELECTION, PAUSE, PAUSE_FOR, SWITCH_LAYOUT, TOGGLE_RECENT_LAYOUTS,
+            UNDO_LAST_CONVERSION,
+        },
+        autoconvert_state::AutoconvertStatus,
+    },
+    conversion::{self, ConvertDirection},
+    domain::text::{activate_layout, layout_policy::LayoutTarget, transform},
+};
+
+type Handler = fn(HWND, &mut AppState);
+
+/// Handler of every registered action.
+const HANDLERS: [(ActionId, Handler); 9] = [
+    (CONVERT_LAST_WORD, convert_smart),
+    (PAUSE, toggle_pause),
+    (CONVERT_SELECTION, convert_selection),
+    (SWITCH_LAYOUT, switch_layout),
+    (UNDO_LAST_CONVERSION, undo),
+    (TOGGLE_RECENT_LAYOUTS, toggle_recent_layouts),
+    (PAUSE_FOR, pause_for),
+    (CONVERT_LAST_PHRASE, convert_last_phrase),
+    (CONVERT_LAST_WORDS, convert_last_words),
+];
+
+pub(crate) fn handler(action: ActionId) -> Option<Handler> {
+    HANDLERS
+        .iter()
+        .find(|(id, _)| *id == action)
+        .map(|&(_, handler)| handler)
+}
+
+pub(crate) fn run(hwnd: HWND, state: &mut AppState, action: ActionId) {
+    if let Some(tag) = action.layout_tag() {
+        switch_to(state, LayoutTarget::Tag(tag));
+        return;
+    }
+    if let Some(id) = action.transform_id() {
+        run_transform(state, id);
+        return;
+    }
+    match handler(action) {
+        Some(handler) => handler(hwnd, state),
+        None => tracing::warn!(action = action.as_str(), "action has no handler"),
+    }
+}
+
+fn toggle_pause(hwnd: HWND, state: &mut AppState) {
+    tracing::warn!(msg = "autoconvert_toggle", source = "hotkey_pause_toggle");
+    autoconvert::toggle(hwnd, state, true);
+}
+
+/// Pauses autoconvert for `pause_minutes` of the config, or resumes it while paused.
+fn pause_for(hwnd: HWND, state: &mut AppState) {
+    if let AutoconvertStatus::Paused { .. } = state.autoconvert.status() {
+        autoconvert::toggle(hwnd, state, true);
+    } else {
+        let minutes = state.config.pause_minutes;
+        autoconvert::pause(hwnd, state, minutes, true);
+    }
+}
+
+fn convert_smart(hwnd: HWND, state: &mut AppState) {
+    let direction = ConvertDirection::Auto;
+    if conversion::convert_selection_if_any(state, direction) {
+        return;
+    }
+    // Converting an autoconverted word back is an undo of the autoconversion.
+    if crate::input_journal::last_token_autoconverted() {
+        dictionary::learn_from_revert(hwnd, state);
+    }
+    conversion::convert_last_word(state, direction);
+}
+
+fn convert_selection(_hwnd: HWND, state: &mut AppState) {
+    conversion::convert_selection(state, ConvertDirection::Auto);
+}
+
+/// Applies the transform `id` to the selection, or to the last word when nothing is selected.
+fn run_transform(state: &mut AppState, id: &str) {
+    let Some(transform) = transform::find(id) else {
+        tracing::warn!(id, "unknown transform");
+        return;
+    };
+    if !conversion::transform_selection_if_any(state, transform) {
+        conversion::transform_last_word(state, transform);
+    }
+}
+
+fn convert_last_phrase(_hwnd: HWND, state: &mut AppState) {
+    conversion::convert_last_phrase(state, ConvertDirection::Auto);
+}
+
+fn convert_last_words(_hwnd: HWND, state: &mut AppState) {
+    conversion::convert_last_words(state, ConvertDirection::Auto);
+}
+
+fn switch_to(state: &mut AppState, target: LayoutTarget<'_>) {
+    if let Err(e) = activate_layout(target, &mut state.recent_layouts) {
+        tracing::warn!(error = ?e, ?target, "layout switch failed");
+    }
+}
+
+fn switch_layout(_hwnd: HWND, state: &mut AppState) {
+    switch_to(state, LayoutTarget::Next);
+}
+
+fn toggle_recent_layouts(_hwnd: HWND, state: &mut AppState) {
+    switch_to(state, LayoutTarget::Recent);
+}
+
+fn undo(hwnd: HWND, state: &mut AppState) {
+    match conversion::undo_last_conversion(state) {
+        Ok(conversion::ConversionKind::Autoconvert) => {
+            dictionary::learn_from_revert(hwnd, state);
+        }
+        Ok(_) => {}
+        Err(reason) => {
+            tracing::info!(reason = reason.as_str(), "undo refused");
+            crate::platform::ui::info_notifier::push(hwnd, state, "Undo", reason.user_text());
+        }
+    }
+}
@@ -0,0 +1,124 @@
+//! Application rule of the foreground window, see `config::app_rules`.
+
+use windows::Win32::{
+    Foundation::HWND,
+    System::Threading::GetCurrentProcessId,
+    UI::WindowsAndMessaging::{
+        GetClassNameW, GetForegroundWindow, GetWindowThreadProcessId, InternalGetWindowText,
+    },
+};
+
+use super::{focus::app_name, io_to_win};
+use crate::{
+    app::{AppState, ForegroundApp, HotkeyBindings},
+    config::{self, AppSettings, WindowInfo},
+    platform::ui::error_notifier::{T_CONFIG, push},
+};
+
+fn window_text(hwnd: HWND, read: unsafe fn(HWND, &mut [u16]) -> i32) -> String {
+    let mut buf = [0u16; 512];
+    let len = unsafe { read(hwnd, &mut buf) };
+    usize::try_from(len).map_or_else(
+        |_| String::new(),
+        |len| String::from_utf16_lossy(&buf[..len.min(buf.len())]),
+    )
+}
+
+fn is_own_window(hwnd: HWND) -> bool {
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&raw mut pid)) };
+    pid == unsafe { GetCurrentProcessId() }
+}
+
+/// Settings of the foreground window after its application rule.
+///
+/// The rule is matched again when another window comes to the foreground or its title
+/// changes. Windows of this process keep the settings of the window before them.
+pub(crate) fn foreground_settings(state: &mut AppState) -> AppSettings {
+    refresh(state);
+    state
+        .foreground_app
+        .as_ref()
+        .map(|app| app.settings.clone())
+        .unwrap_or_default()
+}
+
+/// Matches the application rule of the foreground window again if it changed, and
+/// unbinds the hotkeys the rule disables.
+pub(crate) fn refresh(state: &mut AppState) {
+    let fg = unsafe { GetForegroundWindow() };
+    if fg.0.is_null() || is_own_window(fg) {
+        return;
+    }
+
+    // `InternalGetWindowText` reads the title without sending a message, so a hung
+    // window cannot stall the keyboard hook.
+    let title = window_text(fg, InternalGetWindowText);
+    if let Some(app) = &state.foreground_app
+        && app.window == fg.0 as isize
+        && app.title == title
+    {
+        return;
+    }
+
+    let window = WindowInfo {
+        exe: app_name(fg).unwrap_or_default(),
+        class: window_text(fg, GetClassNameW),
+        title,
+    };
+    let settings = state.config.app_settings(&window);
+
+    let mut bindings = HotkeyBindings::from_config(&state.config);
+    bindings.retain(|id| settings.allows(id));
+    if bindings != state.runtime_hotkeys.bindings {
+        tracing::trace!(exe = window.exe, "hotkeys limited by application rule");
+        state.runtime_hotkeys = crate::app::RuntimeHotkeys::default();
+        state.runtime_hotkeys.bindings = bindings;
+    }
+
+    state.foreground_app = Some(ForegroundApp {
+        window: fg.0 as isize,
+        exe: window.exe,
+        title: window.title,
+        settings,
+    });
+}
+
+/// Delay before a conversion: the rule's, else the one of the settings window.
+pub(crate) fn conversion_delay_ms(state: &AppState, settings: &AppSettings) -> u32 {
+    settings
+        .delay_ms
+        .or_else(|| crate::helpers::get_edit_u32(state.edits.delay_ms))
+        .unwrap_or(100)
+}
+
+/// Executable of the last foreground window of another process, for the tray menu.
+pub(crate) fn last_app(state: &AppState) -> Option<&str> {
+    state
+        .foreground_app
+        .as_ref()
+        .map(|app| app.exe.as_str())
+        .filter(|exe| !exe.is_empty())
+}
+
+/// Turns autoconvert and hotkeys off in `exe`, or back on, and saves the config.
+pub(crate) fn toggle_app_disabled(hwnd: HWND, state: &mut AppState, exe: &str) {
+    let mut cfg = state.config.clone();
+    let disabled = !cfg.is_app_disabled(exe);
+    cfg.set_app_disabled(exe, disabled);
+
+    if let Err(e) = config::save(&cfg) {
+        push(
+            hwnd,
+            state,
+            T_CONFIG,
+            "Failed to save config",
+            &io_to_win(e),
+        );
+        return;
+    }
+    tracing::info!(exe, disabled, "application rule changed from tray");
+
+    state.config = cfg;
+    state.foreground_app = None;
+}
@@ -0,0 +1,127 @@
+//! Turning autoconvert on, off or pausing it, from the tray and from hotkeys.
+//!
+//! The on/off choice is saved in the config. A pause is not: it ends on its own, and a
+//! timer keeps the tray tooltip's remaining time current until then.
+
+use std::time::Duration;
+
+use windows::Win32::{
+    Foundation::{HWND, WPARAM},
+    UI::WindowsAndMessaging::{KillTimer, SetTimer},
+};
+
+use super::{io_to_win, tray};
+use crate::{
+    app::{AppState, actions::PAUSE, autoconvert_state::AutoconvertStatus},
+    config,
+    platform::{
+        ui::error_notifier::{T_CONFIG, push},
+        win::{format_hotkey_sequence, state::with_state_mut_do},
+    },
+};
+
+const PAUSE_TIMER_ID: usize = 0x5157_0002;
+const PAUSE_TIMER_MS: u32 = 1000;
+
+/// Turns autoconvert off when it is on, else on, ending a pause.
+pub(crate) fn toggle(hwnd: HWND, state: &mut AppState, show_balloon: bool) {
+    state.autoconvert.toggle();
+    status_changed(hwnd, state, show_balloon);
+}
+
+/// Stops autoconvert for `minutes`; it is on again afterwards.
+pub(crate) fn pause(hwnd: HWND, state: &mut AppState, minutes: u32, show_balloon: bool) {
+    state
+        .autoconvert
+        .pause(Duration::from_secs(u64::from(minutes) * 60));
+    status_changed(hwnd, state, show_balloon);
+}
+
+/// Handles the pause timer: ends a pause whose time is over, else updates the tooltip.
+///
+/// Returns `false` for other timers.
+pub(crate) fn handle_timer(hwnd: HWND, wparam: WPARAM) -> bool {
+    if wparam.0 != PAUSE_TIMER_ID {
+        return false;
+    }
+
+    with_state_mut_do(hwnd, |state| {
+        if state.autoconvert.resume_if_due() {
+            tracing::info!("autoconvert pause over");
+            status_changed(hwnd, state, true);
+        } else {
+            refresh_tray(hwnd, state);
+        }
+    });
+    true
+}
+
+/// Shows the status in the tray icon and tooltip, and runs the pause timer while paused.
+pub(crate) fn refresh_tray(hwnd: HWND, state: &AppState) {
+    let status = state.autoconvert.status();
+
+    if let Err(e) =
+        tray::switch_tray_icon(hwnd, status == AutoconvertStatus::On, &status.describe())
+    {
+        tracing::warn!(error = ?e, "switch_tray_icon failed");
+    }
+
+    if matches!(status, AutoconvertStatus::Paused { .. }) {
+        let _ = unsafe { SetTimer(Some(hwnd), PAUSE_TIMER_ID, PAUSE_TIMER_MS, None) };
+    } else {
+        let _ = unsafe { KillTimer(Some(hwnd), PAUSE_TIMER_ID) };
+    }
+}
+
+fn status_changed(hwnd: HWND, state: &mut AppState, show_balloon: bool) {
+    tracing::info!(status = ?state.autoconvert.status(), "autoconvert status changed");
+    save_enabled(hwnd, state);
+
+    // A balloon resets the tray icon, so it goes first.
+    if show_balloon {
+        show_status_balloon(hwnd, state);
+    }
+    refresh_tray(hwnd, state);
+}
+
+/// Saves the on/off choice when it differs from the config.
+fn save_enabled(hwnd: HWND, state: &mut AppState) {
+    let enabled = state.autoconvert.is_enabled();
+    if state.config.autoconvert_enabled == enabled {
+        return;
+    }
+
+    let mut cfg = state.config.clone();
+    cfg.autoconvert_enabled = enabled;
+    if let Err(e) = config::save(&cfg) {
+        push(
+            hwnd,
+            state,
+            T_CONFIG,
+            "Failed to save config",
+            &io_to_win(e),
+        );
+        return;
+    }
+    state.config = cfg;
+}
+
+fn show_status_balloon(hwnd: HWND, state: &AppState) {
+    let hotkey_text = format_hotkey_sequence(state.config.hotkey(PAUSE));
+
+    let body = match state.autoconvert.status() {
+        AutoconvertStatus::On => {
+            format!("Status: active.\nAuto convert: ON.\nToggle: {hotkey_text}")
+        }
+        AutoconvertStatus::Off => {
+            format!("Status: paused.\nAuto convert: OFF.\nToggle: {hotkey_text}")
+        }
+        status @ AutoconvertStatus::Paused { .. } => {
+            format!("{}.\nToggle: {hotkey_text}", status.describe())
+        }
+    };
+
+    if let Err(e) = tray::balloon_info(hwnd, "RustSwitcher", &body) {
+        tracing::warn!(error = ?e, "tray balloon failed");
+    }
+}
@@ -1,10 +1,17 @@
 use windows::Win32::{
     Foundation::{HWND, LRESULT, WPARAM},
-    UI::WindowsAndMessaging::{BN_CLICKED, DestroyWindow, EN_KILLFOCUS, EN_SETFOCUS},
+    UI::WindowsAndMessaging::{
+        BN_CLICKED, DestroyWindow, EN_KILLFOCUS, EN_SETFOCUS, LBN_SELCHANGE,
+    },
 };
 
-use super::state::with_state_mut_do;
-use crate::{app::ControlId, platform::ui::error_notifier::T_UI, utils::helpers};
+use super::{dictionary, snippets, state::with_state_mut_do};
+use crate::{
+    app::{ControlId, hotkey_edit_action},
+    domain::text::user_dictionary::DictionaryRule,
+    platform::ui::error_notifier::T_UI,
+    utils::helpers,
+};
 
 #[cfg_attr(
     debug_assertions,
@@ -18,6 +25,24 @@ pub(crate) fn on_command(hwnd: HWND, wparam: WPARAM) -> LRESULT {
         return r;
     }
 
+    if id == ControlId::LearnedList as i32 {
+        return LRESULT(0);
+    }
+
+    if id == ControlId::DictionaryList as i32 {
+        if notif == LBN_SELCHANGE {
+            with_state_mut_do(hwnd, dictionary::handle_selection);
+        }
+        return LRESULT(0);
+    }
+
+    if id == ControlId::SnippetList as i32 {
+        if notif == LBN_SELCHANGE {
+            with_state_mut_do(hwnd, snippets::handle_selection);
+        }
+        return LRESULT(0);
+    }
+
     if notif != BN_CLICKED {
         return LRESULT(0);
     }
@@ -26,21 +51,13 @@ pub(crate) fn on_command(hwnd: HWND, wparam: WPARAM) -> LRESULT {
 }
 
 fn handle_hotkey_capture_focus(hwnd: HWND, id: i32, notif: u32) -> Option<LRESULT> {
-    let cid = ControlId::from_i32(id)?;
-
-    let slot = match cid {
-        ControlId::HotkeyLastWord => crate::app::HotkeySlot::LastWord,
-        ControlId::HotkeyPause => crate::app::HotkeySlot::Pause,
-        ControlId::HotkeySelection => crate::app::HotkeySlot::Selection,
-        ControlId::HotkeySwitchLayout => crate::app::HotkeySlot::SwitchLayout,
-        _ => return None,
-    };
+    let action = hotkey_edit_action(id)?;
 
     match notif {
         EN_SETFOCUS => {
             with_state_mut_do(hwnd, |state| {
                 state.hotkey_capture.active = true;
-                state.hotkey_capture.slot = Some(slot);
+                state.hotkey_capture.action = Some(action);
                 state.hotkey_capture.pending_mods_vks = 0;
                 state.hotkey_capture.pending_mods = 0;
                 state.hotkey_capture.pending_mods_valid = false;
@@ -48,7 +65,7 @@ fn handle_hotkey_capture_focus(hwnd: HWND, id: i32, notif: u32) -> Option<LRESUL
                 state.hotkey_capture.last_input_tick_ms = 0;
 
                 #[cfg(debug_assertions)]
-                tracing::debug!(slot = ?slot, "hotkey.capture.start");
+                tracing::debug!(action = action.as_str(), "hotkey.capture.start");
             });
             Some(LRESULT(0))
         }
@@ -58,7 +75,7 @@ fn handle_hotkey_capture_focus(hwnd: HWND, id: i32, notif: u32) -> Option<LRESUL
                 state.hotkey_capture.active = false;
 
                 #[cfg(debug_assertions)]
-                tracing::debug!(slot = ?slot, "hotkey.capture.stop");
+                tracing::debug!(action = action.as_str(), "hotkey.capture.stop");
             });
             Some(LRESULT(0))
         }
@@ -85,6 +102,38 @@ fn handle_buttons(hwnd: HWND, id: i32) -> LRESULT {
             super::handle_cancel(hwnd, state);
         }),
 
+        ControlId::DictionaryNever => with_state_mut_do(hwnd, |state| {
+            dictionary::handle_add(hwnd, state, DictionaryRule::NeverConvert);
+        }),
+
+        ControlId::DictionaryAlways => with_state_mut_do(hwnd, |state| {
+            dictionary::handle_add(hwnd, state, DictionaryRule::AlwaysConvert);
+        }),
+
+        ControlId::DictionaryRemove => with_state_mut_do(hwnd, |state| {
+            dictionary::handle_remove(hwnd, state);
+        }),
+
+        ControlId::DictionaryImport => with_state_mut_do(hwnd, |state| {
+            dictionary::handle_import(hwnd, state);
+        }),
+
+        ControlId::DictionaryExport => with_state_mut_do(hwnd, |state| {
+            dictionary::handle_export(hwnd, state);
+        }),
+
+        ControlId::LearnedForget => with_state_mut_do(hwnd, |state| {
+            dictionary::handle_forget(hwnd, state);
+        }),
+
+        ControlId::SnippetSave => with_state_mut_do(hwnd, |state| {
+            snippets::handle_save(hwnd, state);
+        }),
+
+        ControlId::SnippetRemove => with_state_mut_do(hwnd, |state| {
+            snippets::handle_remove(hwnd, state);
+        }),
+
         ControlId::Exit => with_state_mut_do(hwnd, |state| {
             if let Err(e) = unsafe { DestroyWindow(hwnd) } {
                 crate::platform::ui::error_notifier::push(
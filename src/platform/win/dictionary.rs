@@ -0,0 +1,300 @@
+//! User dictionary group of the settings window.
+//!
+//! Every change is saved to `config::dictionary_path` and activated right away,
+//! independent of Apply and Cancel. The group also lists the exceptions learned from
+//! reverted autoconversions, saved to `config::learned_path`.
+
+use std::{io, os::windows::ffi::OsStringExt, path::PathBuf};
+
+use windows::{
+    Win32::{
+        Foundation::{HWND, LPARAM, WPARAM},
+        UI::{
+            Controls::Dialogs::{
+                GetOpenFileNameW, GetSaveFileNameW, OFN_EXPLORER, OFN_FILEMUSTEXIST,
+                OFN_HIDEREADONLY, OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST, OPENFILENAMEW,
+            },
+            WindowsAndMessaging::{
+                LB_ADDSTRING, LB_ERR, LB_GETCURSEL, LB_RESETCONTENT, SendMessageW,
+            },
+        },
+    },
+    core::{PCWSTR, PWSTR, w},
+};
+
+use super::io_to_win;
+use crate::{
+    app::AppState,
+    config,
+    domain::text::{
+        last_word,
+        learned_exceptions::{unix_now, with_active_learned},
+        user_dictionary::{
+            DictionaryRule, UserDictionary, active_dictionary, set_active_dictionary,
+        },
+    },
+    platform::ui::error_notifier::{T_CONFIG, T_UI},
+    ui_call, ui_try,
+    utils::helpers,
+};
+
+const PATH_BUF_LEN: usize = 1024;
+
+/// Loads the saved dictionary and learned exceptions, activates them and fills the lists.
+pub(crate) fn load(hwnd: HWND, state: &mut AppState) {
+    ui_try!(
+        hwnd,
+        state,
+        T_CONFIG,
+        "Failed to load the user dictionary",
+        config::load_user_dictionary()
+            .map(set_active_dictionary)
+            .map_err(io_to_win)
+    );
+    refresh_list(state);
+
+    ui_try!(
+        hwnd,
+        state,
+        T_CONFIG,
+        "Failed to load the learned exceptions",
+        config::load_learned_exceptions()
+            .map(|loaded| with_active_learned(|learned| *learned = loaded))
+            .map_err(io_to_win)
+    );
+    refresh_learned_list(state);
+}
+
+/// Counts an undo of the last autoconversion against the word as it was typed.
+///
+/// Does nothing when the last conversion was already counted.
+pub(crate) fn learn_from_revert(hwnd: HWND, state: &mut AppState) {
+    let Some(word) = last_word::take_last_autoconversion() else {
+        return;
+    };
+
+    let now = unix_now();
+    let learned = with_active_learned(|learned| {
+        learned.record_undo(&word, now);
+        learned.prune(now);
+        learned.clone()
+    });
+    tracing::info!(
+        word = %word,
+        score = learned.score(&word, now),
+        "autoconversion reverted"
+    );
+
+    ui_try!(
+        hwnd,
+        state,
+        T_CONFIG,
+        "Failed to save the learned exceptions",
+        config::save_learned_exceptions(&learned).map_err(io_to_win)
+    );
+    refresh_learned_list(state);
+}
+
+/// Forgets the selected learned exception.
+pub(crate) fn handle_forget(hwnd: HWND, state: &mut AppState) {
+    let Some(index) = selected_index(state.dictionary.learned) else {
+        return;
+    };
+
+    let now = unix_now();
+    let learned = with_active_learned(|learned| {
+        let word = learned
+            .exceptions(now)
+            .nth(index)
+            .map(|(w, _)| w.to_string());
+        word.map(|w| {
+            learned.remove(&w);
+            learned.clone()
+        })
+    });
+    let Some(learned) = learned else {
+        return;
+    };
+
+    ui_try!(
+        hwnd,
+        state,
+        T_CONFIG,
+        "Failed to save the learned exceptions",
+        config::save_learned_exceptions(&learned).map_err(io_to_win)
+    );
+    refresh_learned_list(state);
+}
+
+pub(crate) fn handle_add(hwnd: HWND, state: &mut AppState, rule: DictionaryRule) {
+    let word = helpers::get_edit_text(state.dictionary.word);
+
+    ui_call!(
+        hwnd,
+        state,
+        T_UI,
+        "Failed to add the word to the user dictionary",
+        update(state, |dict| dict
+            .insert(&word, rule)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e)))
+    );
+}
+
+pub(crate) fn handle_remove(hwnd: HWND, state: &mut AppState) {
+    let word = helpers::get_edit_text(state.dictionary.word);
+
+    ui_call!(
+        hwnd,
+        state,
+        T_UI,
+        "Failed to remove the word from the user dictionary",
+        update(state, |dict| {
+            dict.remove(&word);
+            Ok(())
+        })
+    );
+
+    let _ = helpers::set_edit_text(state.dictionary.word, "");
+}
+
+/// Merges a dictionary file into the current one, its entries win on conflicts.
+pub(crate) fn handle_import(hwnd: HWND, state: &mut AppState) {
+    let Some(path) = pick_file(hwnd, false) else {
+        return;
+    };
+
+    ui_call!(
+        hwnd,
+        state,
+        T_CONFIG,
+        "Failed to import the user dictionary",
+        update(state, |dict| {
+            dict.merge(&config::read_user_dictionary(&path)?);
+            Ok(())
+        })
+    );
+}
+
+pub(crate) fn handle_export(hwnd: HWND, state: &mut AppState) {
+    let Some(path) = pick_file(hwnd, true) else {
+        return;
+    };
+
+    ui_try!(
+        hwnd,
+        state,
+        T_CONFIG,
+        "Failed to export the user dictionary",
+        config::write_user_dictionary(&path, &active_dictionary()).map_err(io_to_win)
+    );
+}
+
+/// Copies the word of the selected list entry into the word edit.
+pub(crate) fn handle_selection(state: &mut AppState) {
+    let dict = active_dictionary();
+    let Some((word, _)) = selected_index(state.dictionary.list).and_then(|i| dict.entries().nth(i))
+    else {
+        return;
+    };
+
+    let _ = helpers::set_edit_text(state.dictionary.word, word);
+}
+
+pub(super) fn selected_index(list: HWND) -> Option<usize> {
+    let index = unsafe { SendMessageW(list, LB_GETCURSEL, Some(WPARAM(0)), Some(LPARAM(0))) };
+    if index.0 == LB_ERR as isize {
+        return None;
+    }
+    usize::try_from(index.0).ok()
+}
+
+/// Applies `f` to a copy of the active dictionary, then saves and activates it.
+fn update(
+    state: &AppState,
+    f: impl FnOnce(&mut UserDictionary) -> io::Result<()>,
+) -> windows::core::Result<()> {
+    let mut dict = (*active_dictionary()).clone();
+    f(&mut dict).map_err(io_to_win)?;
+    config::save_user_dictionary(&dict).map_err(io_to_win)?;
+
+    tracing::info!(entries = dict.len(), "user dictionary updated");
+    set_active_dictionary(dict);
+    refresh_list(state);
+    Ok(())
+}
+
+fn refresh_list(state: &AppState) {
+    let items: Vec<String> = active_dictionary()
+        .entries()
+        .map(|(word, rule)| format!("{word}  ({})", rule.as_str()))
+        .collect();
+    fill_list(state.dictionary.list, &items);
+}
+
+fn refresh_learned_list(state: &AppState) {
+    let now = unix_now();
+    let items: Vec<String> = with_active_learned(|learned| {
+        learned
+            .exceptions(now)
+            .map(|(word, score)| format!("{word}  ({score:.1})"))
+            .collect()
+    });
+    fill_list(state.dictionary.learned, &items);
+}
+
+pub(super) fn fill_list(list: HWND, items: &[String]) {
+    unsafe {
+        SendMessageW(list, LB_RESETCONTENT, Some(WPARAM(0)), Some(LPARAM(0)));
+    }
+
+    for text in items {
+        let wide: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+        unsafe {
+            SendMessageW(
+                list,
+                LB_ADDSTRING,
+                Some(WPARAM(0)),
+                Some(LPARAM(wide.as_ptr() as isize)),
+            );
+        }
+    }
+}
+
+/// Shows the open or save file dialog. Returns `None` when it was cancelled.
+fn pick_file(hwnd: HWND, save: bool) -> Option<PathBuf> {
+    let filter: Vec<u16> = "Text files (*.txt)\0*.txt\0All files (*.*)\0*.*\0\0"
+        .encode_utf16()
+        .collect();
+    let mut file = vec![0u16; PATH_BUF_LEN];
+
+    let flags = if save {
+        OFN_EXPLORER | OFN_PATHMUSTEXIST | OFN_OVERWRITEPROMPT | OFN_HIDEREADONLY
+    } else {
+        OFN_EXPLORER | OFN_PATHMUSTEXIST | OFN_FILEMUSTEXIST | OFN_HIDEREADONLY
+    };
+
+    let mut ofn = OPENFILENAMEW {
+        lStructSize: std::mem::size_of::<OPENFILENAMEW>() as u32,
+        hwndOwner: hwnd,
+        lpstrFilter: PCWSTR(filter.as_ptr()),
+        lpstrFile: PWSTR(file.as_mut_ptr()),
+        nMaxFile: PATH_BUF_LEN as u32,
+        lpstrDefExt: w!("txt"),
+        Flags: flags,
+        ..Default::default()
+    };
+
+    let ok = unsafe {
+        if save {
+            GetSaveFileNameW(&raw mut ofn)
+        } else {
+            GetOpenFileNameW(&raw mut ofn)
+        }
+    };
+    if !ok.as_bool() {
+        return None;
+    }
+
+    let len = file.iter().position(|&c| c == 0).unwrap_or(file.len());
+    Some(PathBuf::from(std::ffi::OsString::from_wide(&file[..len])))
+}
@@ -0,0 +1,159 @@
+//! Foreground window changes: layout memory per application or window.
+
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+use windows::Win32::{
+    Foundation::{CloseHandle, HWND, LPARAM, WPARAM},
+    System::Threading::{
+        OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+        QueryFullProcessImageNameW,
+    },
+    UI::{
+        Accessibility::{HWINEVENTHOOK, SetWinEventHook},
+        WindowsAndMessaging::{
+            EVENT_SYSTEM_FOREGROUND, GetForegroundWindow, GetWindowThreadProcessId, OBJID_WINDOW,
+            PostMessageW, WINEVENT_OUTOFCONTEXT, WINEVENT_SKIPOWNPROCESS,
+        },
+    },
+};
+
+use crate::{
+    app::AppState,
+    domain::text::{
+        activate_layout,
+        convert::{current_layout_for_window, post_layout_change},
+        layout_memory::{FocusTarget, Restore},
+        layout_policy::LayoutTarget,
+    },
+    platform::ui::error_notifier::WM_APP_FOREGROUND_CHANGED,
+};
+
+static HOOK_HANDLE: AtomicIsize = AtomicIsize::new(0);
+static MAIN_HWND: AtomicIsize = AtomicIsize::new(0);
+/// The foreground window of the last event, the window a change moves away from.
+static LAST_FOREGROUND: AtomicIsize = AtomicIsize::new(0);
+
+/// Reports every foreground change of another process to `hwnd` as
+/// `WM_APP_FOREGROUND_CHANGED`, so layouts are restored as soon as a window gets the focus.
+///
+/// The callback runs on the calling thread, which must pump messages.
+pub(crate) fn install(hwnd: HWND) {
+    MAIN_HWND.store(hwnd.0 as isize, Ordering::Relaxed);
+
+    if HOOK_HANDLE.load(Ordering::Relaxed) != 0 {
+        return;
+    }
+
+    let foreground = unsafe { GetForegroundWindow() };
+    LAST_FOREGROUND.store(foreground.0 as isize, Ordering::Relaxed);
+
+    let hook = unsafe {
+        SetWinEventHook(
+            EVENT_SYSTEM_FOREGROUND,
+            EVENT_SYSTEM_FOREGROUND,
+            None,
+            Some(on_foreground_event),
+            0,
+            0,
+            WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+        )
+    };
+    if hook.is_invalid() {
+        tracing::warn!("EVENT_SYSTEM_FOREGROUND hook not installed");
+        return;
+    }
+    HOOK_HANDLE.store(hook.0 as isize, Ordering::Relaxed);
+}
+
+unsafe extern "system" fn on_foreground_event(
+    _hook: HWINEVENTHOOK,
+    event: u32,
+    hwnd: HWND,
+    id_object: i32,
+    _id_child: i32,
+    _thread: u32,
+    _time: u32,
+) {
+    if event != EVENT_SYSTEM_FOREGROUND || id_object != OBJID_WINDOW.0 || hwnd.is_invalid() {
+        return;
+    }
+
+    let to = hwnd.0 as isize;
+    let from = LAST_FOREGROUND.swap(to, Ordering::Relaxed);
+    let main = MAIN_HWND.load(Ordering::Relaxed);
+    if from == to || main == 0 {
+        return;
+    }
+
+    if let Err(e) = unsafe {
+        PostMessageW(
+            Some(HWND(main as *mut _)),
+            WM_APP_FOREGROUND_CHANGED,
+            WPARAM(from.cast_unsigned()),
+            LPARAM(to),
+        )
+    } {
+        tracing::warn!(error = ?e, "foreground change not posted");
+    }
+}
+
+/// File name of the executable owning `hwnd`, e.g. `code.exe`.
+///
+/// `None` when the window is gone or its process cannot be queried.
+pub(crate) fn app_name(hwnd: HWND) -> Option<String> {
+    let mut pid = 0u32;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&raw mut pid)) };
+    if pid == 0 {
+        return None;
+    }
+
+    let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+    let mut path = [0u16; 1024];
+    let mut len = path.len() as u32;
+    let queried = unsafe {
+        QueryFullProcessImageNameW(
+            process,
+            PROCESS_NAME_WIN32,
+            windows::core::PWSTR(path.as_mut_ptr()),
+            &raw mut len,
+        )
+    };
+    let _ = unsafe { CloseHandle(process) };
+    queried.ok()?;
+
+    let path = String::from_utf16_lossy(&path[..len as usize]);
+    path.rsplit(['\\', '/']).next().map(str::to_string)
+}
+
+fn focus_target(hwnd: HWND) -> Option<FocusTarget> {
+    Some(FocusTarget {
+        window: hwnd.0 as isize,
+        app: app_name(hwnd)?,
+    })
+}
+
+/// Remembers the layout of the window `from` and restores the one `to` should get,
+/// see `LayoutMemory::focus_changed`.
+pub(crate) fn on_foreground_changed(state: &mut AppState, from: HWND, to: HWND) {
+    let Some(entered) = focus_target(to) else {
+        return;
+    };
+    let left = focus_target(from).map(|target| (target, current_layout_for_window(from)));
+
+    let restore = state
+        .layout_memory
+        .focus_changed(left.as_ref().map(|(t, layout)| (t, *layout)), &entered);
+
+    let result = match restore {
+        Some(Restore::Layout(hkl)) if current_layout_for_window(to) != hkl => {
+            post_layout_change(to, hkl)
+        }
+        Some(Restore::Tag(tag)) => {
+            activate_layout(LayoutTarget::Tag(&tag), &mut state.recent_layouts)
+        }
+        _ => Ok(()),
+    };
+    if let Err(e) = result {
+        tracing::warn!(error = ?e, app = entered.app, "layout restore failed");
+    }
+}
@@ -5,6 +5,7 @@ mod keydown;
 mod keyup;
 pub(crate) mod mods;
 pub(crate) mod sequence;
+mod system;
 pub(crate) mod vk;
 
 use std::sync::atomic::{AtomicIsize, Ordering};
@@ -13,15 +14,29 @@ use windows::Win32::{
     Foundation::{HWND, LPARAM, LRESULT, WPARAM},
     System::SystemInformation::GetTickCount64,
     UI::WindowsAndMessaging::{
-        CallNextHookEx, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT, PostMessageW, SetWindowsHookExW,
+        CallNextHookEx, HC_ACTION, HHOOK, KBDLLHOOKSTRUCT, LLKHF_INJECTED, SetWindowsHookExW,
         WH_KEYBOARD_LL,
     },
 };
 
-use self::vk::{is_keydown_msg, is_keyup_msg, mod_bit_for_vk, normalize_vk};
+use self::vk::{is_keydown_msg, is_keyup_msg, normalize_vk};
 use crate::{
-    input,
-    platform::win::keyboard::{keydown::handle_keydown, keyup::handle_keyup},
+    app::RuntimeHotkeys,
+    input::{
+        key_event::{KeyEvent, KeyEventKind},
+        pipeline::{HookDecision, process_event},
+        ring_buffer::with_journal,
+    },
+    platform::win::{
+        app_rules,
+        keyboard::{
+            keydown::handle_keydown_capture,
+            keyup::handle_keyup_capture,
+            mods::{update_mods_down, with_mods_down},
+            system::{MessageSink, SystemKeyboard, replay_key},
+        },
+        with_state_mut,
+    },
 };
 
 static HOOK_HANDLE: AtomicIsize = AtomicIsize::new(0);
@@ -40,18 +55,6 @@ fn main_hwnd() -> Option<HWND> {
     }
 }
 
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
-enum HookDecision {
-    Pass,
-    Swallow,
-}
-
-impl HookDecision {
-    fn should_swallow(self) -> bool {
-        matches!(self, Self::Swallow)
-    }
-}
-
 fn report_hook_error(hwnd: HWND, state: &mut crate::app::AppState, e: &windows::core::Error) {
     crate::platform::ui::error_notifier::push(
         hwnd,
@@ -62,6 +65,47 @@ fn report_hook_error(hwnd: HWND, state: &mut crate::app::AppState, e: &windows::
     );
 }
 
+/// Records the event as a hotkey while the settings window captures one, else runs
+/// it through the input pipeline.
+fn handle_event(event: &KeyEvent) -> windows::core::Result<HookDecision> {
+    let hwnd = main_hwnd();
+    let mut sink = MessageSink { hwnd };
+    let mut run_pipeline = |hotkeys: &mut RuntimeHotkeys| {
+        with_mods_down(|mods| {
+            with_journal(|journal| {
+                process_event(hotkeys, mods, journal, event, &SystemKeyboard, &mut sink)
+            })
+        })
+    };
+
+    let handled = hwnd.and_then(|hwnd| {
+        with_state_mut(hwnd, |state| {
+            if state.hotkey_capture.active {
+                let mods = update_mods_down(event);
+                return match event.kind {
+                    KeyEventKind::Down => handle_keydown_capture(
+                        state,
+                        mods.chord(event.vk),
+                        event.is_mod(),
+                        event.time_ms,
+                    ),
+                    KeyEventKind::Up => handle_keyup_capture(state, event.is_mod(), event.time_ms),
+                };
+            }
+
+            if event.kind == KeyEventKind::Down {
+                // Limits the hotkeys to the ones the foreground application's rule enables.
+                app_rules::refresh(state);
+            }
+            run_pipeline(&mut state.runtime_hotkeys)
+        })
+    });
+
+    // Without the app state no hotkey matches, but the modifiers and the journal are
+    // still kept up to date.
+    handled.unwrap_or_else(|| run_pipeline(&mut RuntimeHotkeys::default()))
+}
+
 extern "system" fn proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
     if code != HC_ACTION.cast_signed() {
         let h = HOOK_HANDLE.load(Ordering::Relaxed);
@@ -77,40 +121,34 @@ extern "system" fn proc(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
     };
 
     let kb = unsafe { &*(lparam.0 as *const KBDLLHOOKSTRUCT) };
-    let vk = normalize_vk(kb);
-    let is_mod = mod_bit_for_vk(vk).is_some();
-
-    let is_keydown = is_keydown_msg(msg);
-    let is_keyup = is_keyup_msg(msg);
 
-    let decision = if is_keydown {
-        handle_keydown(vk, is_mod)
-    } else if is_keyup {
-        handle_keyup(vk, is_mod)
+    let kind = if is_keydown_msg(msg) {
+        KeyEventKind::Down
+    } else if is_keyup_msg(msg) {
+        KeyEventKind::Up
     } else {
-        Ok(HookDecision::Pass)
+        return unsafe { CallNextHookEx(hook, code, wparam, lparam) };
     };
 
-    if is_keydown && matches!(decision.as_ref(), Ok(HookDecision::Pass)) {
-        let typed = input::ring_buffer::record_keydown(kb, vk);
-
-        if typed.is_some()
-            && crate::input::ring_buffer::last_char_triggers_autoconvert()
-            && let Some(hwnd) = main_hwnd()
-        {
-            let _ = unsafe {
-                PostMessageW(
-                    Some(hwnd),
-                    crate::platform::ui::error_notifier::WM_APP_AUTOCONVERT,
-                    WPARAM(0),
-                    LPARAM(0),
-                )
-            };
-        }
-    }
-
-    match decision {
-        Ok(d) if d.should_swallow() && !(is_mod && is_keyup) => return LRESULT(1),
+    let event = KeyEvent {
+        vk: normalize_vk(kb),
+        scan_code: kb.scanCode,
+        kind,
+        injected: kb.flags.contains(LLKHF_INJECTED),
+        time_ms: now_tick_ms(),
+    };
+    match handle_event(&event) {
+        Ok(HookDecision::Replay { vk }) => match replay_key(vk, &event) {
+            Ok(()) => return LRESULT(1),
+            Err(e) => {
+                if let Some(hwnd) = main_hwnd() {
+                    super::with_state_mut_do(hwnd, |state| {
+                        report_hook_error(hwnd, state, &e);
+                    });
+                }
+            }
+        },
+        Ok(d) if d.should_swallow() => return LRESULT(1),
         Ok(_) => {}
         Err(e) => {
             if let Some(hwnd) = main_hwnd() {
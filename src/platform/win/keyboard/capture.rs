@@ -1,21 +1,17 @@
-use windows::Win32::Foundation::HWND;
-
-use crate::{config, platform::win::format_hotkey_sequence, utils::helpers};
-
-pub(crate) fn chord_to_hotkey(ch: config::HotkeyChord) -> config::Hotkey {
-    config::Hotkey {
-        vk: ch.vk.unwrap_or(0),
-        mods: ch.mods,
-    }
-}
-
+use crate::{
+    app::actions::ActionId, config, platform::win::format_hotkey_sequence, utils::helpers,
+};
+
+/// Appends a captured chord to the sequence being recorded.
+///
+/// Keeps the last `config::MAX_CAPTURED_STEPS` chords. A pause longer than
+/// `RESET_AFTER_MS` starts a new sequence.
 pub(crate) fn push_chord_capture(
-    existing: Option<config::HotkeySequence>,
+    existing: Option<&config::HotkeySequence>,
     chord: config::HotkeyChord,
     now_ms: u64,
     last_input_tick_ms: &mut u64,
 ) -> config::HotkeySequence {
-    const DEFAULT_GAP_MS: u32 = 1000;
     const RESET_AFTER_MS: u64 = 2000;
 
     let existing = match (*last_input_tick_ms, existing) {
@@ -24,24 +20,16 @@ pub(crate) fn push_chord_capture(
         (_, s) => s,
     };
 
-    let seq = match existing {
-        None => config::HotkeySequence {
-            first: chord,
-            second: None,
-            max_gap_ms: DEFAULT_GAP_MS,
-        },
-        Some(mut s) => match s.second {
-            None => {
-                s.second = Some(chord);
-                s
-            }
-            Some(prev_second) => {
-                s.first = prev_second;
-                s.second = Some(chord);
-                s
-            }
-        },
-    };
+    let mut seq = existing
+        .cloned()
+        .unwrap_or(config::HotkeySequence { steps: Vec::new() });
+    seq.steps.push(config::HotkeyStep {
+        chord,
+        max_gap_ms: config::DEFAULT_GAP_MS,
+    });
+    if seq.steps.len() > config::MAX_CAPTURED_STEPS {
+        let _ = seq.steps.remove(0);
+    }
 
     *last_input_tick_ms = now_ms;
     seq
@@ -49,25 +37,17 @@ pub(crate) fn push_chord_capture(
 
 pub(crate) fn store_captured_hotkey(
     state: &mut crate::app::AppState,
-    slot: crate::app::HotkeySlot,
-    chord: config::HotkeyChord,
+    action: ActionId,
     seq: config::HotkeySequence,
 ) -> windows::core::Result<()> {
-    state.hotkey_sequence_values.set(slot, Some(seq));
-    state.hotkey_values.set(slot, Some(chord_to_hotkey(chord)));
+    let text = format_hotkey_sequence(Some(&seq));
+
+    state.hotkey_bindings.set(action, Some(seq));
 
-    let text = format_hotkey_sequence(Some(seq));
-    let target = ui_hotkey_target(state, slot);
+    let Some(target) = state.hotkeys.get(action) else {
+        return Ok(());
+    };
 
     helpers::set_edit_text(target, &text)?;
     Ok(())
 }
-
-pub(crate) fn ui_hotkey_target(state: &crate::app::AppState, slot: crate::app::HotkeySlot) -> HWND {
-    match slot {
-        crate::app::HotkeySlot::LastWord => state.hotkeys.last_word,
-        crate::app::HotkeySlot::Pause => state.hotkeys.pause,
-        crate::app::HotkeySlot::Selection => state.hotkeys.selection,
-        crate::app::HotkeySlot::SwitchLayout => state.hotkeys.switch_layout,
-    }
-}
@@ -0,0 +1,183 @@
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, WPARAM},
+    UI::{
+        Input::KeyboardAndMouse::{
+            GetAsyncKeyState, GetKeyboardLayout, GetKeyboardState, INPUT, INPUT_0, INPUT_KEYBOARD,
+            KEYBD_EVENT_FLAGS, KEYBDINPUT, KEYEVENTF_KEYUP, SendInput, ToUnicodeEx, VIRTUAL_KEY,
+            VK_LSHIFT, VK_RSHIFT, VK_SHIFT,
+        },
+        WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId, PostMessageW},
+    },
+};
+
+use crate::{
+    input::{
+        hotkeys::hotkey_id,
+        key_event::{Action, ActionSink, KeyEvent, KeyEventKind, KeyboardState},
+    },
+    platform::{
+        ui::error_notifier::{WM_APP_AUTOCONVERT, WM_APP_AUTOCONVERT_REVERTED},
+        win::keyboard::sequence::post_hotkey,
+    },
+};
+
+/// Keyboard state of the interactive desktop.
+pub(crate) struct SystemKeyboard;
+
+impl KeyboardState for SystemKeyboard {
+    fn foreground_window(&self) -> isize {
+        unsafe { GetForegroundWindow() }.0 as isize
+    }
+
+    fn layout(&self) -> isize {
+        let fg = unsafe { GetForegroundWindow() };
+        if fg.0.is_null() {
+            return 0;
+        }
+        let tid = unsafe { GetWindowThreadProcessId(fg, None) };
+        unsafe { GetKeyboardLayout(tid) }.0 as isize
+    }
+
+    fn translate(&self, event: &KeyEvent) -> Option<String> {
+        let vk = VIRTUAL_KEY(u16::try_from(event.vk).ok()?);
+
+        let fg = unsafe { GetForegroundWindow() };
+        if fg.0.is_null() {
+            return None;
+        }
+
+        let tid = unsafe { GetWindowThreadProcessId(fg, None) };
+        let hkl = unsafe { GetKeyboardLayout(tid) };
+
+        let mut state = [0u8; 256];
+        if unsafe { GetKeyboardState(&mut state) }.is_err() {
+            return None;
+        }
+
+        let async_down = |vk: VIRTUAL_KEY| -> bool {
+            let v = unsafe { GetAsyncKeyState(i32::from(vk.0)) }.cast_unsigned();
+            (v & 0x8000) != 0
+        };
+
+        let apply_async_key = |state: &mut [u8; 256], vk: VIRTUAL_KEY| {
+            let idx = usize::from(vk.0);
+            if idx >= state.len() {
+                return;
+            }
+
+            if async_down(vk) {
+                state[idx] |= 0x80;
+            } else {
+                state[idx] &= !0x80;
+            }
+        };
+
+        apply_async_key(&mut state, VK_SHIFT);
+        apply_async_key(&mut state, VK_LSHIFT);
+        apply_async_key(&mut state, VK_RSHIFT);
+
+        let mut buf = [0u16; 8];
+        let rc = unsafe {
+            ToUnicodeEx(
+                u32::from(vk.0),
+                event.scan_code,
+                &state,
+                &mut buf,
+                0,
+                Some(hkl),
+            )
+        };
+
+        if rc == -1 {
+            let _ = unsafe {
+                ToUnicodeEx(
+                    u32::from(vk.0),
+                    event.scan_code,
+                    &state,
+                    &mut buf,
+                    0,
+                    Some(hkl),
+                )
+            };
+            return None;
+        }
+
+        if rc <= 0 {
+            return None;
+        }
+
+        let rc = usize::try_from(rc).ok()?;
+        let s = String::from_utf16_lossy(&buf[..rc]);
+
+        if s.chars().any(char::is_control) {
+            return None;
+        }
+
+        Some(s)
+    }
+}
+
+/// Delivers actions as messages to the main window. Without a window they are dropped.
+pub(crate) struct MessageSink {
+    pub hwnd: Option<HWND>,
+}
+
+impl ActionSink for MessageSink {
+    type Error = windows::core::Error;
+
+    fn emit(&mut self, action: Action) -> windows::core::Result<()> {
+        let Some(hwnd) = self.hwnd else {
+            return Ok(());
+        };
+
+        let (msg, wparam, lparam) = match action {
+            Action::Hotkey(action) => return post_hotkey(hwnd, hotkey_id(action)),
+            Action::Autoconvert => (WM_APP_AUTOCONVERT, WPARAM(0), LPARAM(0)),
+            Action::AutoconvertReverted => (WM_APP_AUTOCONVERT_REVERTED, WPARAM(0), LPARAM(0)),
+        };
+
+        unsafe { PostMessageW(Some(hwnd), msg, wparam, lparam) }
+    }
+}
+
+fn key_input(vk: u32, scan_code: u32, kind: KeyEventKind) -> Option<INPUT> {
+    Some(INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: VIRTUAL_KEY(u16::try_from(vk).ok()?),
+                wScan: u16::try_from(scan_code).unwrap_or(0),
+                dwFlags: match kind {
+                    KeyEventKind::Down => KEYBD_EVENT_FLAGS::default(),
+                    KeyEventKind::Up => KEYEVENTF_KEYUP,
+                },
+                time: 0,
+                dwExtraInfo: 0,
+            },
+        },
+    })
+}
+
+/// Injects a press of `vk` followed by `event`, see `HookDecision::Replay`.
+///
+/// Called from the hook, so the injected events come back to it marked as injected.
+pub(crate) fn replay_key(vk: u32, event: &KeyEvent) -> windows::core::Result<()> {
+    let inputs: Option<Vec<INPUT>> = [
+        key_input(vk, 0, KeyEventKind::Down),
+        key_input(event.vk, event.scan_code, event.kind),
+    ]
+    .into_iter()
+    .collect();
+    let (Some(inputs), Ok(size)) = (inputs, i32::try_from(std::mem::size_of::<INPUT>())) else {
+        return Err(windows::core::Error::from_hresult(
+            windows::Win32::Foundation::E_INVALIDARG,
+        ));
+    };
+
+    let sent = unsafe { SendInput(&inputs, size) };
+    if usize::try_from(sent).is_ok_and(|n| n == inputs.len()) {
+        Ok(())
+    } else {
+        Err(windows::core::Error::from_thread())
+    }
+}
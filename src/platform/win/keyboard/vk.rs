@@ -8,8 +8,6 @@ use windows::Win32::UI::{
     },
 };
 
-use crate::config;
-
 pub fn is_keydown_msg(msg: u32) -> bool {
     msg == WM_KEYDOWN || msg == WM_SYSKEYDOWN
 }
@@ -44,27 +42,3 @@ pub fn normalize_vk(kb: &KBDLLHOOKSTRUCT) -> u32 {
         _ => vk,
     }
 }
-
-pub fn mod_bit_for_vk(vk: u32) -> Option<u32> {
-    match vk {
-        0xA2 | 0xA3 => Some(windows::Win32::UI::Input::KeyboardAndMouse::MOD_CONTROL.0),
-        0xA0 | 0xA1 => Some(windows::Win32::UI::Input::KeyboardAndMouse::MOD_SHIFT.0),
-        0xA4 | 0xA5 => Some(windows::Win32::UI::Input::KeyboardAndMouse::MOD_ALT.0),
-        0x5B | 0x5C => Some(windows::Win32::UI::Input::KeyboardAndMouse::MOD_WIN.0),
-        _ => None,
-    }
-}
-
-pub fn mod_vk_bit_for_vk(vk: u32) -> Option<u32> {
-    match vk {
-        0xA2 => Some(config::MODVK_LCTRL),
-        0xA3 => Some(config::MODVK_RCTRL),
-        0xA0 => Some(config::MODVK_LSHIFT),
-        0xA1 => Some(config::MODVK_RSHIFT),
-        0xA4 => Some(config::MODVK_LALT),
-        0xA5 => Some(config::MODVK_RALT),
-        0x5B => Some(config::MODVK_LWIN),
-        0x5C => Some(config::MODVK_RWIN),
-        _ => None,
-    }
-}
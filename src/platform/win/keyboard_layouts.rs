@@ -0,0 +1,95 @@
+//! Windows backend for the keyboard layout model.
+//!
+//! Builds a `Layout` of an installed keyboard layout by asking `ToUnicodeEx` which character
+//! every character key produces at the base and the Shift level.
+
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock, PoisonError},
+};
+
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    HKL, MAPVK_VSC_TO_VK_EX, MapVirtualKeyExW, ToUnicodeEx, VK_SHIFT,
+};
+
+use crate::domain::text::layout::{self, Layout, ScanCode, ShiftLevel};
+
+/// `ToUnicodeEx` flag: do not change the keyboard state, so dead keys do not leak into
+/// the next real keystroke (Windows 10 1607 and later).
+const TU_NO_STATE_CHANGE: u32 = 0x4;
+
+const KEY_DOWN: u8 = 0x80;
+
+/// Returns the layout of `hkl`, enumerating it on first use.
+///
+/// Returns `None` when the layout produces no characters, for example for IME layouts.
+pub(crate) fn layout_for_hkl(hkl: HKL) -> Option<Arc<Layout>> {
+    type Cache = HashMap<usize, Option<Arc<Layout>>>;
+    static CACHE: OnceLock<Mutex<Cache>> = OnceLock::new();
+
+    let mut cache = CACHE
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+
+    cache
+        .entry(hkl.0 as usize)
+        .or_insert_with(|| enumerate_layout(hkl).map(Arc::new))
+        .clone()
+}
+
+fn enumerate_layout(hkl: HKL) -> Option<Layout> {
+    let mut keys = Vec::new();
+
+    for level in [ShiftLevel::Base, ShiftLevel::Shift] {
+        let mut key_state = [0u8; 256];
+        if level == ShiftLevel::Shift {
+            key_state[usize::from(VK_SHIFT.0)] = KEY_DOWN;
+        }
+
+        for key in layout::character_keys() {
+            if let Some(ch) = key_char(hkl, key, &key_state) {
+                keys.push((key, level, ch));
+            }
+        }
+    }
+
+    if keys.is_empty() {
+        tracing::warn!(hkl = ?hkl.0, "keyboard layout produced no characters");
+        return None;
+    }
+
+    tracing::trace!(hkl = ?hkl.0, keys = keys.len(), "keyboard layout enumerated");
+    Some(Layout::from_keys(
+        format!("hkl:{:08x}", hkl.0 as usize),
+        keys,
+    ))
+}
+
+/// Returns the single printable character produced by `key`, if any.
+///
+/// Dead keys and keys producing several UTF-16 units are skipped.
+fn key_char(hkl: HKL, key: ScanCode, key_state: &[u8; 256]) -> Option<char> {
+    let scan_code = u32::from(key.0);
+    let vk = unsafe { MapVirtualKeyExW(scan_code, MAPVK_VSC_TO_VK_EX, Some(hkl)) };
+    if vk == 0 {
+        return None;
+    }
+
+    let mut buf = [0u16; 4];
+    let n = unsafe {
+        ToUnicodeEx(
+            vk,
+            scan_code,
+            key_state,
+            &mut buf,
+            TU_NO_STATE_CHANGE,
+            Some(hkl),
+        )
+    };
+    if n != 1 {
+        return None;
+    }
+
+    char::from_u32(u32::from(buf[0])).filter(|ch| !ch.is_control())
+}
@@ -0,0 +1,115 @@
+//! Snippets group of the settings window.
+//!
+//! Every change is saved to `config::snippets_path` and activated right away,
+//! independent of Apply and Cancel, like the user dictionary.
+
+use std::io;
+
+use windows::Win32::Foundation::HWND;
+
+use super::{
+    dictionary::{fill_list, selected_index},
+    io_to_win,
+};
+use crate::{
+    app::AppState,
+    config,
+    domain::text::snippets::{Snippets, active_snippets, set_active_snippets},
+    platform::ui::error_notifier::{T_CONFIG, T_UI},
+    ui_call, ui_try,
+    utils::helpers,
+};
+
+/// Characters of the snippet text shown in the list.
+const PREVIEW_CHARS: usize = 40;
+
+/// Loads the saved snippets, activates them and fills the list.
+pub(crate) fn load(hwnd: HWND, state: &mut AppState) {
+    ui_try!(
+        hwnd,
+        state,
+        T_CONFIG,
+        "Failed to load the snippets",
+        config::load_snippets()
+            .map(set_active_snippets)
+            .map_err(io_to_win)
+    );
+    refresh_list(state);
+}
+
+/// Adds the snippet of the trigger and text edits, or changes the text of its trigger.
+pub(crate) fn handle_save(hwnd: HWND, state: &mut AppState) {
+    let trigger = helpers::get_edit_text(state.snippets.trigger);
+    let text = helpers::get_edit_text(state.snippets.text).replace("\r\n", "\n");
+
+    ui_call!(
+        hwnd,
+        state,
+        T_UI,
+        "Failed to save the snippet",
+        update(state, |snippets| snippets
+            .insert(&trigger, &text)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e)))
+    );
+}
+
+pub(crate) fn handle_remove(hwnd: HWND, state: &mut AppState) {
+    let trigger = helpers::get_edit_text(state.snippets.trigger);
+
+    ui_call!(
+        hwnd,
+        state,
+        T_UI,
+        "Failed to remove the snippet",
+        update(state, |snippets| {
+            snippets.remove(&trigger);
+            Ok(())
+        })
+    );
+
+    let _ = helpers::set_edit_text(state.snippets.trigger, "");
+    let _ = helpers::set_edit_text(state.snippets.text, "");
+}
+
+/// Copies the selected snippet into the trigger and text edits.
+pub(crate) fn handle_selection(state: &mut AppState) {
+    let snippets = active_snippets();
+    let Some((trigger, text)) =
+        selected_index(state.snippets.list).and_then(|i| snippets.entries().nth(i))
+    else {
+        return;
+    };
+
+    let _ = helpers::set_edit_text(state.snippets.trigger, trigger);
+    let _ = helpers::set_edit_text(state.snippets.text, &text.replace('\n', "\r\n"));
+}
+
+/// Applies `f` to a copy of the active snippets, then saves and activates them.
+fn update(
+    state: &AppState,
+    f: impl FnOnce(&mut Snippets) -> io::Result<()>,
+) -> windows::core::Result<()> {
+    let mut snippets = (*active_snippets()).clone();
+    f(&mut snippets).map_err(io_to_win)?;
+    config::save_snippets(&snippets).map_err(io_to_win)?;
+
+    tracing::info!(entries = snippets.len(), "snippets updated");
+    set_active_snippets(snippets);
+    refresh_list(state);
+    Ok(())
+}
+
+fn refresh_list(state: &AppState) {
+    let items: Vec<String> = active_snippets()
+        .entries()
+        .map(|(trigger, text)| {
+            let line = text.lines().next().unwrap_or_default();
+            let mut preview: String = line.chars().take(PREVIEW_CHARS).collect();
+            if preview.len() < text.len() {
+                preview.push('…');
+            }
+            format!("{trigger}  {preview}")
+        })
+        .collect();
+    fill_list(state.snippets.list, &items);
+}
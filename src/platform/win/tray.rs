@@ -19,9 +19,13 @@ use windows::{
     core::{PCWSTR, Result},
 };
 
+use crate::app::autoconvert_state::AutoconvertStatus;
+
 pub enum TrayMenuAction {
     None,
     ToggleAutoConvert,
+    PauseAutoConvert { minutes: u32 },
+    ToggleAppDisabled,
 }
 
 pub const WM_APP_TRAY: u32 = WM_APP + 3;
@@ -30,6 +34,15 @@ const ID_EXIT: u32 = 1001;
 const ID_SHOW_HIDE: u32 = 1002;
 const ID_AUTOCONVERT_TOGGLE: u32 = 1003;
 const ID_CHANGE_THEME: u32 = 1004;
+const ID_APP_DISABLED_TOGGLE: u32 = 1005;
+/// Menu ids of the pause lengths offered in the tray menu, in minutes.
+const PAUSE_ITEMS: [(u32, u32); 3] = [(1006, 5), (1007, 15), (1008, 60)];
+
+/// Application of the last foreground window and whether the app is disabled in it.
+pub struct TrayApp<'a> {
+    pub exe: &'a str,
+    pub disabled: bool,
+}
 
 unsafe fn show_popup_menu_at_cursor(hwnd: HWND, hmenu: HMENU) -> u32 {
     let mut pt = POINT { x: 0, y: 0 };
@@ -262,7 +275,8 @@ pub fn balloon_info(hwnd: HWND, title: &str, text: &str) -> windows::core::Resul
     balloon_common(hwnd, title, text, NIIF_INFO.0, "balloon_info: NIM_MODIFY")
 }
 
-pub fn switch_tray_icon(hwnd: HWND, use_green: bool) -> windows::core::Result<()> {
+/// Sets the tray icon, green for active, and the status line of its tooltip.
+pub fn switch_tray_icon(hwnd: HWND, use_green: bool, status: &str) -> windows::core::Result<()> {
     unsafe {
         let icon = if use_green {
             green_icon(hwnd)?
@@ -280,6 +294,7 @@ pub fn switch_tray_icon(hwnd: HWND, use_green: bool) -> windows::core::Result<()
         nid.uFlags = NIF_ICON | NIF_MESSAGE | NIF_TIP | NIF_SHOWTIP;
         nid.uCallbackMessage = WM_APP_TRAY;
         nid.hIcon = icon;
+        fill_wide(&mut nid.szTip, &format!("RustSwitcher\n{status}"));
 
         shell_notify(NIM_MODIFY, &nid, "switch_tray_icon")
     }
@@ -342,17 +357,18 @@ pub fn remove_icon(hwnd: HWND) {
 pub fn show_tray_context_menu(
     hwnd: HWND,
     window_visible: bool,
-    autoconvert_enabled: bool,
+    autoconvert: AutoconvertStatus,
     current_theme_dark: bool,
+    app: Option<TrayApp<'_>>,
 ) -> Result<TrayMenuAction> {
     unsafe {
-        let hmenu = build_tray_menu(window_visible, autoconvert_enabled, current_theme_dark)?;
+        let hmenu = build_tray_menu(window_visible, autoconvert, current_theme_dark, app)?;
         let cmd = show_popup_menu_at_cursor(hwnd, hmenu);
         let _ = DestroyMenu(hmenu);
         handle_tray_menu_cmd(
             hwnd,
             window_visible,
-            autoconvert_enabled,
+            autoconvert == AutoconvertStatus::On,
             current_theme_dark,
             cmd,
         )
@@ -361,12 +377,15 @@ pub fn show_tray_context_menu(
 
 fn build_tray_menu(
     window_visible: bool,
-    autoconvert_enabled: bool,
+    autoconvert: AutoconvertStatus,
     current_theme_dark: bool,
+    app: Option<TrayApp<'_>>,
 ) -> Result<HMENU> {
     let hmenu = unsafe { CreatePopupMenu() }?;
 
-    unsafe { append_autoconvert_toggle_item(hmenu, autoconvert_enabled) }?;
+    unsafe { append_autoconvert_toggle_item(hmenu, autoconvert) }?;
+    unsafe { append_pause_submenu(hmenu) }?;
+    unsafe { append_app_rule_submenu(hmenu, app) }?;
     unsafe { AppendMenuW(hmenu, MF_SEPARATOR, 0, PCWSTR::null()) }?;
 
     unsafe { append_show_hide_item(hmenu, window_visible) }?;
@@ -380,15 +399,21 @@ fn build_tray_menu(
     Ok(hmenu)
 }
 
-unsafe fn append_autoconvert_toggle_item(hmenu: HMENU, autoconvert_enabled: bool) -> Result<()> {
+unsafe fn append_autoconvert_toggle_item(
+    hmenu: HMENU,
+    autoconvert: AutoconvertStatus,
+) -> Result<()> {
     use windows::Win32::UI::WindowsAndMessaging::{
         AppendMenuW, MF_CHECKED, MF_STRING, MF_UNCHECKED,
     };
 
-    let text = "AutoConvert\0";
+    let text = match autoconvert {
+        AutoconvertStatus::Paused { .. } => format!("AutoConvert ({})\0", autoconvert.describe()),
+        _ => "AutoConvert\0".to_string(),
+    };
     let wide: Vec<u16> = text.encode_utf16().collect();
 
-    let check = if autoconvert_enabled {
+    let check = if autoconvert == AutoconvertStatus::On {
         MF_CHECKED
     } else {
         MF_UNCHECKED
@@ -406,6 +431,75 @@ unsafe fn append_autoconvert_toggle_item(hmenu: HMENU, autoconvert_enabled: bool
     Ok(())
 }
 
+/// "Pause for" submenu with the pause lengths. The submenu is destroyed with `hmenu`.
+unsafe fn append_pause_submenu(hmenu: HMENU) -> Result<()> {
+    use windows::Win32::UI::WindowsAndMessaging::{AppendMenuW, MF_POPUP, MF_STRING};
+
+    let submenu = unsafe { CreatePopupMenu() }?;
+
+    for (id, minutes) in PAUSE_ITEMS {
+        let text = if minutes % 60 == 0 {
+            format!("{} h\0", minutes / 60)
+        } else {
+            format!("{minutes} min\0")
+        };
+        let wide: Vec<u16> = text.encode_utf16().collect();
+        (unsafe { AppendMenuW(submenu, MF_STRING, id as usize, PCWSTR(wide.as_ptr())) })?;
+    }
+
+    let title: Vec<u16> = "Pause AutoConvert for\0".encode_utf16().collect();
+    (unsafe {
+        AppendMenuW(
+            hmenu,
+            MF_STRING | MF_POPUP,
+            submenu.0 as usize,
+            PCWSTR(title.as_ptr()),
+        )
+    })?;
+
+    Ok(())
+}
+
+/// "This application" submenu with the item disabling the app in the last foreground
+/// application. The submenu is destroyed with `hmenu`.
+unsafe fn append_app_rule_submenu(hmenu: HMENU, app: Option<TrayApp<'_>>) -> Result<()> {
+    use windows::Win32::UI::WindowsAndMessaging::{
+        AppendMenuW, MF_CHECKED, MF_GRAYED, MF_POPUP, MF_STRING, MF_UNCHECKED,
+    };
+
+    let submenu = unsafe { CreatePopupMenu() }?;
+
+    let (text, flags, id) = match app {
+        Some(app) => {
+            let check = if app.disabled {
+                MF_CHECKED
+            } else {
+                MF_UNCHECKED
+            };
+            (
+                format!("Disable for {}\0", app.exe),
+                MF_STRING | check,
+                ID_APP_DISABLED_TOGGLE,
+            )
+        }
+        None => ("No application yet\0".to_string(), MF_STRING | MF_GRAYED, 0),
+    };
+    let wide: Vec<u16> = text.encode_utf16().collect();
+    (unsafe { AppendMenuW(submenu, flags, id as usize, PCWSTR(wide.as_ptr())) })?;
+
+    let title: Vec<u16> = "This application\0".encode_utf16().collect();
+    (unsafe {
+        AppendMenuW(
+            hmenu,
+            MF_STRING | MF_POPUP,
+            submenu.0 as usize,
+            PCWSTR(title.as_ptr()),
+        )
+    })?;
+
+    Ok(())
+}
+
 unsafe fn append_change_theme_item(hmenu: HMENU, current_theme_dark: bool) -> Result<()> {
     use windows::Win32::UI::WindowsAndMessaging::{AppendMenuW, MF_STRING};
 
@@ -467,6 +561,7 @@ unsafe fn handle_tray_menu_cmd(
     crate::utils::helpers::debug_log(&format!("cmd={cmd}"));
     match cmd {
         ID_AUTOCONVERT_TOGGLE => Ok(TrayMenuAction::ToggleAutoConvert),
+        ID_APP_DISABLED_TOGGLE => Ok(TrayMenuAction::ToggleAppDisabled),
 
         ID_SHOW_HIDE => {
             unsafe { toggle_window_visibility(hwnd, window_visible) };
@@ -483,6 +578,11 @@ unsafe fn handle_tray_menu_cmd(
             Ok(TrayMenuAction::None)
         }
 
-        _ => Ok(TrayMenuAction::None),
+        _ => Ok(PAUSE_ITEMS
+            .iter()
+            .find(|&&(id, _)| id == cmd)
+            .map_or(TrayMenuAction::None, |&(_, minutes)| {
+                TrayMenuAction::PauseAutoConvert { minutes }
+            })),
     }
 }
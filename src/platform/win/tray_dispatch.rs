@@ -184,10 +184,7 @@ pub fn handle_tray_message(hwnd: HWND, wparam: WPARAM, lparam: LPARAM) -> LRESUL
             set_suppress_next_left_click(true);
             let _ = unsafe { KillTimer(Some(hwnd), TRAY_SINGLE_CLICK_TIMER_ID) };
 
-            with_state_mut_do(hwnd, |state| {
-                let next = !state.autoconvert_enabled;
-                super::set_autoconvert_enabled_from_tray(hwnd, state, next, false);
-            });
+            with_state_mut_do(hwnd, |state| super::autoconvert::toggle(hwnd, state, false));
 
             LRESULT(0)
         }
@@ -200,17 +197,30 @@ pub fn handle_tray_message(hwnd: HWND, wparam: WPARAM, lparam: LPARAM) -> LRESUL
             let window_visible = unsafe { IsWindowVisible(hwnd).as_bool() };
 
             with_state_mut_do(hwnd, |state| {
+                let exe = super::app_rules::last_app(state).map(str::to_string);
+                let app = exe.as_deref().map(|exe| super::tray::TrayApp {
+                    exe,
+                    disabled: state.config.is_app_disabled(exe),
+                });
                 match super::tray::show_tray_context_menu(
                     hwnd,
                     window_visible,
-                    state.autoconvert_enabled,
+                    state.autoconvert.status(),
                     state.current_theme_dark,
+                    app,
                 ) {
                     Ok(action) => match action {
                         super::tray::TrayMenuAction::None => {}
                         super::tray::TrayMenuAction::ToggleAutoConvert => {
-                            let next = !state.autoconvert_enabled;
-                            super::set_autoconvert_enabled_from_tray(hwnd, state, next, false);
+                            super::autoconvert::toggle(hwnd, state, false);
+                        }
+                        super::tray::TrayMenuAction::PauseAutoConvert { minutes } => {
+                            super::autoconvert::pause(hwnd, state, minutes, false);
+                        }
+                        super::tray::TrayMenuAction::ToggleAppDisabled => {
+                            if let Some(exe) = &exe {
+                                super::app_rules::toggle_app_disabled(hwnd, state, exe);
+                            }
                         }
                     },
                     Err(e) => tracing::warn!(error = ?e, "tray menu failed"),
@@ -0,0 +1,156 @@
+use crate::{
+    app::actions::{CONVERT_LAST_WORD, CONVERT_SELECTION, PAUSE},
+    config::{AppSettings, Config, ReplacementStrategy, WindowInfo},
+};
+
+/// Parses `rules` as the end of a config.
+fn parse(rules: &str) -> Config {
+    Config::parse(&format!("delay_ms = 100\n{rules}"))
+        .unwrap_or_else(|e| panic!("failed to parse config: {e}"))
+}
+
+fn window(exe: &str, class: &str, title: &str) -> WindowInfo {
+    WindowInfo {
+        exe: exe.to_string(),
+        class: class.to_string(),
+        title: title.to_string(),
+    }
+}
+
+const RULES: &str = r#"
+[[app_rules]]
+exe = "mstsc.exe"
+replacement = "clipboard"
+delay_ms = 250
+
+[[app_rules]]
+class = "ConsoleWindowClass"
+title = "(?i)ssh"
+autoconvert = false
+actions = ["convert_selection", "no_such_action"]
+
+[[app_rules]]
+exe = "WindowsTerminal.exe"
+autoconvert = false
+"#;
+
+#[test]
+fn windows_without_a_rule_get_the_defaults() {
+    let cfg = parse(RULES);
+    let settings = cfg.app_settings(&window("notepad.exe", "Notepad", "notes.txt"));
+    assert_eq!(settings, AppSettings::default());
+    assert!(settings.autoconvert);
+    assert!(settings.allows(PAUSE));
+}
+
+#[test]
+fn exe_is_matched_ignoring_case() {
+    let cfg = parse(RULES);
+    let settings = cfg.app_settings(&window("MSTSC.EXE", "TscShellContainerClass", "host"));
+    assert_eq!(settings.replacement, ReplacementStrategy::Clipboard);
+    assert_eq!(settings.delay_ms, Some(250));
+    assert!(settings.autoconvert);
+}
+
+#[test]
+fn every_criterion_of_a_rule_must_match() {
+    let cfg = parse(RULES);
+
+    let ssh = cfg.app_settings(&window("conhost.exe", "ConsoleWindowClass", "SSH: build"));
+    assert!(!ssh.autoconvert);
+
+    let local = cfg.app_settings(&window("conhost.exe", "ConsoleWindowClass", "cmd"));
+    assert!(local.autoconvert);
+}
+
+#[test]
+fn rule_actions_limit_hotkeys_and_unknown_ids_are_ignored() {
+    let cfg = parse(RULES);
+    let settings = cfg.app_settings(&window("conhost.exe", "ConsoleWindowClass", "ssh"));
+    assert_eq!(settings.actions, Some(vec![CONVERT_SELECTION]));
+    assert!(settings.allows(CONVERT_SELECTION));
+    assert!(!settings.allows(CONVERT_LAST_WORD));
+}
+
+#[test]
+fn first_matching_rule_wins() {
+    let cfg = parse(
+        r#"
+[[app_rules]]
+exe = "code.exe"
+delay_ms = 10
+
+[[app_rules]]
+title = "Code"
+delay_ms = 20
+"#,
+    );
+    let settings = cfg.app_settings(&window("code.exe", "Chrome_WidgetWin_1", "Code"));
+    assert_eq!(settings.delay_ms, Some(10));
+}
+
+#[test]
+fn rule_without_criteria_matches_nothing() {
+    let cfg = parse("[[app_rules]]\nautoconvert = false\n");
+    assert!(cfg.app_rule(&window("", "", "")).is_none());
+    assert!(cfg.app_rule(&window("a.exe", "A", "a")).is_none());
+}
+
+#[test]
+fn bad_title_regex_fails_the_whole_config() {
+    assert!(Config::parse("delay_ms = 100\n[[app_rules]]\ntitle = \"(unclosed\"\n").is_err());
+}
+
+#[test]
+fn rules_survive_a_round_trip() {
+    let cfg = parse(RULES);
+    let text = toml::to_string(&cfg).unwrap();
+    assert_eq!(Config::parse(&text).unwrap().app_rules, cfg.app_rules);
+}
+
+#[test]
+fn disabling_an_app_adds_a_rule_that_wins() {
+    let mut cfg = parse(RULES);
+    let terminal = window(
+        "windowsterminal.exe",
+        "CASCADIA_HOSTING_WINDOW_CLASS",
+        "pwsh",
+    );
+
+    cfg.set_app_disabled("notepad.exe", true);
+    assert!(cfg.is_app_disabled("notepad.exe"));
+    let settings = cfg.app_settings(&window("notepad.exe", "Notepad", ""));
+    assert!(!settings.autoconvert);
+    assert!(!settings.allows(PAUSE));
+
+    cfg.set_app_disabled("windowsterminal.exe", true);
+    assert_eq!(cfg.app_rules.len(), 4);
+    assert!(!cfg.app_settings(&terminal).allows(CONVERT_SELECTION));
+}
+
+#[test]
+fn enabling_an_app_keeps_its_other_settings() {
+    let mut cfg = parse(RULES);
+
+    cfg.set_app_disabled("mstsc.exe", true);
+    assert!(cfg.is_app_disabled("mstsc.exe"));
+    cfg.set_app_disabled("mstsc.exe", false);
+    assert!(!cfg.is_app_disabled("mstsc.exe"));
+
+    let settings = cfg.app_settings(&window("mstsc.exe", "", ""));
+    assert!(settings.autoconvert);
+    assert_eq!(settings.actions, None);
+    assert_eq!(settings.replacement, ReplacementStrategy::Clipboard);
+}
+
+#[test]
+fn enabling_an_app_removes_a_rule_left_empty() {
+    let mut cfg = Config::default();
+    cfg.set_app_disabled("notepad.exe", true);
+    assert_eq!(cfg.app_rules.len(), 1);
+    cfg.set_app_disabled("NOTEPAD.EXE", false);
+    assert!(cfg.app_rules.is_empty());
+
+    cfg.set_app_disabled("notepad.exe", false);
+    assert!(cfg.app_rules.is_empty());
+}
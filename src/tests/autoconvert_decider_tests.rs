@@ -0,0 +1,181 @@
+use lingua::Language;
+
+use crate::{
+    domain::text::{
+        autoconvert::{
+            Action, AutoconvertDecider, Decision, HeuristicDecider, LanguageScorer, SkipReason,
+            language_detector, pair_languages,
+        },
+        layout,
+        script::looks_like_latin_word,
+    },
+    tests::{RU_EN_LANGS, ru_en},
+};
+
+fn decide_ru_en(word: &str) -> (String, Decision) {
+    let decider = HeuristicDecider::new(language_detector(RU_EN_LANGS), RU_EN_LANGS);
+    let converted = ru_en(word);
+    let decision = decider.decide(word, &converted);
+    (converted, decision)
+}
+
+/// Scores every text the same, whatever the language.
+struct FlatScorer(f64);
+
+impl LanguageScorer for FlatScorer {
+    fn confidence(&self, _text: &str, _lang: Language) -> f64 {
+        self.0
+    }
+}
+
+#[test]
+fn autoconvert_does_not_touch_correct_russian_word() {
+    let (converted, decision) = decide_ru_en("привет");
+    assert_eq!(converted, "ghbdtn");
+    assert_eq!(decision.action, Action::Skip);
+    assert_eq!(decision.reason, Some(SkipReason::AlreadyCorrect));
+}
+
+#[test]
+fn autoconvert_converts_mistyped_russian_layout_word() {
+    let (converted, decision) = decide_ru_en("ghbdtn");
+    assert_eq!(converted, "привет");
+    assert!(decision.is_convert(), "{decision:?}");
+    assert_eq!(decision.reason, None);
+
+    let scores = decision.scores.unwrap();
+    assert!(scores.converted_cyrillic > scores.word_cyrillic);
+}
+
+#[test]
+fn autoconvert_does_not_touch_correct_english_ascii_word() {
+    let (converted, decision) = decide_ru_en("world");
+    assert_ne!(converted, "world");
+    assert_eq!(decision.reason, Some(SkipReason::AlreadyCorrect));
+}
+
+#[test]
+fn autoconvert_skips_too_short_words() {
+    let (converted, decision) = decide_ru_en("rfr");
+    assert_eq!(converted, "как");
+    assert_eq!(decision, Decision::skip(SkipReason::TooShort, None));
+}
+
+#[test]
+fn autoconvert_skips_mixed_or_nonword_tokens() {
+    // Starts with punctuation, fails the script check.
+    let (converted, decision) = decide_ru_en(";tklf");
+    assert_ne!(converted, ";tklf");
+    assert_eq!(
+        decision,
+        Decision::skip(SkipReason::ScriptCheckFailed, None)
+    );
+}
+
+#[test]
+fn autoconvert_skips_english_typo_like_hellp() {
+    let (_, decision) = decide_ru_en("hellp");
+    assert_eq!(decision.action, Action::Skip);
+}
+
+#[test]
+fn autoconvert_regression_reported_sequence_batch() {
+    // (token, expected_autoconvert)
+    let cases = [
+        ("ghbdtn", true),    // привет
+        ("hjyxnmyuj", true), // рончтьнго
+        ("gjyznyj", true),   // понятно
+        ("fdujlyj", true),   // авгодно, garbage that still reads as Russian
+        ("hellp", false),    // English-like typo
+        ("world", false),    // correct English word
+        ("привет", false),   // correct Russian word
+        ("ghbdtn,", true),   // trailing punctuation typed on the same key
+    ];
+
+    for (word, should_convert) in cases {
+        let (converted, decision) = decide_ru_en(word);
+        assert_eq!(
+            decision.is_convert(),
+            should_convert,
+            "{word} -> {converted}: {decision:?}"
+        );
+    }
+}
+
+/// Built-in pairs whose language model is a `lang-*` feature, and whether it is compiled in.
+const OPTIONAL_MODELS: [(&str, bool); 4] = [
+    ("uk-en", cfg!(feature = "lang-uk")),
+    ("be-en", cfg!(feature = "lang-be")),
+    ("kk-en", cfg!(feature = "lang-kk")),
+    ("de-ru", cfg!(feature = "lang-de")),
+];
+
+fn models_built_in(id: &str) -> bool {
+    OPTIONAL_MODELS
+        .iter()
+        .find(|(pair, _)| *pair == id)
+        .is_none_or(|&(_, built_in)| built_in)
+}
+
+#[test]
+fn pair_languages_resolve_for_builtin_pairs_with_their_models() {
+    for pair in layout::builtin_pairs() {
+        let built_in = models_built_in(pair.id());
+        assert_eq!(pair_languages(pair).is_some(), built_in, "{}", pair.id());
+        assert_eq!(
+            HeuristicDecider::for_pair(pair).is_some(),
+            built_in,
+            "{}",
+            pair.id()
+        );
+    }
+}
+
+#[cfg(feature = "lang-de")]
+#[test]
+fn pair_languages_take_the_latin_side_from_the_layout_tags() {
+    let de_ru = layout::builtin_pair("de-ru").unwrap();
+    let langs = pair_languages(&de_ru).unwrap();
+    assert_eq!(langs.latin, Language::German);
+    assert_eq!(langs.cyrillic, Language::Russian);
+}
+
+#[cfg(feature = "lang-uk")]
+#[test]
+fn autoconvert_handles_ukrainian_specific_letters() {
+    let pair = layout::builtin_pair("uk-en").unwrap();
+    let decider = HeuristicDecider::for_pair(&pair).unwrap();
+
+    // "привіт" as typed with the English layout, see mapping_invariants_tests.
+    let (word, converted) = ("ghbdsn", "привіт");
+    let decision = decider.decide(word, converted);
+    assert!(decision.is_convert(), "{word} -> {converted}: {decision:?}");
+
+    let decision = decider.decide(converted, word);
+    assert_eq!(
+        decision.action,
+        Action::Skip,
+        "must keep correct Ukrainian word"
+    );
+}
+
+#[test]
+fn latin_script_check_accepts_german_letters() {
+    assert!(looks_like_latin_word("straße"));
+    assert!(looks_like_latin_word("Grüße"));
+    assert!(!looks_like_latin_word("grüßе")); // Cyrillic 'е' at the end
+}
+
+#[test]
+fn heuristic_decider_uses_the_given_scorer() {
+    let converted = ru_en("ghbdtn");
+
+    let confident = HeuristicDecider::new(&FlatScorer(0.9), RU_EN_LANGS);
+    let decision = confident.decide("ghbdtn", &converted);
+    assert_eq!(decision.reason, Some(SkipReason::NotBetterEnough));
+    assert_eq!(decision.scores.unwrap().word_latin, 0.9);
+
+    let unsure = HeuristicDecider::new(&FlatScorer(0.1), RU_EN_LANGS);
+    let decision = unsure.decide("ghbdtn", &converted);
+    assert_eq!(decision.reason, Some(SkipReason::ConvertedConfidenceLow));
+}
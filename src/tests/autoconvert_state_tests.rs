@@ -0,0 +1,155 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    app::autoconvert_state::{AutoconvertState, AutoconvertStatus, Clock},
+    config::Config,
+};
+
+/// A clock that only moves when told to.
+#[derive(Clone)]
+struct FakeClock(Rc<Cell<Instant>>);
+
+impl FakeClock {
+    fn new() -> Self {
+        Self(Rc::new(Cell::new(Instant::now())))
+    }
+
+    fn advance(&self, by: Duration) {
+        self.0.set(self.0.get() + by);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.0.get()
+    }
+}
+
+fn minutes(n: u64) -> Duration {
+    Duration::from_secs(n * 60)
+}
+
+fn state() -> (AutoconvertState<FakeClock>, FakeClock) {
+    let clock = FakeClock::new();
+    (AutoconvertState::new(clock.clone()), clock)
+}
+
+#[test]
+fn starts_off() {
+    let (s, _) = state();
+    assert_eq!(s.status(), AutoconvertStatus::Off);
+    assert!(!s.is_enabled());
+    assert!(!s.is_active());
+}
+
+#[test]
+fn toggle_turns_on_and_off() {
+    let (mut s, _) = state();
+    assert!(s.toggle());
+    assert!(s.is_active());
+    assert!(s.is_enabled());
+
+    assert!(!s.toggle());
+    assert_eq!(s.status(), AutoconvertStatus::Off);
+    assert!(!s.is_enabled());
+}
+
+#[test]
+fn pause_counts_down_and_resumes() {
+    let (mut s, clock) = state();
+    s.set_enabled(true);
+    s.pause(minutes(15));
+
+    assert_eq!(
+        s.status(),
+        AutoconvertStatus::Paused {
+            remaining: minutes(15)
+        }
+    );
+    assert!(!s.is_active());
+    assert!(s.is_enabled(), "a pause keeps the saved choice");
+
+    clock.advance(minutes(10));
+    assert_eq!(
+        s.status(),
+        AutoconvertStatus::Paused {
+            remaining: minutes(5)
+        }
+    );
+    assert!(!s.resume_if_due());
+
+    clock.advance(minutes(5));
+    assert_eq!(s.status(), AutoconvertStatus::On);
+    assert!(s.resume_if_due());
+    assert!(!s.resume_if_due(), "a pause ends once");
+    assert!(s.is_active());
+}
+
+#[test]
+fn pause_while_off_turns_on_afterwards() {
+    let (mut s, clock) = state();
+    s.pause(minutes(5));
+    assert!(matches!(s.status(), AutoconvertStatus::Paused { .. }));
+
+    clock.advance(minutes(5));
+    assert!(s.resume_if_due());
+    assert_eq!(s.status(), AutoconvertStatus::On);
+}
+
+#[test]
+fn toggle_while_paused_resumes() {
+    let (mut s, _) = state();
+    s.pause(minutes(60));
+    assert!(s.toggle());
+    assert_eq!(s.status(), AutoconvertStatus::On);
+    assert!(!s.resume_if_due());
+}
+
+#[test]
+fn turning_off_ends_a_pause() {
+    let (mut s, clock) = state();
+    s.pause(minutes(5));
+    s.set_enabled(false);
+
+    clock.advance(minutes(5));
+    assert!(!s.resume_if_due());
+    assert_eq!(s.status(), AutoconvertStatus::Off);
+}
+
+#[test]
+fn describe_rounds_minutes_up() {
+    assert_eq!(AutoconvertStatus::On.describe(), "Autoconvert: on");
+    assert_eq!(AutoconvertStatus::Off.describe(), "Autoconvert: off");
+
+    let paused = |remaining| AutoconvertStatus::Paused { remaining };
+    assert_eq!(
+        paused(minutes(15)).describe(),
+        "Autoconvert: paused, 15 min left"
+    );
+    assert_eq!(
+        paused(minutes(14) + Duration::from_secs(1)).describe(),
+        "Autoconvert: paused, 15 min left"
+    );
+    assert_eq!(
+        paused(Duration::from_millis(300)).describe(),
+        "Autoconvert: paused, 1 min left"
+    );
+}
+
+#[test]
+fn config_keeps_the_choice_and_pause_length() {
+    let cfg = Config::parse("delay_ms = 100\n").unwrap();
+    assert!(!cfg.autoconvert_enabled);
+    assert_eq!(cfg.pause_minutes, 15);
+
+    let cfg =
+        Config::parse("delay_ms = 100\nautoconvert_enabled = true\npause_minutes = 5\n").unwrap();
+    let text = toml::to_string(&cfg).unwrap();
+    let cfg = Config::parse(&text).unwrap();
+    assert!(cfg.autoconvert_enabled);
+    assert_eq!(cfg.pause_minutes, 5);
+}
@@ -0,0 +1,89 @@
+use crate::{
+    config::Config,
+    domain::text::{
+        autoconvert::SkipReason,
+        case_fix::{self, CaseError, CaseFix, CaseFixSettings},
+    },
+};
+
+fn fixed(word: &str) -> CaseFix {
+    case_fix::detect(word, true).unwrap()
+}
+
+#[test]
+fn caps_lock_words_are_inverted() {
+    assert_eq!(
+        fixed("hELLO"),
+        CaseFix {
+            error: CaseError::CapsLock,
+            word: "Hello".to_string(),
+        }
+    );
+    assert_eq!(fixed("пРИВЕТ,").word, "Привет,");
+    assert_eq!(fixed("dON'T").word, "Don't");
+}
+
+#[test]
+fn caps_lock_errors_need_caps_lock_on() {
+    for word in ["iOS", "hELLO", "oK"] {
+        assert_eq!(
+            case_fix::detect(word, false),
+            Err(SkipReason::NoCaseError),
+            "{word}"
+        );
+    }
+    assert_eq!(case_fix::detect("HEllo", false).unwrap().word, "Hello");
+}
+
+#[test]
+fn two_capitals_lose_the_second_one() {
+    assert_eq!(
+        fixed("HEllo"),
+        CaseFix {
+            error: CaseError::TwoCapitals,
+            word: "Hello".to_string(),
+        }
+    );
+    assert_eq!(fixed("ПРивет").word, "Привет");
+    assert_eq!(fixed("\"THis\"").word, "\"This\"");
+}
+
+#[test]
+fn abbreviations_are_left_alone() {
+    assert_eq!(
+        case_fix::detect("PCs", true),
+        Err(SkipReason::CaseAbbreviation)
+    );
+    assert_eq!(
+        case_fix::detect("MHz", true),
+        Err(SkipReason::CaseAbbreviation)
+    );
+}
+
+#[test]
+fn words_without_case_errors_are_skipped() {
+    for word in ["hello", "Hello", "HELLO", "iPhone", "McDonald", "ЁЖИК"] {
+        assert_eq!(
+            case_fix::detect(word, true),
+            Err(SkipReason::NoCaseError),
+            "{word}"
+        );
+    }
+    assert_eq!(case_fix::detect("a", true), Err(SkipReason::TooShort));
+    assert_eq!(case_fix::detect("42,", true), Err(SkipReason::TooShort));
+}
+
+#[test]
+fn case_fix_is_on_by_default_and_can_be_turned_off() {
+    let cfg = Config::parse("delay_ms = 100\n").unwrap();
+    assert_eq!(cfg.case_fix, CaseFixSettings::default());
+    assert!(cfg.case_fix.enabled);
+    assert!(cfg.case_fix.turn_off_caps_lock);
+
+    let cfg = Config::parse("delay_ms = 100\n[case_fix]\nturn_off_caps_lock = false\n").unwrap();
+    assert!(cfg.case_fix.enabled);
+    assert!(!cfg.case_fix.turn_off_caps_lock);
+
+    let text = toml::to_string(&cfg).unwrap();
+    assert_eq!(Config::parse(&text).unwrap().case_fix, cfg.case_fix);
+}
@@ -0,0 +1,244 @@
+use crate::{
+    app::{
+        HotkeyBindings,
+        actions::{
+            self, ACTIONS, ActionId, CONVERT_LAST_WORD, CONVERT_SELECTION, PAUSE, SWITCH_LAYOUT,
+            TOGGLE_RECENT_LAYOUTS, UNDO_LAST_CONVERSION,
+        },
+    },
+    config::{Config, HotkeySequence},
+};
+
+fn parse(text: &str) -> Config {
+    Config::parse(text).unwrap_or_else(|e| panic!("failed to parse config: {e}"))
+}
+
+fn seq(text: &str) -> HotkeySequence {
+    text.parse().unwrap()
+}
+
+fn binding(cfg: &Config, id: ActionId) -> Option<String> {
+    cfg.hotkey(id).map(ToString::to_string)
+}
+
+#[test]
+fn registry_ids_are_unique_and_defaults_parse() {
+    for (i, action) in ACTIONS.iter().enumerate() {
+        assert!(
+            ACTIONS[i + 1..].iter().all(|other| other.id != action.id),
+            "{} is registered twice",
+            action.id.as_str()
+        );
+        assert_eq!(ActionId::from_key(action.id.as_str()), Some(action.id));
+        assert!(
+            action.default_binding.is_empty() || action.default_sequence().is_some(),
+            "default of {} does not parse",
+            action.id.as_str()
+        );
+    }
+    assert_eq!(ActionId::from_key("nope"), None);
+}
+
+#[test]
+fn default_config_binds_every_action() {
+    let cfg = Config::default();
+    for action in &ACTIONS {
+        assert_eq!(cfg.hotkey(action.id), action.default_sequence().as_ref());
+    }
+}
+
+#[test]
+fn hotkeys_are_written_by_action_id() {
+    let mut cfg = Config::default();
+    cfg.set_hotkey(PAUSE, Some(seq("Ctrl+Alt+P")));
+    cfg.set_hotkey(UNDO_LAST_CONVERSION, None);
+
+    let text = toml::to_string(&cfg).unwrap();
+    assert!(text.contains("[hotkeys]"), "{text}");
+    assert!(
+        text.contains(r#"autoconvert_pause = "Ctrl+Alt+P""#),
+        "{text}"
+    );
+    assert!(text.contains(r#"undo_last_conversion = """#), "{text}");
+
+    let loaded = parse(&text);
+    assert_eq!(loaded.hotkeys, cfg.hotkeys);
+    assert_eq!(loaded.hotkey(UNDO_LAST_CONVERSION), None);
+}
+
+#[test]
+fn missing_actions_get_their_default_and_unknown_ones_are_kept() {
+    let cfg = parse(
+        r#"
+delay_ms = 100
+
+[hotkeys]
+autoconvert_pause = "Ctrl+Alt+P"
+from_a_newer_version = "Ctrl+Alt+N"
+"#,
+    );
+
+    assert_eq!(binding(&cfg, PAUSE).as_deref(), Some("Ctrl+Alt+P"));
+    assert_eq!(
+        binding(&cfg, SWITCH_LAYOUT).as_deref(),
+        Some("CapsLock (tap 300ms)")
+    );
+    assert!(cfg.hotkeys.contains_key("from_a_newer_version"));
+
+    let bound_by_default = ACTIONS
+        .iter()
+        .filter(|a| a.default_sequence().is_some())
+        .count();
+    let bindings = HotkeyBindings::from_config(&cfg);
+    assert_eq!(bindings.iter().count(), bound_by_default);
+}
+
+#[test]
+fn layout_activations_are_bound_by_language_tag() {
+    let cfg = parse(
+        r#"
+delay_ms = 100
+
+[hotkeys]
+activate_layout_en-US = "Ctrl+Alt+1"
+activate_layout_ru-RU = "Ctrl+Alt+2"
+activate_layout_de-DE = ""
+toggle_recent_layouts = "Ctrl+Alt+0"
+"#,
+    );
+
+    let en = ActionId::from_key("activate_layout_en-US").unwrap();
+    assert_eq!(en.layout_tag(), Some("en-US"));
+    assert_eq!(actions::name(en), "Activate layout en-US");
+    assert_eq!(binding(&cfg, en).as_deref(), Some("Ctrl+Alt+1"));
+    assert_eq!(
+        binding(&cfg, TOGGLE_RECENT_LAYOUTS).as_deref(),
+        Some("Ctrl+Alt+0")
+    );
+
+    let layouts: Vec<&str> = cfg
+        .actions()
+        .iter()
+        .filter_map(|id| id.layout_tag())
+        .collect();
+    assert_eq!(layouts, ["de-DE", "en-US", "ru-RU"]);
+
+    let bindings = HotkeyBindings::from_config(&cfg);
+    let ru = ActionId::from_key("activate_layout_ru-RU").unwrap();
+    assert_eq!(bindings.get(ru), Some(&seq("Ctrl+Alt+2")));
+    let de = ActionId::from_key("activate_layout_de-DE").unwrap();
+    assert_eq!(bindings.get(de), None);
+}
+
+#[test]
+fn layout_activation_keys_need_a_language_tag() {
+    for key in [
+        "activate_layout_",
+        "activate_layout_en--US",
+        "activate_layout_en_US",
+    ] {
+        assert_eq!(ActionId::from_key(key), None, "{key}");
+    }
+    assert_eq!(
+        ActionId::from_key("activate_layout_sr-Latn-RS").and_then(ActionId::layout_tag),
+        Some("sr-Latn-RS")
+    );
+}
+
+#[test]
+fn duplicate_layout_activation_is_reported() {
+    let cfg = parse(
+        r#"
+delay_ms = 100
+
+[hotkeys]
+undo_last_conversion = "RShift, RShift"
+activate_layout_en-US = "RShift, RShift"
+"#,
+    );
+    let issues = cfg.validate_hotkey_sequences().unwrap_err();
+    let en = ActionId::from_key("activate_layout_en-US").unwrap();
+    assert!(
+        issues
+            .iter()
+            .any(|i| i.actions.contains(&en) && i.actions.contains(&UNDO_LAST_CONVERSION)),
+        "{issues:?}"
+    );
+}
+
+#[test]
+fn legacy_sequence_fields_move_into_hotkeys() {
+    let cfg = parse(
+        r#"
+delay_ms = 100
+hotkey_convert_last_word_sequence = "LShift, LShift"
+
+[hotkey_pause_sequence]
+max_gap_ms = 700
+
+[hotkey_pause_sequence.first]
+mods = 2
+vk = 75
+
+[hotkey_switch_layout_sequence]
+[[hotkey_switch_layout_sequence.steps]]
+max_gap_ms = 1000
+chord = { mods = 0, vk = 20, press = { style = "tap", max_ms = 300 } }
+"#,
+    );
+
+    assert_eq!(
+        binding(&cfg, CONVERT_LAST_WORD).as_deref(),
+        Some("LShift, LShift")
+    );
+    assert_eq!(binding(&cfg, PAUSE).as_deref(), Some("Ctrl+K within 700ms"));
+    assert_eq!(
+        binding(&cfg, SWITCH_LAYOUT).as_deref(),
+        Some("CapsLock (tap 300ms)")
+    );
+    // Unbound in the old config, so not given a default.
+    assert_eq!(binding(&cfg, CONVERT_SELECTION), None);
+    assert_eq!(binding(&cfg, UNDO_LAST_CONVERSION), None);
+
+    let text = toml::to_string(&cfg).unwrap();
+    assert!(!text.contains("hotkey_"), "{text}");
+}
+
+#[test]
+fn legacy_single_chords_become_sequences() {
+    let cfg = parse(
+        r#"
+delay_ms = 100
+
+[hotkey_convert_selection]
+vk = 67
+mods = 3
+
+[hotkey_undo_last_conversion]
+vk = 90
+mods = 2
+
+[hotkey_undo_last_conversion_sequence]
+max_gap_ms = 1000
+first = { mods = 4, mods_vks = 8 }
+second = { mods = 4, mods_vks = 8 }
+"#,
+    );
+
+    assert_eq!(
+        binding(&cfg, CONVERT_SELECTION).as_deref(),
+        Some("Ctrl+Alt+C")
+    );
+    // The sequence wins over the single chord.
+    assert_eq!(
+        binding(&cfg, UNDO_LAST_CONVERSION).as_deref(),
+        Some("RShift, RShift")
+    );
+}
+
+#[test]
+fn a_bad_binding_fails_the_whole_config() {
+    assert!(
+        Config::parse("delay_ms = 100\n[hotkeys]\nautoconvert_pause = \"Ctrl+Nope\"\n").is_err()
+    );
+}
@@ -5,9 +5,15 @@ use std::{
     time::{SystemTime, UNIX_EPOCH},
 };
 
-use windows::Win32::UI::Input::KeyboardAndMouse::MOD_CONTROL;
-
-use crate::config::{self, Config, HotkeyChord, HotkeySequence};
+use crate::{
+    app::actions::{CONVERT_LAST_WORD, PAUSE, UNDO_LAST_CONVERSION},
+    config::{self, Config, HotkeyChord, HotkeySequence, PressStyle},
+    domain::text::{
+        learned_exceptions::LearnedExceptions,
+        user_dictionary::{DictionaryRule, UserDictionary},
+    },
+    input::key_event::MOD_CONTROL,
+};
 
 static ENV_LOCK: OnceLock<Mutex<()>> = OnceLock::new();
 
@@ -24,15 +30,15 @@ fn unique_temp_dir(prefix: &str) -> PathBuf {
 }
 
 fn seq_ctrl_a() -> HotkeySequence {
-    HotkeySequence {
-        first: HotkeyChord {
-            mods: MOD_CONTROL.0,
+    HotkeySequence::new(
+        &[HotkeyChord {
+            mods: MOD_CONTROL,
             mods_vks: 0,
             vk: Some(u32::from(b'A')),
-        },
-        second: None,
-        max_gap_ms: 1000,
-    }
+            press: PressStyle::Press,
+        }],
+        1000,
+    )
 }
 
 fn restore_appdata(old: Option<std::ffi::OsString>) {
@@ -51,15 +57,67 @@ fn config_save_and_load_roundtrip_via_appdata() {
     fs::create_dir_all(&dir).unwrap();
     unsafe { std::env::set_var("APPDATA", &dir) };
 
-    let cfg = Config {
-        hotkey_pause_sequence: Some(seq_ctrl_a()),
-        ..Default::default()
-    };
+    let mut cfg = Config::default();
+    cfg.set_hotkey(PAUSE, Some(seq_ctrl_a()));
+    cfg.set_hotkey(UNDO_LAST_CONVERSION, None);
 
     config::save(&cfg).unwrap();
     let loaded = config::load().unwrap();
 
-    assert_eq!(loaded.hotkey_pause_sequence, cfg.hotkey_pause_sequence);
+    assert_eq!(loaded.hotkey(PAUSE), Some(&seq_ctrl_a()));
+    assert_eq!(loaded.hotkey(UNDO_LAST_CONVERSION), None);
+    assert_eq!(loaded.hotkeys, cfg.hotkeys);
+
+    restore_appdata(old);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn config_load_migrates_first_second_sequences() {
+    let _g = lock_env();
+
+    let old = std::env::var_os("APPDATA");
+    let dir = unique_temp_dir("appdata-legacy");
+    fs::create_dir_all(&dir).unwrap();
+    unsafe { std::env::set_var("APPDATA", &dir) };
+
+    let path = config::config_path().unwrap();
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(
+        &path,
+        r#"
+delay_ms = 100
+
+[hotkey_pause_sequence]
+max_gap_ms = 700
+
+[hotkey_pause_sequence.first]
+mods = 2
+vk = 75
+
+[hotkey_pause_sequence.second]
+mods = 2
+mods_vks = 1
+vk = 82
+"#,
+    )
+    .unwrap();
+
+    let loaded = config::load().unwrap();
+    let seq = loaded.hotkey(PAUSE).cloned().unwrap();
+    assert_eq!(seq.len(), 2);
+    assert_eq!(seq.steps[0].chord.vk, Some(75));
+    assert_eq!(seq.steps[1].chord.mods_vks, 1);
+    assert_eq!(seq.steps[1].max_gap_ms, 700);
+
+    config::save(&loaded).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    assert!(
+        text.contains(r#"autoconvert_pause = "Ctrl+K, LCtrl+R within 700ms""#),
+        "{text}"
+    );
+    assert!(!text.contains("second"));
+    assert!(!text.contains("hotkey_pause_sequence"));
 
     restore_appdata(old);
     let _ = fs::remove_dir_all(dir);
@@ -74,15 +132,76 @@ fn config_save_rejects_invalid_sequences() {
     fs::create_dir_all(&dir).unwrap();
     unsafe { std::env::set_var("APPDATA", &dir) };
 
-    let cfg = Config {
-        hotkey_convert_last_word_sequence: Some(seq_ctrl_a()),
-        hotkey_pause_sequence: Some(seq_ctrl_a()),
-        ..Default::default()
-    };
+    let mut cfg = Config::default();
+    cfg.set_hotkey(CONVERT_LAST_WORD, Some(seq_ctrl_a()));
+    cfg.set_hotkey(PAUSE, Some(seq_ctrl_a()));
 
     let err = config::save(&cfg).unwrap_err();
     assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
-    assert!(err.to_string().contains("unique hotkey sequence"));
+    assert!(err.to_string().contains("use the same hotkey sequence"));
+
+    restore_appdata(old);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn user_dictionary_save_and_load_roundtrip_via_appdata() {
+    let _g = lock_env();
+
+    let old = std::env::var_os("APPDATA");
+    let dir = unique_temp_dir("appdata-dictionary");
+    fs::create_dir_all(&dir).unwrap();
+    unsafe { std::env::set_var("APPDATA", &dir) };
+
+    assert!(config::load_user_dictionary().unwrap().is_empty());
+
+    let mut dict = UserDictionary::new();
+    dict.insert("--verbose", DictionaryRule::NeverConvert)
+        .unwrap();
+    dict.insert("hjyxnmyuj", DictionaryRule::AlwaysConvert)
+        .unwrap();
+    config::save_user_dictionary(&dict).unwrap();
+
+    assert!(
+        config::dictionary_path()
+            .unwrap()
+            .ends_with("dictionary.txt")
+    );
+    assert_eq!(config::load_user_dictionary().unwrap(), dict);
+
+    fs::write(config::dictionary_path().unwrap(), "sometimes word\n").unwrap();
+    let err = config::load_user_dictionary().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    assert!(err.to_string().contains("line 1"));
+
+    restore_appdata(old);
+    let _ = fs::remove_dir_all(dir);
+}
+
+#[test]
+fn learned_exceptions_save_and_load_roundtrip_via_appdata() {
+    let _g = lock_env();
+
+    let old = std::env::var_os("APPDATA");
+    let dir = unique_temp_dir("appdata-learned");
+    fs::create_dir_all(&dir).unwrap();
+    unsafe { std::env::set_var("APPDATA", &dir) };
+
+    assert_eq!(
+        config::load_learned_exceptions().unwrap(),
+        LearnedExceptions::new()
+    );
+
+    let mut learned = LearnedExceptions::new();
+    learned.record_undo("ghbdtn", 1_700_000_000);
+    config::save_learned_exceptions(&learned).unwrap();
+
+    assert!(config::learned_path().unwrap().ends_with("learned.toml"));
+    assert_eq!(config::load_learned_exceptions().unwrap(), learned);
+
+    fs::write(config::learned_path().unwrap(), "words = 1\n").unwrap();
+    let err = config::load_learned_exceptions().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
 
     restore_appdata(old);
     let _ = fs::remove_dir_all(dir);
@@ -1,8 +1,10 @@
-use windows::Win32::UI::Input::KeyboardAndMouse::{MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN};
-
-use crate::config::{
-    Config, HotkeyChord, HotkeySequence,
-    constants::{CONVERT_LAST_WORD, CONVERT_SELECTION, PAUSE, SWITCH_LAYOUT},
+use crate::{
+    app::actions::{self, ActionId, CONVERT_LAST_WORD, CONVERT_SELECTION, PAUSE, SWITCH_LAYOUT},
+    config::{
+        Config, HotkeyChord, HotkeySequence, IssueKind, PressStyle, Severity, ValidationIssue,
+        describe_issues,
+    },
+    input::key_event::{MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN},
 };
 
 fn chord(mods: u32, mods_vks: u32, vk: u32) -> HotkeyChord {
@@ -10,39 +12,24 @@ fn chord(mods: u32, mods_vks: u32, vk: u32) -> HotkeyChord {
         mods,
         mods_vks,
         vk: Some(vk),
+        press: PressStyle::Press,
     }
 }
 
 fn seq1(mods: u32, vk: u32) -> HotkeySequence {
-    HotkeySequence {
-        first: chord(mods, 0, vk),
-        second: None,
-        max_gap_ms: 250,
-    }
+    HotkeySequence::new(&[chord(mods, 0, vk)], 250)
 }
 
 fn seq1_gap(mods: u32, vk: u32, max_gap_ms: u32) -> HotkeySequence {
-    HotkeySequence {
-        first: chord(mods, 0, vk),
-        second: None,
-        max_gap_ms,
-    }
+    HotkeySequence::new(&[chord(mods, 0, vk)], max_gap_ms)
 }
 
 fn seq1_modsvks(mods: u32, mods_vks: u32, vk: u32) -> HotkeySequence {
-    HotkeySequence {
-        first: chord(mods, mods_vks, vk),
-        second: None,
-        max_gap_ms: 250,
-    }
+    HotkeySequence::new(&[chord(mods, mods_vks, vk)], 250)
 }
 
 fn seq2(mods1: u32, vk1: u32, mods2: u32, vk2: u32, max_gap_ms: u32) -> HotkeySequence {
-    HotkeySequence {
-        first: chord(mods1, 0, vk1),
-        second: Some(chord(mods2, 0, vk2)),
-        max_gap_ms,
-    }
+    HotkeySequence::new(&[chord(mods1, 0, vk1), chord(mods2, 0, vk2)], max_gap_ms)
 }
 
 fn mk_cfg(
@@ -51,13 +38,12 @@ fn mk_cfg(
     selection: Option<HotkeySequence>,
     layout: Option<HotkeySequence>,
 ) -> Config {
-    Config {
-        hotkey_convert_last_word_sequence: last_word,
-        hotkey_pause_sequence: pause,
-        hotkey_convert_selection_sequence: selection,
-        hotkey_switch_layout_sequence: layout,
-        ..Default::default()
-    }
+    let mut cfg = Config::default();
+    cfg.set_hotkey(CONVERT_LAST_WORD, last_word);
+    cfg.set_hotkey(PAUSE, pause);
+    cfg.set_hotkey(CONVERT_SELECTION, selection);
+    cfg.set_hotkey(SWITCH_LAYOUT, layout);
+    cfg
 }
 
 fn assert_ok(cfg: Config) {
@@ -65,23 +51,20 @@ fn assert_ok(cfg: Config) {
     assert!(res.is_ok(), "expected Ok(()), got Err: {res:?}");
 }
 
-fn assert_err(cfg: Config) -> String {
+fn assert_err(cfg: Config) -> Vec<ValidationIssue> {
     match cfg.validate_hotkey_sequences() {
         Ok(()) => panic!("expected Err, got Ok(())"),
         Err(e) => e,
     }
 }
 
-fn assert_has_common_error_shape(err: &str) {
-    assert!(
-        err.starts_with("Duplicate hotkey sequences found:\n\n"),
-        "bad header: {err}"
-    );
-    assert!(
-        err.contains("\nEach action must have a unique hotkey sequence."),
-        "missing footer: {err}"
-    );
-    assert!(err.contains("• '"), "missing bullet formatting: {err}");
+/// Actions of the issues of `kind`, in report order.
+fn actions_of(issues: &[ValidationIssue], kind: IssueKind) -> Vec<Vec<ActionId>> {
+    issues
+        .iter()
+        .filter(|i| i.kind == kind)
+        .map(|i| i.actions.clone())
+        .collect()
 }
 
 #[test]
@@ -89,10 +72,15 @@ fn no_sequences_ok() {
     assert_ok(mk_cfg(None, None, None, None));
 }
 
+#[test]
+fn default_config_has_no_issues() {
+    assert_eq!(Config::default().hotkey_sequence_issues(), []);
+}
+
 #[test]
 fn only_one_sequence_ok() {
     assert_ok(mk_cfg(
-        Some(seq1(MOD_CONTROL.0, u32::from(b'A'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'A'))),
         None,
         None,
         None,
@@ -102,198 +90,215 @@ fn only_one_sequence_ok() {
 #[test]
 fn no_duplicates_ok() {
     assert_ok(mk_cfg(
-        Some(seq1(MOD_CONTROL.0, u32::from(b'A'))),
-        Some(seq1(MOD_ALT.0, u32::from(b'B'))),
-        Some(seq1(MOD_SHIFT.0, u32::from(b'C'))),
-        Some(seq1(MOD_WIN.0, u32::from(b'D'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'A'))),
+        Some(seq1(MOD_ALT, u32::from(b'B'))),
+        Some(seq1(MOD_SHIFT, u32::from(b'C'))),
+        Some(seq1(MOD_WIN, u32::from(b'D'))),
     ));
 }
 
 #[test]
 fn allowed_duplicate_last_word_and_selection_ok() {
-    let same = seq1(MOD_CONTROL.0, u32::from(b'X'));
-    assert_ok(mk_cfg(
+    let same = seq1(MOD_CONTROL, u32::from(b'X'));
+    let cfg = mk_cfg(
         Some(same),
-        Some(seq1(MOD_ALT.0, u32::from(b'B'))),
-        Some(seq1(MOD_CONTROL.0, u32::from(b'X'))),
-        Some(seq1(MOD_SHIFT.0, u32::from(b'C'))),
-    ));
+        Some(seq1(MOD_ALT, u32::from(b'B'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'X'))),
+        Some(seq1(MOD_SHIFT, u32::from(b'C'))),
+    );
+
+    assert_eq!(cfg.hotkey_sequence_issues(), []);
 }
 
 #[test]
 fn duplicate_pause_and_layout_err() {
-    let dup = seq1(MOD_ALT.0, u32::from(b'B'));
+    let dup = seq1(MOD_ALT, u32::from(b'B'));
 
-    let err = assert_err(mk_cfg(
-        Some(seq1(MOD_CONTROL.0, u32::from(b'A'))),
+    let issues = assert_err(mk_cfg(
+        Some(seq1(MOD_CONTROL, u32::from(b'A'))),
         Some(dup),
-        Some(seq1(MOD_SHIFT.0, u32::from(b'C'))),
-        Some(seq1(MOD_ALT.0, u32::from(b'B'))),
+        Some(seq1(MOD_SHIFT, u32::from(b'C'))),
+        Some(seq1(MOD_ALT, u32::from(b'B'))),
     ));
 
-    assert_has_common_error_shape(&err);
-    assert!(err.contains(PAUSE), "{err}");
-    assert!(err.contains(SWITCH_LAYOUT), "{err}");
-    assert!(
-        err.contains(&format!("• '{PAUSE}' and '{SWITCH_LAYOUT}'\n")),
-        "{err}"
+    assert_eq!(
+        actions_of(&issues, IssueKind::Duplicate),
+        [vec![PAUSE, SWITCH_LAYOUT]]
     );
+    assert_eq!(issues[0].severity, Severity::Error);
 }
 
 #[test]
 fn duplicate_last_word_and_pause_err() {
-    let dup = seq1(MOD_CONTROL.0, u32::from(b'A'));
+    let dup = seq1(MOD_CONTROL, u32::from(b'A'));
 
-    let err = assert_err(mk_cfg(
+    let issues = assert_err(mk_cfg(
         Some(dup),
-        Some(seq1(MOD_CONTROL.0, u32::from(b'A'))),
-        Some(seq1(MOD_SHIFT.0, u32::from(b'C'))),
-        Some(seq1(MOD_ALT.0, u32::from(b'B'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'A'))),
+        Some(seq1(MOD_SHIFT, u32::from(b'C'))),
+        Some(seq1(MOD_ALT, u32::from(b'B'))),
     ));
 
-    assert_has_common_error_shape(&err);
-    assert!(err.contains(CONVERT_LAST_WORD), "{err}");
-    assert!(err.contains(PAUSE), "{err}");
-    assert!(
-        err.contains(&format!("• '{CONVERT_LAST_WORD}' and '{PAUSE}'\n")),
-        "{err}"
+    assert_eq!(
+        actions_of(&issues, IssueKind::Duplicate),
+        [vec![CONVERT_LAST_WORD, PAUSE]]
     );
 }
 
 #[test]
 fn duplicate_selection_and_pause_err() {
-    let dup = seq1(MOD_CONTROL.0, u32::from(b'A'));
+    let dup = seq1(MOD_CONTROL, u32::from(b'A'));
 
-    let err = assert_err(mk_cfg(
-        Some(seq1(MOD_SHIFT.0, u32::from(b'C'))),
+    let issues = assert_err(mk_cfg(
+        Some(seq1(MOD_SHIFT, u32::from(b'C'))),
         Some(dup),
-        Some(seq1(MOD_CONTROL.0, u32::from(b'A'))),
-        Some(seq1(MOD_ALT.0, u32::from(b'B'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'A'))),
+        Some(seq1(MOD_ALT, u32::from(b'B'))),
     ));
 
-    assert_has_common_error_shape(&err);
-    assert!(err.contains(CONVERT_SELECTION), "{err}");
-    assert!(err.contains(PAUSE), "{err}");
-    assert!(
-        err.contains(&format!("• '{PAUSE}' and '{CONVERT_SELECTION}'\n")),
-        "{err}"
+    assert_eq!(
+        actions_of(&issues, IssueKind::Duplicate),
+        [vec![PAUSE, CONVERT_SELECTION]]
     );
 }
 
 #[test]
 fn allowed_duplicate_pair_but_third_action_same_still_err_lists_two_pairs() {
-    let same = seq1(MOD_CONTROL.0, u32::from(b'X'));
+    let same = seq1(MOD_CONTROL, u32::from(b'X'));
 
-    let err = assert_err(mk_cfg(
+    let issues = assert_err(mk_cfg(
         Some(same),
-        Some(seq1(MOD_CONTROL.0, u32::from(b'X'))),
-        Some(seq1(MOD_CONTROL.0, u32::from(b'X'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'X'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'X'))),
         None,
     ));
 
-    assert_has_common_error_shape(&err);
-
-    let expected_1 = format!("• '{CONVERT_LAST_WORD}' and '{PAUSE}'\n");
-    let expected_2 = format!("• '{PAUSE}' and '{CONVERT_SELECTION}'\n");
-
-    assert!(err.contains(&expected_1), "{err}");
-    assert!(err.contains(&expected_2), "{err}");
-
-    let forbidden = format!("• '{CONVERT_LAST_WORD}' and '{CONVERT_SELECTION}'\n");
-    assert!(!err.contains(&forbidden), "{err}");
+    assert_eq!(
+        actions_of(&issues, IssueKind::Duplicate),
+        [
+            vec![CONVERT_LAST_WORD, PAUSE],
+            vec![PAUSE, CONVERT_SELECTION],
+        ]
+    );
 }
 
 #[test]
 fn two_independent_duplicate_pairs_err_lists_both_in_stable_order() {
-    let a = seq1(MOD_CONTROL.0, u32::from(b'A'));
-    let b = seq1(MOD_ALT.0, u32::from(b'B'));
+    let a = seq1(MOD_CONTROL, u32::from(b'A'));
+    let b = seq1(MOD_ALT, u32::from(b'B'));
 
-    let err = assert_err(mk_cfg(
+    let issues = assert_err(mk_cfg(
         Some(a),
-        Some(seq1(MOD_CONTROL.0, u32::from(b'A'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'A'))),
         Some(b),
-        Some(seq1(MOD_ALT.0, u32::from(b'B'))),
+        Some(seq1(MOD_ALT, u32::from(b'B'))),
     ));
 
-    assert_has_common_error_shape(&err);
-
-    let expected = format!(
-        "Duplicate hotkey sequences found:\n\n• '{CONVERT_LAST_WORD}' and '{PAUSE}'\n• '{CONVERT_SELECTION}' and '{SWITCH_LAYOUT}'\n\nEach action must have a unique hotkey sequence."
+    assert_eq!(
+        actions_of(&issues, IssueKind::Duplicate),
+        [
+            vec![CONVERT_LAST_WORD, PAUSE],
+            vec![CONVERT_SELECTION, SWITCH_LAYOUT],
+        ]
     );
-
-    assert_eq!(err, expected);
+    assert_eq!(issues.len(), 2);
 }
 
 #[test]
 fn duplicates_across_non_adjacent_actions_err() {
-    let dup = seq1(MOD_SHIFT.0, u32::from(b'Z'));
+    let dup = seq1(MOD_SHIFT, u32::from(b'Z'));
 
-    let err = assert_err(mk_cfg(
+    let issues = assert_err(mk_cfg(
         Some(dup),
         None,
-        Some(seq1(MOD_CONTROL.0, u32::from(b'A'))),
-        Some(seq1(MOD_SHIFT.0, u32::from(b'Z'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'A'))),
+        Some(seq1(MOD_SHIFT, u32::from(b'Z'))),
     ));
 
-    assert_has_common_error_shape(&err);
-    assert!(
-        err.contains(&format!("• '{CONVERT_LAST_WORD}' and '{SWITCH_LAYOUT}'\n")),
-        "{err}"
+    assert_eq!(
+        actions_of(&issues, IssueKind::Duplicate),
+        [vec![CONVERT_LAST_WORD, SWITCH_LAYOUT]]
+    );
+}
+
+#[test]
+fn different_max_gap_is_ambiguous_not_duplicate() {
+    let s1 = seq1_gap(MOD_CONTROL, u32::from(b'K'), 200);
+    let s2 = seq1_gap(MOD_CONTROL, u32::from(b'K'), 400);
+    let cfg = mk_cfg(Some(s1), Some(s2), None, None);
+
+    let issues = cfg.hotkey_sequence_issues();
+    assert_eq!(
+        actions_of(&issues, IssueKind::Ambiguous),
+        [vec![CONVERT_LAST_WORD, PAUSE]]
     );
+    assert_eq!(issues[0].severity, Severity::Warning);
+    assert_ok(cfg);
 }
 
 #[test]
-fn different_max_gap_is_not_duplicate_current_behavior() {
-    let s1 = seq1_gap(MOD_CONTROL.0, u32::from(b'K'), 200);
-    let s2 = seq1_gap(MOD_CONTROL.0, u32::from(b'K'), 400);
+fn different_mods_vks_is_ambiguous_not_duplicate() {
+    let s1 = seq1_modsvks(MOD_CONTROL, 0, u32::from(b'K'));
+    let s2 = seq1_modsvks(MOD_CONTROL, 1, u32::from(b'K'));
+    let cfg = mk_cfg(Some(s1), Some(s2), None, None);
 
-    assert_ok(mk_cfg(Some(s1), Some(s2), None, None));
+    assert_eq!(
+        actions_of(&cfg.hotkey_sequence_issues(), IssueKind::Ambiguous),
+        [vec![CONVERT_LAST_WORD, PAUSE]]
+    );
+    assert_ok(cfg);
 }
 
 #[test]
-fn different_mods_vks_is_not_duplicate_current_behavior() {
-    let s1 = seq1_modsvks(MOD_CONTROL.0, 0, u32::from(b'K'));
-    let s2 = seq1_modsvks(MOD_CONTROL.0, 1, u32::from(b'K'));
+fn different_sides_of_a_modifier_do_not_overlap() {
+    let s1 = seq1_modsvks(MOD_CONTROL, 1, u32::from(b'K'));
+    let s2 = seq1_modsvks(MOD_CONTROL, 2, u32::from(b'K'));
 
-    assert_ok(mk_cfg(Some(s1), Some(s2), None, None));
+    assert_eq!(
+        mk_cfg(Some(s1), Some(s2), None, None).hotkey_sequence_issues(),
+        []
+    );
 }
 
 #[test]
-fn different_second_chord_is_not_duplicate_current_behavior() {
+fn different_second_chord_is_not_duplicate() {
     let s1 = seq2(
-        MOD_CONTROL.0,
+        MOD_CONTROL,
         u32::from(b'A'),
-        MOD_SHIFT.0,
+        MOD_SHIFT,
         u32::from(b'B'),
         250,
     );
     let s2 = seq2(
-        MOD_CONTROL.0,
+        MOD_CONTROL,
         u32::from(b'A'),
-        MOD_SHIFT.0,
+        MOD_SHIFT,
         u32::from(b'C'),
         250,
     );
 
-    assert_ok(mk_cfg(Some(s1), Some(s2), None, None));
+    assert_eq!(
+        mk_cfg(Some(s1), Some(s2), None, None).hotkey_sequence_issues(),
+        []
+    );
 }
 
 #[test]
 fn same_two_chord_sequence_is_duplicate_err() {
     let s = seq2(
-        MOD_CONTROL.0,
+        MOD_CONTROL,
         u32::from(b'A'),
-        MOD_SHIFT.0,
+        MOD_SHIFT,
         u32::from(b'B'),
         250,
     );
 
-    let err = assert_err(mk_cfg(
+    let issues = assert_err(mk_cfg(
         Some(s),
         Some(seq2(
-            MOD_CONTROL.0,
+            MOD_CONTROL,
             u32::from(b'A'),
-            MOD_SHIFT.0,
+            MOD_SHIFT,
             u32::from(b'B'),
             250,
         )),
@@ -301,45 +306,188 @@ fn same_two_chord_sequence_is_duplicate_err() {
         None,
     ));
 
-    assert_has_common_error_shape(&err);
-    assert!(
-        err.contains(&format!("• '{CONVERT_LAST_WORD}' and '{PAUSE}'\n")),
-        "{err}"
+    assert_eq!(
+        actions_of(&issues, IssueKind::Duplicate),
+        [vec![CONVERT_LAST_WORD, PAUSE]]
     );
 }
 
 #[test]
 fn none_values_are_ignored_when_searching_duplicates() {
-    let dup = seq1(MOD_ALT.0, u32::from(b'Q'));
+    let dup = seq1(MOD_ALT, u32::from(b'Q'));
 
-    let err = assert_err(mk_cfg(
+    let issues = assert_err(mk_cfg(
         None,
         Some(dup),
         None,
-        Some(seq1(MOD_ALT.0, u32::from(b'Q'))),
+        Some(seq1(MOD_ALT, u32::from(b'Q'))),
     ));
 
-    assert_has_common_error_shape(&err);
-    assert!(err.contains(PAUSE), "{err}");
-    assert!(err.contains(SWITCH_LAYOUT), "{err}");
+    assert_eq!(
+        actions_of(&issues, IssueKind::Duplicate),
+        [vec![PAUSE, SWITCH_LAYOUT]]
+    );
 }
 
 #[test]
-fn error_message_includes_only_unique_pairs_once() {
-    let s = seq1(MOD_CONTROL.0, u32::from(b'X'));
+fn prefix_shadows_the_longer_sequence() {
+    let issues = assert_err(mk_cfg(
+        None,
+        Some(seq2(
+            MOD_CONTROL,
+            u32::from(b'K'),
+            MOD_CONTROL,
+            u32::from(b'R'),
+            500,
+        )),
+        None,
+        Some(seq1(MOD_CONTROL, u32::from(b'K'))),
+    ));
 
-    let err = assert_err(mk_cfg(
-        Some(s),
-        Some(seq1(MOD_CONTROL.0, u32::from(b'X'))),
-        Some(seq1(MOD_CONTROL.0, u32::from(b'X'))),
+    assert_eq!(
+        actions_of(&issues, IssueKind::Shadowed),
+        [vec![SWITCH_LAYOUT, PAUSE]]
+    );
+    assert_eq!(issues[0].severity, Severity::Error);
+}
+
+#[test]
+fn suffix_with_higher_priority_shadows_the_longer_sequence() {
+    let issues = assert_err(mk_cfg(
+        None,
+        Some(seq2(
+            MOD_CONTROL,
+            u32::from(b'K'),
+            MOD_CONTROL,
+            u32::from(b'R'),
+            500,
+        )),
         None,
+        Some(seq1(MOD_CONTROL, u32::from(b'R'))),
     ));
 
-    let bullets: Vec<&str> = err.lines().filter(|l| l.starts_with("• '")).collect();
+    assert_eq!(
+        actions_of(&issues, IssueKind::Shadowed),
+        [vec![SWITCH_LAYOUT, PAUSE]]
+    );
+}
+
+#[test]
+fn suffix_with_lower_priority_is_ambiguous() {
+    let cfg = mk_cfg(
+        None,
+        Some(seq1(MOD_CONTROL, u32::from(b'R'))),
+        None,
+        Some(seq2(
+            MOD_CONTROL,
+            u32::from(b'K'),
+            MOD_CONTROL,
+            u32::from(b'R'),
+            500,
+        )),
+    );
+
+    assert_eq!(
+        actions_of(&cfg.hotkey_sequence_issues(), IssueKind::Ambiguous),
+        [vec![PAUSE, SWITCH_LAYOUT]]
+    );
+    assert_ok(cfg);
+}
+
+#[test]
+fn reserved_system_shortcut_err() {
+    let issues = assert_err(mk_cfg(
+        None,
+        Some(seq1(MOD_WIN, u32::from(b'L'))),
+        None,
+        None,
+    ));
+
+    assert_eq!(
+        actions_of(&issues, IssueKind::SystemShortcut),
+        [vec![PAUSE]]
+    );
+    assert!(issues[0].explanation.contains("Win+L"), "{issues:?}");
+}
+
+#[test]
+fn alt_tab_is_a_warning() {
+    let cfg = mk_cfg(
+        Some(HotkeySequence::new(
+            &[chord(MOD_ALT, 0, 0x09), chord(0, 0, u32::from(b'C'))],
+            500,
+        )),
+        None,
+        None,
+        None,
+    );
+
+    let issues = cfg.hotkey_sequence_issues();
+    assert_eq!(
+        actions_of(&issues, IssueKind::SystemShortcut),
+        [vec![CONVERT_LAST_WORD]]
+    );
+    assert_eq!(issues[0].severity, Severity::Warning);
+    assert_ok(cfg);
+}
+
+#[test]
+fn description_lists_every_issue_with_its_severity() {
+    let cfg = mk_cfg(
+        Some(seq1(MOD_CONTROL, u32::from(b'X'))),
+        Some(seq1(MOD_CONTROL, u32::from(b'X'))),
+        None,
+        Some(seq1(MOD_ALT, 0x09)),
+    );
+
+    let text = describe_issues(&cfg.hotkey_sequence_issues());
+
+    assert!(
+        text.contains(&format!(
+            "• Error: '{}' and '{}' use the same hotkey sequence.\n",
+            actions::name(CONVERT_LAST_WORD),
+            actions::name(PAUSE)
+        )),
+        "{text}"
+    );
+    assert!(text.contains("• Warning: "), "{text}");
+}
+
+fn with_press(seq: HotkeySequence, press: PressStyle) -> HotkeySequence {
+    let chords: Vec<HotkeyChord> = seq.chords().map(|c| HotkeyChord { press, ..c }).collect();
+    HotkeySequence::new(&chords, 250)
+}
+
+#[test]
+fn zero_tap_duration_err() {
+    let layout = with_press(seq1(0, 0x14), PressStyle::Tap { max_ms: 0 });
+
+    let issues = assert_err(mk_cfg(None, None, None, Some(layout)));
+
+    assert_eq!(
+        actions_of(&issues, IssueKind::InvalidDuration),
+        [vec![SWITCH_LAYOUT]]
+    );
+}
+
+#[test]
+fn tap_and_hold_of_the_same_key_are_not_duplicates() {
+    let tap = with_press(seq1(0, 0x14), PressStyle::Tap { max_ms: 300 });
+    let hold = with_press(seq1(0, 0x14), PressStyle::Hold { min_ms: 600 });
+
+    assert_eq!(
+        mk_cfg(None, Some(hold), None, Some(tap)).hotkey_sequence_issues(),
+        []
+    );
+}
+
+#[test]
+fn press_overlaps_a_tap_of_the_same_key() {
+    let tap = with_press(seq1(0, 0x14), PressStyle::Tap { max_ms: 300 });
+    let cfg = mk_cfg(None, Some(seq1(0, 0x14)), None, Some(tap));
 
     assert_eq!(
-        bullets.len(),
-        2,
-        "expected exactly 2 bullet lines, got {bullets:?}\n{err}"
+        actions_of(&cfg.hotkey_sequence_issues(), IssueKind::Ambiguous),
+        [vec![PAUSE, SWITCH_LAYOUT]]
     );
 }
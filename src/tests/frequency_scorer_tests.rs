@@ -0,0 +1,123 @@
+use lingua::Language;
+
+use crate::{
+    domain::text::{
+        autoconvert::{AutoconvertDecider, Decision, LanguageScorer, SkipReason, decider_for_pair},
+        frequency::{FrequencyScorer, ShortWordDecider, frequency_scorer, parse_frequency_list},
+        layout,
+    },
+    tests::{RU_EN_LANGS, ru_en},
+};
+
+fn decide_short(word: &str) -> (String, Decision) {
+    let decider = ShortWordDecider::new(frequency_scorer(), RU_EN_LANGS).unwrap();
+    let converted = ru_en(word);
+    let decision = decider.decide(word, &converted);
+    (converted, decision)
+}
+
+#[test]
+fn embedded_lists_parse_and_cover_ru_en() {
+    let scorer = frequency_scorer();
+    assert!(scorer.supports(Language::English));
+    assert!(scorer.supports(Language::Russian));
+    #[cfg(feature = "lang-kk")]
+    assert!(!scorer.supports(Language::Kazakh));
+
+    assert!(scorer.is_known("НЕ", Language::Russian));
+    assert!(!scorer.is_known("не", Language::English));
+}
+
+#[test]
+fn listed_words_score_high_and_foreign_letters_score_zero() {
+    let scorer = frequency_scorer();
+
+    assert!(scorer.confidence("не", Language::Russian) > 0.99);
+    assert!(scorer.confidence("the", Language::English) > 0.99);
+    assert_eq!(scorer.confidence("yt", Language::Russian), 0.0);
+    assert_eq!(scorer.confidence("не", Language::English), 0.0);
+    assert!(scorer.confidence("yt", Language::English) < 0.5);
+}
+
+#[test]
+fn char_model_prefers_word_like_strings() {
+    let scorer = frequency_scorer();
+
+    let wordlike = scorer.confidence("tharing", Language::English);
+    let noise = scorer.confidence("qxzvkjw", Language::English);
+    assert!(wordlike > noise, "{wordlike} <= {noise}");
+}
+
+#[test]
+fn short_mistyped_words_convert() {
+    // (token, expected conversion)
+    let cases = [
+        ("yt", "не"),
+        ("ghb", "при"),
+        ("xnj", "что"),
+        ("rfr", "как"),
+        ("ещ", "to"),
+    ];
+
+    for (word, expected) in cases {
+        let (converted, decision) = decide_short(word);
+        assert_eq!(converted, expected);
+        assert!(decision.is_convert(), "{word} -> {converted}: {decision:?}");
+    }
+
+    let decider = ShortWordDecider::new(frequency_scorer(), RU_EN_LANGS).unwrap();
+    assert!(decider.decide("yt,", "не,").is_convert());
+}
+
+#[test]
+fn short_correct_or_unknown_words_stay() {
+    let (_, decision) = decide_short("to");
+    assert_eq!(decision.reason, Some(SkipReason::AlreadyCorrect));
+
+    let (_, decision) = decide_short("не");
+    assert_eq!(decision.reason, Some(SkipReason::AlreadyCorrect));
+
+    // "vs" is listed as English, so it does not become "мы".
+    let (_, decision) = decide_short("vs");
+    assert_eq!(decision.reason, Some(SkipReason::AlreadyCorrect));
+
+    let (converted, decision) = decide_short("qz");
+    assert_eq!(converted, "йя");
+    assert_eq!(decision.reason, Some(SkipReason::ConvertedConfidenceLow));
+
+    let (_, decision) = decide_short("b");
+    assert_eq!(decision.reason, Some(SkipReason::TooShort));
+}
+
+#[test]
+fn pair_decider_routes_short_words_to_frequency_lists() {
+    let ru_en = layout::builtin_pair("ru-en").unwrap();
+    let decider = decider_for_pair(&ru_en).unwrap();
+    assert!(decider.decide("yt", "не").is_convert());
+    assert!(decider.decide("ghbdtn", "привет").is_convert());
+}
+
+#[cfg(feature = "lang-kk")]
+#[test]
+fn pairs_without_a_frequency_list_keep_the_lingua_behavior() {
+    let kk_en = layout::builtin_pair("kk-en").unwrap();
+    let decider = decider_for_pair(&kk_en).unwrap();
+    assert_eq!(
+        decider.decide("yt", "не").reason,
+        Some(SkipReason::TooShort)
+    );
+}
+
+#[test]
+fn frequency_lists_reject_malformed_lines() {
+    assert_eq!(
+        parse_frequency_list("# c\nне 10\nи 20\n").unwrap(),
+        [("не".to_string(), 10), ("и".to_string(), 20)]
+    );
+    assert!(parse_frequency_list("").is_err());
+    assert!(parse_frequency_list("не\n").is_err());
+    assert!(parse_frequency_list("не ten\n").is_err());
+    assert!(parse_frequency_list("не 0\n").is_err());
+    assert!(parse_frequency_list("не 1\nНе 2\n").is_err());
+    assert!(FrequencyScorer::from_lists(&[(Language::Russian, "не 1 2")]).is_err());
+}
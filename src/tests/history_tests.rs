@@ -0,0 +1,94 @@
+use crate::domain::text::history::{
+    ConversionHistory, ConversionKind, ConversionRecord, HISTORY_LEN, UndoRefusal,
+};
+
+fn record(kind: ConversionKind, original: &str, converted: &str) -> ConversionRecord {
+    ConversionRecord {
+        kind,
+        original: original.to_string(),
+        converted: converted.to_string(),
+        caret_delta: 1,
+        window: 42,
+        previous_layout: Some(0x0409_0409),
+        journal_after: format!("{converted} "),
+        journal_before: format!("{original} "),
+    }
+}
+
+#[test]
+fn push_keeps_the_newest_records() {
+    let mut history = ConversionHistory::new();
+    assert!(history.last().is_none());
+
+    for i in 0..HISTORY_LEN + 3 {
+        history.push(record(ConversionKind::LastWord, &format!("w{i}"), "x"));
+    }
+
+    assert_eq!(history.len(), HISTORY_LEN);
+    let newest = format!("w{}", HISTORY_LEN + 2);
+    assert_eq!(
+        history.last().map(|r| r.original.as_str()),
+        Some(newest.as_str())
+    );
+}
+
+#[test]
+fn pop_walks_back_through_conversions() {
+    let mut history = ConversionHistory::new();
+    history.push(record(ConversionKind::LastWord, "ghbdtn", "привет"));
+    history.push(record(ConversionKind::Selection, "vbh", "мир"));
+
+    assert_eq!(
+        history.pop().map(|r| r.kind),
+        Some(ConversionKind::Selection)
+    );
+    assert_eq!(history.last().map(|r| r.original.as_str()), Some("ghbdtn"));
+    assert_eq!(
+        history.pop().map(|r| r.kind),
+        Some(ConversionKind::LastWord)
+    );
+    assert!(history.pop().is_none());
+}
+
+#[test]
+fn undo_is_refused_in_another_window() {
+    let r = record(ConversionKind::Selection, "vbh", "мир");
+    assert_eq!(r.check_undo(7, "мир "), Err(UndoRefusal::WindowChanged));
+    assert_eq!(r.check_undo(42, "мир "), Ok(()));
+}
+
+#[test]
+fn word_undo_is_refused_after_more_typing() {
+    for kind in [ConversionKind::LastWord, ConversionKind::Autoconvert] {
+        let r = record(kind, "ghbdtn", "привет");
+        assert_eq!(r.check_undo(42, "привет "), Ok(()));
+        assert_eq!(
+            r.check_undo(42, "привет м"),
+            Err(UndoRefusal::JournalChanged)
+        );
+        assert_eq!(r.check_undo(42, ""), Err(UndoRefusal::JournalChanged));
+    }
+}
+
+#[test]
+fn selection_undo_ignores_the_journal() {
+    let r = record(ConversionKind::Selection, "vbh", "мир");
+    assert_eq!(r.check_undo(42, "something else"), Ok(()));
+}
+
+#[test]
+fn refusal_reasons_are_distinct() {
+    let all = [
+        UndoRefusal::NothingToUndo,
+        UndoRefusal::WindowChanged,
+        UndoRefusal::JournalChanged,
+        UndoRefusal::SelectionChanged,
+        UndoRefusal::InjectionFailed,
+    ];
+    for (i, a) in all.iter().enumerate() {
+        assert!(!a.user_text().is_empty());
+        for b in &all[i + 1..] {
+            assert_ne!(a.as_str(), b.as_str());
+        }
+    }
+}
@@ -2,34 +2,67 @@
 
 use windows::Win32::UI::Input::KeyboardAndMouse::{MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN};
 
-use crate::{config, platform::win::hotkey_format::format_hotkey};
+use crate::{config, platform::win::hotkey_format::format_hotkey_sequence};
+
+fn single(mods: u32, vk: u8) -> config::HotkeySequence {
+    config::HotkeySequence::new(
+        &[config::HotkeyChord {
+            mods,
+            mods_vks: 0,
+            vk: Some(u32::from(vk)),
+            press: config::PressStyle::Press,
+        }],
+        1000,
+    )
+}
 
 #[test]
-fn format_hotkey_none() {
-    assert_eq!(format_hotkey(None), "None");
+fn format_hotkey_sequence_none() {
+    assert_eq!(format_hotkey_sequence(None), "None");
+    assert_eq!(
+        format_hotkey_sequence(Some(&config::HotkeySequence::default())),
+        "None"
+    );
 }
 
 #[test]
-fn format_hotkey_letter_fast_path() {
-    let hk = config::Hotkey {
-        mods: MOD_CONTROL.0,
-        vk: u32::from(b'A'),
-    };
-    let s = format_hotkey(Some(hk));
+fn format_hotkey_sequence_letter() {
+    let s = format_hotkey_sequence(Some(&single(MOD_CONTROL.0, b'A')));
     assert!(s.contains("Ctrl"));
     assert!(s.contains("A"));
 }
 
 #[test]
-fn format_hotkey_multiple_mods_fast_path() {
-    let hk = config::Hotkey {
-        mods: MOD_CONTROL.0 | MOD_SHIFT.0 | MOD_ALT.0 | MOD_WIN.0,
-        vk: u32::from(b'9'),
-    };
-    let s = format_hotkey(Some(hk));
+fn format_hotkey_sequence_multiple_mods() {
+    let seq = single(MOD_CONTROL.0 | MOD_SHIFT.0 | MOD_ALT.0 | MOD_WIN.0, b'9');
+    let s = format_hotkey_sequence(Some(&seq));
     assert!(s.contains("Ctrl"));
     assert!(s.contains("Shift"));
     assert!(s.contains("Alt"));
     assert!(s.contains("Win"));
     assert!(s.contains("9"));
 }
+
+#[test]
+fn format_hotkey_sequence_shows_press_style() {
+    let chord = |press| config::HotkeyChord {
+        mods: 0,
+        mods_vks: 0,
+        vk: Some(u32::from(b'K')),
+        press,
+    };
+    let seq = config::HotkeySequence::new(
+        &[
+            chord(config::PressStyle::Press),
+            chord(config::PressStyle::Tap { max_ms: 200 }),
+            chord(config::PressStyle::Hold { min_ms: 500 }),
+            chord(config::PressStyle::DoubleTap { max_gap_ms: 300 }),
+        ],
+        1000,
+    );
+
+    assert_eq!(
+        format_hotkey_sequence(Some(&seq)),
+        "K, K (tap 200ms), K (hold 500ms), K (double tap 300ms)"
+    );
+}
@@ -0,0 +1,250 @@
+use crate::{
+    app::actions::ACTIONS,
+    config::{
+        Config, DEFAULT_GAP_MS, HotkeyChord, HotkeyParseError, HotkeySequence, HotkeyStep,
+        MODVK_LCTRL, MODVK_LSHIFT, MODVK_LWIN, MODVK_RALT, MODVK_RSHIFT, PressStyle,
+    },
+    input::key_event::{MOD_ALT, MOD_CONTROL, MOD_SHIFT, MOD_WIN},
+};
+
+fn chord(mods: u32, mods_vks: u32, vk: Option<u32>) -> HotkeyChord {
+    HotkeyChord {
+        mods,
+        mods_vks,
+        vk,
+        press: PressStyle::Press,
+    }
+}
+
+fn parse(text: &str) -> HotkeySequence {
+    text.parse()
+        .unwrap_or_else(|e| panic!("failed to parse '{text}': {e}"))
+}
+
+fn assert_round_trip(seq: &HotkeySequence) {
+    let text = seq.to_string();
+    assert_eq!(&parse(&text), seq, "text: {text}");
+}
+
+#[test]
+fn both_shifts_together() {
+    let seq = parse("LShift+RShift");
+    assert_eq!(
+        seq,
+        HotkeySequence::new(
+            &[chord(MOD_SHIFT, MODVK_LSHIFT | MODVK_RSHIFT, None)],
+            DEFAULT_GAP_MS
+        )
+    );
+    assert_eq!(seq.to_string(), "LShift+RShift");
+}
+
+#[test]
+fn double_tap_with_a_shared_gap() {
+    let lshift = chord(MOD_SHIFT, MODVK_LSHIFT, None);
+    let seq = parse("LShift, LShift within 400ms");
+    assert_eq!(seq, HotkeySequence::new(&[lshift, lshift], 400));
+    assert_eq!(seq.to_string(), "LShift, LShift within 400ms");
+}
+
+#[test]
+fn default_gap_is_not_written() {
+    let seq = parse("Ctrl+K, Ctrl+R, L");
+    assert!(seq.steps.iter().all(|s| s.max_gap_ms == DEFAULT_GAP_MS));
+    assert_eq!(seq.to_string(), "Ctrl+K, Ctrl+R, L");
+}
+
+#[test]
+fn steps_with_their_own_gaps() {
+    let seq = HotkeySequence {
+        steps: vec![
+            HotkeyStep {
+                chord: chord(MOD_CONTROL, 0, Some(0x4B)),
+                max_gap_ms: 1000,
+            },
+            HotkeyStep {
+                chord: chord(MOD_CONTROL, 0, Some(0x52)),
+                max_gap_ms: 500,
+            },
+        ],
+    };
+
+    assert_eq!(seq.to_string(), "Ctrl+K within 1000ms, Ctrl+R within 500ms");
+    assert_round_trip(&seq);
+
+    let partial = parse("Ctrl+K within 300ms, Ctrl+R");
+    assert_eq!(partial.steps[0].max_gap_ms, 300);
+    assert_eq!(partial.steps[1].max_gap_ms, DEFAULT_GAP_MS);
+}
+
+#[test]
+fn press_styles() {
+    let seq = parse("CapsLock (tap 300ms), F13 (hold 600ms), Esc (double tap 250ms)");
+    let presses: Vec<PressStyle> = seq.chords().map(|c| c.press).collect();
+    assert_eq!(
+        presses,
+        [
+            PressStyle::Tap { max_ms: 300 },
+            PressStyle::Hold { min_ms: 600 },
+            PressStyle::DoubleTap { max_gap_ms: 250 },
+        ]
+    );
+    assert_round_trip(&seq);
+}
+
+#[test]
+fn names_are_case_insensitive_and_spacing_is_free() {
+    assert_eq!(parse("ctrl + alt + delete"), parse("Ctrl+Alt+Delete"),);
+    assert_eq!(parse("lctrl+ralt+q"), parse("LCtrl+RAlt+Q"));
+    assert_eq!(parse("Escape"), parse("Esc"));
+    assert_eq!(
+        parse("CapsLock (TAP 300 ms)"),
+        parse("CapsLock (tap 300ms)")
+    );
+}
+
+#[test]
+fn generic_and_sided_modifiers() {
+    let seq = parse("Ctrl+RAlt+Win+Comma");
+    let c = seq.steps[0].chord;
+    assert_eq!(c.mods, MOD_CONTROL | MOD_ALT | MOD_WIN);
+    assert_eq!(c.mods_vks, MODVK_RALT);
+    assert_eq!(c.vk, Some(0xBC));
+    assert_eq!(seq.to_string(), "Ctrl+RAlt+Win+Comma");
+}
+
+#[test]
+fn unnamed_keys_use_hex() {
+    let seq = HotkeySequence::new(&[chord(MOD_CONTROL, MODVK_LCTRL, Some(0xE5))], 1000);
+    assert_eq!(seq.to_string(), "LCtrl+0xE5");
+    assert_round_trip(&seq);
+}
+
+#[test]
+fn every_modifier_combination_round_trips() {
+    let families = [
+        (MOD_CONTROL, MODVK_LCTRL),
+        (MOD_ALT, MODVK_RALT),
+        (MOD_SHIFT, MODVK_LSHIFT | MODVK_RSHIFT),
+        (MOD_WIN, MODVK_LWIN),
+    ];
+    let keys = [
+        None,
+        Some(0x41),
+        Some(0x35),
+        Some(0x14),
+        Some(0x70),
+        Some(0x87),
+        Some(0x60),
+        Some(0xDE),
+    ];
+
+    for combo in 0u32..81 {
+        let mut mods = 0;
+        let mut mods_vks = 0;
+        let mut rest = combo;
+        for (bit, sides) in families {
+            // 0: absent, 1: no side, 2: sided.
+            match rest % 3 {
+                1 => mods |= bit,
+                2 => {
+                    mods |= bit;
+                    mods_vks |= sides;
+                }
+                _ => {}
+            }
+            rest /= 3;
+        }
+
+        for vk in keys {
+            let seq = HotkeySequence::new(&[chord(mods, mods_vks, vk)], 700);
+            assert_round_trip(&seq);
+        }
+    }
+}
+
+#[test]
+fn default_sequences_round_trip() {
+    let cfg = Config::default();
+    for action in ACTIONS.iter().filter(|a| !a.default_binding.is_empty()) {
+        let seq = cfg
+            .hotkey(action.id)
+            .unwrap_or_else(|| panic!("{} has no default", action.id.as_str()));
+        assert_eq!(seq.to_string(), action.default_binding);
+        assert_round_trip(seq);
+    }
+}
+
+#[test]
+fn parse_errors() {
+    let err = |text: &str| text.parse::<HotkeySequence>().unwrap_err();
+
+    assert_eq!(err("Ctrl+Foo"), HotkeyParseError::UnknownKey("Foo".into()));
+    assert_eq!(
+        err("Ctrl+A+B"),
+        HotkeyParseError::SeveralKeys("Ctrl+A+B".into())
+    );
+    assert_eq!(err("Ctrl+, A"), HotkeyParseError::EmptyChord);
+    assert_eq!(
+        err("A within soon"),
+        HotkeyParseError::BadDuration("soon".into())
+    );
+    assert_eq!(
+        err("A (press 5ms)"),
+        HotkeyParseError::UnknownPressStyle("press 5ms".into())
+    );
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Holder {
+    seq: HotkeySequence,
+}
+
+#[test]
+fn config_writes_text_and_reads_every_form() {
+    let lshift = chord(MOD_SHIFT, MODVK_LSHIFT, None);
+    let expected = HotkeySequence::new(&[lshift, lshift], 500);
+
+    let written = toml::to_string(&Holder {
+        seq: expected.clone(),
+    })
+    .unwrap();
+    assert_eq!(written.trim(), r#"seq = "LShift, LShift within 500ms""#);
+
+    let text: Holder = toml::from_str(&written).unwrap();
+    assert_eq!(text.seq, expected);
+
+    let steps: Holder = toml::from_str(
+        r#"
+[[seq.steps]]
+max_gap_ms = 500
+chord = { mods = 4, mods_vks = 4 }
+
+[[seq.steps]]
+max_gap_ms = 500
+chord = { mods = 4, mods_vks = 4 }
+"#,
+    )
+    .unwrap();
+    assert_eq!(steps.seq, expected);
+
+    let pair: Holder = toml::from_str(
+        r#"
+[seq]
+max_gap_ms = 500
+first = { mods = 4, mods_vks = 4 }
+second = { mods = 4, mods_vks = 4 }
+"#,
+    )
+    .unwrap();
+    assert_eq!(pair.seq, expected);
+
+    let single: Holder =
+        toml::from_str("[seq]\nmax_gap_ms = 500\nfirst = { mods = 0, vk = 20 }\n").unwrap();
+    assert_eq!(
+        single.seq,
+        HotkeySequence::new(&[chord(0, 0, Some(20))], 500)
+    );
+
+    assert!(toml::from_str::<Holder>(r#"seq = "Ctrl+Nope""#).is_err());
+}
@@ -0,0 +1,470 @@
+use std::{cell::Cell, convert::Infallible};
+
+use crate::{
+    app::{
+        RuntimeHotkeys,
+        actions::{CONVERT_LAST_WORD, PAUSE, SWITCH_LAYOUT},
+    },
+    config::{Config, HotkeyChord, HotkeySequence, MODVK_LSHIFT, MODVK_RSHIFT, PressStyle},
+    input::{
+        key_event::{
+            Action, ActionSink, KeyEvent, KeyEventKind, KeyboardState, MOD_SHIFT, ModsDown,
+            VK_BACK, VK_LCONTROL, VK_LEFT, VK_LSHIFT, VK_RSHIFT,
+        },
+        pipeline::{HookDecision, process_event},
+        ring_buffer::InputJournal,
+    },
+};
+
+const VK_CAPITAL: u32 = 0x14;
+const VK_SPACE: u32 = 0x20;
+const VK_OEM_COMMA: u32 = 0xBC;
+
+/// US layout for letters, space and comma, in a window that can be switched.
+struct FakeKeyboard {
+    window: Cell<isize>,
+}
+
+impl KeyboardState for FakeKeyboard {
+    fn foreground_window(&self) -> isize {
+        self.window.get()
+    }
+
+    fn layout(&self) -> isize {
+        0x0409
+    }
+
+    fn translate(&self, event: &KeyEvent) -> Option<String> {
+        match event.vk {
+            0x41..=0x5A => char::from_u32(event.vk + 0x20).map(String::from),
+            VK_SPACE => Some(" ".to_string()),
+            VK_OEM_COMMA => Some(",".to_string()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Default)]
+struct RecordingSink {
+    actions: Vec<Action>,
+}
+
+impl ActionSink for RecordingSink {
+    type Error = Infallible;
+
+    fn emit(&mut self, action: Action) -> Result<(), Infallible> {
+        self.actions.push(action);
+        Ok(())
+    }
+}
+
+/// Runs recorded events through the pipeline with the default config.
+struct Replay {
+    hotkeys: RuntimeHotkeys,
+    mods: ModsDown,
+    journal: InputJournal,
+    keyboard: FakeKeyboard,
+    sink: RecordingSink,
+    now_ms: u64,
+}
+
+impl Replay {
+    fn new() -> Self {
+        Self {
+            hotkeys: RuntimeHotkeys::from_config(&Config::default()),
+            mods: ModsDown::default(),
+            journal: InputJournal::new(100),
+            keyboard: FakeKeyboard {
+                window: Cell::new(1),
+            },
+            sink: RecordingSink::default(),
+            now_ms: 1_000,
+        }
+    }
+
+    fn event(&mut self, event: KeyEvent) -> HookDecision {
+        self.now_ms = event.time_ms;
+        let Ok(decision) = process_event(
+            &mut self.hotkeys,
+            &mut self.mods,
+            &mut self.journal,
+            &event,
+            &self.keyboard,
+            &mut self.sink,
+        );
+        decision
+    }
+
+    /// Feeds a recorded stream, one `<ms> <down|up> <vk hex> [injected]` event per line.
+    fn feed(&mut self, recording: &str) -> Vec<HookDecision> {
+        recording
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+            .map(|line| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                let time_ms = parts[0].parse().unwrap();
+                let vk = u32::from_str_radix(parts[2].trim_start_matches("0x"), 16).unwrap();
+                let mut event = match parts[1] {
+                    "down" => KeyEvent::down(vk, time_ms),
+                    "up" => KeyEvent::up(vk, time_ms),
+                    other => panic!("unknown event kind {other}"),
+                };
+                event.injected = parts.get(3) == Some(&"injected");
+                self.event(event)
+            })
+            .collect()
+    }
+
+    /// Presses and releases each key, 50 ms apart.
+    fn tap(&mut self, vks: &[u32]) {
+        for &vk in vks {
+            let t = self.now_ms + 50;
+            self.event(KeyEvent::down(vk, t));
+            self.event(KeyEvent::up(vk, t + 10));
+        }
+    }
+
+    fn type_text(&mut self, text: &str) {
+        let vks: Vec<u32> = text
+            .chars()
+            .map(|ch| match ch {
+                ' ' => VK_SPACE,
+                ',' => VK_OEM_COMMA,
+                c => u32::from(c.to_ascii_uppercase()),
+            })
+            .collect();
+        self.tap(&vks);
+    }
+}
+
+#[test]
+fn typed_word_is_journaled_and_triggers_autoconvert_once() {
+    let mut r = Replay::new();
+    r.type_text("ghbdtn");
+    assert!(r.sink.actions.is_empty());
+
+    r.type_text(" ");
+    assert_eq!(r.journal.text(), "ghbdtn ");
+    assert_eq!(r.sink.actions, [Action::Autoconvert]);
+
+    r.type_text(" ");
+    assert_eq!(r.sink.actions, [Action::Autoconvert]);
+}
+
+#[test]
+fn punctuation_after_a_word_triggers_autoconvert() {
+    let mut r = Replay::new();
+    r.type_text("ntrcn,");
+    assert_eq!(r.journal.text(), "ntrcn,");
+    assert_eq!(r.sink.actions, [Action::Autoconvert]);
+}
+
+#[test]
+fn recorded_double_tap_of_left_shift_converts_the_last_word() {
+    let mut r = Replay::new();
+    let decisions = r.feed(
+        "
+        1000 down 0x41
+        1010 up 0x41
+        1100 down 0xA0
+        1150 up 0xA0
+        1300 down 0xA0
+        1350 up 0xA0
+        ",
+    );
+
+    assert_eq!(decisions[0], HookDecision::Pass);
+    // The release that completes the sequence still reaches the system.
+    assert_eq!(decisions[5], HookDecision::Pass);
+    assert_eq!(r.sink.actions, [Action::Hotkey(CONVERT_LAST_WORD)]);
+    assert_eq!(r.journal.text(), "a");
+}
+
+#[test]
+fn slow_double_tap_does_not_match() {
+    let mut r = Replay::new();
+    r.feed(
+        "
+        1000 down 0xA0
+        1050 up 0xA0
+        2500 down 0xA0
+        2550 up 0xA0
+        ",
+    );
+    assert!(r.sink.actions.is_empty());
+}
+
+#[test]
+fn both_shifts_together_toggle_autoconvert() {
+    let mut r = Replay::new();
+    r.event(KeyEvent::down(VK_LSHIFT, 1000));
+    r.event(KeyEvent::down(VK_RSHIFT, 1010));
+    assert!(r.sink.actions.is_empty());
+
+    r.event(KeyEvent::up(VK_LSHIFT, 1050));
+    r.event(KeyEvent::up(VK_RSHIFT, 1060));
+    assert_eq!(r.sink.actions, [Action::Hotkey(PAUSE)]);
+}
+
+#[test]
+fn caps_lock_tap_switches_layout_and_is_swallowed() {
+    let mut r = Replay::new();
+    r.type_text("ab");
+
+    let decision = r.event(KeyEvent::down(VK_CAPITAL, 2000));
+    assert_eq!(decision, HookDecision::Swallow);
+    assert!(r.sink.actions.is_empty());
+
+    let decision = r.event(KeyEvent::up(VK_CAPITAL, 2120));
+    assert_eq!(decision, HookDecision::Swallow);
+    assert_eq!(r.sink.actions, [Action::Hotkey(SWITCH_LAYOUT)]);
+    assert_eq!(r.journal.text(), "ab");
+}
+
+#[test]
+fn held_caps_lock_is_given_back_on_autorepeat() {
+    let mut r = Replay::new();
+    let decisions = r.feed(
+        "
+        1000 down 0x14
+        1500 down 0x14
+        1530 down 0x14
+        1600 up 0x14
+        ",
+    );
+
+    assert_eq!(
+        decisions,
+        [
+            HookDecision::Swallow,
+            HookDecision::Pass,
+            HookDecision::Pass,
+            HookDecision::Pass
+        ]
+    );
+    assert!(r.sink.actions.is_empty());
+}
+
+#[test]
+fn slow_caps_lock_release_is_replayed_as_a_keystroke() {
+    let mut r = Replay::new();
+    let decisions = r.feed(
+        "
+        1000 down 0x14
+        1400 up 0x14
+        1400 down 0x14 injected
+        1400 up 0x14 injected
+        ",
+    );
+
+    assert_eq!(
+        decisions,
+        [
+            HookDecision::Swallow,
+            HookDecision::Replay { vk: VK_CAPITAL },
+            HookDecision::Pass,
+            HookDecision::Pass
+        ]
+    );
+    assert!(r.sink.actions.is_empty());
+}
+
+#[test]
+fn a_key_pressed_during_a_tap_replays_the_held_key_first() {
+    let mut r = Replay::new();
+    let decisions = r.feed(
+        "
+        1000 down 0x14
+        1100 down 0x41
+        1100 down 0x14 injected
+        1100 down 0x41 injected
+        1150 up 0x41
+        1200 up 0x14
+        ",
+    );
+
+    assert_eq!(
+        decisions,
+        [
+            HookDecision::Swallow,
+            HookDecision::Replay { vk: VK_CAPITAL },
+            HookDecision::Pass,
+            HookDecision::Pass,
+            HookDecision::Pass,
+            HookDecision::Pass
+        ]
+    );
+    assert!(r.sink.actions.is_empty());
+    assert_eq!(r.journal.text(), "a");
+}
+
+#[test]
+fn double_tap_of_a_key_fires_once() {
+    let mut r = Replay::new();
+    r.hotkeys.bindings.set(
+        SWITCH_LAYOUT,
+        Some(HotkeySequence::new(
+            &[HotkeyChord {
+                mods: 0,
+                mods_vks: 0,
+                vk: Some(VK_CAPITAL),
+                press: PressStyle::DoubleTap { max_gap_ms: 250 },
+            }],
+            1000,
+        )),
+    );
+
+    let decisions = r.feed(
+        "
+        1000 down 0x14
+        1080 up 0x14
+        1200 down 0x14
+        1260 up 0x14
+        1400 down 0x14
+        1460 up 0x14
+        ",
+    );
+
+    assert!(decisions.iter().all(|d| *d == HookDecision::Swallow));
+    assert_eq!(r.sink.actions, [Action::Hotkey(SWITCH_LAYOUT)]);
+}
+
+#[test]
+fn hold_of_both_shifts_is_told_from_a_tap() {
+    let mut r = Replay::new();
+    r.hotkeys.bindings.set(
+        PAUSE,
+        Some(HotkeySequence::new(
+            &[HotkeyChord {
+                mods: MOD_SHIFT,
+                mods_vks: MODVK_LSHIFT | MODVK_RSHIFT,
+                vk: None,
+                press: PressStyle::Hold { min_ms: 500 },
+            }],
+            1000,
+        )),
+    );
+
+    r.feed(
+        "
+        1000 down 0xA0
+        1010 down 0xA1
+        1100 up 0xA0
+        1110 up 0xA1
+        ",
+    );
+    assert!(r.sink.actions.is_empty());
+
+    r.feed(
+        "
+        2000 down 0xA0
+        2010 down 0xA1
+        2600 up 0xA0
+        2610 up 0xA1
+        ",
+    );
+    assert_eq!(r.sink.actions, [Action::Hotkey(PAUSE)]);
+}
+
+#[test]
+fn shift_with_a_letter_is_not_a_modifier_chord() {
+    let mut r = Replay::new();
+    r.feed(
+        "
+        1000 down 0xA0
+        1010 down 0x41
+        1020 up 0x41
+        1030 up 0xA0
+        1100 down 0xA0
+        1110 up 0xA0
+        ",
+    );
+    assert!(r.sink.actions.is_empty());
+}
+
+#[test]
+fn injected_keys_are_not_journaled() {
+    let mut r = Replay::new();
+    r.feed(
+        "
+        1000 down 0x41 injected
+        1010 up 0x41 injected
+        1020 down 0x20 injected
+        ",
+    );
+    assert_eq!(r.journal.text(), "");
+    assert!(r.sink.actions.is_empty());
+}
+
+#[test]
+fn navigation_and_ctrl_chords_clear_the_journal() {
+    let mut r = Replay::new();
+    r.type_text("abc");
+    r.tap(&[VK_LEFT]);
+    assert_eq!(r.journal.text(), "");
+
+    r.type_text("abc");
+    let t = r.now_ms + 50;
+    r.event(KeyEvent::down(VK_LCONTROL, t));
+    r.event(KeyEvent::down(0x56, t + 10));
+    assert_eq!(r.journal.text(), "");
+    assert_eq!(r.event(KeyEvent::up(0x56, t + 20)), HookDecision::Pass);
+    r.event(KeyEvent::up(VK_LCONTROL, t + 30));
+}
+
+#[test]
+fn switching_windows_starts_a_new_journal() {
+    let mut r = Replay::new();
+    r.type_text("abc");
+    r.keyboard.window.set(2);
+    r.type_text("d");
+    assert_eq!(r.journal.text(), "d");
+    assert!(r.sink.actions.is_empty());
+}
+
+#[test]
+fn backspace_over_a_whole_autoconverted_word_is_reported() {
+    let mut r = Replay::new();
+    r.type_text("ok ghbdtn ");
+    r.journal.mark_last_token_autoconverted();
+    r.sink.actions.clear();
+
+    for _ in 0..6 {
+        r.tap(&[VK_BACK]);
+    }
+    assert_eq!(r.journal.text(), "ok g");
+    assert!(r.sink.actions.is_empty());
+
+    r.tap(&[VK_BACK]);
+    assert_eq!(r.journal.text(), "ok ");
+    assert_eq!(r.sink.actions, [Action::AutoconvertReverted]);
+
+    r.tap(&[VK_BACK]);
+    assert_eq!(r.sink.actions, [Action::AutoconvertReverted]);
+}
+
+#[test]
+fn fixing_a_typo_in_an_autoconverted_word_is_not_reported() {
+    let mut r = Replay::new();
+    r.type_text("ghbdtn");
+    r.journal.mark_last_token_autoconverted();
+
+    r.tap(&[VK_BACK]);
+    r.type_text("n");
+    assert_eq!(r.journal.text(), "ghbdtn");
+
+    for _ in 0..6 {
+        r.tap(&[VK_BACK]);
+    }
+    assert!(r.sink.actions.is_empty());
+}
+
+#[test]
+fn events_carry_their_kind() {
+    assert_eq!(KeyEvent::down(0x41, 5).kind, KeyEventKind::Down);
+    let up = KeyEvent::up(VK_LSHIFT, 5);
+    assert_eq!(up.kind, KeyEventKind::Up);
+    assert!(up.is_mod());
+    assert!(!KeyEvent::down(0x41, 5).is_mod());
+}
@@ -1,9 +1,12 @@
-#![cfg(windows)]
-
-use crate::{config, platform::win::keyboard::sequence::chord_matches};
+use crate::{config, input::sequence_matcher::chord_matches};
 
 fn ch(mods: u32, mods_vks: u32, vk: Option<u32>) -> config::HotkeyChord {
-    config::HotkeyChord { mods, mods_vks, vk }
+    config::HotkeyChord {
+        mods,
+        mods_vks,
+        vk,
+        press: config::PressStyle::Press,
+    }
 }
 
 #[test]
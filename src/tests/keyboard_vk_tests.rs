@@ -2,7 +2,7 @@
 
 use windows::Win32::UI::WindowsAndMessaging::{WM_KEYDOWN, WM_KEYUP, WM_SYSKEYDOWN, WM_SYSKEYUP};
 
-use crate::platform::win::keyboard::vk;
+use crate::{input::key_event, platform::win::keyboard::vk};
 
 #[test]
 fn keydown_keyup_msg_classification() {
@@ -19,26 +19,26 @@ fn keydown_keyup_msg_classification() {
 
 #[test]
 fn mod_bit_for_vk_known_values() {
-    assert!(vk::mod_bit_for_vk(0xA2).is_some()); // LCtrl
-    assert!(vk::mod_bit_for_vk(0xA3).is_some()); // RCtrl
-    assert!(vk::mod_bit_for_vk(0xA0).is_some()); // LShift
-    assert!(vk::mod_bit_for_vk(0xA1).is_some()); // RShift
-    assert!(vk::mod_bit_for_vk(0xA4).is_some()); // LAlt
-    assert!(vk::mod_bit_for_vk(0xA5).is_some()); // RAlt
-    assert!(vk::mod_bit_for_vk(0x5B).is_some()); // LWin
-    assert!(vk::mod_bit_for_vk(0x5C).is_some()); // RWin
-    assert!(vk::mod_bit_for_vk(0x30).is_none()); // 0
+    assert!(key_event::mod_bit_for_vk(0xA2).is_some()); // LCtrl
+    assert!(key_event::mod_bit_for_vk(0xA3).is_some()); // RCtrl
+    assert!(key_event::mod_bit_for_vk(0xA0).is_some()); // LShift
+    assert!(key_event::mod_bit_for_vk(0xA1).is_some()); // RShift
+    assert!(key_event::mod_bit_for_vk(0xA4).is_some()); // LAlt
+    assert!(key_event::mod_bit_for_vk(0xA5).is_some()); // RAlt
+    assert!(key_event::mod_bit_for_vk(0x5B).is_some()); // LWin
+    assert!(key_event::mod_bit_for_vk(0x5C).is_some()); // RWin
+    assert!(key_event::mod_bit_for_vk(0x30).is_none()); // 0
 }
 
 #[test]
 fn mod_vk_bit_for_vk_known_values() {
-    assert!(vk::mod_vk_bit_for_vk(0xA2).is_some());
-    assert!(vk::mod_vk_bit_for_vk(0xA3).is_some());
-    assert!(vk::mod_vk_bit_for_vk(0xA0).is_some());
-    assert!(vk::mod_vk_bit_for_vk(0xA1).is_some());
-    assert!(vk::mod_vk_bit_for_vk(0xA4).is_some());
-    assert!(vk::mod_vk_bit_for_vk(0xA5).is_some());
-    assert!(vk::mod_vk_bit_for_vk(0x5B).is_some());
-    assert!(vk::mod_vk_bit_for_vk(0x5C).is_some());
-    assert!(vk::mod_vk_bit_for_vk(0x30).is_none());
+    assert!(key_event::mod_vk_bit_for_vk(0xA2).is_some());
+    assert!(key_event::mod_vk_bit_for_vk(0xA3).is_some());
+    assert!(key_event::mod_vk_bit_for_vk(0xA0).is_some());
+    assert!(key_event::mod_vk_bit_for_vk(0xA1).is_some());
+    assert!(key_event::mod_vk_bit_for_vk(0xA4).is_some());
+    assert!(key_event::mod_vk_bit_for_vk(0xA5).is_some());
+    assert!(key_event::mod_vk_bit_for_vk(0x5B).is_some());
+    assert!(key_event::mod_vk_bit_for_vk(0x5C).is_some());
+    assert!(key_event::mod_vk_bit_for_vk(0x30).is_none());
 }
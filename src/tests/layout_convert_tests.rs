@@ -0,0 +1,115 @@
+use crate::domain::text::{
+    layout::{self, Layout, ScanCode, ShiftLevel},
+    mapping::{ConvertDirection, convert_with_layouts, convert_with_pair},
+};
+
+fn en_ru() -> (Layout, Layout) {
+    let pair = layout::builtin_pair(layout::RU_EN).unwrap();
+    (pair.primary().clone(), pair.secondary().clone())
+}
+
+fn auto(text: &str, current: &Layout, other: &Layout) -> String {
+    convert_with_layouts(text, current, other, ConvertDirection::Auto)
+}
+
+#[test]
+fn scan_codes_follow_the_letter_block() {
+    assert_eq!(ScanCode::at(0, 0), Some(ScanCode(0x29)));
+    assert_eq!(ScanCode::at(1, 0), Some(ScanCode(0x10)));
+    assert_eq!(ScanCode::at(1, 12), Some(ScanCode(0x2B)));
+    assert_eq!(ScanCode::at(3, 9), Some(ScanCode(0x35)));
+    assert_eq!(ScanCode::at(3, 10), None);
+    assert_eq!(ScanCode::at(4, 0), None);
+
+    let total: usize = layout::ROW_LENGTHS.iter().sum();
+    assert_eq!(layout::character_keys().count(), total + 1);
+}
+
+#[test]
+fn convert_between_maps_through_the_source_key() {
+    let (en, ru) = en_ru();
+
+    assert_eq!(
+        layout::convert_between("ghbdtn? vbh/", &en, &ru),
+        "привет, мир."
+    );
+    assert_eq!(
+        layout::convert_between("Привет, мир.", &ru, &en),
+        "Ghbdtn? vbh/"
+    );
+}
+
+#[test]
+fn shared_punctuation_follows_the_current_layout() {
+    let (en, ru) = en_ru();
+
+    // ',' and '.' exist in both layouts on different keys.
+    assert_eq!(auto("a,b.", &en, &ru), "фбию");
+    assert_eq!(auto("а,б.", &ru, &en), "f?,/");
+    assert_eq!(auto(",.", &en, &ru), "бю");
+    assert_eq!(auto(",.", &ru, &en), "?/");
+}
+
+#[test]
+fn text_typed_in_the_other_layout_flips_the_direction() {
+    let (en, ru) = en_ru();
+
+    assert_eq!(auto("ghbdtn", &ru, &en), "привет");
+    assert_eq!(auto("привет", &en, &ru), "ghbdtn");
+}
+
+#[test]
+fn from_keys_reverse_maps_to_the_first_key() {
+    let a = ScanCode(0x10);
+    let b = ScanCode(0x11);
+    let source = Layout::from_keys(
+        "src",
+        [
+            (a, ShiftLevel::Base, 'x'),
+            (b, ShiftLevel::Base, 'y'),
+            (b, ShiftLevel::Shift, 'x'),
+        ],
+    );
+    let target = Layout::from_keys(
+        "dst",
+        [
+            (a, ShiftLevel::Base, '1'),
+            (b, ShiftLevel::Base, '2'),
+            (b, ShiftLevel::Shift, '3'),
+        ],
+    );
+
+    assert_eq!(source.key_for('x'), Some((a, ShiftLevel::Base)));
+    assert_eq!(layout::convert_between("xyz", &source, &target), "12z");
+}
+
+#[test]
+fn mixed_text_converts_only_the_mistyped_runs() {
+    let (en, ru) = en_ru();
+
+    assert_eq!(auto("ghbdtn, мир", &en, &ru), "привет, мир");
+    assert_eq!(auto("ghbdtn, мир", &ru, &en), "привет, мир");
+    assert_eq!(auto("привет, vbh", &en, &ru), "привет, мир");
+    assert_eq!(auto("hello, ьшк", &ru, &en), "hello, mir");
+    assert_eq!(auto("мир [jhjij!", &en, &ru), "мир хорошо!");
+}
+
+#[test]
+fn explicit_direction_converts_every_other_run() {
+    let (en, ru) = en_ru();
+    let pair = layout::builtin_pair(layout::RU_EN).unwrap();
+
+    let to_ru = |text: &str| convert_with_layouts(text, &en, &ru, ConvertDirection::ToRu);
+    let to_en = |text: &str| convert_with_layouts(text, &en, &ru, ConvertDirection::ToEn);
+
+    assert_eq!(to_ru("hello мир"), "руддщ мир");
+    assert_eq!(to_en("hello мир"), "hello vbh");
+    assert_eq!(to_ru("привет"), "привет");
+    assert_eq!(to_en("привет"), "ghbdtn");
+    assert_eq!(to_ru("ghbdtn/"), "привет.");
+
+    assert_eq!(
+        convert_with_pair("hello мир", &pair, ConvertDirection::ToEn),
+        "hello vbh"
+    );
+}
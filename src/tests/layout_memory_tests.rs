@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use crate::{
+    config::Config,
+    domain::text::layout_memory::{FocusTarget, LayoutMemory, LayoutMemoryScope, Restore},
+};
+
+const EN: u32 = 0x409;
+const RU: u32 = 0x419;
+
+fn target(window: isize, app: &str) -> FocusTarget {
+    FocusTarget {
+        window,
+        app: app.to_string(),
+    }
+}
+
+fn memory(scope: LayoutMemoryScope, pinned: &[(&str, &str)]) -> LayoutMemory<u32> {
+    let pinned: BTreeMap<String, String> = pinned
+        .iter()
+        .map(|(app, tag)| ((*app).to_string(), (*tag).to_string()))
+        .collect();
+    let mut memory = LayoutMemory::default();
+    memory.configure(scope, &pinned);
+    memory
+}
+
+/// Moves the focus from `left` with `layout` to `entered`.
+fn switch(
+    memory: &mut LayoutMemory<u32>,
+    left: &FocusTarget,
+    layout: u32,
+    entered: &FocusTarget,
+) -> Option<Restore<u32>> {
+    memory.focus_changed(Some((left, layout)), entered)
+}
+
+#[test]
+fn off_restores_nothing() {
+    let mut m = memory(LayoutMemoryScope::Off, &[]);
+    let ide = target(1, "code.exe");
+    let chat = target(2, "telegram.exe");
+
+    assert_eq!(switch(&mut m, &ide, EN, &chat), None);
+    assert_eq!(switch(&mut m, &chat, RU, &ide), None);
+}
+
+#[test]
+fn application_layout_is_restored_on_alt_tab() {
+    let mut m = memory(LayoutMemoryScope::Application, &[]);
+    let ide = target(1, "code.exe");
+    let chat = target(2, "telegram.exe");
+
+    // Nothing is known about the chat yet.
+    assert_eq!(switch(&mut m, &ide, EN, &chat), None);
+    assert_eq!(switch(&mut m, &chat, RU, &ide), Some(Restore::Layout(EN)));
+    assert_eq!(switch(&mut m, &ide, EN, &chat), Some(Restore::Layout(RU)));
+}
+
+#[test]
+fn application_memory_is_shared_by_its_windows() {
+    let mut m = memory(LayoutMemoryScope::Application, &[]);
+    let chat = target(2, "Telegram.exe");
+    let other_chat = target(3, "telegram.exe");
+    let ide = target(1, "code.exe");
+
+    switch(&mut m, &chat, RU, &ide);
+    assert_eq!(
+        switch(&mut m, &ide, EN, &other_chat),
+        Some(Restore::Layout(RU))
+    );
+}
+
+#[test]
+fn window_memory_keeps_windows_of_one_application_apart() {
+    let mut m = memory(LayoutMemoryScope::Window, &[]);
+    let chat_a = target(2, "telegram.exe");
+    let chat_b = target(3, "telegram.exe");
+
+    assert_eq!(switch(&mut m, &chat_a, RU, &chat_b), None);
+    assert_eq!(
+        switch(&mut m, &chat_b, EN, &chat_a),
+        Some(Restore::Layout(RU))
+    );
+    assert_eq!(
+        switch(&mut m, &chat_a, RU, &chat_b),
+        Some(Restore::Layout(EN))
+    );
+}
+
+#[test]
+fn pinned_layout_wins_over_the_remembered_one() {
+    let mut m = memory(LayoutMemoryScope::Application, &[("Code.exe", "en-US")]);
+    let ide = target(1, "code.exe");
+    let chat = target(2, "telegram.exe");
+
+    switch(&mut m, &ide, RU, &chat);
+    assert_eq!(
+        switch(&mut m, &chat, RU, &ide),
+        Some(Restore::Tag("en-US".to_string()))
+    );
+}
+
+#[test]
+fn pinned_layout_applies_without_memory() {
+    let mut m = memory(LayoutMemoryScope::Off, &[("code.exe", "en-US")]);
+    let ide = target(1, "code.exe");
+    let chat = target(2, "telegram.exe");
+
+    assert_eq!(
+        switch(&mut m, &chat, RU, &ide),
+        Some(Restore::Tag("en-US".to_string()))
+    );
+    assert_eq!(switch(&mut m, &ide, EN, &chat), None);
+}
+
+#[test]
+fn closed_window_is_not_remembered() {
+    let mut m = memory(LayoutMemoryScope::Application, &[]);
+    let chat = target(2, "telegram.exe");
+    let ide = target(1, "code.exe");
+
+    assert_eq!(m.focus_changed(None, &ide), None);
+    switch(&mut m, &ide, EN, &chat);
+    assert_eq!(m.focus_changed(None, &ide), Some(Restore::Layout(EN)));
+}
+
+#[test]
+fn changing_the_scope_forgets_layouts() {
+    let mut m = memory(LayoutMemoryScope::Application, &[]);
+    let ide = target(1, "code.exe");
+    let chat = target(2, "telegram.exe");
+    switch(&mut m, &ide, EN, &chat);
+
+    m.configure(LayoutMemoryScope::Application, &BTreeMap::new());
+    assert_eq!(switch(&mut m, &chat, RU, &ide), Some(Restore::Layout(EN)));
+
+    m.configure(LayoutMemoryScope::Window, &BTreeMap::new());
+    assert_eq!(switch(&mut m, &ide, EN, &chat), None);
+}
+
+#[test]
+fn oldest_windows_are_forgotten() {
+    let mut m = memory(LayoutMemoryScope::Window, &[]);
+    let first = target(1, "a.exe");
+    let mut left = first.clone();
+    for window in 2..100 {
+        let entered = target(window, "a.exe");
+        switch(&mut m, &left, RU, &entered);
+        left = entered;
+    }
+    assert_eq!(switch(&mut m, &left, RU, &first), None);
+}
+
+#[test]
+fn config_reads_scope_and_pinned_layouts() {
+    let cfg = Config::parse(
+        r#"
+delay_ms = 100
+layout_memory = "window"
+
+[app_layouts]
+"code.exe" = "en-US"
+"#,
+    )
+    .unwrap();
+    assert_eq!(cfg.layout_memory, LayoutMemoryScope::Window);
+    assert_eq!(cfg.app_layouts["code.exe"], "en-US");
+
+    let text = toml::to_string(&cfg).unwrap();
+    let back = Config::parse(&text).unwrap();
+    assert_eq!(back.layout_memory, LayoutMemoryScope::Window);
+    assert_eq!(back.app_layouts, cfg.app_layouts);
+
+    assert_eq!(Config::default().layout_memory, LayoutMemoryScope::Off);
+}
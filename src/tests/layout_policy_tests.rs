@@ -0,0 +1,138 @@
+use crate::domain::text::layout_policy::{
+    InstalledLayout, LayoutTarget, RecentLayouts, choose_layout, layout_with_tag, next_layout,
+};
+
+fn installed(tags: &[&str]) -> Vec<InstalledLayout<usize>> {
+    tags.iter()
+        .enumerate()
+        .map(|(handle, tag)| InstalledLayout {
+            handle,
+            tag: (*tag).to_string(),
+        })
+        .collect()
+}
+
+#[test]
+fn next_layout_cycles() {
+    assert_eq!(next_layout(&[1, 2, 3], 1), 2);
+    assert_eq!(next_layout(&[1, 2, 3], 3), 1);
+    assert_eq!(next_layout(&[1], 1), 1);
+}
+
+#[test]
+fn next_layout_of_unknown_current_is_current() {
+    assert_eq!(next_layout(&[1, 2, 3], 7), 7);
+    assert_eq!(next_layout(&[], 7), 7);
+}
+
+#[test]
+fn tag_matches_exactly_ignoring_case() {
+    let layouts = installed(&["en-US", "ru-RU", "en-GB"]);
+    assert_eq!(layout_with_tag(&layouts, "en-GB"), Some(2));
+    assert_eq!(layout_with_tag(&layouts, "RU-ru"), Some(1));
+    assert_eq!(layout_with_tag(&layouts, "de-DE"), None);
+}
+
+#[test]
+fn language_tag_matches_first_layout_of_the_language() {
+    let layouts = installed(&["ru-RU", "en-GB", "en-US"]);
+    assert_eq!(layout_with_tag(&layouts, "en"), Some(1));
+    assert_eq!(layout_with_tag(&layouts, "e"), None);
+}
+
+#[test]
+fn exact_tag_wins_over_language() {
+    let layouts = installed(&["en-US", "en"]);
+    assert_eq!(layout_with_tag(&layouts, "en"), Some(1));
+}
+
+#[test]
+fn tag_target_skips_active_and_missing_layouts() {
+    let layouts = installed(&["en-US", "ru-RU", "de-DE"]);
+    let recent = RecentLayouts::default();
+    assert_eq!(
+        choose_layout(&layouts, 0, LayoutTarget::Tag("de-DE"), &recent),
+        Some(2)
+    );
+    assert_eq!(
+        choose_layout(&layouts, 2, LayoutTarget::Tag("de-DE"), &recent),
+        None
+    );
+    assert_eq!(
+        choose_layout(&layouts, 0, LayoutTarget::Tag("uk-UA"), &recent),
+        None
+    );
+}
+
+#[test]
+fn next_target_cycles_through_three_layouts() {
+    let layouts = installed(&["en-US", "ru-RU", "de-DE"]);
+    let recent = RecentLayouts::default();
+    assert_eq!(
+        choose_layout(&layouts, 1, LayoutTarget::Next, &recent),
+        Some(2)
+    );
+    assert_eq!(
+        choose_layout(&layouts, 2, LayoutTarget::Next, &recent),
+        Some(0)
+    );
+}
+
+#[test]
+fn recent_keeps_the_two_latest_distinct_layouts() {
+    let mut recent = RecentLayouts::default();
+    assert_eq!(recent.other_than(0), None);
+
+    recent.observe(0);
+    recent.observe(0);
+    assert_eq!(recent.other_than(0), None);
+
+    recent.observe(2);
+    assert_eq!(recent.other_than(2), Some(0));
+    assert_eq!(recent.other_than(1), Some(2));
+
+    recent.observe(1);
+    assert_eq!(recent.other_than(1), Some(2));
+}
+
+#[test]
+fn recent_target_toggles_between_the_last_two() {
+    let layouts = installed(&["en-US", "ru-RU", "de-DE"]);
+    let mut recent = RecentLayouts::default();
+    recent.observe(0);
+    recent.observe(2);
+
+    let chosen = choose_layout(&layouts, 2, LayoutTarget::Recent, &recent);
+    assert_eq!(chosen, Some(0));
+
+    recent.observe(0);
+    let chosen = choose_layout(&layouts, 0, LayoutTarget::Recent, &recent);
+    assert_eq!(chosen, Some(2));
+}
+
+#[test]
+fn recent_target_falls_back_to_next() {
+    let layouts = installed(&["en-US", "ru-RU", "de-DE"]);
+    let mut recent = RecentLayouts::default();
+    assert_eq!(
+        choose_layout(&layouts, 0, LayoutTarget::Recent, &recent),
+        Some(1)
+    );
+
+    // The recent layout was removed.
+    recent.observe(7);
+    recent.observe(1);
+    assert_eq!(
+        choose_layout(&layouts, 1, LayoutTarget::Recent, &recent),
+        Some(2)
+    );
+}
+
+#[test]
+fn single_layout_has_nothing_to_switch_to() {
+    let layouts = installed(&["en-US"]);
+    let recent = RecentLayouts::default();
+    for target in [LayoutTarget::Next, LayoutTarget::Recent] {
+        assert_eq!(choose_layout(&layouts, 0, target, &recent), None);
+    }
+}
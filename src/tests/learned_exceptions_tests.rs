@@ -0,0 +1,105 @@
+use crate::domain::text::learned_exceptions::{
+    EXCEPTION_THRESHOLD, HALF_LIFE_SECS, LearnedExceptions,
+};
+
+const T0: u64 = 1_700_000_000;
+
+#[test]
+fn one_revert_keeps_the_word_when_it_is_typed_again() {
+    let mut learned = LearnedExceptions::new();
+    // "ghbdtn" was autoconverted, backspaced over and is typed again a few seconds later.
+    learned.record_undo("ghbdtn", T0);
+
+    assert_eq!(learned.score("ghbdtn", T0), 1.0);
+    assert!(learned.is_exception("ghbdtn", T0 + 5));
+    assert!(learned.is_exception("Ghbdtn,", T0 + 60));
+    assert_eq!(learned.exceptions(T0 + 5).count(), 1);
+}
+
+#[test]
+fn one_undo_is_forgotten_after_it_halves() {
+    let mut learned = LearnedExceptions::new();
+    learned.record_undo("ghbdtn", T0);
+
+    let half = learned.score("ghbdtn", T0 + HALF_LIFE_SECS);
+    assert!((half - 0.5).abs() < 1e-9, "{half}");
+    assert!(learned.is_exception("ghbdtn", T0 + HALF_LIFE_SECS - 60));
+    assert!(!learned.is_exception("ghbdtn", T0 + HALF_LIFE_SECS + 60));
+}
+
+#[test]
+fn repeated_undos_accumulate_after_decay() {
+    let mut learned = LearnedExceptions::new();
+    learned.record_undo("ghbdtn", T0);
+    learned.record_undo("ghbdtn", T0 + HALF_LIFE_SECS);
+
+    let score = learned.score("ghbdtn", T0 + HALF_LIFE_SECS);
+    assert!((score - 1.5).abs() < 1e-9, "{score}");
+    assert!(learned.is_exception("ghbdtn", T0 + 2 * HALF_LIFE_SECS));
+    assert!(!learned.is_exception("ghbdtn", T0 + 3 * HALF_LIFE_SECS));
+}
+
+#[test]
+fn words_match_case_insensitively_without_trailing_punct() {
+    let mut learned = LearnedExceptions::new();
+    learned.record_undo("Ghbdtn,", T0);
+    learned.record_undo("GHBDTN", T0);
+
+    assert!(learned.is_exception("ghbdtn", T0));
+    assert!(learned.is_exception("GHBDTN?", T0));
+
+    learned.record_undo("  ", T0);
+    assert_eq!(learned.exceptions(T0).count(), 1);
+}
+
+#[test]
+fn remove_and_prune_forget_words() {
+    let mut learned = LearnedExceptions::new();
+    learned.record_undo("ghbdtn", T0);
+    learned.record_undo("rjirf", T0);
+
+    assert!(learned.remove("RJIRF"));
+    assert!(!learned.remove("rjirf"));
+
+    learned.prune(T0 + 10 * HALF_LIFE_SECS);
+    assert_eq!(learned.score("ghbdtn", T0), 0.0);
+    assert_eq!(learned, LearnedExceptions::new());
+}
+
+#[test]
+fn exceptions_list_only_words_above_threshold() {
+    let mut learned = LearnedExceptions::new();
+    for _ in 0..2 {
+        learned.record_undo("old", T0);
+        learned.record_undo("recent", T0 + 3 * HALF_LIFE_SECS);
+    }
+
+    let now = T0 + 3 * HALF_LIFE_SECS;
+    let words: Vec<&str> = learned.exceptions(now).map(|(w, _)| w).collect();
+    assert_eq!(words, ["recent"]);
+    assert!(learned.score("old", now) < EXCEPTION_THRESHOLD);
+}
+
+#[test]
+fn toml_roundtrip_keeps_scores_and_times() {
+    let mut learned = LearnedExceptions::new();
+    learned.record_undo("ghbdtn", T0);
+    learned.record_undo("ghbdtn", T0 + 60);
+    learned.record_undo("rjirf", T0 + 120);
+
+    let text = learned.to_toml().unwrap();
+    assert!(text.contains("ghbdtn"));
+    assert_eq!(LearnedExceptions::parse(&text).unwrap(), learned);
+
+    assert!(
+        LearnedExceptions::parse("")
+            .unwrap()
+            .exceptions(T0)
+            .next()
+            .is_none()
+    );
+    assert!(
+        LearnedExceptions::parse("[[words]]\nword = \"a b\"\nscore = 1.0\nupdated_at = 0\n")
+            .is_err()
+    );
+}
@@ -1,4 +1,17 @@
-use crate::domain::text::mapping::convert_ru_en_bidirectional;
+use crate::domain::text::{
+    layout::{self, LayoutPair, LayoutPairTable, LayoutTable},
+    mapping::{ConvertDirection, convert_ru_en_bidirectional, convert_with_pair},
+};
+
+type Convert = fn(&LayoutPair, &str) -> String;
+
+fn to_secondary(pair: &LayoutPair, text: &str) -> String {
+    layout::convert_between(text, pair.primary(), pair.secondary())
+}
+
+fn to_primary(pair: &LayoutPair, text: &str) -> String {
+    layout::convert_between(text, pair.secondary(), pair.primary())
+}
 
 const LATIN_BIJECTIVE: &str = "qwertyuiop[]asdfghjkl;'zxcvbnm,.`QWERTYUIOP{}ASDFGHJKL:\"ZXCVBNM<>~";
 
@@ -24,38 +37,246 @@ fn gen_string(seed: &mut u64, alphabet: &[char], max_len: usize) -> String {
     out
 }
 
-#[test]
-fn mapping_roundtrip_latin_only_is_identity_on_double_convert() {
-    let alphabet: Vec<char> = LATIN_BIJECTIVE.chars().collect();
+/// Primary characters whose counterpart is produced only by the secondary layout.
+///
+/// Strings over this alphabet converted to secondary and back must be unchanged.
+fn primary_bijective(pair: &LayoutPair) -> Vec<char> {
+    let mut out: Vec<char> = pair
+        .primary()
+        .chars()
+        .filter(|&ch| {
+            pair.primary()
+                .map_to(pair.secondary(), ch)
+                .is_some_and(|t| !pair.primary().contains(t))
+        })
+        .collect();
+    out.sort_unstable();
+    out
+}
 
-    let mut seed = 0xD1A5_3EED_5EED_1234u64;
+/// Secondary only characters that have a counterpart in the primary layout.
+fn secondary_bijective(pair: &LayoutPair) -> Vec<char> {
+    let mut out: Vec<char> = pair
+        .secondary()
+        .chars()
+        .filter(|&ch| {
+            !pair.primary().contains(ch) && pair.secondary().map_to(pair.primary(), ch).is_some()
+        })
+        .collect();
+    out.sort_unstable();
+    out
+}
+
+fn assert_double_convert_is_identity(
+    pair: &LayoutPair,
+    alphabet: &[char],
+    from_primary: bool,
+    mut seed: u64,
+) {
+    assert!(!alphabet.is_empty(), "empty alphabet for {}", pair.id());
+
+    let (there, back): (Convert, Convert) = if from_primary {
+        (to_secondary, to_primary)
+    } else {
+        (to_primary, to_secondary)
+    };
     for _ in 0..2000 {
-        let s = gen_string(&mut seed, &alphabet, 64);
-        let t = convert_ru_en_bidirectional(&s);
-        let u = convert_ru_en_bidirectional(&t);
-        assert_eq!(u, s, "latin roundtrip failed: s={s:?} t={t:?} u={u:?}");
+        let s = gen_string(&mut seed, alphabet, 64);
+        let t = there(pair, &s);
+        let u = back(pair, &t);
+        assert_eq!(
+            u,
+            s,
+            "{} roundtrip failed: s={s:?} t={t:?} u={u:?}",
+            pair.id()
+        );
     }
 }
 
 #[test]
-fn mapping_roundtrip_cyrillic_only_is_identity_on_double_convert() {
-    let alphabet: Vec<char> = CYRILLIC_BIJECTIVE.chars().collect();
+fn builtin_pairs_are_shipped_and_compile() {
+    let ids: Vec<&str> = layout::builtin_pairs().iter().map(|p| p.id()).collect();
+    assert!(ids.contains(&layout::RU_EN), "{ids:?}");
+}
 
-    let mut seed = 0xBADC_0FFE_EE12_3456u64;
-    for _ in 0..2000 {
-        let s = gen_string(&mut seed, &alphabet, 64);
-        let t = convert_ru_en_bidirectional(&s);
-        let u = convert_ru_en_bidirectional(&t);
-        assert_eq!(u, s, "cyr roundtrip failed: s={s:?} t={t:?} u={u:?}");
+#[test]
+fn mapping_roundtrip_primary_only_is_identity_on_double_convert_for_every_pair() {
+    for pair in layout::builtin_pairs() {
+        let alphabet = primary_bijective(pair);
+        assert_double_convert_is_identity(pair, &alphabet, true, 0xD1A5_3EED_5EED_1234u64);
+    }
+}
+
+#[test]
+fn mapping_roundtrip_secondary_only_is_identity_on_double_convert_for_every_pair() {
+    for pair in layout::builtin_pairs() {
+        let alphabet = secondary_bijective(pair);
+        assert_double_convert_is_identity(pair, &alphabet, false, 0xBADC_0FFE_EE12_3456u64);
+    }
+}
+
+#[test]
+fn mapping_is_injective_on_same_key_for_every_pair() {
+    for pair in layout::builtin_pairs() {
+        for ch in pair.primary().chars() {
+            if let Some(t) = pair.primary().map_to(pair.secondary(), ch) {
+                let back = pair.secondary().map_to(pair.primary(), t);
+                // A character on both levels of one key maps back to the base level,
+                // which maps to it again.
+                let base_level = back.and_then(|b| pair.primary().map_to(pair.secondary(), b));
+                assert!(
+                    back == Some(ch) || base_level == Some(t),
+                    "{}: {ch:?} -> {t:?} -> {back:?}",
+                    pair.id()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn ru_en_bijective_alphabets_match_table() {
+    let pair = layout::builtin_pair(layout::RU_EN).unwrap();
+
+    let mut latin: Vec<char> = LATIN_BIJECTIVE.chars().collect();
+    latin.sort_unstable();
+    assert_eq!(primary_bijective(&pair), latin);
+
+    let mut cyrillic: Vec<char> = CYRILLIC_BIJECTIVE.chars().collect();
+    cyrillic.sort_unstable();
+    assert_eq!(secondary_bijective(&pair), cyrillic);
+}
+
+#[test]
+fn ru_en_bidirectional_converts_with_the_builtin_pair() {
+    let pair = layout::builtin_pair(layout::RU_EN).unwrap();
+    for text in ["ghbdtn", "руддщ", "ghbdtn, мир", "Ghbdtn!", "123"] {
+        assert_eq!(
+            convert_ru_en_bidirectional(text),
+            convert_with_pair(text, &pair, ConvertDirection::Auto),
+            "{text}"
+        );
     }
+    assert_eq!(convert_ru_en_bidirectional("ghbdtn, мир"), "привет, мир");
+    assert_eq!(convert_ru_en_bidirectional("руддщ"), "hello");
 }
 
 #[test]
-fn punctuation_rules_apply_only_in_en_to_ru_mode() {
-    // Lat dominates: en_to_ru, so '/' -> '.' and '?' -> ','
-    assert_eq!(convert_ru_en_bidirectional("a/"), "ф.");
-    assert_eq!(convert_ru_en_bidirectional("a?"), "ф,");
+fn punctuation_maps_through_its_key_in_the_source_layout() {
+    let pair = layout::builtin_pair(layout::RU_EN).unwrap();
+
+    // In EN '/' and '?' are on the key of RU '.' and ','.
+    assert_eq!(to_secondary(&pair, "a/"), "ф.");
+    assert_eq!(to_secondary(&pair, "a?"), "ф,");
+
+    // The RU table has no key for '/', so it is copied unchanged.
+    assert_eq!(to_primary(&pair, "/а"), "/f");
+}
+
+fn table(id: &str, primary_base: &str, secondary_base: &str) -> LayoutPairTable {
+    LayoutPairTable {
+        id: id.to_string(),
+        name: id.to_string(),
+        primary: LayoutTable {
+            tag: "xx".to_string(),
+            base: vec![String::new(), primary_base.to_string()],
+            shift: Vec::new(),
+        },
+        secondary: LayoutTable {
+            tag: "yy".to_string(),
+            base: vec![String::new(), secondary_base.to_string()],
+            shift: Vec::new(),
+        },
+    }
+}
+
+#[test]
+fn table_rejects_duplicate_characters_and_long_rows() {
+    let dup = table("dup", "qq", "ab");
+    assert!(LayoutPair::from_table(&dup).unwrap_err().contains("twice"));
+
+    // The same character with and without Shift on one key is fine.
+    let mut same_key = table("same-key", "q", "'");
+    same_key.secondary.shift = vec![String::new(), "'".to_string()];
+    assert!(LayoutPair::from_table(&same_key).is_ok());
+
+    let long = table("long", "qwertyuiop[]\\x", "ab");
+    assert!(LayoutPair::from_table(&long).is_err());
+}
+
+#[test]
+fn table_spaces_leave_keys_unmapped() {
+    let pair = LayoutPair::from_table(&table("gap", "q e", "й у")).unwrap();
+    assert_eq!(
+        convert_with_pair("qwe", &pair, ConvertDirection::Auto),
+        "йwу"
+    );
+}
+
+#[test]
+fn user_tables_override_builtin_by_id_and_extend_the_list() {
+    let tables = [table(layout::RU_EN, "q", "й"), table("xx-yy", "q", "x")];
+    let pairs = layout::merge_with_builtin(&tables).unwrap();
+
+    let ru_en = pairs.iter().find(|p| p.id() == layout::RU_EN).unwrap();
+    assert_eq!(convert_with_pair("qw", ru_en, ConvertDirection::Auto), "йw");
+    assert!(pairs.iter().any(|p| p.id() == "xx-yy"));
+}
+
+#[test]
+fn builtin_table_file_parses() {
+    let text = include_str!("../../res/layouts/ru-en.toml");
+    let t = LayoutPairTable::parse(text).unwrap();
+    assert_eq!(t.id, layout::RU_EN);
+    assert_eq!(t.primary.tag, "en-US");
+    assert_eq!(t.secondary.tag, "ru-RU");
+}
+
+#[test]
+fn kazakh_words_round_trip_and_numbers_stay() {
+    let pair = layout::builtin_pair("kk-en").unwrap();
+    let auto = |text: &str| convert_with_pair(text, &pair, ConvertDirection::Auto);
+
+    for (typed, word) in [
+        ("0fpf0", "қазақ"),
+        ("c2ktv", "сәлем"),
+        ("hf[vtn", "рахмет"),
+        ("3ktv", "ілем"),
+        ("0fpf0 n3k3", "қазақ тілі"),
+    ] {
+        assert_eq!(auto(typed), word);
+        assert_eq!(auto(word), typed);
+    }
+
+    assert_eq!(auto("0fpf0 2024"), "қазақ 2024");
+    assert_eq!(auto("қазақ 2024"), "0fpf0 2024");
+    assert_eq!(auto("3.14 c2ktv"), "3.14 сәлем");
+    assert_eq!(auto("2024-10-18"), "2024-10-18");
+}
+
+#[test]
+fn builtin_pairs_map_language_specific_letters() {
+    let convert = |id: &str, text: &str| to_secondary(&layout::builtin_pair(id).unwrap(), text);
+    let back = |id: &str, text: &str| to_primary(&layout::builtin_pair(id).unwrap(), text);
+
+    // Ukrainian: і, ї, є, ґ and the apostrophe on the grave key.
+    assert_eq!(convert("uk-en", "ghbdsn"), "привіт");
+    assert_eq!(back("uk-en", "ї'є\\ґ"), "]`'\\\\");
+    assert_eq!(convert("uk-en", "v`zcj"), "м'ясо");
+
+    // Belarusian: ў on the щ key, і on the и key.
+    assert_eq!(convert("be-en", "lpzre.]"), "дзякую'");
+    assert_eq!(back("be-en", "ўі"), "ob");
+    assert_eq!(convert("be-en", "]}"), "''");
+    assert_eq!(back("be-en", "'"), "]");
+
+    // Kazakh: ә і ң ғ ү ұ қ ө һ on the digit row, on both levels.
+    assert_eq!(convert("kk-en", "2345890-="), "әіңғүұқөһ");
+    assert_eq!(convert("kk-en", "@#$%*()_+"), "ӘІҢҒҮҰҚӨҺ");
+    assert_eq!(back("kk-en", "ҚАЗАҚ"), ")FPF)");
 
-    // Cyr dominates: ru_to_en, so '/' is not remapped by en_to_ru punctuation rule
-    assert_eq!(convert_ru_en_bidirectional("/а"), "/f");
+    // German QWERTZ: swapped Y/Z, umlauts and ß.
+    assert_eq!(convert("de-ru", "zeit"), "нуше");
+    assert_eq!(convert("de-ru", "üöäß"), "хжэ-");
+    assert_eq!(back("de-ru", "Ыекф-у"), "Straße");
 }
@@ -1,11 +1,46 @@
 #![allow(clippy::expect_used, clippy::unwrap_used)]
 
+use lingua::Language;
+
+use crate::domain::text::{autoconvert::PairLanguages, mapping::convert_ru_en_bidirectional};
+
+pub mod app_rules_tests;
+pub mod autoconvert_decider_tests;
+pub mod autoconvert_state_tests;
+pub mod case_fix_tests;
+pub mod config_hotkeys_tests;
 pub mod config_io_tests;
 pub mod config_path_tests;
 pub mod config_validator_tests;
+#[cfg(feature = "frequency-scorer")]
+pub mod frequency_scorer_tests;
+pub mod history_tests;
 pub mod hotkey_format_tests;
+pub mod hotkey_text_tests;
+pub mod key_replay_tests;
 pub mod keyboard_sequence_tests;
 pub mod keyboard_vk_tests;
+pub mod layout_convert_tests;
+pub mod layout_memory_tests;
+pub mod layout_policy_tests;
+pub mod learned_exceptions_tests;
 pub mod mapping_invariants_tests;
 pub mod on_hotkey_tests;
 pub mod ring_buffer_tests;
+pub mod selection_tests;
+pub mod sequence_matcher_tests;
+pub mod snippets_tests;
+pub mod transform_tests;
+pub mod user_dictionary_tests;
+pub mod word_boundaries_tests;
+
+/// Languages of the built-in `ru-en` pair.
+pub const RU_EN_LANGS: PairLanguages = PairLanguages {
+    latin: Language::English,
+    cyrillic: Language::Russian,
+};
+
+/// Converts `text` with the built-in `ru-en` pair, in the direction its script calls for.
+pub fn ru_en(text: &str) -> String {
+    convert_ru_en_bidirectional(text)
+}
@@ -1,78 +1,81 @@
+#![cfg(windows)]
+
 use windows::Win32::Foundation::WPARAM;
 
 use crate::{
-    input::hotkeys::{
-        HK_CONVERT_LAST_WORD_ID, HK_CONVERT_SELECTION_ID, HK_PAUSE_TOGGLE_ID, HK_SWITCH_LAYOUT_ID,
-        HotkeyAction, action_from_id,
+    app::actions::{
+        self, ACTIONS, CONVERT_LAST_WORD, CONVERT_SELECTION, PAUSE, SWITCH_LAYOUT,
+        TOGGLE_RECENT_LAYOUTS, UNDO_LAST_CONVERSION,
     },
-    platform::win::{hotkey_action_from_wparam, hotkey_id_from_wparam},
+    input::hotkeys::{action_from_id, hotkey_id},
+    platform::win::{action_handlers, hotkey_action_from_wparam, hotkey_id_from_wparam},
 };
 
+fn wparam(id: i32) -> WPARAM {
+    WPARAM(id as usize)
+}
+
 #[test]
 fn hotkey_id_from_wparam_roundtrip() {
-    assert_eq!(
-        hotkey_id_from_wparam(WPARAM(HK_CONVERT_LAST_WORD_ID as usize)),
-        HK_CONVERT_LAST_WORD_ID
-    );
-    assert_eq!(
-        hotkey_id_from_wparam(WPARAM(HK_PAUSE_TOGGLE_ID as usize)),
-        HK_PAUSE_TOGGLE_ID
-    );
-    assert_eq!(
-        hotkey_id_from_wparam(WPARAM(HK_CONVERT_SELECTION_ID as usize)),
-        HK_CONVERT_SELECTION_ID
-    );
-    assert_eq!(
-        hotkey_id_from_wparam(WPARAM(HK_SWITCH_LAYOUT_ID as usize)),
-        HK_SWITCH_LAYOUT_ID
-    );
+    for action in &ACTIONS {
+        let id = hotkey_id(action.id);
+        assert_eq!(hotkey_id_from_wparam(wparam(id)), id);
+    }
+}
+
+#[test]
+fn hotkey_ids_are_distinct_and_clear_of_control_ids() {
+    let mut ids: Vec<i32> = ACTIONS.iter().map(|a| hotkey_id(a.id)).collect();
+    assert!(ids.iter().all(|&id| id > 20000));
+
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), ACTIONS.len());
 }
 
 #[test]
 fn action_from_id_known_values() {
-    assert_eq!(
-        action_from_id(HK_CONVERT_LAST_WORD_ID),
-        Some(HotkeyAction::ConvertLastWord)
-    );
-    assert_eq!(
-        action_from_id(HK_PAUSE_TOGGLE_ID),
-        Some(HotkeyAction::PauseToggle)
-    );
-    assert_eq!(
-        action_from_id(HK_CONVERT_SELECTION_ID),
-        Some(HotkeyAction::ConvertSelection)
-    );
-    assert_eq!(
-        action_from_id(HK_SWITCH_LAYOUT_ID),
-        Some(HotkeyAction::SwitchLayout)
-    );
+    for id in [
+        CONVERT_LAST_WORD,
+        PAUSE,
+        CONVERT_SELECTION,
+        SWITCH_LAYOUT,
+        UNDO_LAST_CONVERSION,
+        TOGGLE_RECENT_LAYOUTS,
+    ] {
+        assert_eq!(action_from_id(hotkey_id(id)), Some(id));
+    }
+}
+
+#[test]
+fn layout_activations_get_hotkey_ids_after_the_registry() {
+    let en = actions::activate_layout("en-US").id;
+    let ru = actions::activate_layout("ru-RU").id;
+
+    assert_ne!(hotkey_id(en), hotkey_id(ru));
+    for id in [en, ru] {
+        assert!(hotkey_id(id) > hotkey_id(TOGGLE_RECENT_LAYOUTS));
+        assert_eq!(action_from_id(hotkey_id(id)), Some(id));
+        assert_eq!(hotkey_action_from_wparam(wparam(hotkey_id(id))), Some(id));
+    }
 }
 
 #[test]
 fn action_from_id_unknown_is_none() {
     assert_eq!(action_from_id(0), None);
     assert_eq!(action_from_id(19999), None);
+    assert_eq!(action_from_id(20000), None);
     assert_eq!(action_from_id(29999), None);
 }
 
 #[test]
 fn hotkey_action_from_wparam_known_values() {
-    assert_eq!(
-        hotkey_action_from_wparam(WPARAM(HK_CONVERT_LAST_WORD_ID as usize)),
-        Some(HotkeyAction::ConvertLastWord)
-    );
-    assert_eq!(
-        hotkey_action_from_wparam(WPARAM(HK_PAUSE_TOGGLE_ID as usize)),
-        Some(HotkeyAction::PauseToggle)
-    );
-    assert_eq!(
-        hotkey_action_from_wparam(WPARAM(HK_CONVERT_SELECTION_ID as usize)),
-        Some(HotkeyAction::ConvertSelection)
-    );
-    assert_eq!(
-        hotkey_action_from_wparam(WPARAM(HK_SWITCH_LAYOUT_ID as usize)),
-        Some(HotkeyAction::SwitchLayout)
-    );
+    for action in &ACTIONS {
+        assert_eq!(
+            hotkey_action_from_wparam(wparam(hotkey_id(action.id))),
+            Some(action.id)
+        );
+    }
 }
 
 #[test]
@@ -81,3 +84,14 @@ fn hotkey_action_from_wparam_unknown_is_none() {
     assert_eq!(hotkey_action_from_wparam(WPARAM(19999)), None);
     assert_eq!(hotkey_action_from_wparam(WPARAM(29999)), None);
 }
+
+#[test]
+fn every_action_has_a_handler() {
+    for action in &ACTIONS {
+        assert!(
+            action_handlers::handler(action.id).is_some(),
+            "no handler for {}",
+            action.id.as_str()
+        );
+    }
+}
@@ -1,12 +1,18 @@
-use crate::input::ring_buffer;
+use crate::input::ring_buffer::{self, InputJournal, LastWord, Span};
+
+fn journal(text: &str) -> InputJournal {
+    let mut journal = InputJournal::new(100);
+    journal.push_str(text);
+    journal
+}
 
 #[test]
 fn take_last_word_with_suffix_basic() {
     ring_buffer::invalidate();
     ring_buffer::push_text("hello world   ");
     let p = ring_buffer::take_last_word_with_suffix().expect("expected payload");
-    assert_eq!(p.0, "world");
-    assert_eq!(p.1, "   ");
+    assert_eq!(p.word, "world");
+    assert_eq!(p.suffix, "   ");
 }
 
 #[test]
@@ -18,19 +24,15 @@ fn take_last_word_with_suffix_ignores_trailing_whitespace_only() {
 
 #[test]
 fn last_char_triggers_autoconvert_on_first_whitespace_after_word() {
-    ring_buffer::invalidate();
-    ring_buffer::push_text("abc");
-    assert!(!ring_buffer::last_char_triggers_autoconvert());
-    ring_buffer::push_text(" ");
-    assert!(ring_buffer::last_char_triggers_autoconvert());
+    let mut journal = journal("abc");
+    assert!(!journal.last_char_triggers_autoconvert());
+    journal.push_str(" ");
+    assert!(journal.last_char_triggers_autoconvert());
 }
 
 #[test]
 fn last_char_triggers_autoconvert_on_punctuation_after_nonspace() {
-    ring_buffer::invalidate();
-    ring_buffer::push_text("abc");
-    ring_buffer::push_text(".");
-    assert!(ring_buffer::last_char_triggers_autoconvert());
+    assert!(journal("abc.").last_char_triggers_autoconvert());
 }
 
 #[test]
@@ -57,7 +59,7 @@ fn mark_last_token_autoconverted_roundtrip() {
 fn take_last_word_with_suffix_keeps_trailing_punct_in_word_when_suffix_is_spaces() {
     ring_buffer::invalidate();
     ring_buffer::push_text("hello, ");
-    let (word, suffix) = ring_buffer::take_last_word_with_suffix().unwrap();
+    let LastWord { word, suffix, .. } = ring_buffer::take_last_word_with_suffix().unwrap();
     assert_eq!(word, "hello,");
     assert_eq!(suffix, " ");
 }
@@ -66,7 +68,7 @@ fn take_last_word_with_suffix_keeps_trailing_punct_in_word_when_suffix_is_spaces
 fn take_last_word_with_suffix_splits_on_newline() {
     ring_buffer::invalidate();
     ring_buffer::push_text("hello\nworld ");
-    let (word, suffix) = ring_buffer::take_last_word_with_suffix().unwrap();
+    let LastWord { word, suffix, .. } = ring_buffer::take_last_word_with_suffix().unwrap();
     assert_eq!(word, "world");
     assert_eq!(suffix, " ");
 }
@@ -75,7 +77,7 @@ fn take_last_word_with_suffix_splits_on_newline() {
 fn take_last_word_with_suffix_handles_tabs_and_multiple_spaces() {
     ring_buffer::invalidate();
     ring_buffer::push_text("hello\tworld   ");
-    let (word, suffix) = ring_buffer::take_last_word_with_suffix().unwrap();
+    let LastWord { word, suffix, .. } = ring_buffer::take_last_word_with_suffix().unwrap();
     assert_eq!(word, "world");
     assert_eq!(suffix, "   ");
 }
@@ -84,7 +86,7 @@ fn take_last_word_with_suffix_handles_tabs_and_multiple_spaces() {
 fn take_last_word_with_suffix_single_letter_with_punct() {
     ring_buffer::invalidate();
     ring_buffer::push_text("a. ");
-    let (word, suffix) = ring_buffer::take_last_word_with_suffix().unwrap();
+    let LastWord { word, suffix, .. } = ring_buffer::take_last_word_with_suffix().unwrap();
     assert_eq!(word, "a.");
     assert_eq!(suffix, " ");
 }
@@ -94,3 +96,69 @@ fn take_last_word_with_suffix_returns_none_when_buffer_empty() {
     ring_buffer::invalidate();
     assert!(ring_buffer::take_last_word_with_suffix().is_none());
 }
+
+#[test]
+fn restore_puts_back_a_snapshot() {
+    ring_buffer::invalidate();
+    ring_buffer::push_text("ghbdtn ");
+    let before = ring_buffer::snapshot();
+
+    ring_buffer::push_text("vbh");
+    assert_eq!(ring_buffer::snapshot(), "ghbdtn vbh");
+
+    ring_buffer::restore(&before);
+    assert_eq!(ring_buffer::snapshot(), "ghbdtn ");
+}
+
+#[test]
+fn take_last_words_keeps_what_separates_them() {
+    let mut j = journal("one ghbdtn,  vbh\t ");
+    let last = j.take_last(Span::Words(2)).unwrap();
+    assert_eq!(last.word, "ghbdtn,  vbh");
+    assert_eq!(last.suffix, "\t ");
+    assert_eq!(last.words, 2);
+    assert_eq!(j.text(), "one ");
+}
+
+#[test]
+fn take_last_words_stops_at_the_line_start() {
+    let mut j = journal("first\nghbdtn vbh ");
+    let last = j.take_last(Span::Words(5)).unwrap();
+    assert_eq!(last.word, "ghbdtn vbh");
+    assert_eq!(last.words, 2);
+    assert_eq!(j.text(), "first\n");
+
+    let mut j = journal("ghbdtn");
+    let last = j.take_last(Span::Words(3)).unwrap();
+    assert_eq!(last.word, "ghbdtn");
+    assert_eq!(last.words, 1);
+}
+
+#[test]
+fn take_last_phrase_starts_after_the_sentence_end() {
+    let mut j = journal("Done. Yes! ghbdtn? rfr ltkf. ");
+    let last = j.take_last(Span::Phrase).unwrap();
+    assert_eq!(last.word, "rfr ltkf.");
+    assert_eq!(last.suffix, " ");
+    assert_eq!(last.words, 2);
+    assert_eq!(j.text(), "Done. Yes! ghbdtn? ");
+}
+
+#[test]
+fn take_last_phrase_ignores_sentence_ends_inside_words() {
+    let mut j = journal("gj.kf. dctv");
+    let last = j.take_last(Span::Phrase).unwrap();
+    assert_eq!(last.word, "dctv");
+
+    let mut j = journal("line\n  z.,sr ldf");
+    let last = j.take_last(Span::Phrase).unwrap();
+    assert_eq!(last.word, "z.,sr ldf");
+    assert_eq!(j.text(), "line\n  ");
+}
+
+#[test]
+fn take_last_phrase_without_words_leaves_the_journal() {
+    let mut j = journal(" \n\t ");
+    assert!(j.take_last(Span::Phrase).is_none());
+    assert_eq!(j.text(), " \n\t ");
+}
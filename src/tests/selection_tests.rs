@@ -0,0 +1,86 @@
+use crate::{
+    config::Config,
+    domain::text::{
+        layout,
+        mapping::{ConvertDirection, convert_with_layouts},
+        selection::{
+            caret_steps, clipboard_line_endings, has_line_break, is_convertible_selection, runs,
+        },
+    },
+};
+
+#[test]
+fn multi_line_selections_are_convertible_up_to_the_bound() {
+    assert!(is_convertible_selection("ghbdtn\r\nvbh", 100));
+    assert!(is_convertible_selection("\n", 1));
+    assert!(is_convertible_selection("abc", 3));
+    assert!(!is_convertible_selection("abcd", 3));
+    assert!(!is_convertible_selection("", 100));
+}
+
+#[test]
+fn every_kind_of_line_break_is_found() {
+    assert!(has_line_break("one\r\ntwo"));
+    assert!(has_line_break("one\ntwo"));
+    assert!(has_line_break("one\rtwo"));
+    assert!(has_line_break("\n"));
+    assert!(!has_line_break("one two"));
+    assert!(!has_line_break(""));
+}
+
+#[test]
+fn long_lines_are_chunked_without_splitting_characters() {
+    assert_eq!(runs("ab😀cd", 3), vec!["ab", "😀c", "d"]);
+
+    let long = "ф".repeat(1000);
+    let got = runs(&long, 256);
+    assert_eq!(got.len(), 4);
+    assert_eq!(got.concat(), long);
+}
+
+#[test]
+fn a_character_longer_than_a_chunk_is_its_own_chunk() {
+    assert_eq!(runs("😀😀", 1), vec!["😀", "😀"]);
+}
+
+#[test]
+fn pasted_line_breaks_are_crlf_and_keep_their_caret_steps() {
+    assert_eq!(clipboard_line_endings("a\nb\r\nc\rd"), "a\r\nb\r\nc\rd");
+    assert_eq!(clipboard_line_endings("one line"), "one line");
+
+    let snippet = "Best regards,\nJohn";
+    assert_eq!(
+        caret_steps(&clipboard_line_endings(snippet)),
+        snippet.chars().count()
+    );
+}
+
+#[test]
+fn caret_steps_count_each_line_break_once() {
+    assert_eq!(caret_steps(""), 0);
+    assert_eq!(caret_steps("руд"), 3);
+    assert_eq!(caret_steps("a\r\nb"), 3);
+    assert_eq!(caret_steps("a\nb\rc"), 5);
+}
+
+#[test]
+fn conversion_keeps_line_endings() {
+    let pair = layout::builtin_pair(layout::RU_EN).unwrap();
+    let converted = convert_with_layouts(
+        "ghbdtn\r\nvbh\n\nghbdtn",
+        pair.primary(),
+        pair.secondary(),
+        ConvertDirection::Auto,
+    );
+    assert_eq!(converted, "привет\r\nмир\n\nпривет");
+}
+
+#[test]
+fn selection_bound_defaults_and_round_trips() {
+    let cfg = Config::parse("delay_ms = 100\n").unwrap();
+    assert_eq!(cfg.max_selection_chars, 10_000);
+
+    let cfg = Config::parse("delay_ms = 100\nmax_selection_chars = 50000\n").unwrap();
+    let text = toml::to_string(&cfg).unwrap();
+    assert_eq!(Config::parse(&text).unwrap().max_selection_chars, 50_000);
+}
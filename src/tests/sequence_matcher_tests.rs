@@ -0,0 +1,382 @@
+use crate::{
+    app::{
+        HotkeyBindings,
+        actions::{
+            ActionId, CONVERT_LAST_WORD, CONVERT_SELECTION, PAUSE, SWITCH_LAYOUT,
+            UNDO_LAST_CONVERSION,
+        },
+    },
+    config::{HotkeyChord, HotkeySequence, HotkeyStep, PressStyle},
+    input::sequence_matcher::{ChordInput, MatchOutcome, SequenceMatcher, release_window_ms},
+};
+
+const CTRL: u32 = 0x0002;
+const SHIFT: u32 = 0x0004;
+const MODVK_LSHIFT: u32 = 1 << 2;
+
+fn key(mods: u32, vk: u32) -> HotkeyChord {
+    HotkeyChord {
+        mods,
+        mods_vks: 0,
+        vk: Some(vk),
+        press: PressStyle::Press,
+    }
+}
+
+fn lshift_tap() -> HotkeyChord {
+    HotkeyChord {
+        mods: SHIFT,
+        mods_vks: MODVK_LSHIFT,
+        vk: None,
+        press: PressStyle::Press,
+    }
+}
+
+/// Input of `chord` at `t`: the press of a key, or the instant release of modifiers.
+fn at(chord: HotkeyChord, t: u64) -> ChordInput {
+    if chord.vk.is_some() {
+        ChordInput::down(chord, t)
+    } else {
+        ChordInput::up(chord, 0, None, t)
+    }
+}
+
+fn bind(sequences: &[(ActionId, HotkeySequence)]) -> HotkeyBindings {
+    let mut bindings = HotkeyBindings::default();
+    for (id, seq) in sequences {
+        bindings.set(*id, Some(seq.clone()));
+    }
+    bindings
+}
+
+fn feed_all(
+    matcher: &mut SequenceMatcher,
+    sequences: &HotkeyBindings,
+    chords: &[(HotkeyChord, u64)],
+) -> Vec<MatchOutcome> {
+    chords
+        .iter()
+        .map(|&(chord, t)| matcher.feed(sequences, at(chord, t)))
+        .collect()
+}
+
+#[test]
+fn triple_tap_completes_on_the_third_tap() {
+    let sequences = bind(&[(
+        UNDO_LAST_CONVERSION,
+        HotkeySequence::new(&[lshift_tap(); 3], 400),
+    )]);
+    let mut m = SequenceMatcher::default();
+
+    let out = feed_all(
+        &mut m,
+        &sequences,
+        &[(lshift_tap(), 0), (lshift_tap(), 300), (lshift_tap(), 600)],
+    );
+    assert_eq!(
+        out,
+        [
+            MatchOutcome::Progress,
+            MatchOutcome::Progress,
+            MatchOutcome::Completed(UNDO_LAST_CONVERSION)
+        ]
+    );
+}
+
+#[test]
+fn chord_sequence_with_a_plain_key() {
+    let sequences = bind(&[(
+        CONVERT_SELECTION,
+        HotkeySequence::new(&[key(CTRL, 0x4B), key(CTRL, 0x52), key(0, 0x4C)], 1000),
+    )]);
+    let mut m = SequenceMatcher::default();
+
+    assert_eq!(
+        m.feed(&sequences, at(key(CTRL, 0x4B), 0)),
+        MatchOutcome::Progress
+    );
+    assert_eq!(
+        m.feed(&sequences, at(key(CTRL, 0x52), 100)),
+        MatchOutcome::Progress
+    );
+    assert_eq!(
+        m.feed(&sequences, at(key(0, 0x4C), 200)),
+        MatchOutcome::Completed(CONVERT_SELECTION)
+    );
+    assert_eq!(
+        m.feed(&sequences, at(key(0, 0x4C), 300)),
+        MatchOutcome::NoMatch
+    );
+}
+
+#[test]
+fn a_wrong_chord_breaks_the_sequence() {
+    let sequences = bind(&[(
+        CONVERT_SELECTION,
+        HotkeySequence::new(&[key(CTRL, 0x4B), key(CTRL, 0x52)], 1000),
+    )]);
+    let mut m = SequenceMatcher::default();
+
+    assert_eq!(
+        m.feed(&sequences, at(key(CTRL, 0x4B), 0)),
+        MatchOutcome::Progress
+    );
+    assert_eq!(
+        m.feed(&sequences, at(key(0, 0x41), 50)),
+        MatchOutcome::NoMatch
+    );
+    assert_eq!(
+        m.feed(&sequences, at(key(CTRL, 0x52), 100)),
+        MatchOutcome::NoMatch
+    );
+}
+
+#[test]
+fn each_step_has_its_own_gap() {
+    let seq = HotkeySequence {
+        steps: vec![
+            HotkeyStep {
+                chord: key(CTRL, 0x4B),
+                max_gap_ms: 0,
+            },
+            HotkeyStep {
+                chord: key(CTRL, 0x52),
+                max_gap_ms: 2000,
+            },
+            HotkeyStep {
+                chord: key(0, 0x4C),
+                max_gap_ms: 100,
+            },
+        ],
+    };
+    let sequences = bind(&[(PAUSE, seq)]);
+
+    let mut m = SequenceMatcher::default();
+    let out = feed_all(
+        &mut m,
+        &sequences,
+        &[
+            (key(CTRL, 0x4B), 0),
+            (key(CTRL, 0x52), 1500),
+            (key(0, 0x4C), 1550),
+        ],
+    );
+    assert_eq!(out[2], MatchOutcome::Completed(PAUSE));
+
+    let mut m = SequenceMatcher::default();
+    let out = feed_all(
+        &mut m,
+        &sequences,
+        &[
+            (key(CTRL, 0x4B), 0),
+            (key(CTRL, 0x52), 1500),
+            (key(0, 0x4C), 1700),
+        ],
+    );
+    assert_eq!(out[2], MatchOutcome::NoMatch);
+}
+
+#[test]
+fn a_late_tap_starts_over() {
+    let sequences = bind(&[(
+        CONVERT_LAST_WORD,
+        HotkeySequence::new(&[lshift_tap(); 2], 1000),
+    )]);
+    let mut m = SequenceMatcher::default();
+
+    let out = feed_all(
+        &mut m,
+        &sequences,
+        &[
+            (lshift_tap(), 0),
+            (lshift_tap(), 1500),
+            (lshift_tap(), 1900),
+        ],
+    );
+    assert_eq!(
+        out,
+        [
+            MatchOutcome::Progress,
+            MatchOutcome::Progress,
+            MatchOutcome::Completed(CONVERT_LAST_WORD)
+        ]
+    );
+}
+
+#[test]
+fn slots_progress_together_and_the_first_complete_one_wins() {
+    let sequences = bind(&[
+        (
+            CONVERT_LAST_WORD,
+            HotkeySequence::new(&[lshift_tap(); 2], 1000),
+        ),
+        (
+            CONVERT_SELECTION,
+            HotkeySequence::new(&[lshift_tap(); 2], 1000),
+        ),
+        (
+            UNDO_LAST_CONVERSION,
+            HotkeySequence::new(&[lshift_tap(); 3], 1000),
+        ),
+    ]);
+    let mut m = SequenceMatcher::default();
+
+    assert_eq!(
+        m.feed(&sequences, at(lshift_tap(), 0)),
+        MatchOutcome::Progress
+    );
+    assert_eq!(
+        m.feed(&sequences, at(lshift_tap(), 100)),
+        MatchOutcome::Completed(CONVERT_LAST_WORD)
+    );
+    // Completion resets every partial match, so the triple tap needs three new taps.
+    assert_eq!(
+        m.feed(&sequences, at(lshift_tap(), 200)),
+        MatchOutcome::Progress
+    );
+}
+
+#[test]
+fn single_chords_complete_immediately() {
+    let sequences = bind(&[(SWITCH_LAYOUT, HotkeySequence::new(&[key(0, 0x14)], 1000))]);
+    let mut m = SequenceMatcher::default();
+
+    assert_eq!(
+        m.feed(&sequences, at(key(0, 0x14), 0)),
+        MatchOutcome::Completed(SWITCH_LAYOUT)
+    );
+    assert_eq!(
+        m.feed(&sequences, at(key(0, 0x41), 10)),
+        MatchOutcome::NoMatch
+    );
+}
+
+const VK_CAPITAL: u32 = 0x14;
+
+fn styled(chord: HotkeyChord, press: PressStyle) -> HotkeyChord {
+    HotkeyChord { press, ..chord }
+}
+
+fn caps_with(press: PressStyle) -> HotkeyBindings {
+    bind(&[(
+        SWITCH_LAYOUT,
+        HotkeySequence::new(&[styled(key(0, VK_CAPITAL), press)], 1000),
+    )])
+}
+
+#[test]
+fn a_tap_waits_for_a_quick_release() {
+    let sequences = caps_with(PressStyle::Tap { max_ms: 300 });
+    let caps = key(0, VK_CAPITAL);
+    let mut m = SequenceMatcher::default();
+
+    assert_eq!(
+        m.feed(&sequences, ChordInput::down(caps, 0)),
+        MatchOutcome::Progress
+    );
+    assert_eq!(
+        m.feed(&sequences, ChordInput::up(caps, 120, None, 120)),
+        MatchOutcome::Completed(SWITCH_LAYOUT)
+    );
+    assert_eq!(
+        m.feed(&sequences, ChordInput::up(caps, 600, None, 2000)),
+        MatchOutcome::NoMatch
+    );
+}
+
+#[test]
+fn a_hold_needs_a_long_press() {
+    let sequences = caps_with(PressStyle::Hold { min_ms: 500 });
+    let caps = key(0, VK_CAPITAL);
+    let mut m = SequenceMatcher::default();
+
+    assert_eq!(
+        m.feed(&sequences, ChordInput::up(caps, 100, None, 100)),
+        MatchOutcome::NoMatch
+    );
+    assert_eq!(
+        m.feed(&sequences, ChordInput::up(caps, 700, None, 1500)),
+        MatchOutcome::Completed(SWITCH_LAYOUT)
+    );
+}
+
+#[test]
+fn a_double_tap_needs_two_short_taps_close_together() {
+    let sequences = caps_with(PressStyle::DoubleTap { max_gap_ms: 250 });
+    let caps = key(0, VK_CAPITAL);
+    let mut m = SequenceMatcher::default();
+
+    assert_eq!(
+        m.feed(&sequences, ChordInput::up(caps, 80, None, 80)),
+        MatchOutcome::Progress
+    );
+    assert_eq!(
+        m.feed(&sequences, ChordInput::up(caps, 80, Some(400), 560)),
+        MatchOutcome::Progress
+    );
+    assert_eq!(
+        m.feed(&sequences, ChordInput::up(caps, 80, Some(100), 740)),
+        MatchOutcome::Completed(SWITCH_LAYOUT)
+    );
+    assert_eq!(
+        m.feed(&sequences, ChordInput::up(caps, 400, Some(100), 1240)),
+        MatchOutcome::NoMatch
+    );
+}
+
+#[test]
+fn a_press_keeps_a_tap_step_of_a_sequence_waiting() {
+    let sequences = bind(&[(
+        CONVERT_SELECTION,
+        HotkeySequence::new(
+            &[
+                key(CTRL, 0x4B),
+                styled(key(0, VK_CAPITAL), PressStyle::Tap { max_ms: 300 }),
+            ],
+            1000,
+        ),
+    )]);
+    let caps = key(0, VK_CAPITAL);
+    let mut m = SequenceMatcher::default();
+
+    assert_eq!(
+        m.feed(&sequences, at(key(CTRL, 0x4B), 0)),
+        MatchOutcome::Progress
+    );
+    assert_eq!(
+        m.feed(&sequences, ChordInput::down(caps, 500)),
+        MatchOutcome::Progress
+    );
+    assert_eq!(
+        m.feed(&sequences, ChordInput::up(caps, 100, None, 600)),
+        MatchOutcome::Completed(CONVERT_SELECTION)
+    );
+}
+
+#[test]
+fn release_window_covers_styled_steps_only() {
+    let caps = key(0, VK_CAPITAL);
+    let sequences = bind(&[
+        (
+            SWITCH_LAYOUT,
+            HotkeySequence::new(&[styled(caps, PressStyle::Tap { max_ms: 300 })], 1000),
+        ),
+        (
+            UNDO_LAST_CONVERSION,
+            HotkeySequence::new(
+                &[styled(caps, PressStyle::DoubleTap { max_gap_ms: 400 })],
+                1000,
+            ),
+        ),
+        (
+            CONVERT_SELECTION,
+            HotkeySequence::new(&[key(CTRL, 0x4B)], 1000),
+        ),
+    ]);
+
+    assert_eq!(release_window_ms(&sequences, caps), Some(400));
+    assert_eq!(release_window_ms(&sequences, key(CTRL, 0x4B)), None);
+
+    let held = caps_with(PressStyle::Hold { min_ms: 500 });
+    assert_eq!(release_window_ms(&held, caps), Some(u64::MAX));
+}
@@ -0,0 +1,112 @@
+use crate::{
+    domain::text::snippets::{Expansion, Placeholder, SnippetError, Snippets, expand},
+    tests::ru_en,
+};
+
+fn sample() -> Snippets {
+    let mut snippets = Snippets::new();
+    snippets.insert("Sig", "Best regards,\n{cursor}").unwrap();
+    snippets.insert("дата", "{date}").unwrap();
+    snippets
+}
+
+fn values(p: Placeholder) -> String {
+    match p {
+        Placeholder::Date => "2026-10-18".to_string(),
+        Placeholder::Time => "09:05".to_string(),
+        Placeholder::Clipboard => "one\r\ntwo".to_string(),
+        Placeholder::Cursor => unreachable!("cursor has no value"),
+    }
+}
+
+#[test]
+fn insert_normalizes_triggers_and_rejects_bad_ones() {
+    let mut snippets = sample();
+
+    assert_eq!(snippets.get("SIG"), Some("Best regards,\n{cursor}"));
+    assert_eq!(snippets.insert(" ", "x"), Err(SnippetError::EmptyTrigger));
+    assert!(matches!(
+        snippets.insert("two words", "x"),
+        Err(SnippetError::TriggerHasWhitespace(_))
+    ));
+    assert_eq!(
+        snippets.insert("sig", ""),
+        Err(SnippetError::EmptyText("sig".to_string()))
+    );
+
+    assert!(snippets.remove("Sig"));
+    assert!(!snippets.remove("sig"));
+    assert_eq!(snippets.len(), 1);
+}
+
+#[test]
+fn find_matches_the_trigger_typed_in_either_layout() {
+    let snippets = sample();
+
+    let found = snippets.find("sig", "ышп").unwrap();
+    assert_eq!(found.trigger, "sig");
+    assert_eq!(found.punct, "");
+
+    let typed = ru_en("дата");
+    assert_eq!(typed, "lfnf");
+    let converted = ru_en(&typed);
+    let found = snippets.find(&typed, &converted).unwrap();
+    assert_eq!(found.text, "{date}");
+
+    assert!(snippets.find("signature", "ышптфегку").is_none());
+}
+
+#[test]
+fn find_keeps_trailing_punctuation() {
+    let snippets = sample();
+    let found = snippets.find("sig,", "ышп?").unwrap();
+    assert_eq!(found.trigger, "sig");
+    assert_eq!(found.punct, ",");
+}
+
+#[test]
+fn toml_format_roundtrips() {
+    let snippets = sample();
+    let text = snippets.to_toml().unwrap();
+    assert_eq!(Snippets::parse(&text).unwrap(), snippets);
+}
+
+#[test]
+fn parse_rejects_duplicates_and_bad_syntax() {
+    let dup =
+        "[[snippet]]\ntrigger = \"a\"\ntext = \"1\"\n[[snippet]]\ntrigger = \"A\"\ntext = \"2\"\n";
+    assert_eq!(
+        Snippets::parse(dup),
+        Err(SnippetError::DuplicateTrigger("A".to_string()))
+    );
+    assert!(matches!(
+        Snippets::parse("[[snippet]]\ntrigger = \"a\"\n"),
+        Err(SnippetError::Syntax(_))
+    ));
+    assert!(Snippets::parse("").unwrap().is_empty());
+}
+
+#[test]
+fn expand_fills_placeholders_and_normalizes_line_breaks() {
+    assert_eq!(
+        expand("{date} {time}: {clipboard}", values),
+        Expansion {
+            text: "2026-10-18 09:05: one\ntwo".to_string(),
+            caret_back: 0,
+        }
+    );
+}
+
+#[test]
+fn expand_measures_the_caret_from_the_end() {
+    let e = expand("<b>{cursor}</b>\r\n{cursor}", values);
+    assert_eq!(e.text, "<b></b>\n");
+    assert_eq!(e.caret_back, 5);
+}
+
+#[test]
+fn expand_keeps_escaped_and_unknown_braces() {
+    let e = expand("{{date}} {name} {date", values);
+    assert_eq!(e.text, "{date} {name} {date");
+    assert_eq!(e.caret_back, 0);
+}
@@ -0,0 +1,122 @@
+use crate::{
+    app::actions::{self, ActionId},
+    config::Config,
+    domain::text::transform::{self, CaseTransform, TRANSFORMS, TextTransform},
+};
+
+fn apply(id: &str, text: &str) -> String {
+    transform::find(id).unwrap().apply(text)
+}
+
+#[test]
+fn transform_ids_are_unique_and_found() {
+    for t in TRANSFORMS {
+        assert_eq!(transform::find(t.id()).unwrap().id(), t.id());
+        assert_eq!(TRANSFORMS.iter().filter(|o| o.id() == t.id()).count(), 1);
+    }
+    assert!(transform::find("rot13").is_none());
+}
+
+#[test]
+fn only_the_layout_swap_switches_the_layout() {
+    let switching: Vec<_> = TRANSFORMS
+        .iter()
+        .filter(|t| t.switches_layout())
+        .map(|t| t.id())
+        .collect();
+    assert_eq!(switching, ["layout_swap"]);
+}
+
+#[test]
+fn invert_case_undoes_caps_lock() {
+    assert_eq!(apply("invert_case", "hELLO wORLD"), "Hello World");
+    assert_eq!(apply("invert_case", "пРИВЕТ, 42!"), "Привет, 42!");
+}
+
+#[test]
+fn upper_lower_and_title_case() {
+    assert_eq!(CaseTransform::Upper.apply("straße ёж"), "STRASSE ЁЖ");
+    assert_eq!(CaseTransform::Lower.apply("ПРИВЕТ World"), "привет world");
+    assert_eq!(
+        CaseTransform::Title.apply("hELLO wORLD, don't stop-now"),
+        "Hello World, Don't Stop-Now"
+    );
+    assert_eq!(CaseTransform::Title.apply("  ещё РАЗ"), "  Ещё Раз");
+}
+
+#[test]
+fn iso9_transliterates_both_ways() {
+    assert_eq!(apply("translit_iso9", "Щука и ёж, Юля!"), "Ŝuka i ëž, Ûlâ!");
+    assert_eq!(apply("translit_iso9", "Ŝuka i ëž, Ûlâ!"), "Щука и ёж, Юля!");
+    assert_eq!(apply("translit_iso9", "Подъезд, объём"), "Podʺezd, obʺëm");
+}
+
+#[test]
+fn iso9_keeps_plain_latin_text() {
+    assert_eq!(apply("translit_iso9", "Hello, world!"), "Hello, world!");
+    assert_eq!(apply("translit_iso9", "Ulja"), "Ulja");
+}
+
+#[test]
+fn iso9_spells_ghe_with_upturn_with_a_combining_grave() {
+    assert_eq!(
+        apply("translit_iso9", "Ґанок ґудзик"),
+        "G\u{300}anok g\u{300}udzik"
+    );
+    assert_eq!(
+        apply("translit_iso9", "G\u{300}anok g\u{300}udzik"),
+        "Ґанок ґудзик"
+    );
+}
+
+#[test]
+fn iso9_round_trips_the_alphabet() {
+    let alphabet = "абвгдеёжзийклмнопрстуфхцчшщъыьэюя АБВГДЕЁЖЗИЙКЛМНОПРСТУФХЦЧШЩЫЭЮЯ";
+    let latin = apply("translit_iso9", alphabet);
+    assert!(
+        !latin
+            .chars()
+            .any(|c| ('а'..='я').contains(&c.to_lowercase().next().unwrap()))
+    );
+    assert_eq!(apply("translit_iso9", &latin), alphabet);
+}
+
+#[test]
+fn typography_sets_quotes_and_dashes() {
+    assert_eq!(
+        apply("typography", r#"He said "yes" - then "no"."#),
+        "He said «yes» — then «no»."
+    );
+    assert_eq!(apply("typography", r#"("quoted")"#), "(«quoted»)");
+    assert_eq!(apply("typography", "a--b, well-known"), "a—b, well-known");
+    assert_eq!(apply("typography", "- start"), "- start");
+}
+
+#[test]
+fn transforms_are_bound_by_config_key() {
+    let id = ActionId::from_key("transform_upper_case").unwrap();
+    assert_eq!(id.transform_id(), Some("upper_case"));
+    assert!(id.is_config_only());
+    assert_eq!(actions::name(id), "Upper case");
+    assert_eq!(actions::transform("upper_case").id, id);
+    assert!(ActionId::from_key("transform_unknown").is_none());
+
+    let cfg = Config::parse(
+        "delay_ms = 100\n[hotkeys]\ntransform_invert_case = \"Ctrl+Alt+I\"\ntransform_unknown = \"Ctrl+Alt+U\"\n",
+    )
+    .unwrap();
+    let actions = cfg.actions();
+    assert!(actions.contains(&actions::transform("invert_case").id));
+    assert_eq!(actions.iter().filter(|id| id.is_config_only()).count(), 1);
+    assert!(cfg.hotkeys.contains_key("transform_unknown"));
+}
+
+#[test]
+fn layout_swaps_with_an_explicit_direction_are_bound_by_config_key() {
+    let to_latin = ActionId::from_key("transform_layout_to_latin").unwrap();
+    assert_eq!(actions::name(to_latin), "Retype in the Latin layout");
+
+    let to_cyrillic = transform::find("layout_to_cyrillic").unwrap();
+    assert_eq!(to_cyrillic.name(), "Retype in the Cyrillic layout");
+    assert!(!to_cyrillic.switches_layout());
+}
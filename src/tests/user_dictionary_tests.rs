@@ -0,0 +1,123 @@
+use crate::domain::text::{
+    autoconvert::{Action, SkipReason},
+    user_dictionary::{DictionaryError, DictionaryRule, UserDictionary},
+};
+
+fn sample() -> UserDictionary {
+    let mut dict = UserDictionary::new();
+    dict.insert("Rust-Switcher", DictionaryRule::NeverConvert)
+        .unwrap();
+    dict.insert("--verbose", DictionaryRule::NeverConvert)
+        .unwrap();
+    dict.insert("рончтьнго", DictionaryRule::AlwaysConvert)
+        .unwrap();
+    dict
+}
+
+#[test]
+fn insert_normalizes_case_and_rejects_blank_words() {
+    let mut dict = sample();
+
+    assert_eq!(
+        dict.get("RUST-SWITCHER"),
+        Some(DictionaryRule::NeverConvert)
+    );
+    assert_eq!(
+        dict.insert("  ", DictionaryRule::NeverConvert),
+        Err(DictionaryError::EmptyWord)
+    );
+    assert!(matches!(
+        dict.insert("two words", DictionaryRule::NeverConvert),
+        Err(DictionaryError::WordHasWhitespace(_))
+    ));
+
+    dict.insert("--VERBOSE", DictionaryRule::AlwaysConvert)
+        .unwrap();
+    assert_eq!(dict.len(), 3);
+    assert_eq!(dict.get("--verbose"), Some(DictionaryRule::AlwaysConvert));
+
+    assert!(dict.remove("--Verbose"));
+    assert!(!dict.remove("--verbose"));
+    assert_eq!(dict.len(), 2);
+}
+
+#[test]
+fn text_format_roundtrips() {
+    let dict = sample();
+    let text = dict.to_text();
+
+    assert_eq!(
+        text,
+        "never --verbose\nnever rust-switcher\nalways рончтьнго\n"
+    );
+    assert_eq!(UserDictionary::parse(&text).unwrap(), dict);
+}
+
+#[test]
+fn parse_skips_comments_and_reports_line_numbers() {
+    let dict = UserDictionary::parse("# exported\n\n  never   git  \nalways ghbdtn\n").unwrap();
+    assert_eq!(dict.get("git"), Some(DictionaryRule::NeverConvert));
+    assert_eq!(dict.get("ghbdtn"), Some(DictionaryRule::AlwaysConvert));
+
+    assert_eq!(
+        UserDictionary::parse("never git\nmaybe word"),
+        Err(DictionaryError::UnknownRule {
+            line: 2,
+            rule: "maybe".to_string()
+        })
+    );
+    assert_eq!(
+        UserDictionary::parse("always"),
+        Err(DictionaryError::MissingWord { line: 1 })
+    );
+    assert_eq!(
+        UserDictionary::parse("\nnever two words"),
+        Err(DictionaryError::TrailingText { line: 2 })
+    );
+}
+
+#[test]
+fn never_entries_match_the_typed_word_only() {
+    let dict = sample();
+
+    let decision = dict.decide("rust-switcher", "кгые-ыцшесрук").unwrap();
+    assert_eq!(decision.action, Action::Skip);
+    assert_eq!(decision.reason, Some(SkipReason::UserNeverConvert));
+
+    assert_eq!(
+        dict.decide("--verbose.", "--мукищыую").map(|d| d.action),
+        Some(Action::Skip)
+    );
+    assert_eq!(dict.decide("кгые-ыцшесрук", "rust-switcher"), None);
+
+    assert!(dict.never_converts("Rust-Switcher,"));
+    assert!(!dict.never_converts("рончтьнго"));
+}
+
+#[test]
+fn always_entries_match_the_typed_or_the_converted_word() {
+    let dict = sample();
+
+    let decision = dict.decide("hjyxnmyuj", "рончтьнго").unwrap();
+    assert!(decision.is_convert());
+
+    let decision = dict.decide("рончтьнго,", "hjyxnmyuj?").unwrap();
+    assert!(decision.is_convert());
+
+    assert_eq!(dict.decide("ghbdtn", "привет"), None);
+}
+
+#[test]
+fn merge_prefers_the_imported_rules() {
+    let mut dict = sample();
+    let imported = UserDictionary::parse("always rust-switcher\nnever jj\n").unwrap();
+
+    dict.merge(&imported);
+
+    assert_eq!(dict.len(), 4);
+    assert_eq!(
+        dict.get("rust-switcher"),
+        Some(DictionaryRule::AlwaysConvert)
+    );
+    assert_eq!(dict.get("jj"), Some(DictionaryRule::NeverConvert));
+}
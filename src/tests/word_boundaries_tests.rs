@@ -0,0 +1,190 @@
+use crate::{
+    config::Config,
+    input::{
+        ring_buffer::{InputJournal, Span},
+        word_boundaries::{BoundaryChars, WordBoundaries},
+    },
+};
+
+const EN: isize = 0x0409_0409;
+const RU: isize = 0x0419_0419;
+
+fn layouts() -> Vec<(isize, String)> {
+    vec![(EN, "en-US".to_string()), (RU, "ru-RU".to_string())]
+}
+
+fn journal(boundaries: &WordBoundaries) -> InputJournal {
+    let mut journal = InputJournal::new(100);
+    journal.set_boundaries(boundaries.resolve(&layouts()));
+    journal
+}
+
+fn chars(triggers: &str, separators: &str) -> BoundaryChars {
+    BoundaryChars {
+        triggers: triggers.to_string(),
+        separators: separators.to_string(),
+        ..BoundaryChars::default()
+    }
+}
+
+/// Types `text` in `layout` and returns whether its last character triggers autoconvert.
+fn type_triggers(journal: &mut InputJournal, text: &str, layout: isize) -> bool {
+    journal.push_typed(text, layout);
+    journal.last_char_triggers_autoconvert()
+}
+
+#[test]
+fn default_triggers_follow_a_word() {
+    let mut j = journal(&WordBoundaries::default());
+    assert!(!type_triggers(&mut j, "abc", EN));
+    assert!(type_triggers(&mut j, "\n", EN));
+    assert!(!type_triggers(&mut j, " ", EN));
+    assert!(!type_triggers(&mut j, ".", EN));
+}
+
+#[test]
+fn enter_can_be_left_out_of_the_triggers() {
+    let mut j = journal(&WordBoundaries {
+        default: chars(".,!?;: \t", ""),
+        ..WordBoundaries::default()
+    });
+    j.push_typed("abc", EN);
+    assert!(!type_triggers(&mut j, "\n", EN));
+    j.push_typed("def", EN);
+    assert!(type_triggers(&mut j, "\t", EN));
+}
+
+#[test]
+fn triggers_are_looked_up_by_the_layout_of_the_character() {
+    let mut boundaries = WordBoundaries::default();
+    // In the English layout `,` and `.` are the keys of `б` and `ю`.
+    boundaries
+        .layouts
+        .insert("en".to_string(), chars("!?;: \t\n", ""));
+    let mut j = journal(&boundaries);
+
+    j.push_typed("hf", EN);
+    assert!(!type_triggers(&mut j, ",", EN));
+    j.push_typed("jnf", EN);
+    assert!(type_triggers(&mut j, " ", EN));
+
+    j.push_typed("да", RU);
+    assert!(type_triggers(&mut j, ",", RU));
+}
+
+#[test]
+fn layout_sets_match_by_tag_then_language() {
+    let mut boundaries = WordBoundaries::default();
+    boundaries.layouts.insert("en".to_string(), chars("a", ""));
+    boundaries
+        .layouts
+        .insert("EN-GB".to_string(), chars("b", ""));
+
+    assert_eq!(boundaries.for_tag("en-gb").triggers, "b");
+    assert_eq!(boundaries.for_tag("en-US").triggers, "a");
+    assert_eq!(boundaries.for_tag("ru-RU"), &BoundaryChars::default());
+
+    let rules = boundaries.resolve(&layouts());
+    assert_eq!(rules.for_layout(EN).triggers, "a");
+    assert_eq!(rules.for_layout(RU), &BoundaryChars::default());
+    assert_eq!(rules.for_layout(0), &BoundaryChars::default());
+}
+
+#[test]
+fn separators_end_the_last_word() {
+    let mut j = journal(&WordBoundaries {
+        default: chars(" ", "()"),
+        ..WordBoundaries::default()
+    });
+    j.push_typed("(ghbdtn) ", EN);
+
+    let last = j.take_last_word().unwrap();
+    assert_eq!(last.word, "ghbdtn");
+    assert_eq!(last.suffix, ") ");
+    assert_eq!(j.text(), "(");
+}
+
+#[test]
+fn last_word_carries_the_boundaries_of_its_suffix_layout() {
+    let mut boundaries = WordBoundaries::default();
+    boundaries.layouts.insert(
+        "ru".to_string(),
+        BoundaryChars {
+            word_punctuation: String::new(),
+            ..BoundaryChars::default()
+        },
+    );
+    let mut j = journal(&boundaries);
+
+    j.push_typed("ghbdtn", EN);
+    j.push_typed(" ", RU);
+    let last = j.take_last_word().unwrap();
+    assert!(last.boundaries.word_punctuation.is_empty());
+
+    j.push_typed("ghbdtn ", EN);
+    let last = j.take_last_word().unwrap();
+    assert!(last.boundaries.is_word_punctuation(','));
+}
+
+#[test]
+fn no_word_leaves_the_journal_as_it_is() {
+    let mut j = journal(&WordBoundaries::default());
+    j.push_typed(" \t", EN);
+    assert!(j.take_last_word().is_none());
+    assert_eq!(j.text(), " \t");
+}
+
+#[test]
+fn config_reads_layout_sets_and_writes_them_back() {
+    let cfg = Config::parse(
+        r#"
+delay_ms = 100
+
+[word_boundaries]
+triggers = ".! \t"
+
+[word_boundaries.layouts.en]
+triggers = "! "
+separators = "()"
+"#,
+    )
+    .unwrap();
+
+    let boundaries = &cfg.word_boundaries;
+    assert_eq!(boundaries.default.triggers, ".! \t");
+    assert_eq!(
+        boundaries.default.word_punctuation,
+        BoundaryChars::default().word_punctuation
+    );
+    assert_eq!(boundaries.for_tag("en-US").separators, "()");
+
+    let text = toml::to_string(&cfg).unwrap();
+    assert_eq!(&Config::parse(&text).unwrap().word_boundaries, boundaries);
+}
+
+#[test]
+fn configs_without_word_boundaries_get_the_defaults() {
+    let cfg = Config::parse("delay_ms = 100\n").unwrap();
+    assert_eq!(cfg.word_boundaries, WordBoundaries::default());
+}
+
+#[test]
+fn sentence_ends_are_looked_up_by_layout() {
+    let mut boundaries = WordBoundaries::default();
+    // In the English layout `.` is the key of `ю`.
+    boundaries.layouts.insert(
+        "en".to_string(),
+        BoundaryChars {
+            sentence_ends: "!?".to_string(),
+            ..BoundaryChars::default()
+        },
+    );
+    let mut j = journal(&boundaries);
+
+    j.push_typed("Ok. ", RU);
+    j.push_typed("vs. nfr", EN);
+    let last = j.take_last(Span::Phrase).unwrap();
+    assert_eq!(last.word, "vs. nfr");
+    assert_eq!(last.words, 2);
+    assert_eq!(j.text(), "Ok. ");
+}
@@ -1,2 +1,3 @@
-pub mod helpers;
+#[cfg(windows)]
+pub(crate) mod helpers;
 pub mod tracing;